@@ -288,13 +288,15 @@ function generateStreamEvents(
   const res : IManifestStreamEvent[] = [];
   for (const eventStreamIr of baseIr) {
     const { schemeIdUri = "",
-            timescale = 1 } = eventStreamIr.attributes;
+            timescale = 1,
+            presentationTimeOffset = 0 } = eventStreamIr.attributes;
     const allNamespaces = xmlNamespaces
       .concat(eventStreamIr.attributes.namespaces ?? []);
 
     for (const eventIr of eventStreamIr.children.events) {
       if (eventIr.eventStreamData !== undefined) {
-        const start = ((eventIr.presentationTime ?? 0) / timescale) + periodStart;
+        const start = (((eventIr.presentationTime ?? 0) - presentationTimeOffset) /
+                       timescale) + periodStart;
         const end = eventIr.duration === undefined ?
           undefined :
           start + (eventIr.duration / timescale);
@@ -65,6 +65,13 @@ function parseAdaptationSetChildren(
           }
           break;
 
+        case "AudioChannelConfiguration":
+          if (children.audioChannelConfigurations === undefined) {
+            children.audioChannelConfigurations = [];
+          }
+          children.audioChannelConfigurations.push(parseScheme(currentElement));
+          break;
+
         case "BaseURL":
           const [baseURLObj, baseURLWarnings] = parseBaseURL(currentElement);
           if (baseURLObj !== undefined) {
@@ -57,6 +57,12 @@ export default function parseEventStream(
       case "value":
         eventStreamIR.attributes.value = attr.value;
         break;
+
+      case "presentationTimeOffset":
+        parseValue(attr.value, { asKey: "presentationTimeOffset",
+                                 parser: parseMPDInteger,
+                                 dashName: "presentationTimeOffset" });
+        break;
     }
   }
 
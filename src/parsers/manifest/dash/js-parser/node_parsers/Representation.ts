@@ -58,6 +58,12 @@ function parseRepresentationChildren(
           }
           warnings = warnings.concat(baseURLWarnings);
           break;
+        case "AudioChannelConfiguration":
+          if (children.audioChannelConfigurations === undefined) {
+            children.audioChannelConfigurations = [];
+          }
+          children.audioChannelConfigurations.push(parseScheme(currentElement));
+          break;
         case "InbandEventStream":
           if (children.inbandEventStreams === undefined) {
             children.inbandEventStreams = [];
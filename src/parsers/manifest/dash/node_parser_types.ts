@@ -63,6 +63,22 @@ export interface IMPDChildren {
    * node, from the first encountered to the last encountered.
    */
   utcTimings : IScheme[];
+  /**
+   * Low-latency playback parameters given by the content author.
+   *
+   * This is the content of all `ServiceDescription` elements encountered in
+   * this node, from the first encountered to the last encountered.
+   * Only set by the WebAssembly parser.
+   */
+  serviceDescriptions? : IServiceDescriptionIntermediateRepresentation[];
+  /**
+   * Steering server from which the order in which CDNs should be used can be
+   * obtained.
+   *
+   * This is the content of the `ContentSteering` element of this MPD node.
+   * Only set by the WebAssembly parser.
+   */
+  contentSteering? : IContentSteeringIntermediateRepresentation;
 }
 
 /* Intermediate representation for the root's attributes. */
@@ -139,6 +155,23 @@ export interface IPeriodChildren {
    * node, from the first encountered to the last encountered.
    */
   eventStreams : IEventStreamIntermediateRepresentation[];
+  /**
+   * Combinations of audio components selectable as a single track, e.g. the
+   * dialogue or commentary presentations of an AC-4 or MPEG-H stream.
+   *
+   * This is the content of all `Preselection` elements encountered in this
+   * node, from the first encountered to the last encountered.
+   * Only set by the WebAssembly parser.
+   */
+  preselections? : IPreselectionIntermediateRepresentation[];
+  /**
+   * Low-latency playback parameters given by the content author.
+   *
+   * This is the content of all `ServiceDescription` elements encountered in
+   * this node, from the first encountered to the last encountered.
+   * Only set by the WebAssembly parser.
+   */
+  serviceDescriptions? : IServiceDescriptionIntermediateRepresentation[];
 }
 
 /* Intermediate representation for A Period node's attributes. */
@@ -190,10 +223,15 @@ export interface IAdaptationSetChildren {
 
   // optional
   accessibilities? : IScheme[] | undefined;
+  audioChannelConfigurations? : IAudioChannelConfiguration[] | undefined;
   contentComponent? : IContentComponentAttributes | undefined;
   contentProtections? : IContentProtectionIntermediateRepresentation[] | undefined;
   essentialProperties? : IScheme[] | undefined;
   inbandEventStreams? : IScheme[] | undefined;
+  preselections? : IPreselectionIntermediateRepresentation[] | undefined;
+  producerReferenceTimes? : IProducerReferenceTimeIntermediateRepresentation[] |
+                            undefined;
+  resyncs? : IResyncAttributes[] | undefined;
   roles? : IScheme[];
   supplementalProperties? : IScheme[] | undefined;
 
@@ -249,8 +287,11 @@ export interface IRepresentationChildren {
   baseURLs : IBaseUrlIntermediateRepresentation[];
 
   // optional
+  audioChannelConfigurations? : IAudioChannelConfiguration[];
   contentProtections? : IContentProtectionIntermediateRepresentation[];
   inbandEventStreams? : IScheme[];
+  producerReferenceTimes? : IProducerReferenceTimeIntermediateRepresentation[];
+  resyncs? : IResyncAttributes[];
   segmentBase? : ISegmentBaseIntermediateRepresentation;
   segmentList? : ISegmentListIntermediateRepresentation;
   segmentTemplate? : ISegmentTemplateIntermediateRepresentation;
@@ -334,12 +375,84 @@ export interface IContentProtectionIntermediateRepresentation {
 
 export interface IContentProtectionChildren {
   cencPssh : Uint8Array[];
+  /**
+   * SystemID and key ids of the `pssh` boxes of `cencPssh` which could be
+   * parsed, in the same order.
+   * Only set by the WebAssembly parser.
+   */
+  cencPsshInfo? : ICencPsshInfo[];
+  playReady? : IPlayReadyInfo;
+  /**
+   * License server URLs of the `<dashif:Laurl>` elements.
+   * Only set by the WebAssembly parser.
+   */
+  laUrls? : ILicenseUrl[];
+  /**
+   * Authorization service URLs of the `<dashif:Authzurl>` elements.
+   * Only set by the WebAssembly parser.
+   */
+  authzUrls? : ILicenseUrl[];
+  /**
+   * License server URLs of the `<clearkey:Laurl>` elements.
+   * Only set by the WebAssembly parser.
+   */
+  clearKeyLaUrls? : ILicenseUrl[];
+}
+
+/** A license-related URL declared in a `<ContentProtection>`. */
+export interface ILicenseUrl {
+  url : string;
+  /**
+   * Its `licenseType` attribute (`Lic_type` for `<clearkey:Laurl>`), e.g.
+   * `"EME-1.0"`.
+   */
+  licenseType? : string;
+}
+
+/**
+ * Information from the `mspr:` elements of a `<ContentProtection>`.
+ * Only set by the WebAssembly parser.
+ */
+export interface IPlayReadyInfo {
+  /** PlayReady Object, decoded from the base64 `<mspr:pro>` element. */
+  object? : Uint8Array;
+  /** Key ids of its WRM header, in the UUID byte order. */
+  keyIds : Uint8Array[];
+  laUrl? : string;
+  luiUrl? : string;
+  /** Inner XML of the WRM header's `<CUSTOMATTRIBUTES>` element. */
+  customAttributes? : string;
+  /** Content of the `<mspr:IsEncrypted>` element. */
+  isEncrypted? : boolean;
+}
+
+export interface ICencPsshInfo {
+  systemId : Uint8Array;
+  keyIds : Uint8Array[];
 }
 
 export interface IContentProtectionAttributes {
   schemeIdUri? : string;
   value? : string;
   keyId? : Uint8Array;
+  /**
+   * All key ids of the `cenc:default_KID` attribute, when several are listed
+   * (`keyId` being the first one).
+   * Only set by the WebAssembly parser.
+   */
+  keyIds? : Uint8Array[];
+  robustness? : string;
+  /**
+   * Identifier allowing other `<ContentProtection>` elements to reference
+   * this one.
+   */
+  refId? : string;
+  /**
+   * `refId` of the `<ContentProtection>` this one references. The WebAssembly
+   * parser already copies the attributes and children of the referenced
+   * element into this one.
+   */
+  ref? : string;
 }
 
 export interface ISegmentTemplateIntermediateRepresentation {
@@ -364,6 +477,13 @@ export interface ISegmentTimelineElement {
   start : number;
   duration : number;
   repeatCount : number;
+  /** Number of the first segment, from the `<S>` element's `n` attribute. */
+  number? : number | undefined;
+  /**
+   * Number of chunks each segment is made of, from the `<S>` element's `k`
+   * attribute.
+   */
+  chunkCount? : number | undefined;
 }
 
 /** Intermediate representation for a BaseURL node. */
@@ -376,6 +496,25 @@ export interface IBaseUrlIntermediateRepresentation {
   value: string;
 }
 
+/** Intermediate representation for a ContentSteering node. */
+export interface IContentSteeringIntermediateRepresentation {
+  /**
+   * URL of the steering server.
+   *
+   * This is the inner content of a ContentSteering node.
+   */
+  value : string;
+  /** Contains data about the element's attributes. */
+  attributes : IContentSteeringAttributes;
+}
+
+/* Intermediate representation for a ContentSteering node's attributes. */
+export interface IContentSteeringAttributes {
+  defaultServiceLocation? : string;
+  queryBeforeStart? : boolean;
+  proxyServerUrl? : string;
+}
+
 /** Intermediate representation for a Node following a "scheme" format. */
 export interface IScheme {
   /**
@@ -388,6 +527,133 @@ export interface IScheme {
   value? : string | undefined;
 }
 
+/** Intermediate representation for an `<AudioChannelConfiguration>`. */
+export interface IAudioChannelConfiguration extends IScheme {
+  /**
+   * Number of audio channels, decoded from `value` when `schemeIdUri` is a
+   * known scheme.
+   * Only set by the WebAssembly parser.
+   */
+  channelCount? : number;
+  /**
+   * Labels of the speakers those channels are for (e.g. `["L", "R"]`), when
+   * indicated by that scheme.
+   * Only set by the WebAssembly parser.
+   */
+  speakers? : string[];
+}
+
+export interface IPreselectionIntermediateRepresentation {
+  children : IPreselectionChildren;
+  attributes : IPreselectionAttributes;
+}
+
+export interface IPreselectionChildren {
+  accessibilities : IScheme[];
+  audioChannelConfigurations : IAudioChannelConfiguration[];
+  roles : IScheme[];
+}
+
+export interface IPreselectionAttributes {
+  id? : string;
+  /** Space-separated ids of the components combined, as written in the MPD. */
+  preselectionComponents? : string;
+  /**
+   * Components combined, in the order of `preselectionComponents`. The first
+   * one is the main `AdaptationSet`.
+   */
+  components? : IPreselectionComponent[];
+  codecs? : string;
+  label? : string;
+  language? : string;
+  tag? : string;
+}
+
+/** Reference to an `AdaptationSet` or one of its `ContentComponent`. */
+export interface IPreselectionComponent {
+  adaptationSetId : string;
+  /** `undefined` if the whole `AdaptationSet` is referenced. */
+  contentComponentId? : string;
+}
+
+/**
+ * Intermediate representation for a `<ServiceDescription>`, describing the
+ * latency and playback rates aimed for when playing close to the live edge.
+ */
+export interface IServiceDescriptionIntermediateRepresentation {
+  children : IServiceDescriptionChildren;
+  attributes : { id? : string };
+}
+
+export interface IServiceDescriptionChildren {
+  /** Clients the `ServiceDescription` applies to. Applies to all if empty. */
+  scopes : IScheme[];
+  latencies : ILatencyAttributes[];
+  playbackRates : IPlaybackRateAttributes[];
+  operatingQualities : IOperatingQualityAttributes[];
+  operatingBandwidths : IOperatingBandwidthAttributes[];
+}
+
+/** Attributes of a `<Latency>` element, all in milliseconds but `referenceId`. */
+export interface ILatencyAttributes {
+  /** `id` of the `<ProducerReferenceTime>` the latency is measured against. */
+  referenceId? : number;
+  target? : number;
+  min? : number;
+  max? : number;
+}
+
+export interface IPlaybackRateAttributes {
+  min? : number;
+  max? : number;
+}
+
+export interface IOperatingQualityAttributes {
+  mediaType? : string;
+  min? : number;
+  max? : number;
+  target? : number;
+  type? : string;
+  maxDifference? : number;
+}
+
+/** Attributes of an `<OperatingBandwidth>` element, in bits per second. */
+export interface IOperatingBandwidthAttributes {
+  mediaType? : string;
+  min? : number;
+  max? : number;
+  target? : number;
+}
+
+/**
+ * Intermediate representation for a `<ProducerReferenceTime>`, associating a
+ * media presentation time to the wall clock time at which it was produced.
+ */
+export interface IProducerReferenceTimeIntermediateRepresentation {
+  children : { utcTimings : IScheme[] };
+  attributes : IProducerReferenceTimeAttributes;
+}
+
+export interface IProducerReferenceTimeAttributes {
+  id? : string;
+  inband? : boolean;
+  type? : string;
+  applicationScheme? : string;
+  /** Wall clock time, as a unix timestamp in milliseconds. */
+  wallClockTime? : number;
+  /** Presentation time, in the timescale of the parent element. */
+  presentationTime? : number;
+}
+
+/** Attributes of a `<Resync>` element, signaling resynchronization points. */
+export interface IResyncAttributes {
+  type? : number;
+  dT? : number;
+  dImax? : number;
+  dImin? : number;
+  marker? : boolean;
+}
+
 export interface IEventStreamIntermediateRepresentation {
   /** Contains data about the element's children. */
   children : IEventStreamChildren;
@@ -400,6 +666,12 @@ export interface IEventStreamAttributes {
   timescale? : number | undefined;
   value? : string | undefined;
 
+  /**
+   * Offset, in `timescale` units, to subtract from the `presentationTime` of
+   * the `<Event>` elements.
+   */
+  presentationTimeOffset? : number | undefined;
+
   /**
    * XML namespaces linked to the `<EventStream>` element.
    *
@@ -426,6 +698,13 @@ export interface IEventStreamEventIntermediateRepresentation {
    *   - Either as the Element's UTF-8 textual representation.
    */
   eventStreamData? : Element | ArrayBuffer;
+
+  /**
+   * Payload of the event: its `messageData` attribute in UTF-8 or, when its
+   * `contentEncoding` is `base64`, its content once decoded.
+   * Only set by the WebAssembly parser.
+   */
+  messageData? : Uint8Array;
 }
 
 export type ITimelineParser = () => HTMLCollection;
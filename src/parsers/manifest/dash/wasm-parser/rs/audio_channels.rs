@@ -0,0 +1,245 @@
+use std::convert::TryFrom;
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::utils;
+
+/// MPEG-DASH scheme, whose value is directly the number of channels.
+const MPEG_DASH_SCHEME: &[u8] = b"urn:mpeg:dash:23003:3:audio_channel_configuration:2011";
+
+/// MPEG CICP scheme, whose value is a `ChannelConfiguration` code as defined
+/// by ISO/IEC 23091-3.
+const CICP_SCHEME: &[u8] = b"urn:mpeg:mpegB:cicp:ChannelConfiguration";
+
+/// Dolby scheme used for AC-4, whose value is a 24-bit mask in hexadecimal
+/// (ETSI TS 103 190-2).
+const DOLBY_AC4_SCHEME: &[u8] = b"tag:dolby.com,2014:dash:audio_channel_configuration:2011";
+
+/// Dolby scheme used for (E-)AC-3, whose value is a 16-bit mask in
+/// hexadecimal (ETSI TS 102 366).
+const DOLBY_SCHEME: &[u8] = b"urn:dolby:dash:audio_channel_configuration:2011";
+
+/// Speakers of each `ChannelConfiguration` code of the CICP scheme, from
+/// `1`, or only their number when their layout is not described here.
+const CICP_LAYOUTS: [(u32, &[&str]); 20] = [
+    (1, &["C"]),
+    (2, &["L", "R"]),
+    (3, &["C", "L", "R"]),
+    (4, &["C", "L", "R", "Cs"]),
+    (5, &["C", "L", "R", "Ls", "Rs"]),
+    (6, &["C", "L", "R", "Ls", "Rs", "LFE"]),
+    (8, &["C", "L", "R", "Lw", "Rw", "Ls", "Rs", "LFE"]),
+    // Two independent mono channels
+    (2, &[]),
+    (3, &["L", "R", "Cs"]),
+    (4, &["L", "R", "Ls", "Rs"]),
+    (7, &["C", "L", "R", "Ls", "Rs", "Cs", "LFE"]),
+    (8, &["C", "L", "R", "Ls", "Rs", "Lb", "Rb", "LFE"]),
+    (24, &[]),
+    (8, &["C", "L", "R", "Ls", "Rs", "LFE", "Tfl", "Tfr"]),
+    (12, &[]),
+    (
+        10,
+        &["C", "L", "R", "Ls", "Rs", "LFE", "Tfl", "Tfr", "Tbl", "Tbr"],
+    ),
+    (12, &[]),
+    (14, &[]),
+    (
+        12,
+        &[
+            "C", "L", "R", "Ls", "Rs", "Lb", "Rb", "LFE", "Tfl", "Tfr", "Tbl", "Tbr",
+        ],
+    ),
+    (14, &[]),
+];
+
+/// Speakers signaled by each bit of the AC-4 mask, from its least
+/// significant one.
+const DOLBY_AC4_SPEAKERS: [&[&str]; 19] = [
+    &["L", "R"],
+    &["C"],
+    &["Ls", "Rs"],
+    &["Lb", "Rb"],
+    &["Tfl", "Tfr"],
+    &["Tbl", "Tbr"],
+    &["LFE"],
+    &["Tl", "Tr"],
+    &["Tsl", "Tsr"],
+    &["Tfc"],
+    &["Tbc"],
+    &["Tc"],
+    &["LFE2"],
+    &["Bfl", "Bfr"],
+    &["Bfc"],
+    &["Cb"],
+    &["Lscr", "Rscr"],
+    &["Lw", "Rw"],
+    &["Vhl", "Vhr"],
+];
+
+/// Speakers signaled by each bit of the (E-)AC-3 mask, from its most
+/// significant one.
+const DOLBY_SPEAKERS: [&[&str]; 16] = [
+    &["L"],
+    &["C"],
+    &["R"],
+    &["Ls"],
+    &["Rs"],
+    &["Lc", "Rc"],
+    &["Lrs", "Rrs"],
+    &["Cs"],
+    &["Ts"],
+    &["Lsd", "Rsd"],
+    &["Lw", "Rw"],
+    &["Vhl", "Vhr"],
+    &["Vhc"],
+    &["Lts", "Rts"],
+    &["LFE2"],
+    &["LFE"],
+];
+
+/// Audio channels described by an `<AudioChannelConfiguration>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioChannelConfiguration {
+    pub channel_count: u32,
+
+    /// Labels of the speakers the channels are for (e.g. `["L", "R"]` for
+    /// stereo), if indicated by the scheme used.
+    pub speakers: Option<Vec<&'static str>>,
+}
+
+impl AudioChannelConfiguration {
+    /// Decode the `value` of an `<AudioChannelConfiguration>` element whose
+    /// `schemeIdUri` is `scheme_id_uri`.
+    ///
+    /// Returns `Ok(None)` for unknown schemes and for values not defined by
+    /// them, and an error when the value is not even in the expected format.
+    pub fn from_scheme(scheme_id_uri: &[u8], value: &[u8]) -> Result<Option<Self>> {
+        match scheme_id_uri {
+            MPEG_DASH_SCHEME => {
+                let channel_count = parse_u32(value)?;
+                Ok(Some(AudioChannelConfiguration {
+                    channel_count,
+                    speakers: None,
+                }))
+            }
+            CICP_SCHEME => {
+                let code = parse_u32(value)?;
+                let layout = (code as usize)
+                    .checked_sub(1)
+                    .and_then(|index| CICP_LAYOUTS.get(index));
+                Ok(
+                    layout.map(|(channel_count, speakers)| AudioChannelConfiguration {
+                        channel_count: *channel_count,
+                        speakers: if speakers.is_empty() {
+                            None
+                        } else {
+                            Some(speakers.to_vec())
+                        },
+                    }),
+                )
+            }
+            DOLBY_AC4_SCHEME => {
+                let mask = parse_mask(value, 6)?;
+                let speakers = (0..DOLBY_AC4_SPEAKERS.len())
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .flat_map(|bit| DOLBY_AC4_SPEAKERS[bit].iter().copied());
+                Ok(Some(AudioChannelConfiguration::from_speakers(
+                    speakers.collect(),
+                )))
+            }
+            DOLBY_SCHEME => {
+                let mask = parse_mask(value, 4)?;
+                let speakers = (0..DOLBY_SPEAKERS.len())
+                    .filter(|bit| mask & (0x8000 >> bit) != 0)
+                    .flat_map(|bit| DOLBY_SPEAKERS[bit].iter().copied());
+                Ok(Some(AudioChannelConfiguration::from_speakers(
+                    speakers.collect(),
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn from_speakers(speakers: Vec<&'static str>) -> Self {
+        AudioChannelConfiguration {
+            channel_count: speakers.len() as u32,
+            speakers: Some(speakers),
+        }
+    }
+}
+
+fn parse_u32(value: &[u8]) -> Result<u32> {
+    let value = utils::parse_u64(value)?;
+    u32::try_from(value).map_err(|_| {
+        ParsingError::new(
+            ParsingErrorKind::InvalidNumber,
+            format!("Too many audio channels: {}.", value),
+        )
+    })
+}
+
+/// Parse a channel mask written as `digits` hexadecimal digits.
+fn parse_mask(value: &[u8], digits: usize) -> Result<u32> {
+    let mask = std::str::from_utf8(value)
+        .ok()
+        .filter(|val| val.len() == digits && val.bytes().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|val| u32::from_str_radix(val, 16).ok());
+    mask.ok_or_else(|| {
+        ParsingError::new(
+            ParsingErrorKind::InvalidNumber,
+            format!(
+                "Invalid channel mask, expected {} hexadecimal digits: {}",
+                digits,
+                String::from_utf8_lossy(value)
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(scheme_id_uri: &[u8], value: &str) -> (u32, Option<Vec<&'static str>>) {
+        let config = AudioChannelConfiguration::from_scheme(scheme_id_uri, value.as_bytes())
+            .unwrap()
+            .unwrap();
+        (config.channel_count, config.speakers)
+    }
+
+    #[test]
+    fn test_decode_audio_channel_configuration() {
+        assert_eq!(decode(MPEG_DASH_SCHEME, "6"), (6, None));
+        assert_eq!(decode(CICP_SCHEME, "2"), (2, Some(vec!["L", "R"])));
+        assert_eq!(
+            decode(CICP_SCHEME, "6"),
+            (6, Some(vec!["C", "L", "R", "Ls", "Rs", "LFE"]))
+        );
+        assert_eq!(decode(CICP_SCHEME, "13"), (24, None));
+        assert_eq!(
+            decode(DOLBY_AC4_SCHEME, "000047"),
+            (6, Some(vec!["L", "R", "C", "Ls", "Rs", "LFE"]))
+        );
+        assert_eq!(decode(DOLBY_SCHEME, "A000"), (2, Some(vec!["L", "R"])));
+        assert_eq!(
+            decode(DOLBY_SCHEME, "F801"),
+            (6, Some(vec!["L", "C", "R", "Ls", "Rs", "LFE"]))
+        );
+    }
+
+    #[test]
+    fn test_reject_invalid_audio_channel_configuration() {
+        assert_eq!(
+            AudioChannelConfiguration::from_scheme(CICP_SCHEME, b"0"),
+            Ok(None)
+        );
+        assert_eq!(
+            AudioChannelConfiguration::from_scheme(b"urn:example", b"2"),
+            Ok(None)
+        );
+        let err = AudioChannelConfiguration::from_scheme(DOLBY_SCHEME, b"F80").unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidNumber);
+        let err = AudioChannelConfiguration::from_scheme(MPEG_DASH_SCHEME, b"two").unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidNumber);
+    }
+}
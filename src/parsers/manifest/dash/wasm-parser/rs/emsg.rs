@@ -0,0 +1,425 @@
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::events::{AttributeName, TagName};
+use crate::isobmff::{BoxHeader, BoxReader};
+use crate::model::Scheme;
+use crate::sink::{AttrValue, MpdEventSink};
+
+/// Value of `event_duration` signaling an unknown duration.
+const UNKNOWN_DURATION: u32 = 0xFFFF_FFFF;
+
+/// An Event Message (`emsg`) ISOBMFF box, as defined by ISO/IEC 23009-1 and
+/// found in media segments whose `<InbandEventStream>` declares its scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmsgBox {
+    /// Version of the box: `0` or `1`.
+    pub version: u8,
+
+    pub scheme_id_uri: String,
+    pub value: String,
+
+    /// Number of ticks per second of `presentation_time` and
+    /// `event_duration`.
+    pub timescale: u32,
+
+    pub presentation_time: EmsgPresentationTime,
+
+    /// Duration of the event, in `timescale` units. `None` if unknown.
+    pub event_duration: Option<u32>,
+
+    pub id: u32,
+    pub message_data: Vec<u8>,
+}
+
+/// When an `EmsgBox`'s event starts, in its `timescale` units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmsgPresentationTime {
+    /// `presentation_time_delta` of a version `0` box: offset from the
+    /// earliest presentation time of the segment containing it.
+    Delta(u32),
+
+    /// `presentation_time` of a version `1` box: presentation time on the
+    /// media timeline.
+    Absolute(u64),
+}
+
+/// An `EmsgBox` and its byte range (end not included) in the segment
+/// containing it.
+pub type LocatedEmsgBox = (EmsgBox, (usize, usize));
+
+impl EmsgBox {
+    /// Parse the given data, which should contain a single `emsg` box.
+    pub fn parse(data: &[u8]) -> Result<EmsgBox> {
+        let header = BoxHeader::read(data, 0, ParsingErrorKind::InvalidEmsg)?;
+        if &header.box_type != b"emsg" {
+            return Err(emsg_error(format!(
+                "Not an emsg box: type is {:?}.",
+                String::from_utf8_lossy(&header.box_type)
+            )));
+        }
+        if header.size != data.len() {
+            return Err(emsg_error(format!(
+                "The box size ({}) does not match its data length ({}).",
+                header.size,
+                data.len()
+            )));
+        }
+        let mut cursor = BoxReader::new(data, header.header_size, ParsingErrorKind::InvalidEmsg);
+        let version = cursor.read_bytes(4, "version and flags")?[0];
+        let emsg = match version {
+            0 => {
+                let scheme_id_uri = cursor.read_string("scheme_id_uri")?;
+                let value = cursor.read_string("value")?;
+                let timescale = cursor.read_u32("timescale")?;
+                let delta = cursor.read_u32("presentation_time_delta")?;
+                EmsgBox {
+                    version,
+                    scheme_id_uri,
+                    value,
+                    timescale,
+                    presentation_time: EmsgPresentationTime::Delta(delta),
+                    event_duration: duration(cursor.read_u32("event_duration")?),
+                    id: cursor.read_u32("id")?,
+                    message_data: Vec::new(),
+                }
+            }
+            1 => {
+                let timescale = cursor.read_u32("timescale")?;
+                let presentation_time = cursor.read_u64("presentation_time")?;
+                let event_duration = duration(cursor.read_u32("event_duration")?);
+                let id = cursor.read_u32("id")?;
+                EmsgBox {
+                    version,
+                    scheme_id_uri: cursor.read_string("scheme_id_uri")?,
+                    value: cursor.read_string("value")?,
+                    timescale,
+                    presentation_time: EmsgPresentationTime::Absolute(presentation_time),
+                    event_duration,
+                    id,
+                    message_data: Vec::new(),
+                }
+            }
+            _ => return Err(emsg_error(format!("Unknown version: {}.", version))),
+        };
+        Ok(EmsgBox {
+            message_data: data[cursor.pos..].to_vec(),
+            ..emsg
+        })
+    }
+
+    /// Parse the `emsg` boxes found at the top level of `segment`, the data
+    /// of a media segment, along with the byte range (end not included) of
+    /// each of them in it.
+    ///
+    /// Returns an error if a box, `emsg` or not, is malformed, as the
+    /// following ones cannot be located.
+    pub fn parse_segment(segment: &[u8]) -> Result<Vec<LocatedEmsgBox>> {
+        let (boxes, result) = parse_segment_leniently(segment);
+        result.map(|_| boxes)
+    }
+
+    /// Returns the time at which this box's event starts, in its `timescale`
+    /// units.
+    ///
+    /// `segment_start` is the earliest presentation time, in seconds, of the
+    /// segment containing the box, to which the `presentation_time_delta` of
+    /// version `0` boxes is relative.
+    pub fn presentation_time(&self, segment_start: f64) -> u64 {
+        match self.presentation_time {
+            EmsgPresentationTime::Delta(delta) => {
+                let start = (segment_start * f64::from(self.timescale)).round();
+                (start.max(0.) as u64).saturating_add(u64::from(delta))
+            }
+            EmsgPresentationTime::Absolute(time) => time,
+        }
+    }
+
+    /// Returns `true` if this box's scheme is the one declared by the given
+    /// `<InbandEventStream>`, any value matching when it has none.
+    fn matches(&self, inband_event_stream: &Scheme) -> bool {
+        inband_event_stream.scheme_id_uri.as_deref() == Some(self.scheme_id_uri.as_str())
+            && inband_event_stream
+                .value
+                .as_deref()
+                .is_none_or(|value| value == self.value)
+    }
+}
+
+/// Report the events of the `emsg` boxes found at the top level of
+/// `segment`, the data of a media segment, whose scheme is declared by one of
+/// the given `<InbandEventStream>` elements.
+///
+/// They are reported as the `<Event>` elements of `<EventStream>` elements
+/// are: an `EventStream` tag per scheme, value and timescale, in the order in
+/// which they first appear, with the `SchemeIdUri`, `SchemeValue` and
+/// `TimeScale` attributes, and in it an `EventStreamElt` tag per event with
+/// the `EventPresentationTime`, `Duration` (if known), `Id` and
+/// `MessageData` attributes.
+/// The presentation time of version `0` boxes is computed from
+/// `segment_start`, the earliest presentation time of the segment in seconds.
+/// Boxes repeating the scheme, value and id of a previous one describe the
+/// same event and are ignored, even if their timescale differs.
+///
+/// A malformed box is reported as an error, after the events of the boxes
+/// preceding it.
+pub fn report_inband_events<S: MpdEventSink>(
+    segment: &[u8],
+    inband_event_streams: &[Scheme],
+    segment_start: f64,
+    sink: &mut S,
+) {
+    let (boxes, result) = parse_segment_leniently(segment);
+    let mut streams: Vec<Vec<&EmsgBox>> = Vec::new();
+    for (emsg, _) in &boxes {
+        if !inband_event_streams
+            .iter()
+            .any(|stream| emsg.matches(stream))
+        {
+            continue;
+        }
+        // As defined by ISO/IEC 23009-1, the timescale is not part of an
+        // event's identity
+        let is_repeated = streams.iter().flatten().any(|other| {
+            other.scheme_id_uri == emsg.scheme_id_uri
+                && other.value == emsg.value
+                && other.id == emsg.id
+        });
+        if is_repeated {
+            continue;
+        }
+        let same_stream = |other: &EmsgBox| {
+            other.scheme_id_uri == emsg.scheme_id_uri
+                && other.value == emsg.value
+                && other.timescale == emsg.timescale
+        };
+        match streams.iter_mut().find(|stream| same_stream(stream[0])) {
+            Some(stream) => stream.push(emsg),
+            None => streams.push(vec![emsg]),
+        }
+    }
+
+    for stream in &streams {
+        let first = stream[0];
+        TagName::EventStream.report_tag_open(sink);
+        AttributeName::SchemeIdUri.report(first.scheme_id_uri.as_str(), sink);
+        AttributeName::SchemeValue.report(first.value.as_str(), sink);
+        AttributeName::TimeScale.report(f64::from(first.timescale), sink);
+        for emsg in stream {
+            TagName::EventStreamElt.report_tag_open(sink);
+            AttributeName::EventPresentationTime
+                .report(emsg.presentation_time(segment_start), sink);
+            if let Some(duration) = emsg.event_duration {
+                AttributeName::Duration.report(f64::from(duration), sink);
+            }
+            AttributeName::Id.report(emsg.id.to_string().as_str(), sink);
+            AttributeName::MessageData.report(AttrValue::Bytes(&emsg.message_data), sink);
+            TagName::EventStreamElt.report_tag_close(sink);
+        }
+        TagName::EventStream.report_tag_close(sink);
+    }
+    if let Err(err) = result {
+        err.report_err(sink);
+    }
+}
+
+/// Same as `EmsgBox::parse_segment`, but also returns the boxes preceding a
+/// malformed one.
+fn parse_segment_leniently(segment: &[u8]) -> (Vec<LocatedEmsgBox>, Result<()>) {
+    let mut boxes = Vec::new();
+    let mut pos = 0;
+    while pos < segment.len() {
+        let header = match BoxHeader::read(segment, pos, ParsingErrorKind::InvalidEmsg) {
+            Ok(header) => header,
+            Err(err) => return (boxes, Err(at_offset(err, pos))),
+        };
+        let end = pos + header.size;
+        if &header.box_type == b"emsg" {
+            match EmsgBox::parse(&segment[pos..end]) {
+                Ok(emsg) => boxes.push((emsg, (pos, end))),
+                Err(err) => return (boxes, Err(at_offset(err, pos))),
+            }
+        }
+        pos = end;
+    }
+    (boxes, Ok(()))
+}
+
+fn duration(event_duration: u32) -> Option<u32> {
+    Some(event_duration).filter(|duration| *duration != UNKNOWN_DURATION)
+}
+
+/// Set the offset of an error encountered in the box starting at `pos` in
+/// the segment.
+fn at_offset(mut err: ParsingError, pos: usize) -> ParsingError {
+    err.offset = Some(pos);
+    err
+}
+
+fn emsg_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidEmsg, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records every event as a String, so they can easily be compared.
+    #[derive(Default)]
+    struct RecordingSink(Vec<String>);
+
+    impl MpdEventSink for RecordingSink {
+        fn tag_open(&mut self, tag_name: TagName) {
+            self.0.push(format!("open {:?}", tag_name));
+        }
+        fn tag_close(&mut self, tag_name: TagName) {
+            self.0.push(format!("close {:?}", tag_name));
+        }
+        fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+            self.0.push(format!("attr {:?} {:?}", attr_name, value));
+        }
+        fn error(&mut self, err: &ParsingError) {
+            self.0.push(format!("error {:?}", err.kind));
+        }
+    }
+
+    fn build_box(
+        version: u8,
+        scheme: &str,
+        value: &str,
+        time: u64,
+        id: u32,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut emsg = Vec::new();
+        emsg.extend(b"emsg");
+        emsg.extend([version, 0, 0, 0]);
+        let strings = |emsg: &mut Vec<u8>| {
+            emsg.extend(scheme.as_bytes());
+            emsg.push(0);
+            emsg.extend(value.as_bytes());
+            emsg.push(0);
+        };
+        if version == 0 {
+            strings(&mut emsg);
+            emsg.extend(1000u32.to_be_bytes());
+            emsg.extend((time as u32).to_be_bytes());
+        } else {
+            emsg.extend(1000u32.to_be_bytes());
+            emsg.extend(time.to_be_bytes());
+        }
+        emsg.extend(UNKNOWN_DURATION.to_be_bytes());
+        emsg.extend(id.to_be_bytes());
+        if version == 1 {
+            strings(&mut emsg);
+        }
+        emsg.extend(data);
+        let mut with_size = ((emsg.len() + 4) as u32).to_be_bytes().to_vec();
+        with_size.extend(emsg);
+        with_size
+    }
+
+    #[test]
+    fn test_parse_emsg_boxes() {
+        let v0 = build_box(0, "urn:scte:scte35:2013:bin", "", 500, 7, b"\xfc\x30");
+        assert_eq!(
+            EmsgBox::parse(&v0),
+            Ok(EmsgBox {
+                version: 0,
+                scheme_id_uri: "urn:scte:scte35:2013:bin".to_owned(),
+                value: "".to_owned(),
+                timescale: 1000,
+                presentation_time: EmsgPresentationTime::Delta(500),
+                event_duration: None,
+                id: 7,
+                message_data: b"\xfc\x30".to_vec(),
+            })
+        );
+        assert_eq!(EmsgBox::parse(&v0).unwrap().presentation_time(10.), 10500);
+
+        let v1 = build_box(1, "urn:mpeg:dash:event:2012", "1", 1 << 40, 1, b"");
+        let emsg = EmsgBox::parse(&v1).unwrap();
+        assert_eq!(
+            (&emsg.scheme_id_uri[..], &emsg.value[..], emsg.id),
+            ("urn:mpeg:dash:event:2012", "1", 1)
+        );
+        assert_eq!(emsg.presentation_time(10.), 1 << 40);
+
+        let mut segment = v0.clone();
+        segment.extend([0, 0, 0, 8]);
+        segment.extend(b"moof");
+        segment.extend(&v1);
+        let boxes = EmsgBox::parse_segment(&segment).unwrap();
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[1].1, (v0.len() + 8, segment.len()));
+    }
+
+    #[test]
+    fn test_reject_malformed_emsg_boxes() {
+        let v0 = build_box(0, "urn:example", "", 0, 0, b"");
+        let err = EmsgBox::parse(&v0[..v0.len() - 1]).unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidEmsg);
+
+        let mut v2 = v0.clone();
+        v2[8] = 2;
+        assert_eq!(
+            EmsgBox::parse(&v2).unwrap_err().message,
+            "Unknown version: 2."
+        );
+
+        let mut unterminated = build_box(1, "", "", 0, 0, b"");
+        unterminated.truncate(unterminated.len() - 1);
+        unterminated[3] -= 1;
+        assert!(EmsgBox::parse(&unterminated).is_err());
+
+        let mut segment = v0;
+        segment.extend([0, 0, 0, 100]);
+        segment.extend(b"mdat");
+        assert!(EmsgBox::parse_segment(&segment).is_err());
+    }
+
+    #[test]
+    fn test_report_inband_events() {
+        let mut segment = build_box(0, "urn:scte:scte35:2013:bin", "", 500, 7, b"\xfc");
+        segment.extend(build_box(1, "urn:example:ignored", "", 0, 1, b""));
+        segment.extend(build_box(
+            0,
+            "urn:scte:scte35:2013:bin",
+            "",
+            500,
+            7,
+            b"\xfc",
+        ));
+        // Same event, in another timescale
+        let mut repeated = build_box(1, "urn:scte:scte35:2013:bin", "", 94500, 7, b"\xfc");
+        repeated[12..16].copy_from_slice(&9000u32.to_be_bytes());
+        segment.extend(repeated);
+        segment.extend(build_box(1, "urn:scte:scte35:2013:bin", "", 12000, 8, b""));
+        segment.extend([0, 0, 0, 7]);
+        let inband_event_streams = [Scheme {
+            scheme_id_uri: Some("urn:scte:scte35:2013:bin".to_owned()),
+            value: None,
+        }];
+        let mut sink = RecordingSink::default();
+        report_inband_events(&segment, &inband_event_streams, 10., &mut sink);
+        assert_eq!(
+            sink.0,
+            [
+                "open EventStream",
+                r#"attr SchemeIdUri String("urn:scte:scte35:2013:bin")"#,
+                r#"attr SchemeValue String("")"#,
+                "attr TimeScale F64(1000.0)",
+                "open EventStreamElt",
+                "attr EventPresentationTime U64(10500)",
+                r#"attr Id String("7")"#,
+                "attr MessageData Bytes([252])",
+                "close EventStreamElt",
+                "open EventStreamElt",
+                "attr EventPresentationTime U64(12000)",
+                r#"attr Id String("8")"#,
+                "attr MessageData Bytes([])",
+                "close EventStreamElt",
+                "close EventStream",
+                "error InvalidEmsg",
+            ]
+        );
+    }
+}
@@ -1,28 +1,211 @@
-use crate::events::CustomEventType;
-use crate::onCustomEvent;
+use std::fmt;
+
+use crate::sink::MpdEventSink;
 
 pub type Result<T> = std::result::Result<T, ParsingError>;
 
-/// Very simple error type, only used to generate a String description of a
-/// parsing error, to then be reported on the JS-side.
-#[derive(Debug, Clone)]
-pub struct ParsingError(pub String);
+/// Error encountered while parsing an MPD (or a document related to it).
+///
+/// When reported by the `MPDProcessor`, the error also indicates where it
+/// was encountered in the MPD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsingError {
+    pub kind: ParsingErrorKind,
+
+    /// Human-readable description of the error.
+    pub message: String,
+
+    /// Path of the element and potentially of the attribute concerned by the
+    /// error, e.g. `MPD/Period[2]/AdaptationSet[id=3]/SegmentTemplate@duration`.
+    ///
+    /// Elements having an `id` attribute are identified by it, others by
+    /// their position among elements with the same name (when not the
+    /// first one).
+    ///
+    /// This path is filled by the `MPDProcessor`. Errors created while
+    /// parsing an attribute only contain the attribute part (e.g.
+    /// `@duration`), to which the `MPDProcessor` prepends the element's path.
+    pub path: String,
+
+    /// Position in bytes in the MPD where the error was encountered, `None`
+    /// if unknown.
+    pub offset: Option<usize>,
+}
+
+/// Category of a `ParsingError`.
+///
+/// The JS-side relies on these values, which should thus stay stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ParsingErrorKind {
+    /// Any error not part of the other categories.
+    Other = 0,
+    /// An attribute which should be a number is not one (or too large).
+    InvalidNumber = 1,
+    /// An attribute which should be a boolean is not one.
+    InvalidBoolean = 2,
+    /// An attribute which should be an ISO 8601 duration is not one.
+    InvalidDuration = 3,
+    /// An attribute which should be an xs:dateTime is not one.
+    InvalidDateTime = 4,
+    /// An attribute which should be a byte range is not one.
+    InvalidRange = 5,
+    /// Text which is not valid UTF-8 or which contains invalid XML escapes.
+    InvalidEncoding = 6,
+    /// The document ended in the middle of an element.
+    UnexpectedEof = 7,
+    /// The document is not well-formed XML.
+    XmlSyntax = 8,
+    /// An element which was needed was not found.
+    MissingElement = 9,
+    /// An MPD Patch document or operation is invalid, or could not be
+    /// applied.
+    InvalidPatch = 10,
+    /// The data could not be read.
+    Io = 11,
+    /// An element lacks an attribute it should always have (only reported
+    /// in strict mode).
+    MissingAttribute = 12,
+    /// An attribute has a value not defined by the specification (only
+    /// reported in strict mode).
+    UnknownValue = 13,
+    /// Elements or attributes are combined in a way not allowed by the
+    /// specification (only reported in strict mode).
+    InvalidStructure = 14,
+    /// A segment of a `<SegmentTimeline>` starts before the end of the
+    /// previous one.
+    ///
+    /// Like gaps, this is only a warning, which never stops the parsing.
+    TimelineOverlap = 15,
+    /// A segment of a `<SegmentTimeline>` starts after the end of the
+    /// previous one.
+    ///
+    /// This is allowed, errors of this kind thus never stop the parsing.
+    TimelineGap = 16,
+    /// A `<SegmentTemplate>` URL template is invalid, or could not be
+    /// expanded.
+    InvalidTemplate = 17,
+    /// An XLink could not be resolved, e.g. because too many XLinks are
+    /// nested.
+    InvalidXLink = 18,
+    /// A base64-encoded value, such as the text of a `<cenc:pssh>` element,
+    /// is invalid.
+    InvalidBase64 = 19,
+    /// A `pssh` box, from a `<cenc:pssh>` element, is malformed.
+    InvalidPssh = 20,
+    /// A key id, such as a `cenc:default_KID` attribute, is not a valid UUID.
+    InvalidKeyId = 21,
+    /// A PlayReady Object, from a `<mspr:pro>` element, is malformed.
+    InvalidPlayReadyObject = 22,
+    /// A `ref` attribute, such as the one of a `<ContentProtection>`,
+    /// references no element defined before it.
+    InvalidReference = 23,
+    /// A Content Steering manifest, sent by a steering server, is invalid.
+    InvalidSteeringManifest = 24,
+    /// An `emsg` box, from a media segment, is malformed.
+    InvalidEmsg = 25,
+}
+
+impl ParsingErrorKind {
+    /// Returns `true` for errors signaling something unusual but allowed by
+    /// the specification, which are not fatal even in strict mode.
+    pub fn is_warning(self) -> bool {
+        matches!(
+            self,
+            ParsingErrorKind::TimelineGap | ParsingErrorKind::TimelineOverlap
+        )
+    }
+}
 
 impl ParsingError {
-    /// Call JS-side callback with this ParsingError in argument to report an
-    /// error.
-    pub fn report_err(&self) {
-        let len = self.0.len();
-        // UNSAFE: We're using FFI, so we don't know how the pointer is used.
-        // Hopefully, the JavaScript-side should clone that value synchronously.
-        unsafe {
-            onCustomEvent(CustomEventType::Error, (*self.0).as_ptr(), len);
+    pub fn new<T: Into<String>>(kind: ParsingErrorKind, message: T) -> ParsingError {
+        ParsingError {
+            kind,
+            message: message.into(),
+            path: String::new(),
+            offset: None,
         }
     }
+
+    /// Indicate that this error concerns the attribute with the given name
+    /// (@see `ParsingError::path`).
+    pub fn in_attribute(mut self, name: &[u8]) -> ParsingError {
+        self.path = format!("@{}", String::from_utf8_lossy(name));
+        self
+    }
+
+    /// Report this ParsingError to the given `MpdEventSink` as an error.
+    pub fn report_err<S: MpdEventSink>(&self, sink: &mut S) {
+        sink.error(self);
+    }
+}
+
+impl fmt::Display for ParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        f.write_str(&self.message)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (at byte {})", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParsingError {}
+
+impl From<std::num::ParseFloatError> for ParsingError {
+    fn from(err: std::num::ParseFloatError) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::InvalidNumber, err.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for ParsingError {
+    fn from(err: std::num::ParseIntError) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::InvalidNumber, err.to_string())
+    }
+}
+
+impl From<std::str::Utf8Error> for ParsingError {
+    fn from(err: std::str::Utf8Error) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::InvalidEncoding, err.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for ParsingError {
+    fn from(err: std::string::FromUtf8Error) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::InvalidEncoding, err.to_string())
+    }
+}
+
+impl From<quick_xml::escape::EscapeError> for ParsingError {
+    fn from(err: quick_xml::escape::EscapeError) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::InvalidEncoding, err.to_string())
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for ParsingError {
+    fn from(err: quick_xml::events::attributes::AttrError) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::XmlSyntax, err.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for ParsingError {
+    fn from(err: quick_xml::Error) -> ParsingError {
+        let kind = match &err {
+            quick_xml::Error::Io(_) => ParsingErrorKind::Io,
+            quick_xml::Error::NonDecodable(_) => ParsingErrorKind::InvalidEncoding,
+            quick_xml::Error::EscapeError(_) => ParsingErrorKind::InvalidEncoding,
+            quick_xml::Error::UnexpectedEof(_) => ParsingErrorKind::UnexpectedEof,
+            _ => ParsingErrorKind::XmlSyntax,
+        };
+        ParsingError::new(kind, err.to_string())
+    }
 }
 
-impl<T: std::error::Error> From<T> for ParsingError {
-    fn from(err: T) -> ParsingError {
-        ParsingError(err.to_string())
+impl From<std::io::Error> for ParsingError {
+    fn from(err: std::io::Error) -> ParsingError {
+        ParsingError::new(ParsingErrorKind::Io, err.to_string())
     }
 }
@@ -1,17 +1,31 @@
-use crate::{onTagClose, onTagOpen, ParsingError};
+use crate::errors::{ParsingError, ParsingErrorKind};
+use crate::sink::MpdEventSink;
+use crate::template::UrlTemplate;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 #[repr(C)]
 pub enum CustomEventType {
     /// Variant that can be used to log various information on the RxPlayer's
     /// logger.
     ///
     /// Useful for debugging, for example.
-    #[allow(dead_code)]
     Log = 0,
 
     /// Variant used to report parsing errors to the RxPlayer.
+    ///
+    /// Its payload is made of:
+    ///   - the kind of error (@see `ParsingErrorKind`), as an u8
+    ///   - the position in bytes in the MPD where it was encountered, as an
+    ///     f64 (NaN if unknown)
+    ///   - the length in bytes of the path of the concerned element (@see
+    ///     `ParsingError::path`), as a big-endian u32
+    ///   - that path, in UTF-8
+    ///   - the error's message, in UTF-8, until the end of the payload
     Error = 1,
+
+    /// Variant used to communicate the URL obtained by expanding a
+    /// `<SegmentTemplate>` URL template, in UTF-8.
+    ExpandedTemplate = 2,
 }
 
 /// `TagName` enumerates parsed XML elements in an MPD.
@@ -19,7 +33,7 @@ pub enum CustomEventType {
 /// Note that not all parsed elements have an entry in `TagName`, the simpler
 /// ones might actually have an entry in `AttributeName` instead to simplify
 /// the parser's implementation.
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 #[repr(C)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum TagName {
@@ -86,9 +100,47 @@ pub enum TagName {
     // -- Inside a <SegmentList> --
     /// Indicate a <SegmentURL> node
     SegmentUrl = 20,
+
+    // -- Inside an <AdaptationSet> or a <Representation> --
+    /// Indicate an <AudioChannelConfiguration> node
+    AudioChannelConfiguration = 21,
+
+    /// Indicate a <ProducerReferenceTime> node
+    ProducerReferenceTime = 22,
+
+    /// Indicate a <Resync> node
+    Resync = 23,
+
+    // -- Inside an <MPD> or a <Period> --
+    /// Indicate a <ServiceDescription> node
+    ServiceDescription = 24,
+
+    // -- Inside a <ServiceDescription> --
+    /// Indicate a <Scope> node
+    Scope = 25,
+
+    /// Indicate a <Latency> node
+    Latency = 26,
+
+    /// Indicate a <PlaybackRate> node
+    PlaybackRate = 27,
+
+    /// Indicate an <OperatingQuality> node
+    OperatingQuality = 28,
+
+    /// Indicate an <OperatingBandwidth> node
+    OperatingBandwidth = 29,
+
+    // -- Inside a <Period> or an <AdaptationSet> --
+    /// Indicate a <Preselection> node
+    Preselection = 30,
+
+    // -- Inside an <MPD> --
+    /// Indicate a <ContentSteering> node
+    ContentSteering = 31,
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 #[repr(C)]
 pub enum AttributeName {
     /// Describes the "id" attribute that can be found in many, many elements.
@@ -138,8 +190,8 @@ pub enum AttributeName {
 
     // ContentProtection
     ContentProtectionValue = 13,    // String
-    ContentProtectionKeyId = 14,    // ArrayBuffer
-    ContentProtectionCencPSSH = 15, // ArrayBuffer
+    ContentProtectionKeyId = 14,    // ArrayBuffer (16 bytes, once per key id)
+    ContentProtectionCencPSSH = 15, // ArrayBuffer (the decoded `pssh` box)
 
     // Various schemes (Accessibility) + EventStream + ContentProtection
     SchemeIdUri = 16, // String
@@ -151,7 +203,7 @@ pub enum AttributeName {
     MediaRange = 18, // [f64, f64]
 
     // SegmentTimeline
-    SegmentTimeline = 19, // Vec<SElement>
+    SegmentTimeline = 19, // Vec<SElement> (t, d, r, n and k, 8 bytes each)
 
     // SegmentTemplate
     StartNumber = 20, // f64
@@ -159,10 +211,10 @@ pub enum AttributeName {
     // SegmentTemplate + SegmentBase
     AvailabilityTimeComplete = 22, // u8 (bool)
     IndexRangeExact = 23,          // u8 (bool)
-    PresentationTimeOffset = 24,   // f64
+    PresentationTimeOffset = 24,   // f64, or u64 with exact timestamps
 
     // EventStream
-    EventPresentationTime = 25, // f64
+    EventPresentationTime = 25, // f64, or u64 with exact timestamps
 
     // SegmentTemplate + SegmentBase + EventStream + EventStreamElt
     TimeScale = 27, // f64
@@ -182,9 +234,9 @@ pub enum AttributeName {
 
     // MPD
     Type = 33,                       // String
-    AvailabilityStartTime = 34,      // f64
-    AvailabilityEndTime = 35,        // f64
-    PublishTime = 36,                // f64
+    AvailabilityStartTime = 34,      // f64 (milliseconds since the Unix epoch)
+    AvailabilityEndTime = 35,        // f64 (milliseconds since the Unix epoch)
+    PublishTime = 36,                // f64 (milliseconds since the Unix epoch)
     MinimumUpdatePeriod = 37,        // f64
     MinBufferTime = 38,              // f64
     TimeShiftBufferDepth = 39,       // f64
@@ -278,96 +330,395 @@ pub enum AttributeName {
 
     ServiceLocation = 72, // String
 
+    // ContentSteering
+    // (its text, the steering server's URL, being reported as `Text`)
+    /// `queryBeforeStart` attribute of `<ContentSteering>` elements.
+    QueryBeforeStart = 73, // u8 (bool)
+
+    /// `proxyServerURL` attribute of `<ContentSteering>` elements.
+    ProxyServerUrl = 74, // String
+
+    /// `defaultServiceLocation` attribute of `<ContentSteering>` elements:
+    /// space-separated `serviceLocation` of the `<BaseURL>` elements to use
+    /// before a steering manifest is loaded.
+    DefaultServiceLocation = 75, // String
+
     // SegmentTemplate
     EndNumber = 76, // f64
 
-    SupplementalCodecs = 77 // string
+    SupplementalCodecs = 77, // string
+
+    /// A `<BaseURL>` applying to the current `<Representation>`, resolved
+    /// against those of its parent elements and against the MPD's URL.
+    /// Only reported when BaseURLs are resolved (@see
+    /// `ParsingOptions::resolve_base_urls`), right before the
+    /// `<Representation>` closes, once per resolved URL.
+    ///
+    /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+    /// the resolved URL and its `serviceLocation` (empty if none).
+    ResolvedBaseUrl = 78,
+
+    /// SystemID of the `pssh` box of a `<cenc:pssh>` element, as 16 bytes.
+    /// Reported right after its `ContentProtectionCencPSSH`, if that box
+    /// could be parsed.
+    ContentProtectionPsshSystemId = 79,
+
+    /// A key id listed by that same `pssh` box (only for its version `1`), as
+    /// 16 bytes. Reported once per key id, after the
+    /// `ContentProtectionPsshSystemId`.
+    ContentProtectionPsshKeyId = 80,
+
+    /// A PlayReady Object, decoded from the base64 text of a `<mspr:pro>`
+    /// element.
+    ContentProtectionPlayReadyObject = 81, // ArrayBuffer
+
+    /// A key id listed by the WRM header of that PlayReady Object, as 16
+    /// bytes in the UUID byte order (the GUID one of PlayReady being
+    /// converted). Reported once per key id.
+    ContentProtectionPlayReadyKeyId = 82,
+
+    /// `LA_URL` (license server URL) of that WRM header.
+    ContentProtectionPlayReadyLaUrl = 83, // String
+
+    /// `LUI_URL` (license acquisition web page URL) of that WRM header.
+    ContentProtectionPlayReadyLuiUrl = 84, // String
+
+    /// Inner XML of the `CUSTOMATTRIBUTES` element of that WRM header.
+    ContentProtectionPlayReadyCustomAttributes = 85, // String
+
+    /// Content of a `<mspr:IsEncrypted>` element.
+    ContentProtectionPlayReadyIsEncrypted = 86, // bool
+
+    /// `robustness` attribute of a `<ContentProtection>` element.
+    ContentProtectionRobustness = 87, // String
+
+    /// `refId` attribute of a `<ContentProtection>` element, which may then
+    /// be referenced by other `<ContentProtection>` elements.
+    ContentProtectionRefId = 88, // String
+
+    /// `ref` attribute of a `<ContentProtection>` element. When it references
+    /// an element already encountered, the attributes and children of that
+    /// element are reported as if they were part of the referencing one.
+    ContentProtectionRef = 89, // String
+
+    /// Text of a `<dashif:Laurl>` element, the URL of a license server.
+    ///
+    /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+    /// the URL and its `licenseType` (empty if none).
+    ContentProtectionDashIfLaUrl = 90,
+
+    /// Text of a `<dashif:Authzurl>` element, the URL of an authorization
+    /// service, communicated in the same way than
+    /// `ContentProtectionDashIfLaUrl`.
+    ContentProtectionDashIfAuthzUrl = 91,
+
+    /// Text of a `<clearkey:Laurl>` element, the URL of a ClearKey license
+    /// server.
+    ///
+    /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+    /// the URL and its `Lic_type` (empty if none).
+    ContentProtectionClearKeyLaUrl = 92,
+
+    /// Number of audio channels described by an `<AudioChannelConfiguration>`
+    /// element, decoded from its `value` when its `schemeIdUri` is a known
+    /// one.
+    AudioChannelCount = 93, // f64
+
+    /// Space-separated labels of the speakers those audio channels are for
+    /// (e.g. `L R` for stereo or `C L R Ls Rs LFE` for 5.1), when indicated
+    /// by that scheme.
+    AudioChannelLayout = 94, // String
+
+    // ServiceDescription's children
+    /// `min` attribute of `<Latency>` (in milliseconds), `<PlaybackRate>`,
+    /// `<OperatingQuality>` and `<OperatingBandwidth>` (in bits per second)
+    /// elements.
+    Min = 95, // f64
+
+    /// `max` attribute of those same elements.
+    Max = 96, // f64
+
+    /// `target` attribute of `<Latency>`, `<OperatingQuality>` and
+    /// `<OperatingBandwidth>` elements.
+    Target = 97, // f64
+
+    /// `referenceId` attribute of `<Latency>` elements, the `id` of the
+    /// `<ProducerReferenceTime>` the latency is measured against.
+    ReferenceId = 98, // f64
+
+    /// `mediaType` attribute of `<OperatingQuality>` and
+    /// `<OperatingBandwidth>` elements.
+    MediaType = 99, // String
+
+    /// `maxDifference` attribute of `<OperatingQuality>` elements.
+    MaxDifference = 100, // f64
+
+    // ProducerReferenceTime
+    // (its `id` and `type` attributes being reported as `Id` and `Type`, as
+    // is the `type` of `<OperatingQuality>` elements)
+    /// `inband` attribute of `<ProducerReferenceTime>` elements.
+    Inband = 101, // u8 (bool)
+
+    /// `applicationScheme` attribute of `<ProducerReferenceTime>` elements.
+    ApplicationScheme = 102, // String
+
+    /// `wallClockTime` attribute of `<ProducerReferenceTime>` elements,
+    /// whether an xs:dateTime or an NTP timestamp.
+    WallClockTime = 103, // f64 (milliseconds since the Unix epoch)
+
+    /// `presentationTime` attribute of `<ProducerReferenceTime>` elements.
+    PresentationTime = 104, // f64, or u64 with exact timestamps
+
+    // Resync
+    /// `type` attribute of `<Resync>` elements, from `0` to `3`.
+    ResyncType = 105, // f64
+
+    /// `dT` attribute of `<Resync>` elements.
+    ResyncDT = 106, // f64
+
+    /// `dImax` attribute of `<Resync>` elements.
+    ResyncDIMax = 107, // f64
+
+    /// `dImin` attribute of `<Resync>` elements.
+    ResyncDIMin = 108, // f64
+
+    /// `marker` attribute of `<Resync>` elements.
+    ResyncMarker = 109, // u8 (bool)
+
+    // Preselection
+    // (its `id`, `lang` and `codecs` attributes being reported as `Id`,
+    // `Language` and `Codecs`)
+    /// `preselectionComponents` attribute of `<Preselection>` elements, as
+    /// written in the MPD.
+    PreselectionComponents = 110, // String
+
+    /// One of the components of a `<Preselection>`, reported once per id
+    /// listed in its `preselectionComponents` attribute, in the same order.
+    ///
+    /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+    /// the `id` of the `<AdaptationSet>` referenced (or containing the
+    /// `<ContentComponent>` referenced) and the `id` of the
+    /// `<ContentComponent>` referenced (empty if an `<AdaptationSet>` is).
+    PreselectionComponent = 111,
+
+    /// `tag` attribute of `<Preselection>` elements.
+    PreselectionTag = 112, // String
+
+    // Event
+    /// Binary data of an event: the `message_data` of an `emsg` box or, for
+    /// `<Event>` elements, their `messageData` attribute in UTF-8 or their
+    /// content once decoded when their `contentEncoding` is `base64`.
+    MessageData = 116, // Vec<u8>
+
+    /// The `<Event>` element itself, as the raw XML read in the MPD: its
+    /// opening tag with all its attributes, its content and its closing tag.
+    ///
+    /// This allows the JS-side to construct the corresponding `Element`
+    /// without having to keep the whole MPD.
+    EventStreamEltXml = 117, // String
 }
 
 impl TagName {
     /// Signal a new tag opening to the application
-    pub fn report_tag_open(self) {
-        debug_assert!(self as u64 <= u8::MAX as u64);
-
-        // UNSAFE: We're using FFI, but there should be no risk at all here
-        unsafe { onTagOpen(self) };
+    #[inline(always)]
+    pub fn report_tag_open<S: MpdEventSink>(self, sink: &mut S) {
+        sink.tag_open(self);
     }
 
     /// Signal that a previously-open tag closed to the application
-    pub fn report_tag_close(self) {
-        debug_assert!(self as u64 <= u8::MAX as u64);
-
-        // UNSAFE: We're using FFI, but there should be no risk at all here
-        unsafe { onTagClose(self) };
+    #[inline(always)]
+    pub fn report_tag_close<S: MpdEventSink>(self, sink: &mut S) {
+        sink.tag_close(self);
     }
 }
 
-use crate::reportable::ReportableAttribute;
-use crate::utils;
+use crate::sink::AttrValue;
+use crate::utils::{self, DurationParsingMode};
 
 impl AttributeName {
     #[inline(always)]
-    pub fn report<T: ReportableAttribute>(self, val: T) {
-        val.report_as_attr(self)
+    pub fn report<'a, T: Into<AttrValue<'a>>, S: MpdEventSink>(self, val: T, sink: &mut S) {
+        sink.attribute(self, val.into())
     }
 
-    pub fn try_report_as_string(self, attr: &quick_xml::events::attributes::Attribute) {
+    pub fn try_report_as_string<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match attr.unescape_value() {
-            Ok(val) => self.report(val),
-            Err(_) => ParsingError("Could not escape original value".to_owned()).report_err(),
+            Ok(val) => self.report(&*val, sink),
+            Err(_) => ParsingError::new(
+                ParsingErrorKind::InvalidEncoding,
+                "Could not escape original value",
+            )
+            .in_attribute(attr.key.as_ref())
+            .report_err(sink),
         }
     }
 
-    pub fn try_report_as_f64(self, attr: &quick_xml::events::attributes::Attribute) {
+    /// Same as `try_report_as_string` for URL templates, also reporting an
+    /// error if the template is invalid (@see `UrlTemplate`).
+    pub fn try_report_as_template<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        self.try_report_as_string(attr, sink);
+        if let Ok(val) = attr.unescape_value() {
+            if let Err(err) = UrlTemplate::compile(&val) {
+                err.in_attribute(attr.key.as_ref()).report_err(sink);
+            }
+        }
+    }
+
+    pub fn try_report_as_f64<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match utils::parse_f64(&attr.value) {
-            Ok(val) => self.report(val),
-            Err(error) => error.report_err(),
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
+        }
+    }
+
+    pub fn try_report_as_iso_8601_duration<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        match utils::parse_iso_8601_duration(&attr.value, DurationParsingMode::Lenient) {
+            Ok(duration) => {
+                if duration.is_approximate {
+                    sink.log(&format!(
+                        "Approximated ISO 8601 duration {}: years are considered to last \
+                         365 days and months 30 days.",
+                        String::from_utf8_lossy(&attr.value)
+                    ));
+                }
+                self.report(duration.seconds, sink)
+            }
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_iso_8601_duration(self, attr: &quick_xml::events::attributes::Attribute) {
-        match utils::parse_iso_8601_duration(&attr.value) {
-            Ok(val) => self.report(val),
-            Err(error) => error.report_err(),
+    pub fn try_report_as_iso_8601_date_time<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        match utils::parse_iso_8601_date_time(&attr.value) {
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_u64(self, attr: &quick_xml::events::attributes::Attribute) {
+    /// @see `utils::parse_wall_clock_time`
+    pub fn try_report_as_wall_clock_time<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        match utils::parse_wall_clock_time(&attr.value) {
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
+        }
+    }
+
+    pub fn try_report_as_u64<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        match utils::parse_u64(&attr.value) {
+            Ok(val) => self.report(val as f64, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
+        }
+    }
+
+    /// Same as `try_report_as_u64`, but the value is reported as an exact
+    /// integer, for timestamps which may exceed the precision of an f64.
+    pub fn try_report_as_timestamp<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match utils::parse_u64(&attr.value) {
-            Ok(val) => self.report(val as f64),
-            Err(error) => error.report_err(),
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_u64_or_bool(self, attr: &quick_xml::events::attributes::Attribute) {
+    pub fn try_report_as_u64_or_bool<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match utils::parse_u64_or_bool(&attr.value) {
-            Ok(val) => self.report(val),
-            Err(error) => error.report_err(),
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_bool(self, attr: &quick_xml::events::attributes::Attribute) {
+    pub fn try_report_as_bool<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match utils::parse_bool(&attr.value) {
-            Ok(val) => self.report(val),
-            Err(error) => error.report_err(),
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_range(self, attr: &quick_xml::events::attributes::Attribute) {
+    pub fn try_report_as_range<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
         match utils::parse_byte_range(&attr.value) {
-            Ok(val) => self.report(val),
-            Err(error) => error.report_err(),
+            Ok(val) => self.report(val, sink),
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
+        }
+    }
+
+    /// Report each key id of a whitespace-separated list of key ids as 16
+    /// bytes (@see `utils::parse_key_ids`), or only an error if one of them
+    /// is invalid.
+    pub fn try_report_as_key_ids<S: MpdEventSink>(
+        self,
+        attr: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
+    ) {
+        match utils::parse_key_ids(&attr.value) {
+            Ok(key_ids) => {
+                for key_id in &key_ids {
+                    self.report(AttrValue::Bytes(key_id), sink);
+                }
+            }
+            Err(error) => error.in_attribute(attr.key.as_ref()).report_err(sink),
         }
     }
 
-    pub fn try_report_as_key_value(
+    pub fn try_report_as_key_value<S: MpdEventSink>(
         self,
         key: &[u8],
         value: &quick_xml::events::attributes::Attribute,
+        sink: &mut S,
     ) {
+        let key = match std::str::from_utf8(key) {
+            Ok(key) => key,
+            Err(err) => return ParsingError::from(err).report_err(sink),
+        };
         match value.unescape_value() {
-            Ok(val) => self.report((key, val)),
-            Err(_) => ParsingError("Could not escape original value".to_owned()).report_err(),
+            Ok(val) => self.report(AttrValue::KeyValue(key, &val), sink),
+            Err(_) => ParsingError::new(
+                ParsingErrorKind::InvalidEncoding,
+                "Could not escape original value",
+            )
+            .in_attribute(value.key.as_ref())
+            .report_err(sink),
         }
     }
 }
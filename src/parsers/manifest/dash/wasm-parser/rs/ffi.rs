@@ -0,0 +1,527 @@
+use crate::emsg;
+use crate::errors::Result;
+use crate::errors::{ParsingError, ParsingErrorKind};
+use crate::events::{AttributeName, CustomEventType, TagName};
+use crate::model::Scheme;
+use crate::processor::{MPDProcessor, MPDPushProcessor, ParsingOptions};
+use crate::reader::MPDReader;
+use crate::sink::{AttrValue, MpdEventSink};
+use crate::template::{TemplateValues, UrlTemplate};
+use std::cell::{Cell, RefCell};
+use std::io::{BufReader, Read};
+
+extern "C" {
+    /// JS callback called each time a new known tag is encountered in the MPD.
+    ///
+    /// The `tag_name` corresponds to the value of the TagName enum (@see
+    /// events), casted as a single byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_name` - u8 describing the name of the tag encountered.
+    fn onTagOpen(tag_name: TagName);
+
+    /// JS callback called each time a previously-opened known tag is encountered in
+    /// the MPD now closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_name` - u8 describing the name of the tag which just closed.
+    fn onTagClose(tag_name: TagName);
+
+    /// JS Callback called when a new attribute has been parsed in the last
+    /// encountered element.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_name` - u8 describing the name of the attribute that has just
+    ///   been encountered.
+    ///
+    /// * `ptr` - Pointer to the beginning of the corresponding data in the
+    ///   WebAssembly's linear memory.
+    ///
+    /// * `len` - Length of the data - starting at `ptr` - in bytes.
+    fn onAttribute(attr_name: AttributeName, ptr: *const u8, len: usize);
+
+    /// JS callback for other specific operations, for example logging and warnings.
+    ///
+    /// # Arguments
+    ///
+    /// * `evt_type` - Identify the type of event that is wanted.
+    ///
+    /// * `ptr` - Pointer to the beginning of the corresponding data in the
+    ///   WebAssembly's linear memory.
+    ///
+    /// * `len` - Length of the data - starting at `ptr` - in bytes.
+    fn onCustomEvent(evt_type: CustomEventType, ptr: *const u8, len: usize);
+
+    /// JS callback allowing to read data from the MPD, which is stored in the
+    /// JS-side.
+    ///
+    /// This function returns the number of bytes that have been read and put at
+    /// `ptr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ptr` - Pointer to where the MPD data should be set, in WebAssembly's
+    ///   linear memory.
+    ///
+    /// * `size` - Optimal length of data that is wanted, in bytes.
+    ///   Less data (but not more) can be read. The true read length is returned
+    ///   by this function.
+    pub(crate) fn readNext(ptr: *const u8, size: usize) -> usize;
+}
+
+/// `MpdEventSink` implementation forwarding every event to the JS-side through
+/// the corresponding FFI callbacks.
+///
+/// Attributes' values are communicated through a pointer and a length to their
+/// data in WebAssembly's linear memory, under the format documented for each
+/// `AttributeName` variant.
+///
+/// Integers which may exceed the precision of an f64 (`AttrValue::U64` and
+/// the `SegmentObject`s of a `SegmentTimeline`) are by default converted to
+/// f64 values. When `exact_timestamps` is set, they are instead communicated
+/// as 64-bit integers in little endian (which can also be read as couples
+/// of u32 values, the low one first, when BigInt cannot be used), the
+/// `SegmentObject`s' `repeat_count` being signed.
+pub struct JsEventSink {
+    exact_timestamps: bool,
+}
+
+impl JsEventSink {
+    /// Creates a new JsEventSink from the flags given to `init`.
+    fn from_flags(flags: u32) -> Self {
+        JsEventSink {
+            exact_timestamps: flags & FLAG_EXACT_TIMESTAMPS != 0,
+        }
+    }
+
+    #[inline(always)]
+    fn report_attr_bytes(attr_name: AttributeName, val: &[u8]) {
+        debug_assert!(attr_name as u64 <= u8::MAX as u64);
+
+        // UNSAFE: We're using FFI, so we don't know how the pointer is used.
+        // Hopefully, the JavaScript-side should clone that value synchronously.
+        unsafe { onAttribute(attr_name, val.as_ptr(), val.len()) };
+    }
+}
+
+impl MpdEventSink for JsEventSink {
+    #[inline(always)]
+    fn tag_open(&mut self, tag_name: TagName) {
+        debug_assert!(tag_name as u64 <= u8::MAX as u64);
+
+        // UNSAFE: We're using FFI, but there should be no risk at all here
+        unsafe { onTagOpen(tag_name) };
+    }
+
+    #[inline(always)]
+    fn tag_close(&mut self, tag_name: TagName) {
+        debug_assert!(tag_name as u64 <= u8::MAX as u64);
+
+        // UNSAFE: We're using FFI, but there should be no risk at all here
+        unsafe { onTagClose(tag_name) };
+    }
+
+    fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+        // Note: numbers are communicated in the platform's native endianness,
+        // which is little endian in WebAssembly, as the JS-side reads them
+        // directly in the linear memory.
+        match value {
+            AttrValue::String(val) => Self::report_attr_bytes(attr_name, val.as_bytes()),
+            AttrValue::F64(val) => Self::report_attr_bytes(attr_name, &val.to_ne_bytes()),
+            AttrValue::U64(val) if self.exact_timestamps => {
+                Self::report_attr_bytes(attr_name, &val.to_le_bytes())
+            }
+            AttrValue::U64(val) => Self::report_attr_bytes(attr_name, &(val as f64).to_ne_bytes()),
+            AttrValue::Bool(val) => Self::report_attr_bytes(attr_name, &[val as u8]),
+            AttrValue::Range(start, end) => {
+                let mut msg = [0u8; 16];
+                msg[..8].copy_from_slice(&start.to_ne_bytes());
+                msg[8..].copy_from_slice(&end.to_ne_bytes());
+                Self::report_attr_bytes(attr_name, &msg);
+            }
+            AttrValue::Timeline(val) => {
+                // Each segment is made of five 8-bytes values: its start,
+                // duration, repeat count, number and chunk count, the last two
+                // being NaN (or all bits set for exact timestamps) if unknown.
+                let mut msg = Vec::with_capacity(val.len() * 40);
+                for segment in val {
+                    if self.exact_timestamps {
+                        msg.extend(segment.start.to_le_bytes());
+                        msg.extend(segment.duration.to_le_bytes());
+                        msg.extend(segment.repeat_count.to_le_bytes());
+                        msg.extend(segment.number.unwrap_or(u64::MAX).to_le_bytes());
+                        msg.extend(segment.chunk_count.unwrap_or(u64::MAX).to_le_bytes());
+                    } else {
+                        msg.extend((segment.start as f64).to_ne_bytes());
+                        msg.extend((segment.duration as f64).to_ne_bytes());
+                        msg.extend((segment.repeat_count as f64).to_ne_bytes());
+                        let number = segment.number.map_or(f64::NAN, |n| n as f64);
+                        msg.extend(number.to_ne_bytes());
+                        let chunk_count = segment.chunk_count.map_or(f64::NAN, |k| k as f64);
+                        msg.extend(chunk_count.to_ne_bytes());
+                    }
+                }
+                Self::report_attr_bytes(attr_name, &msg);
+            }
+            AttrValue::Bytes(val) => Self::report_attr_bytes(attr_name, val),
+            AttrValue::KeyValue(key, val) => {
+                let mut msg = Vec::with_capacity(key.len() + val.len() + 8);
+                msg.extend((key.len() as u32).to_be_bytes());
+                msg.extend(key.as_bytes());
+                msg.extend((val.len() as u32).to_be_bytes());
+                msg.extend(val.as_bytes());
+                Self::report_attr_bytes(attr_name, &msg);
+            }
+        }
+    }
+
+    /// Errors are reported with the following binary layout (@see
+    /// `CustomEventType::Error`):
+    ///   - the `ParsingErrorKind`, as an u8
+    ///   - the offset, as an f64 (NaN if unknown)
+    ///   - the length of the path, as a big-endian u32, followed by the path
+    ///   - the message, until the end
+    fn error(&mut self, err: &ParsingError) {
+        let offset = err.offset.map_or(f64::NAN, |offset| offset as f64);
+        let mut msg = Vec::with_capacity(13 + err.path.len() + err.message.len());
+        msg.push(err.kind as u8);
+        msg.extend(offset.to_ne_bytes());
+        msg.extend((err.path.len() as u32).to_be_bytes());
+        msg.extend(err.path.as_bytes());
+        msg.extend(err.message.as_bytes());
+
+        // UNSAFE: We're using FFI, so we don't know how the pointer is used.
+        // Hopefully, the JavaScript-side should clone that value synchronously.
+        unsafe { onCustomEvent(CustomEventType::Error, msg.as_ptr(), msg.len()) };
+    }
+
+    fn log(&mut self, message: &str) {
+        // UNSAFE: We're using FFI, so we don't know how the pointer is used.
+        // Hopefully, the JavaScript-side should clone that value synchronously.
+        unsafe { onCustomEvent(CustomEventType::Log, message.as_ptr(), message.len()) };
+    }
+}
+
+/// Value returned by parsing functions when the parsing went on until the
+/// end (errors may still have been reported).
+const STATUS_OK: u8 = 0;
+
+/// Value returned by parsing functions when the parsing has been stopped
+/// because of an error, in strict mode.
+const STATUS_FAILURE: u8 = 1;
+
+/// Bit of the `init` flags enabling the strict mode.
+const FLAG_STRICT: u32 = 1;
+
+/// Bit of the `init` flags enabling the exact encoding of timestamps (@see
+/// `JsEventSink`).
+const FLAG_EXACT_TIMESTAMPS: u32 = 2;
+
+/// Bit of the `init` flags enabling the resolution of negative repeat counts
+/// (@see `ParsingOptions::resolve_repeat_counts`).
+const FLAG_RESOLVE_REPEAT_COUNTS: u32 = 4;
+
+/// Bit of the `init` flags enabling the resolution of BaseURLs (@see
+/// `ParsingOptions::resolve_base_urls`).
+const FLAG_RESOLVE_BASE_URLS: u32 = 8;
+
+fn status(result: Result<()>) -> u8 {
+    match result {
+        Ok(()) => STATUS_OK,
+        Err(_) => STATUS_FAILURE,
+    }
+}
+
+/// Configure how the next MPDs will be parsed.
+///
+/// # Arguments
+///
+/// * `flags` - Bit field of options. The bit `1` enables the strict mode
+///   (@see `ParsingOptions::strict`), the bit `2` the exact encoding of
+///   timestamps (@see `JsEventSink`) and the bit `4` the resolution of
+///   negative repeat counts (@see `ParsingOptions::resolve_repeat_counts`).
+///   The bit `8` enables the resolution of BaseURLs (@see
+///   `ParsingOptions::resolve_base_urls`), in which case the URL of the MPD
+///   is then read through the `readNext` callback, in UTF-8 (empty if
+///   unknown).
+#[no_mangle]
+pub extern "C" fn init(flags: u32) {
+    FLAGS.with(|f| f.set(flags));
+    let mut document_url = None;
+    if flags & FLAG_RESOLVE_BASE_URLS != 0 {
+        let mut data = Vec::new();
+        if (MPDReader {}).read_to_end(&mut data).is_ok() {
+            document_url = String::from_utf8(data).ok().filter(|url| !url.is_empty());
+        }
+    }
+    DOCUMENT_URL.with(|u| *u.borrow_mut() = document_url);
+    PERIOD_DURATIONS.with(|d| d.borrow_mut().clear());
+}
+
+/// Indicate the durations of the Periods of the next MPDs parsed (@see
+/// `ParsingOptions::period_durations`), until the next `init` call.
+///
+/// They are read through the `readNext` callback, as little-endian f64
+/// values in seconds, `NaN` for unknown ones.
+#[no_mangle]
+pub extern "C" fn set_period_durations() {
+    let mut data = Vec::new();
+    let durations = if (MPDReader {}).read_to_end(&mut data).is_ok() {
+        data.chunks_exact(8)
+            .map(|bytes| {
+                let mut buf = [0; 8];
+                buf.copy_from_slice(bytes);
+                Some(f64::from_le_bytes(buf)).filter(|duration| !duration.is_nan())
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    PERIOD_DURATIONS.with(|d| *d.borrow_mut() = durations);
+}
+
+fn parsing_options(flags: u32) -> ParsingOptions {
+    ParsingOptions {
+        strict: flags & FLAG_STRICT != 0,
+        resolve_repeat_counts: flags & FLAG_RESOLVE_REPEAT_COUNTS != 0,
+        resolve_base_urls: flags & FLAG_RESOLVE_BASE_URLS != 0,
+        document_url: DOCUMENT_URL.with(|u| u.borrow().clone()),
+        period_durations: PERIOD_DURATIONS.with(|d| d.borrow().clone()),
+    }
+}
+
+/// Parse the whole MPD read through the `readNext` callback.
+///
+/// Returns `1` if the parsing has been stopped because of an error in strict
+/// mode (that error having been reported), `0` otherwise.
+#[no_mangle]
+pub extern "C" fn parse() -> u8 {
+    let buf_read = BufReader::new(MPDReader {});
+    let flags = FLAGS.with(Cell::get);
+    let sink = JsEventSink::from_flags(flags);
+    let mut processor = MPDProcessor::with_options(buf_read, sink, parsing_options(flags));
+    status(processor.process_tags())
+}
+
+thread_local! {
+    /// Flags set through `init`.
+    static FLAGS: Cell<u32> = const { Cell::new(0) };
+
+    /// URL of the MPD, set through `init`.
+    static DOCUMENT_URL: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    /// Durations of the Periods, set through `set_period_durations`.
+    static PERIOD_DURATIONS: RefCell<Vec<Option<f64>>> = const { RefCell::new(Vec::new()) };
+
+    /// MPDPushProcessor used when the MPD is parsed chunk by chunk, between a
+    /// `parse_start` and a `parse_end` call.
+    static PUSH_PROCESSOR: RefCell<Option<MPDPushProcessor<JsEventSink>>> = const { RefCell::new(None) };
+}
+
+/// Begin parsing an MPD chunk by chunk, for example while it is still being
+/// loaded.
+///
+/// Chunks are then given by calling `parse_chunk` each time new data is
+/// available, and `parse_end` once the whole MPD has been communicated.
+/// Calling `parse_start` again before `parse_end` discards the previous
+/// parsing operation.
+#[no_mangle]
+pub extern "C" fn parse_start() {
+    let flags = FLAGS.with(Cell::get);
+    let sink = JsEventSink::from_flags(flags);
+    let processor = MPDPushProcessor::with_options(sink, parsing_options(flags));
+    PUSH_PROCESSOR.with(|p| *p.borrow_mut() = Some(processor));
+}
+
+/// Parse the next chunk of the MPD begun with `parse_start`.
+///
+/// The chunk is read through the `readNext` callback until it returns `0`,
+/// and every complete element it contains is directly reported.
+///
+/// Returns `1` if the parsing has been stopped because of an error in strict
+/// mode, `0` otherwise.
+#[no_mangle]
+pub extern "C" fn parse_chunk() -> u8 {
+    let mut buf = [0u8; 16384];
+    let mut reader = MPDReader {};
+    PUSH_PROCESSOR.with(|p| {
+        if let Some(processor) = p.borrow_mut().as_mut() {
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(size) => {
+                        if let Err(err) = processor.feed(&buf[..size]) {
+                            return status(Err(err));
+                        }
+                    }
+                }
+            }
+        }
+        STATUS_OK
+    })
+}
+
+/// Signal that the whole MPD begun with `parse_start` has been given through
+/// `parse_chunk` calls, so its parsing can be completed.
+///
+/// Returns `1` if the parsing has been stopped because of an error in strict
+/// mode, `0` otherwise.
+#[no_mangle]
+pub extern "C" fn parse_end() -> u8 {
+    match PUSH_PROCESSOR.with(|p| p.borrow_mut().take()) {
+        Some(processor) => status(processor.finish().1),
+        None => STATUS_OK,
+    }
+}
+
+/// Bits of the `present` argument of `expand_template`, indicating which
+/// values are given.
+const TEMPLATE_HAS_REPRESENTATION_ID: u32 = 1;
+const TEMPLATE_HAS_BANDWIDTH: u32 = 2;
+const TEMPLATE_HAS_NUMBER: u32 = 4;
+const TEMPLATE_HAS_TIME: u32 = 8;
+const TEMPLATE_HAS_SUB_NUMBER: u32 = 16;
+
+/// Expand a `<SegmentTemplate>` URL template (@see `UrlTemplate`).
+///
+/// The template and the Representation's id are read through the `readNext`
+/// callback, as the length in bytes of the id as a big-endian u32, followed
+/// by the id and by the template, both in UTF-8.
+/// The resulting URL is communicated through a
+/// `CustomEventType::ExpandedTemplate` event.
+///
+/// Returns `1` if the template could not be expanded (that error having been
+/// reported), `0` otherwise.
+///
+/// # Arguments
+///
+/// * `present` - Bit field indicating which values are given: `1` for the
+///   Representation's id, `2` for `bandwidth`, `4` for `number`, `8` for
+///   `time` and `16` for `sub_number`.
+#[no_mangle]
+pub extern "C" fn expand_template(
+    present: u32,
+    bandwidth: u64,
+    number: u64,
+    time: u64,
+    sub_number: u64,
+) -> u8 {
+    let mut data = Vec::new();
+    let result = MPDReader {}
+        .read_to_end(&mut data)
+        .map_err(ParsingError::from)
+        .and_then(|_| {
+            let invalid = || ParsingError::new(ParsingErrorKind::InvalidTemplate, "Invalid data.");
+            let id_len = data.get(..4).ok_or_else(invalid)?;
+            let id_len = u32::from_be_bytes([id_len[0], id_len[1], id_len[2], id_len[3]]) as usize;
+            // `id_len` comes from JS: it may overflow on 32-bit targets
+            let id_end = 4usize.checked_add(id_len).ok_or_else(invalid)?;
+            let id = data.get(4..id_end).ok_or_else(invalid)?;
+            let id = std::str::from_utf8(id)?;
+            let template = std::str::from_utf8(&data[id_end..])?;
+            let has = |flag: u32| present & flag != 0;
+            let values = TemplateValues {
+                representation_id: Some(id).filter(|_| has(TEMPLATE_HAS_REPRESENTATION_ID)),
+                bandwidth: Some(bandwidth).filter(|_| has(TEMPLATE_HAS_BANDWIDTH)),
+                number: Some(number).filter(|_| has(TEMPLATE_HAS_NUMBER)),
+                time: Some(time).filter(|_| has(TEMPLATE_HAS_TIME)),
+                sub_number: Some(sub_number).filter(|_| has(TEMPLATE_HAS_SUB_NUMBER)),
+            };
+            UrlTemplate::compile(template)?.expand(&values)
+        });
+    match result {
+        Ok(url) => {
+            // UNSAFE: We're using FFI, so we don't know how the pointer is used.
+            // Hopefully, the JavaScript-side should clone that value synchronously.
+            unsafe { onCustomEvent(CustomEventType::ExpandedTemplate, url.as_ptr(), url.len()) };
+            STATUS_OK
+        }
+        Err(err) => {
+            JsEventSink::from_flags(0).error(&err);
+            STATUS_FAILURE
+        }
+    }
+}
+
+/// Value of a scheme's `value` length signaling that it has none, in the data
+/// read by `parse_emsg_boxes`.
+const NO_SCHEME_VALUE: u32 = 0xFFFF_FFFF;
+
+/// Report the events of the `emsg` boxes of a media segment whose scheme is
+/// declared by one of its `<InbandEventStream>` elements (@see
+/// `emsg::report_inband_events`).
+///
+/// The schemes and the segment are read through the `readNext` callback: the
+/// number of schemes as a big-endian u32, then for each of them the length in
+/// bytes of its `schemeIdUri` as a big-endian u32 followed by it in UTF-8, and
+/// the same for its `value` (whose length is `0xFFFFFFFF` if it has none),
+/// and finally the whole segment.
+///
+/// Returns `1` if the schemes could not be read (that error having been
+/// reported), `0` otherwise.
+///
+/// # Arguments
+///
+/// * `segment_start` - Earliest presentation time of the segment, in seconds.
+#[no_mangle]
+pub extern "C" fn parse_emsg_boxes(segment_start: f64) -> u8 {
+    let mut data = Vec::new();
+    let flags = FLAGS.with(Cell::get);
+    let mut sink = JsEventSink::from_flags(flags);
+    let result = MPDReader {}
+        .read_to_end(&mut data)
+        .map_err(ParsingError::from)
+        .and_then(|_| read_inband_event_streams(&data));
+    match result {
+        Ok((inband_event_streams, segment_pos)) => {
+            emsg::report_inband_events(
+                &data[segment_pos..],
+                &inband_event_streams,
+                segment_start,
+                &mut sink,
+            );
+            STATUS_OK
+        }
+        Err(err) => {
+            sink.error(&err);
+            STATUS_FAILURE
+        }
+    }
+}
+
+/// Read the schemes at the start of the data given to `parse_emsg_boxes`,
+/// returning them along with the position of the segment following them.
+fn read_inband_event_streams(data: &[u8]) -> Result<(Vec<Scheme>, usize)> {
+    let invalid = || ParsingError::new(ParsingErrorKind::InvalidEmsg, "Invalid scheme data.");
+    let mut pos = 0;
+    let read_u32 = |pos: &mut usize| -> Result<u32> {
+        let bytes = data.get(*pos..*pos + 4).ok_or_else(invalid)?;
+        *pos += 4;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let read_string = |pos: &mut usize, len: u32| -> Result<String> {
+        // A length overflowing on 32-bit targets cannot be in `data` either
+        let end = pos.checked_add(len as usize).ok_or_else(invalid)?;
+        let bytes = data.get(*pos..end).ok_or_else(invalid)?;
+        *pos = end;
+        Ok(std::str::from_utf8(bytes)?.to_owned())
+    };
+    let count = read_u32(&mut pos)?;
+    let mut schemes = Vec::new();
+    for _ in 0..count {
+        let len = read_u32(&mut pos)?;
+        let scheme_id_uri = read_string(&mut pos, len)?;
+        let value = match read_u32(&mut pos)? {
+            NO_SCHEME_VALUE => None,
+            len => Some(read_string(&mut pos, len)?),
+        };
+        schemes.push(Scheme {
+            scheme_id_uri: Some(scheme_id_uri),
+            value,
+        });
+    }
+    Ok((schemes, pos))
+}
@@ -0,0 +1,148 @@
+//! Reading of the ISOBMFF boxes found in MPDs (`pssh`) or in the media
+//! segments they describe (`emsg`).
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+
+/// Size and type of an ISOBMFF box.
+#[derive(Debug)]
+pub(crate) struct BoxHeader {
+    /// Size of the whole box, in bytes.
+    pub size: usize,
+    pub box_type: [u8; 4],
+    /// Size of the `size`, `largesize` and `type` fields.
+    pub header_size: usize,
+}
+
+impl BoxHeader {
+    /// Read the header of the box starting at `pos` in `data`, checking that
+    /// the whole box is contained in it.
+    ///
+    /// Errors are of the given `kind`.
+    pub fn read(data: &[u8], pos: usize, kind: ParsingErrorKind) -> Result<BoxHeader> {
+        let mut reader = BoxReader::new(data, pos, kind);
+        let size = reader.read_u32("size")?;
+        let mut box_type = [0u8; 4];
+        box_type.copy_from_slice(reader.read_bytes(4, "type")?);
+        let size = match size {
+            0 => (data.len() - pos) as u64,
+            1 => reader.read_u64("largesize")?,
+            size => u64::from(size),
+        };
+        let header_size = reader.pos - pos;
+        if size < header_size as u64 || size > (data.len() - pos) as u64 {
+            return Err(reader.error(format!(
+                "Invalid size for the {:?} box at offset {}: {} bytes, while {} remain.",
+                String::from_utf8_lossy(&box_type),
+                pos,
+                size,
+                data.len() - pos
+            )));
+        }
+        Ok(BoxHeader {
+            size: size as usize,
+            box_type,
+            header_size,
+        })
+    }
+}
+
+/// Reads the big-endian fields of an ISOBMFF box one after the other,
+/// reporting errors of the given `ParsingErrorKind`.
+pub(crate) struct BoxReader<'a> {
+    data: &'a [u8],
+    pub pos: usize,
+    kind: ParsingErrorKind,
+}
+
+impl<'a> BoxReader<'a> {
+    /// Creates a new `BoxReader` reading `data` from `pos`.
+    pub fn new(data: &'a [u8], pos: usize, kind: ParsingErrorKind) -> Self {
+        BoxReader { data, pos, kind }
+    }
+
+    /// Creates an error of this reader's kind.
+    pub fn error<T: Into<String>>(&self, message: T) -> ParsingError {
+        ParsingError::new(self.kind, message)
+    }
+
+    /// Read the next `len` bytes of the field `field`.
+    pub fn read_bytes(&mut self, len: usize, field: &str) -> Result<&'a [u8]> {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.data.len() => {
+                let bytes = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(bytes)
+            }
+            _ => Err(self.error(format!(
+                "Truncated box: {} needs {} bytes at offset {} but only {} remain.",
+                field,
+                len,
+                self.pos,
+                self.data.len() - self.pos
+            ))),
+        }
+    }
+
+    pub fn read_u32(&mut self, field: &str) -> Result<u32> {
+        let bytes = self.read_bytes(4, field)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_u64(&mut self, field: &str) -> Result<u64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8, field)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Read a 16-byte identifier, such as a UUID.
+    pub fn read_id(&mut self, field: &str) -> Result<[u8; 16]> {
+        let mut id = [0u8; 16];
+        id.copy_from_slice(self.read_bytes(16, field)?);
+        Ok(id)
+    }
+
+    /// Read a null-terminated UTF-8 string.
+    pub fn read_string(&mut self, field: &str) -> Result<String> {
+        let remaining = &self.data[self.pos..];
+        let len = remaining.iter().position(|c| *c == 0).ok_or_else(|| {
+            self.error(format!(
+                "Truncated box: {} at offset {} is not null-terminated.",
+                field, self.pos
+            ))
+        })?;
+        let string = String::from_utf8(remaining[..len].to_vec())
+            .map_err(|_| self.error(format!("Invalid UTF-8 in {}.", field)))?;
+        self.pos += len + 1;
+        Ok(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_box_header() {
+        let mut data = vec![0, 0, 0, 1];
+        data.extend(b"free");
+        data.extend(20u64.to_be_bytes());
+        data.extend([0; 4]);
+        let header = BoxHeader::read(&data, 0, ParsingErrorKind::InvalidEmsg).unwrap();
+        assert_eq!(
+            (header.size, &header.box_type, header.header_size),
+            (20, b"free", 16)
+        );
+
+        data.extend([0, 0, 0, 0]);
+        data.extend(b"mdat");
+        let header = BoxHeader::read(&data, 20, ParsingErrorKind::InvalidEmsg).unwrap();
+        assert_eq!((header.size, header.header_size), (8, 8));
+
+        let err = BoxHeader::read(&data[..19], 0, ParsingErrorKind::InvalidPssh).unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidPssh);
+        assert_eq!(
+            err.message,
+            "Invalid size for the \"free\" box at offset 0: 20 bytes, while 19 remain."
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Minimal JSON (RFC 8259) parser, for the few JSON documents linked to an
+//! MPD, such as Content Steering manifests.
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+
+    /// Members of an object, in the order they are written.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Parse `data`, which should contain a single JSON value.
+    ///
+    /// On error, returns a message describing it.
+    pub(crate) fn parse(data: &[u8]) -> Result<JsonValue, String> {
+        let mut parser = Parser { data, pos: 0 };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.pos < data.len() {
+            return Err(parser.error("unexpected data after the JSON value"));
+        }
+        Ok(value)
+    }
+
+    /// Returns the value of the member `key` if this is an object containing
+    /// it (the last one if it appears several times).
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum depth of nested arrays and objects, to avoid overflowing the stack
+/// on malicious documents.
+const MAX_DEPTH: u32 = 64;
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("Invalid JSON at offset {}: {}.", self.pos, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.data.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume `expected` if it comes next.
+    fn eat(&mut self, expected: &[u8]) -> bool {
+        if self.data[self.pos..].starts_with(expected) {
+            self.pos += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self, depth: u32) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.data.get(self.pos) {
+            Some(b'{') => self.parse_object(depth + 1),
+            Some(b'[') => self.parse_array(depth + 1),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ if self.eat(b"null") => Ok(JsonValue::Null),
+            _ if self.eat(b"true") => Ok(JsonValue::Bool(true)),
+            _ if self.eat(b"false") => Ok(JsonValue::Bool(false)),
+            None => Err(self.error("unexpected end of data")),
+            Some(_) => Err(self.error("expected a value")),
+        }
+    }
+
+    fn parse_object(&mut self, depth: u32) -> Result<JsonValue, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("too many nested values"));
+        }
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"}") {
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.data.get(self.pos) != Some(&b'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let name = self.parse_string()?;
+            self.skip_whitespace();
+            if !self.eat(b":") {
+                return Err(self.error("expected ':'"));
+            }
+            let value = self.parse_value(depth)?;
+            members.push((name, value));
+            self.skip_whitespace();
+            if self.eat(b"}") {
+                return Ok(JsonValue::Object(members));
+            }
+            if !self.eat(b",") {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: u32) -> Result<JsonValue, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("too many nested values"));
+        }
+        self.pos += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b"]") {
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value(depth)?);
+            self.skip_whitespace();
+            if self.eat(b"]") {
+                return Ok(JsonValue::Array(values));
+            }
+            if !self.eat(b",") {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.data.get(self.pos) {
+            self.pos += 1;
+        }
+        // The characters read are all ASCII
+        let number = std::str::from_utf8(&self.data[start..self.pos]).unwrap_or_default();
+        match number.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(JsonValue::Number(number)),
+            _ => {
+                self.pos = start;
+                Err(self.error("invalid number"))
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            match self.data.get(self.pos) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = match self.data.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.parse_unicode_escape()?,
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    bytes.extend(escaped.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(c) if *c < 0x20 => return Err(self.error("control character in string")),
+                Some(c) => {
+                    bytes.push(*c);
+                    self.pos += 1;
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+
+    /// Parse the `XXXX` of a `\uXXXX` escape sequence, `pos` being on the
+    /// `u`, as well as the following one if they form a surrogate pair.
+    ///
+    /// `pos` is left on the last character of the sequence.
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.data[self.pos + 1..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    /// Parse the 4 hexadecimal digits following `pos`, moving `pos` to the
+    /// last one.
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .data
+            .get(self.pos + 1..self.pos + 5)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .filter(|digits| digits.bytes().all(|c| c.is_ascii_hexdigit()))
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok());
+        match digits {
+            Some(code) => {
+                self.pos += 4;
+                Ok(code)
+            }
+            None => Err(self.error("invalid unicode escape sequence")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let value = JsonValue::parse(
+            r#" {"a": [1, -2.5e1, true, null], "b": {"c": "\"\u00e9\ud83d\ude00é\n"}, "a": {}} "#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                (
+                    "a".to_owned(),
+                    JsonValue::Array(vec![
+                        JsonValue::Number(1.),
+                        JsonValue::Number(-25.),
+                        JsonValue::Bool(true),
+                        JsonValue::Null,
+                    ])
+                ),
+                (
+                    "b".to_owned(),
+                    JsonValue::Object(vec![(
+                        "c".to_owned(),
+                        JsonValue::String("\"é😀é\n".to_owned())
+                    )])
+                ),
+                ("a".to_owned(), JsonValue::Object(vec![])),
+            ])
+        );
+        assert_eq!(value.get("a"), Some(&JsonValue::Object(vec![])));
+        assert_eq!(value.get("d"), None);
+    }
+
+    #[test]
+    fn test_reject_invalid_json() {
+        assert_eq!(
+            JsonValue::parse(br#"{"a": 1,}"#),
+            Err("Invalid JSON at offset 8: expected a member name.".to_owned())
+        );
+        assert_eq!(
+            JsonValue::parse(b"[1] 2"),
+            Err("Invalid JSON at offset 4: unexpected data after the JSON value.".to_owned())
+        );
+        assert!(JsonValue::parse(br#"["\ud83d"]"#).is_err());
+        assert!(JsonValue::parse(b"[1.2.3]").is_err());
+        assert!(JsonValue::parse(&[b'['; 100]).is_err());
+        assert!(JsonValue::parse(b"").is_err());
+    }
+}
@@ -0,0 +1,765 @@
+use super::*;
+use crate::events::{AttributeName, TagName};
+use crate::sink::{AttrValue, MpdEventSink};
+use crate::utils::{encode_base64, format_key_id};
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::Reader;
+use std::convert::TryFrom;
+
+/// `MpdEventSink` constructing an `Mpd` from the reported events.
+///
+/// Like the TypeScript code does, it keeps a stack of the elements which are
+/// currently open: attributes are set on the last one and elements are
+/// attached to their parent once closed.
+#[derive(Debug, Default)]
+pub struct MpdBuilder {
+    stack: Vec<Node>,
+    mpd: Option<Mpd>,
+
+    /// Elements other than `<MPD>` which were closed while not being in any
+    /// other element. This only happens when parsing a fragment of MPD.
+    roots: Vec<Node>,
+
+    errors: Vec<ParsingError>,
+}
+
+impl MpdBuilder {
+    /// Returns the `Mpd` constructed (`None` if no `<MPD>` element has been
+    /// closed) and all minor errors reported while parsing.
+    pub fn finish(self) -> (Option<Mpd>, Vec<ParsingError>) {
+        (self.mpd, self.errors)
+    }
+}
+
+/// Parse `xml`, a fragment of MPD made of one or several elements, such as
+/// the content of an MPD Patch operation.
+///
+/// Returns the elements found at the root of that fragment and all minor
+/// errors reported while parsing.
+pub(super) fn parse_fragment(xml: &str) -> (Vec<Node>, Vec<ParsingError>) {
+    let mut processor = MPDProcessor::new(xml.as_bytes(), MpdBuilder::default());
+    // Errors are collected by the `MpdBuilder` in lenient mode
+    let _ = processor.process_tags();
+    let builder = processor.into_sink();
+    (builder.roots, builder.errors)
+}
+
+/// An element which is currently open.
+///
+/// Only a few of those are alive at once, so the size difference between
+/// variants does not matter much here.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub(super) enum Node {
+    Mpd(Mpd),
+    Period(Period),
+    AdaptationSet(AdaptationSet),
+    Representation(Representation),
+    BaseUrl(BaseUrl),
+    ContentSteering(ContentSteering),
+    Scheme(TagName, Scheme),
+    ContentComponent(ContentComponent),
+    ContentProtection(ContentProtection),
+    Preselection(Preselection),
+    SegmentBase(SegmentBase),
+    SegmentList(SegmentList),
+    SegmentUrl(SegmentUrl),
+    SegmentTemplate(SegmentTemplate),
+    EventStream(EventStream),
+    Event(Event),
+    ServiceDescription(ServiceDescription),
+    Latency(Latency),
+    PlaybackRate(PlaybackRate),
+    OperatingQuality(TagName, OperatingQuality),
+    ProducerReferenceTime(ProducerReferenceTime),
+    Resync(Resync),
+}
+
+impl Node {
+    pub(super) fn new(tag_name: TagName) -> Node {
+        match tag_name {
+            TagName::MPD => Node::Mpd(Mpd::default()),
+            TagName::Period => Node::Period(Period::default()),
+            TagName::AdaptationSet => Node::AdaptationSet(AdaptationSet::default()),
+            TagName::Representation => Node::Representation(Representation::default()),
+            TagName::BaseURL => Node::BaseUrl(BaseUrl::default()),
+            TagName::ContentSteering => Node::ContentSteering(ContentSteering::default()),
+            TagName::ContentComponent => Node::ContentComponent(ContentComponent::default()),
+            TagName::ContentProtection => Node::ContentProtection(ContentProtection::default()),
+            TagName::Preselection => Node::Preselection(Preselection::default()),
+            TagName::SegmentBase => Node::SegmentBase(SegmentBase::default()),
+            TagName::SegmentList => Node::SegmentList(SegmentList::default()),
+            TagName::SegmentUrl => Node::SegmentUrl(SegmentUrl::default()),
+            TagName::SegmentTemplate => Node::SegmentTemplate(SegmentTemplate::default()),
+            TagName::EventStream => Node::EventStream(EventStream::default()),
+            TagName::EventStreamElt => Node::Event(Event::default()),
+            TagName::UtcTiming
+            | TagName::Accessibility
+            | TagName::AudioChannelConfiguration
+            | TagName::EssentialProperty
+            | TagName::InbandEventStream
+            | TagName::Role
+            | TagName::Scope
+            | TagName::SupplementalProperty => Node::Scheme(tag_name, Scheme::default()),
+            TagName::ServiceDescription => Node::ServiceDescription(ServiceDescription::default()),
+            TagName::Latency => Node::Latency(Latency::default()),
+            TagName::PlaybackRate => Node::PlaybackRate(PlaybackRate::default()),
+            TagName::OperatingQuality | TagName::OperatingBandwidth => {
+                Node::OperatingQuality(tag_name, OperatingQuality::default())
+            }
+            TagName::ProducerReferenceTime => {
+                Node::ProducerReferenceTime(ProducerReferenceTime::default())
+            }
+            TagName::Resync => Node::Resync(Resync::default()),
+        }
+    }
+
+    pub(super) fn tag_name(&self) -> TagName {
+        match self {
+            Node::Mpd(_) => TagName::MPD,
+            Node::Period(_) => TagName::Period,
+            Node::AdaptationSet(_) => TagName::AdaptationSet,
+            Node::Representation(_) => TagName::Representation,
+            Node::BaseUrl(_) => TagName::BaseURL,
+            Node::ContentSteering(_) => TagName::ContentSteering,
+            Node::Scheme(tag_name, _) => *tag_name,
+            Node::ContentComponent(_) => TagName::ContentComponent,
+            Node::ContentProtection(_) => TagName::ContentProtection,
+            Node::Preselection(_) => TagName::Preselection,
+            Node::SegmentBase(_) => TagName::SegmentBase,
+            Node::SegmentList(_) => TagName::SegmentList,
+            Node::SegmentUrl(_) => TagName::SegmentUrl,
+            Node::SegmentTemplate(_) => TagName::SegmentTemplate,
+            Node::EventStream(_) => TagName::EventStream,
+            Node::Event(_) => TagName::EventStreamElt,
+            Node::ServiceDescription(_) => TagName::ServiceDescription,
+            Node::Latency(_) => TagName::Latency,
+            Node::PlaybackRate(_) => TagName::PlaybackRate,
+            Node::OperatingQuality(tag_name, _) => *tag_name,
+            Node::ProducerReferenceTime(_) => TagName::ProducerReferenceTime,
+            Node::Resync(_) => TagName::Resync,
+        }
+    }
+
+    /// Set the attribute `name` of this node to `value`, or unset it if
+    /// `value` is `None`.
+    pub(super) fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        match self {
+            Node::Mpd(mpd) => mpd.set_attribute(name, value),
+            Node::Period(period) => period.set_attribute(name, value),
+            Node::AdaptationSet(adaptation_set) => adaptation_set.set_attribute(name, value),
+            Node::Representation(representation) => representation.set_attribute(name, value),
+            Node::BaseUrl(base_url) => match name {
+                AttributeName::Text => base_url.value = string(value).unwrap_or_default(),
+                AttributeName::ServiceLocation => base_url.service_location = string(value),
+                _ => {}
+            },
+            Node::ContentSteering(content_steering) => match name {
+                AttributeName::Text => content_steering.value = string(value).unwrap_or_default(),
+                AttributeName::DefaultServiceLocation => {
+                    content_steering.default_service_location = string(value)
+                }
+                AttributeName::QueryBeforeStart => {
+                    content_steering.query_before_start = boolean(value)
+                }
+                AttributeName::ProxyServerUrl => content_steering.proxy_server_url = string(value),
+                _ => {}
+            },
+            Node::Scheme(_, scheme) => match name {
+                AttributeName::SchemeIdUri => scheme.scheme_id_uri = string(value),
+                AttributeName::SchemeValue => scheme.value = string(value),
+                _ => {}
+            },
+            Node::ContentComponent(content_component) => match name {
+                AttributeName::Id => content_component.id = string(value),
+                AttributeName::Language => content_component.language = string(value),
+                AttributeName::ContentType => content_component.content_type = string(value),
+                AttributeName::Par => content_component.par = string(value),
+                _ => {}
+            },
+            Node::ContentProtection(content_protection) => match name {
+                AttributeName::SchemeIdUri => content_protection.scheme_id_uri = string(value),
+                AttributeName::ContentProtectionValue => content_protection.value = string(value),
+                AttributeName::ContentProtectionRobustness => {
+                    content_protection.robustness = string(value)
+                }
+                AttributeName::ContentProtectionRefId => content_protection.ref_id = string(value),
+                // Reported once per key id, as 16 bytes
+                AttributeName::ContentProtectionKeyId => {
+                    if let Some(AttrValue::Bytes(key_id)) = value {
+                        if let Ok(key_id) = <&[u8; 16]>::try_from(key_id) {
+                            let key_id = format_key_id(key_id);
+                            content_protection.default_kid =
+                                Some(match content_protection.default_kid.take() {
+                                    Some(key_ids) => format!("{} {}", key_ids, key_id),
+                                    None => key_id,
+                                });
+                        }
+                    }
+                }
+                // Reported decoded, but kept as in the MPD
+                AttributeName::ContentProtectionCencPSSH => {
+                    if let Some(AttrValue::Bytes(pssh)) = value {
+                        content_protection.cenc_pssh.push(encode_base64(pssh));
+                    }
+                }
+                AttributeName::ContentProtectionPlayReadyObject => {
+                    if let Some(AttrValue::Bytes(pro)) = value {
+                        content_protection.mspr_pro = Some(encode_base64(pro));
+                    }
+                }
+                AttributeName::ContentProtectionPlayReadyIsEncrypted => {
+                    content_protection.mspr_is_encrypted = boolean(value)
+                }
+                AttributeName::ContentProtectionDashIfLaUrl => {
+                    content_protection.dashif_la_urls.extend(license_url(value))
+                }
+                AttributeName::ContentProtectionDashIfAuthzUrl => content_protection
+                    .dashif_authz_urls
+                    .extend(license_url(value)),
+                AttributeName::ContentProtectionClearKeyLaUrl => content_protection
+                    .clearkey_la_urls
+                    .extend(license_url(value)),
+                _ => {}
+            },
+            Node::Preselection(preselection) => match name {
+                AttributeName::Id => preselection.id = string(value),
+                AttributeName::PreselectionComponents => {
+                    preselection.preselection_components = string(value)
+                }
+                AttributeName::Language => preselection.language = string(value),
+                AttributeName::PreselectionTag => preselection.tag = string(value),
+                AttributeName::Codecs => preselection.codecs = string(value),
+                AttributeName::Label => preselection.labels.extend(string(value)),
+                _ => {}
+            },
+            Node::SegmentBase(segment_base) => segment_base.set_attribute(name, value),
+            Node::SegmentList(segment_list) => match (name, value) {
+                (AttributeName::SegmentTimeline, Some(AttrValue::Timeline(timeline))) => {
+                    segment_list.timeline = Some(SegmentTimeline(timeline.to_vec()))
+                }
+                _ => segment_list.base.set_attribute(name, value),
+            },
+            Node::SegmentUrl(segment_url) => match name {
+                AttributeName::Media => segment_url.media = string(value),
+                AttributeName::MediaRange => segment_url.media_range = range(value),
+                AttributeName::Index => segment_url.index = string(value),
+                AttributeName::IndexRange => segment_url.index_range = range(value),
+                _ => {}
+            },
+            Node::SegmentTemplate(segment_template) => match (name, value) {
+                (AttributeName::Media, _) => segment_template.media = string(value),
+                (AttributeName::Index, _) => segment_template.index = string(value),
+                (AttributeName::BitstreamSwitching, _) => {
+                    segment_template.bitstream_switching = boolean(value)
+                }
+                (AttributeName::SegmentTimeline, Some(AttrValue::Timeline(timeline))) => {
+                    segment_template.timeline = Some(SegmentTimeline(timeline.to_vec()))
+                }
+                _ => segment_template.base.set_attribute(name, value),
+            },
+            Node::EventStream(event_stream) => match (name, value) {
+                (AttributeName::SchemeIdUri, _) => event_stream.scheme_id_uri = string(value),
+                (AttributeName::SchemeValue, _) => event_stream.value = string(value),
+                (AttributeName::TimeScale, _) => event_stream.timescale = integer(value),
+                (AttributeName::PresentationTimeOffset, _) => {
+                    event_stream.presentation_time_offset = integer(value)
+                }
+                (AttributeName::Namespace, Some(AttrValue::KeyValue(key, value))) => {
+                    event_stream.namespaces.push(namespace(key, value))
+                }
+                _ => {}
+            },
+            Node::Event(event) => match name {
+                AttributeName::Id => event.id = string(value),
+                AttributeName::EventPresentationTime => event.presentation_time = integer(value),
+                AttributeName::Duration => event.duration = integer(value),
+                AttributeName::MessageData => {
+                    event.message_data = match value {
+                        Some(AttrValue::Bytes(data)) => Some(data.to_vec()),
+                        _ => None,
+                    }
+                }
+                AttributeName::EventStreamEltRange => event.range = range(value),
+                AttributeName::EventStreamEltXml => {
+                    let (content_encoding, content) = match value {
+                        Some(AttrValue::String(xml)) => event_content(xml),
+                        _ => (None, None),
+                    };
+                    event.content_encoding = content_encoding;
+                    event.content = content;
+                }
+                _ => {}
+            },
+            Node::ServiceDescription(service_description) => {
+                if name == AttributeName::Id {
+                    service_description.id = string(value)
+                }
+            }
+            Node::Latency(latency) => match name {
+                AttributeName::ReferenceId => latency.reference_id = integer(value),
+                AttributeName::Target => latency.target = integer(value),
+                AttributeName::Min => latency.min = integer(value),
+                AttributeName::Max => latency.max = integer(value),
+                _ => {}
+            },
+            Node::PlaybackRate(playback_rate) => match name {
+                AttributeName::Min => playback_rate.min = number(value),
+                AttributeName::Max => playback_rate.max = number(value),
+                _ => {}
+            },
+            Node::OperatingQuality(_, operating_quality) => match name {
+                AttributeName::MediaType => operating_quality.media_type = string(value),
+                AttributeName::Min => operating_quality.min = integer(value),
+                AttributeName::Max => operating_quality.max = integer(value),
+                AttributeName::Target => operating_quality.target = integer(value),
+                AttributeName::Type => operating_quality.quality_type = string(value),
+                AttributeName::MaxDifference => operating_quality.max_difference = integer(value),
+                _ => {}
+            },
+            Node::ProducerReferenceTime(producer_reference_time) => match name {
+                AttributeName::Id => producer_reference_time.id = string(value),
+                AttributeName::Inband => producer_reference_time.inband = boolean(value),
+                AttributeName::Type => producer_reference_time.reference_type = string(value),
+                AttributeName::ApplicationScheme => {
+                    producer_reference_time.application_scheme = string(value)
+                }
+                AttributeName::WallClockTime => {
+                    producer_reference_time.wall_clock_time = number(value)
+                }
+                AttributeName::PresentationTime => {
+                    producer_reference_time.presentation_time = integer(value)
+                }
+                _ => {}
+            },
+            Node::Resync(resync) => match name {
+                AttributeName::ResyncType => resync.resync_type = integer(value),
+                AttributeName::ResyncDT => resync.d_t = integer(value),
+                AttributeName::ResyncDIMax => resync.d_i_max = number(value),
+                AttributeName::ResyncDIMin => resync.d_i_min = number(value),
+                AttributeName::ResyncMarker => resync.marker = boolean(value),
+                _ => {}
+            },
+        }
+    }
+
+    /// Attach `child`, a node that has just been closed, to this node.
+    ///
+    /// Children which cannot be contained in this node are ignored.
+    fn append_child(&mut self, child: Node) {
+        match (self, child) {
+            (Node::Mpd(mpd), Node::Period(period)) => mpd.periods.push(period),
+            (Node::Mpd(mpd), Node::BaseUrl(base_url)) => mpd.base_urls.push(base_url),
+            (Node::Mpd(mpd), Node::ContentSteering(content_steering)) => {
+                mpd.content_steering = Some(content_steering)
+            }
+            (Node::Mpd(mpd), Node::Scheme(TagName::UtcTiming, scheme)) => {
+                mpd.utc_timings.push(scheme)
+            }
+            (Node::Mpd(mpd), Node::ServiceDescription(service_description)) => {
+                mpd.service_descriptions.push(service_description)
+            }
+
+            (Node::Period(period), Node::AdaptationSet(adaptation_set)) => {
+                period.adaptation_sets.push(adaptation_set)
+            }
+            (Node::Period(period), Node::BaseUrl(base_url)) => period.base_urls.push(base_url),
+            (Node::Period(period), Node::EventStream(event_stream)) => {
+                period.event_streams.push(event_stream)
+            }
+            (Node::Period(period), Node::Preselection(preselection)) => {
+                period.preselections.push(preselection)
+            }
+            (Node::Period(period), Node::ServiceDescription(service_description)) => {
+                period.service_descriptions.push(service_description)
+            }
+            (Node::Period(period), Node::SegmentBase(segment_base)) => {
+                period.segment_base = Some(segment_base)
+            }
+            (Node::Period(period), Node::SegmentList(segment_list)) => {
+                period.segment_list = Some(segment_list)
+            }
+            (Node::Period(period), Node::SegmentTemplate(segment_template)) => {
+                period.segment_template = Some(segment_template)
+            }
+
+            (Node::AdaptationSet(adaptation_set), child) => match child {
+                Node::Representation(representation) => {
+                    adaptation_set.representations.push(representation)
+                }
+                Node::BaseUrl(base_url) => adaptation_set.base_urls.push(base_url),
+                Node::Scheme(TagName::Accessibility, scheme) => {
+                    adaptation_set.accessibilities.push(scheme)
+                }
+                Node::Scheme(TagName::AudioChannelConfiguration, scheme) => {
+                    adaptation_set.audio_channel_configurations.push(scheme)
+                }
+                Node::Scheme(TagName::EssentialProperty, scheme) => {
+                    adaptation_set.essential_properties.push(scheme)
+                }
+                Node::Scheme(TagName::InbandEventStream, scheme) => {
+                    adaptation_set.inband_event_streams.push(scheme)
+                }
+                Node::Scheme(TagName::Role, scheme) => adaptation_set.roles.push(scheme),
+                Node::Scheme(TagName::SupplementalProperty, scheme) => {
+                    adaptation_set.supplemental_properties.push(scheme)
+                }
+                Node::ContentComponent(content_component) => {
+                    adaptation_set.content_components.push(content_component)
+                }
+                Node::ContentProtection(content_protection) => {
+                    adaptation_set.content_protections.push(content_protection)
+                }
+                Node::ProducerReferenceTime(producer_reference_time) => adaptation_set
+                    .producer_reference_times
+                    .push(producer_reference_time),
+                Node::Resync(resync) => adaptation_set.resyncs.push(resync),
+                Node::Preselection(preselection) => adaptation_set.preselections.push(preselection),
+                Node::SegmentBase(segment_base) => adaptation_set.segment_base = Some(segment_base),
+                Node::SegmentList(segment_list) => adaptation_set.segment_list = Some(segment_list),
+                Node::SegmentTemplate(segment_template) => {
+                    adaptation_set.segment_template = Some(segment_template)
+                }
+                _ => {}
+            },
+
+            (Node::Representation(representation), child) => match child {
+                Node::BaseUrl(base_url) => representation.base_urls.push(base_url),
+                Node::Scheme(TagName::AudioChannelConfiguration, scheme) => {
+                    representation.audio_channel_configurations.push(scheme)
+                }
+                Node::Scheme(TagName::EssentialProperty, scheme) => {
+                    representation.essential_properties.push(scheme)
+                }
+                Node::Scheme(TagName::InbandEventStream, scheme) => {
+                    representation.inband_event_streams.push(scheme)
+                }
+                Node::Scheme(TagName::SupplementalProperty, scheme) => {
+                    representation.supplemental_properties.push(scheme)
+                }
+                Node::ContentProtection(content_protection) => {
+                    representation.content_protections.push(content_protection)
+                }
+                Node::ProducerReferenceTime(producer_reference_time) => representation
+                    .producer_reference_times
+                    .push(producer_reference_time),
+                Node::Resync(resync) => representation.resyncs.push(resync),
+                Node::SegmentBase(segment_base) => representation.segment_base = Some(segment_base),
+                Node::SegmentList(segment_list) => representation.segment_list = Some(segment_list),
+                Node::SegmentTemplate(segment_template) => {
+                    representation.segment_template = Some(segment_template)
+                }
+                _ => {}
+            },
+
+            (Node::Preselection(preselection), child) => match child {
+                Node::Scheme(TagName::Accessibility, scheme) => {
+                    preselection.accessibilities.push(scheme)
+                }
+                Node::Scheme(TagName::AudioChannelConfiguration, scheme) => {
+                    preselection.audio_channel_configurations.push(scheme)
+                }
+                Node::Scheme(TagName::Role, scheme) => preselection.roles.push(scheme),
+                _ => {}
+            },
+
+            (Node::ServiceDescription(service_description), child) => match child {
+                Node::Scheme(TagName::Scope, scheme) => service_description.scopes.push(scheme),
+                Node::Latency(latency) => service_description.latencies.push(latency),
+                Node::PlaybackRate(playback_rate) => {
+                    service_description.playback_rates.push(playback_rate)
+                }
+                Node::OperatingQuality(TagName::OperatingQuality, operating_quality) => {
+                    service_description
+                        .operating_qualities
+                        .push(operating_quality)
+                }
+                Node::OperatingQuality(TagName::OperatingBandwidth, operating_bandwidth) => {
+                    service_description
+                        .operating_bandwidths
+                        .push(operating_bandwidth)
+                }
+                _ => {}
+            },
+            (
+                Node::ProducerReferenceTime(producer_reference_time),
+                Node::Scheme(TagName::UtcTiming, scheme),
+            ) => producer_reference_time.utc_timings.push(scheme),
+
+            (Node::SegmentList(segment_list), Node::SegmentUrl(segment_url)) => {
+                segment_list.segment_urls.push(segment_url)
+            }
+            (Node::EventStream(event_stream), Node::Event(event)) => {
+                event_stream.events.push(event)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl MpdEventSink for MpdBuilder {
+    fn tag_open(&mut self, tag_name: TagName) {
+        self.stack.push(Node::new(tag_name));
+    }
+
+    fn tag_close(&mut self, tag_name: TagName) {
+        match self.stack.last() {
+            Some(node) if node.tag_name() == tag_name => {}
+            _ => return,
+        }
+        let node = self.stack.pop().unwrap();
+        match (self.stack.last_mut(), node) {
+            (None, Node::Mpd(mpd)) => self.mpd = Some(mpd),
+            (None, node) => self.roots.push(node),
+            (Some(parent), node) => parent.append_child(node),
+        }
+    }
+
+    fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+        if let Some(node) = self.stack.last_mut() {
+            node.set_attribute(attr_name, Some(value));
+        }
+    }
+
+    fn error(&mut self, err: &ParsingError) {
+        self.errors.push(err.clone());
+    }
+}
+
+impl Mpd {
+    fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        use AttributeName::*;
+        match (name, value) {
+            (Id, _) => self.id = string(value),
+            (Profiles, _) => self.profiles = string(value),
+            (Type, _) => self.mpd_type = string(value),
+            (AvailabilityStartTime, _) => self.availability_start_time = number(value),
+            (AvailabilityEndTime, _) => self.availability_end_time = number(value),
+            (PublishTime, _) => self.publish_time = number(value),
+            (MediaPresentationDuration, _) => self.media_presentation_duration = number(value),
+            (MinimumUpdatePeriod, _) => self.minimum_update_period = number(value),
+            (MinBufferTime, _) => self.min_buffer_time = number(value),
+            (TimeShiftBufferDepth, _) => self.time_shift_buffer_depth = number(value),
+            (SuggestedPresentationDelay, _) => self.suggested_presentation_delay = number(value),
+            (MaxSegmentDuration, _) => self.max_segment_duration = number(value),
+            (MaxSubsegmentDuration, _) => self.max_subsegment_duration = number(value),
+            (Location, _) => self.locations.extend(string(value)),
+            (Namespace, Some(AttrValue::KeyValue(key, value))) => {
+                self.namespaces.push(namespace(key, value))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Period {
+    fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        use AttributeName::*;
+        match (name, value) {
+            (Id, _) => self.id = string(value),
+            (Start, _) => self.start = number(value),
+            (Duration, _) => self.duration = number(value),
+            (BitstreamSwitching, _) => self.bitstream_switching = boolean(value),
+            (AvailabilityTimeOffset, _) => self.availability_time_offset = number(value),
+            (XLinkHref, _) => self.xlink_href = string(value),
+            (XLinkActuate, _) => self.xlink_actuate = string(value),
+            (Namespace, Some(AttrValue::KeyValue(key, value))) => {
+                self.namespaces.push(namespace(key, value))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl AdaptationSet {
+    fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        use AttributeName::*;
+        match name {
+            Id => self.id = string(value),
+            Group => self.group = integer(value),
+            Language => self.language = string(value),
+            ContentType => self.content_type = string(value),
+            Par => self.par = string(value),
+            MinBandwidth => self.min_bandwidth = integer(value),
+            MaxBandwidth => self.max_bandwidth = integer(value),
+            MinWidth => self.min_width = integer(value),
+            MaxWidth => self.max_width = integer(value),
+            MinHeight => self.min_height = integer(value),
+            MaxHeight => self.max_height = integer(value),
+            MinFrameRate => self.min_frame_rate = string(value),
+            MaxFrameRate => self.max_frame_rate = string(value),
+            SelectionPriority => self.selection_priority = integer(value),
+            SegmentAlignment => self.segment_alignment = integer_or_boolean(value),
+            SubsegmentAlignment => self.subsegment_alignment = integer_or_boolean(value),
+            BitstreamSwitching => self.bitstream_switching = boolean(value),
+            AudioSamplingRate => self.audio_sampling_rate = string(value),
+            Codecs => self.codecs = string(value),
+            SupplementalCodecs => self.supplemental_codecs = string(value),
+            Profiles => self.profiles = string(value),
+            SegmentProfiles => self.segment_profiles = string(value),
+            MimeType => self.mime_type = string(value),
+            CodingDependency => self.coding_dependency = boolean(value),
+            FrameRate => self.frame_rate = string(value),
+            Height => self.height = integer(value),
+            Width => self.width = integer(value),
+            MaxPlayoutRate => self.max_playout_rate = number(value),
+            MaxSAPPeriod => self.max_sap_period = number(value),
+            AvailabilityTimeOffset => self.availability_time_offset = number(value),
+            AvailabilityTimeComplete => self.availability_time_complete = boolean(value),
+            Label => self.labels.extend(string(value)),
+            _ => {}
+        }
+    }
+}
+
+impl Representation {
+    fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        use AttributeName::*;
+        match name {
+            Id => self.id = string(value),
+            AudioSamplingRate => self.audio_sampling_rate = string(value),
+            Bitrate => self.bandwidth = integer(value),
+            Codecs => self.codecs = string(value),
+            SupplementalCodecs => self.supplemental_codecs = string(value),
+            CodingDependency => self.coding_dependency = boolean(value),
+            FrameRate => self.frame_rate = string(value),
+            Height => self.height = integer(value),
+            Width => self.width = integer(value),
+            MaxPlayoutRate => self.max_playout_rate = number(value),
+            MaxSAPPeriod => self.max_sap_period = number(value),
+            MimeType => self.mime_type = string(value),
+            Profiles => self.profiles = string(value),
+            QualityRanking => self.quality_ranking = integer(value),
+            SegmentProfiles => self.segment_profiles = string(value),
+            AvailabilityTimeOffset => self.availability_time_offset = number(value),
+            AvailabilityTimeComplete => self.availability_time_complete = boolean(value),
+            Label => self.labels.extend(string(value)),
+            _ => {}
+        }
+    }
+}
+
+impl SegmentBase {
+    fn set_attribute(&mut self, name: AttributeName, value: Option<AttrValue>) {
+        use AttributeName::*;
+        match name {
+            TimeScale => self.timescale = integer(value),
+            PresentationTimeOffset => self.presentation_time_offset = integer(value),
+            IndexRange => self.index_range = range(value),
+            IndexRangeExact => self.index_range_exact = boolean(value),
+            AvailabilityTimeOffset => self.availability_time_offset = number(value),
+            AvailabilityTimeComplete => self.availability_time_complete = boolean(value),
+            Duration => self.duration = integer(value),
+            StartNumber => self.start_number = integer(value),
+            EndNumber => self.end_number = integer(value),
+            InitializationMedia => {
+                self.initialization
+                    .get_or_insert_with(Default::default)
+                    .media = string(value)
+            }
+            InitializationRange => {
+                self.initialization
+                    .get_or_insert_with(Default::default)
+                    .range = range(value)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn string(value: Option<AttrValue>) -> Option<String> {
+    match value {
+        Some(AttrValue::String(val)) => Some(val.to_owned()),
+        _ => None,
+    }
+}
+
+fn number(value: Option<AttrValue>) -> Option<f64> {
+    match value {
+        Some(AttrValue::F64(val)) => Some(val),
+        _ => None,
+    }
+}
+
+/// Integers are reported as f64 values (except timestamps), convert them
+/// back.
+fn integer(value: Option<AttrValue>) -> Option<u64> {
+    match value {
+        Some(AttrValue::U64(val)) => Some(val),
+        Some(AttrValue::F64(val)) if val >= 0. && val.is_finite() => Some(val as u64),
+        _ => None,
+    }
+}
+
+fn boolean(value: Option<AttrValue>) -> Option<bool> {
+    match value {
+        Some(AttrValue::Bool(val)) => Some(val),
+        _ => None,
+    }
+}
+
+/// Booleans are reported as infinite f64 values when an attribute can also be
+/// an integer (@see `utils::parse_u64_or_bool`).
+fn integer_or_boolean(value: Option<AttrValue>) -> Option<U64OrBool> {
+    match value {
+        Some(AttrValue::F64(val)) if val == f64::INFINITY => Some(U64OrBool::Bool(true)),
+        Some(AttrValue::F64(val)) if val == f64::NEG_INFINITY => Some(U64OrBool::Bool(false)),
+        _ => integer(value).map(U64OrBool::U64),
+    }
+}
+
+fn range(value: Option<AttrValue>) -> Option<(f64, f64)> {
+    match value {
+        Some(AttrValue::Range(start, end)) => Some((start, end)),
+        _ => None,
+    }
+}
+
+/// License URLs are reported with their license type, empty if none.
+fn license_url(value: Option<AttrValue>) -> Option<LicenseUrl> {
+    match value {
+        Some(AttrValue::KeyValue(url, license_type)) => Some(LicenseUrl {
+            url: url.to_owned(),
+            license_type: Some(license_type)
+                .filter(|t| !t.is_empty())
+                .map(str::to_owned),
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the `contentEncoding` attribute and the raw content of the
+/// `<Event>` element whose XML is `xml`.
+fn event_content(xml: &str) -> (Option<String>, Option<String>) {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(XmlEvent::Start(tag)) => {
+                let content_start = reader.buffer_position();
+                let content = xml
+                    .rfind("</")
+                    .filter(|end| *end > content_start)
+                    .map(|end| xml[content_start..end].to_owned());
+                return (content_encoding(&tag), content);
+            }
+            Ok(XmlEvent::Empty(tag)) => return (content_encoding(&tag), None),
+            Ok(XmlEvent::Eof) | Err(_) => return (None, None),
+            Ok(_) => {}
+        }
+    }
+}
+
+fn content_encoding(tag: &BytesStart) -> Option<String> {
+    tag.try_get_attribute("contentEncoding")
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+fn namespace(key: &str, value: &str) -> super::Namespace {
+    super::Namespace {
+        key: key.to_owned(),
+        value: value.to_owned(),
+    }
+}
@@ -0,0 +1,788 @@
+//! Owned and typed representation of a parsed MPD.
+//!
+//! This is the Rust equivalent of the "intermediate representation" the
+//! TypeScript code constructs from the events reported by the `MPDProcessor`
+//! (@see the `ts/generators` directory): same hierarchy, same elements and
+//! same attributes, converted to the same types.
+
+use std::io::BufRead;
+
+mod builder;
+mod patch;
+mod serializer;
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::processor::{MPDProcessor, ParsingOptions, SegmentObject};
+
+pub use builder::MpdBuilder;
+pub use patch::{AddPosition, Patch, PatchOperation, PatchOperationKind};
+
+/// Value of an attribute which can either be an unsigned integer or a boolean,
+/// such as `AdaptationSet@segmentAlignment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum U64OrBool {
+    U64(u64),
+    Bool(bool),
+}
+
+/// An XML namespace declared on an element, through an `xmlns:` attribute.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Namespace {
+    /// The namespace's name (the part coming after "xmlns:").
+    pub key: String,
+    /// The namespace's URI.
+    pub value: String,
+}
+
+/// A parsed `<MPD>` element, the root of the document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mpd {
+    pub id: Option<String>,
+    pub profiles: Option<String>,
+    pub mpd_type: Option<String>,
+    /// `availabilityStartTime` attribute, in milliseconds since the Unix
+    /// epoch.
+    pub availability_start_time: Option<f64>,
+    /// `availabilityEndTime` attribute, in milliseconds since the Unix epoch.
+    pub availability_end_time: Option<f64>,
+    /// `publishTime` attribute, in milliseconds since the Unix epoch.
+    pub publish_time: Option<f64>,
+    /// `mediaPresentationDuration` attribute, in seconds.
+    pub media_presentation_duration: Option<f64>,
+    pub minimum_update_period: Option<f64>,
+    pub min_buffer_time: Option<f64>,
+    pub time_shift_buffer_depth: Option<f64>,
+    pub suggested_presentation_delay: Option<f64>,
+    pub max_segment_duration: Option<f64>,
+    pub max_subsegment_duration: Option<f64>,
+    pub namespaces: Vec<Namespace>,
+
+    pub base_urls: Vec<BaseUrl>,
+    pub locations: Vec<String>,
+    pub service_descriptions: Vec<ServiceDescription>,
+    pub content_steering: Option<ContentSteering>,
+    pub periods: Vec<Period>,
+    pub utc_timings: Vec<Scheme>,
+}
+
+/// A parsed `<Period>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Period {
+    pub id: Option<String>,
+    /// `start` attribute, in seconds.
+    pub start: Option<f64>,
+    /// `duration` attribute, in seconds.
+    pub duration: Option<f64>,
+    pub bitstream_switching: Option<bool>,
+    pub availability_time_offset: Option<f64>,
+    pub xlink_href: Option<String>,
+    pub xlink_actuate: Option<String>,
+    pub namespaces: Vec<Namespace>,
+
+    pub base_urls: Vec<BaseUrl>,
+    pub adaptation_sets: Vec<AdaptationSet>,
+    pub event_streams: Vec<EventStream>,
+    pub service_descriptions: Vec<ServiceDescription>,
+    pub preselections: Vec<Preselection>,
+    pub segment_base: Option<SegmentBase>,
+    pub segment_list: Option<SegmentList>,
+    pub segment_template: Option<SegmentTemplate>,
+}
+
+/// A parsed `<AdaptationSet>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdaptationSet {
+    pub id: Option<String>,
+    pub group: Option<u64>,
+    pub language: Option<String>,
+    pub content_type: Option<String>,
+    pub par: Option<String>,
+    pub min_bandwidth: Option<u64>,
+    pub max_bandwidth: Option<u64>,
+    pub min_width: Option<u64>,
+    pub max_width: Option<u64>,
+    pub min_height: Option<u64>,
+    pub max_height: Option<u64>,
+    pub min_frame_rate: Option<String>,
+    pub max_frame_rate: Option<String>,
+    pub selection_priority: Option<u64>,
+    pub segment_alignment: Option<U64OrBool>,
+    pub subsegment_alignment: Option<U64OrBool>,
+    pub bitstream_switching: Option<bool>,
+    pub audio_sampling_rate: Option<String>,
+    pub codecs: Option<String>,
+    pub supplemental_codecs: Option<String>,
+    pub profiles: Option<String>,
+    pub segment_profiles: Option<String>,
+    pub mime_type: Option<String>,
+    pub coding_dependency: Option<bool>,
+    pub frame_rate: Option<String>,
+    pub height: Option<u64>,
+    pub width: Option<u64>,
+    pub max_playout_rate: Option<f64>,
+    pub max_sap_period: Option<f64>,
+    pub availability_time_offset: Option<f64>,
+    pub availability_time_complete: Option<bool>,
+
+    pub base_urls: Vec<BaseUrl>,
+    pub accessibilities: Vec<Scheme>,
+    pub audio_channel_configurations: Vec<Scheme>,
+    pub content_components: Vec<ContentComponent>,
+    pub content_protections: Vec<ContentProtection>,
+    pub essential_properties: Vec<Scheme>,
+    pub inband_event_streams: Vec<Scheme>,
+    pub roles: Vec<Scheme>,
+    pub supplemental_properties: Vec<Scheme>,
+    pub labels: Vec<String>,
+    pub producer_reference_times: Vec<ProducerReferenceTime>,
+    pub resyncs: Vec<Resync>,
+    pub preselections: Vec<Preselection>,
+    pub representations: Vec<Representation>,
+    pub segment_base: Option<SegmentBase>,
+    pub segment_list: Option<SegmentList>,
+    pub segment_template: Option<SegmentTemplate>,
+}
+
+/// A parsed `<Representation>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Representation {
+    pub id: Option<String>,
+    pub audio_sampling_rate: Option<String>,
+    pub bandwidth: Option<u64>,
+    pub codecs: Option<String>,
+    pub supplemental_codecs: Option<String>,
+    pub coding_dependency: Option<bool>,
+    pub frame_rate: Option<String>,
+    pub height: Option<u64>,
+    pub width: Option<u64>,
+    pub max_playout_rate: Option<f64>,
+    pub max_sap_period: Option<f64>,
+    pub mime_type: Option<String>,
+    pub profiles: Option<String>,
+    pub quality_ranking: Option<u64>,
+    pub segment_profiles: Option<String>,
+    pub availability_time_offset: Option<f64>,
+    pub availability_time_complete: Option<bool>,
+
+    pub base_urls: Vec<BaseUrl>,
+    pub audio_channel_configurations: Vec<Scheme>,
+    pub content_protections: Vec<ContentProtection>,
+    pub essential_properties: Vec<Scheme>,
+    pub inband_event_streams: Vec<Scheme>,
+    pub supplemental_properties: Vec<Scheme>,
+    pub labels: Vec<String>,
+    pub producer_reference_times: Vec<ProducerReferenceTime>,
+    pub resyncs: Vec<Resync>,
+    pub segment_base: Option<SegmentBase>,
+    pub segment_list: Option<SegmentList>,
+    pub segment_template: Option<SegmentTemplate>,
+}
+
+/// A parsed `<BaseURL>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BaseUrl {
+    /// The URL itself, which is the inner content of the element.
+    pub value: String,
+    pub service_location: Option<String>,
+}
+
+/// A parsed `<ContentSteering>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSteering {
+    /// URL of the steering server, which is the inner content of the element.
+    pub value: String,
+    pub default_service_location: Option<String>,
+    pub query_before_start: Option<bool>,
+    pub proxy_server_url: Option<String>,
+}
+
+/// A parsed `<ServiceDescription>` element, describing how low-latency
+/// contents should be played.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServiceDescription {
+    pub id: Option<String>,
+
+    pub scopes: Vec<Scheme>,
+    pub latencies: Vec<Latency>,
+    pub playback_rates: Vec<PlaybackRate>,
+    pub operating_qualities: Vec<OperatingQuality>,
+    pub operating_bandwidths: Vec<OperatingQuality>,
+}
+
+/// A parsed `<Latency>` element, from a `<ServiceDescription>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Latency {
+    /// `referenceId` attribute, the `id` of the `<ProducerReferenceTime>` the
+    /// latency is measured against.
+    pub reference_id: Option<u64>,
+    /// `target` attribute, in milliseconds.
+    pub target: Option<u64>,
+    /// `min` attribute, in milliseconds.
+    pub min: Option<u64>,
+    /// `max` attribute, in milliseconds.
+    pub max: Option<u64>,
+}
+
+/// A parsed `<PlaybackRate>` element, from a `<ServiceDescription>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaybackRate {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// A parsed `<OperatingQuality>` or `<OperatingBandwidth>` element, from a
+/// `<ServiceDescription>` element.
+///
+/// `<OperatingBandwidth>` elements, in bits per second, only have the
+/// `media_type`, `min`, `max` and `target` attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatingQuality {
+    pub media_type: Option<String>,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub target: Option<u64>,
+    /// `type` attribute.
+    pub quality_type: Option<String>,
+    pub max_difference: Option<u64>,
+}
+
+/// A parsed `<ProducerReferenceTime>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProducerReferenceTime {
+    pub id: Option<String>,
+    pub inband: Option<bool>,
+    /// `type` attribute.
+    pub reference_type: Option<String>,
+    pub application_scheme: Option<String>,
+    /// `wallClockTime` attribute, in milliseconds since the Unix epoch.
+    ///
+    /// It is written back as an xs:dateTime, even if it was an NTP
+    /// timestamp.
+    pub wall_clock_time: Option<f64>,
+    pub presentation_time: Option<u64>,
+
+    pub utc_timings: Vec<Scheme>,
+}
+
+/// A parsed `<Resync>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resync {
+    /// `type` attribute, from `0` to `3`.
+    pub resync_type: Option<u64>,
+    /// `dT` attribute.
+    pub d_t: Option<u64>,
+    /// `dImax` attribute.
+    pub d_i_max: Option<f64>,
+    /// `dImin` attribute.
+    pub d_i_min: Option<f64>,
+    pub marker: Option<bool>,
+}
+
+/// A "scheme-like" element, only defined by a `schemeIdUri` and a `value`
+/// attribute, such as `<Role>`, `<Accessibility>`, `<EssentialProperty>`,
+/// `<AudioChannelConfiguration>`, `<SupplementalProperty>`,
+/// `<InbandEventStream>`, `<UTCTiming>` or `<Scope>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scheme {
+    pub scheme_id_uri: Option<String>,
+    pub value: Option<String>,
+}
+
+/// A parsed `<ContentComponent>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentComponent {
+    pub id: Option<String>,
+    pub language: Option<String>,
+    pub content_type: Option<String>,
+    pub par: Option<String>,
+}
+
+/// A parsed `<Preselection>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preselection {
+    pub id: Option<String>,
+    /// `preselectionComponents` attribute, as written in the MPD.
+    pub preselection_components: Option<String>,
+    pub language: Option<String>,
+    pub tag: Option<String>,
+    pub codecs: Option<String>,
+
+    pub accessibilities: Vec<Scheme>,
+    pub audio_channel_configurations: Vec<Scheme>,
+    pub roles: Vec<Scheme>,
+    pub labels: Vec<String>,
+}
+
+/// A parsed `<ContentProtection>` element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentProtection {
+    pub scheme_id_uri: Option<String>,
+    pub value: Option<String>,
+    /// `cenc:default_KID` attribute.
+    pub default_kid: Option<String>,
+    pub robustness: Option<String>,
+    /// `refId` attribute. Elements referencing another one through a `ref`
+    /// attribute are parsed as a copy of it instead.
+    pub ref_id: Option<String>,
+    /// Inner content of all `<cenc:pssh>` child elements.
+    pub cenc_pssh: Vec<String>,
+    /// Inner content of the `<mspr:pro>` child element: a base64-encoded
+    /// PlayReady Object.
+    pub mspr_pro: Option<String>,
+    /// Inner content of the `<mspr:IsEncrypted>` child element.
+    pub mspr_is_encrypted: Option<bool>,
+    /// `<dashif:Laurl>` child elements.
+    pub dashif_la_urls: Vec<LicenseUrl>,
+    /// `<dashif:Authzurl>` child elements.
+    pub dashif_authz_urls: Vec<LicenseUrl>,
+    /// `<clearkey:Laurl>` child elements.
+    pub clearkey_la_urls: Vec<LicenseUrl>,
+}
+
+/// A parsed `<dashif:Laurl>`, `<dashif:Authzurl>` or `<clearkey:Laurl>`
+/// element.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicenseUrl {
+    /// The URL itself, which is the inner content of the element.
+    pub url: String,
+    /// `licenseType` attribute (`Lic_type` for `<clearkey:Laurl>`).
+    pub license_type: Option<String>,
+}
+
+/// A parsed `<Initialization>` element, or the `initialization` attribute of
+/// a `<SegmentTemplate>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Initialization {
+    /// `sourceURL` attribute (or `initialization` template for a
+    /// `<SegmentTemplate>`).
+    pub media: Option<String>,
+    pub range: Option<(f64, f64)>,
+}
+
+/// A parsed `<SegmentBase>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentBase {
+    pub timescale: Option<u64>,
+    pub presentation_time_offset: Option<u64>,
+    pub index_range: Option<(f64, f64)>,
+    pub index_range_exact: Option<bool>,
+    pub availability_time_offset: Option<f64>,
+    pub availability_time_complete: Option<bool>,
+    pub duration: Option<u64>,
+    pub start_number: Option<u64>,
+    pub end_number: Option<u64>,
+    pub initialization: Option<Initialization>,
+}
+
+/// A parsed `<SegmentList>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentList {
+    /// Attributes and `<Initialization>` child, shared with `<SegmentBase>`.
+    pub base: SegmentBase,
+    pub timeline: Option<SegmentTimeline>,
+    pub segment_urls: Vec<SegmentUrl>,
+}
+
+/// A parsed `<SegmentURL>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentUrl {
+    pub media: Option<String>,
+    pub media_range: Option<(f64, f64)>,
+    pub index: Option<String>,
+    pub index_range: Option<(f64, f64)>,
+}
+
+/// A parsed `<SegmentTemplate>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentTemplate {
+    /// Attributes shared with `<SegmentBase>`, `initialization` being the
+    /// template's `initialization` attribute.
+    pub base: SegmentBase,
+    pub media: Option<String>,
+    pub index: Option<String>,
+    pub bitstream_switching: Option<bool>,
+    pub timeline: Option<SegmentTimeline>,
+}
+
+/// A parsed `<SegmentTimeline>` element, as the list of its `<S>` elements.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentTimeline(pub Vec<SegmentObject>);
+
+/// A parsed `<EventStream>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventStream {
+    pub scheme_id_uri: Option<String>,
+    pub value: Option<String>,
+    pub timescale: Option<u64>,
+    pub presentation_time_offset: Option<u64>,
+    pub namespaces: Vec<Namespace>,
+    pub events: Vec<Event>,
+}
+
+/// A parsed `<Event>` element, from an `<EventStream>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Event {
+    pub id: Option<String>,
+    pub presentation_time: Option<u64>,
+    pub duration: Option<u64>,
+    /// `messageData` attribute in UTF-8 or, when the `contentEncoding` is
+    /// `base64`, decoded content of the element.
+    pub message_data: Option<Vec<u8>>,
+    pub content_encoding: Option<String>,
+    /// Content of the element (e.g. an inline SCTE-35 signal), as the raw XML
+    /// read between its opening and closing tags.
+    pub content: Option<String>,
+    /// Byte range (end not included) of the whole element in the MPD.
+    ///
+    /// For `<Event>` elements added by an MPD Patch, this range is relative
+    /// to the content of the Patch operation which added it.
+    pub range: Option<(f64, f64)>,
+}
+
+impl Mpd {
+    /// Parse the whole MPD document read from `reader` into an `Mpd`.
+    ///
+    /// As when reporting events, minor errors encountered do not stop the
+    /// parsing. They are returned alongside the parsed `Mpd`.
+    ///
+    /// Returns an error if no `<MPD>` element has been found.
+    pub fn parse<R: BufRead>(reader: R) -> Result<(Mpd, Vec<ParsingError>)> {
+        Mpd::parse_with_options(reader, ParsingOptions::default())
+    }
+
+    /// Same as `Mpd::parse`, but parsing the MPD according to the given
+    /// `ParsingOptions`.
+    ///
+    /// In strict mode, the first error encountered is returned instead.
+    pub fn parse_with_options<R: BufRead>(
+        reader: R,
+        options: ParsingOptions,
+    ) -> Result<(Mpd, Vec<ParsingError>)> {
+        let mut processor = MPDProcessor::with_options(reader, MpdBuilder::default(), options);
+        processor.process_tags()?;
+        let (mpd, errors) = processor.into_sink().finish();
+        match mpd {
+            Some(mpd) => Ok((mpd, errors)),
+            None => Err(ParsingError::new(
+                ParsingErrorKind::MissingElement,
+                "No MPD element found.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mpd() {
+        let (mpd, errors) = Mpd::parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD type="dynamic" minBufferTime="PT2S" availabilityStartTime="2024-01-01T01:00:00+01:00"
+  xmlns:scte35="urn:scte:scte35:2013:xml">
+  <Location>https://example.com/live.mpd</Location>
+  <BaseURL serviceLocation="cdn1">https://cdn1.example.com/</BaseURL>
+  <Period id="p0" start="PT0S">
+    <AdaptationSet id="1" contentType="audio" lang="fr" segmentAlignment="true">
+      <Role schemeIdUri="urn:mpeg:dash:role:2011" value="main"/>
+      <Label>Français</Label>
+      <SegmentTemplate timescale="48000" media="$Time$.mp4" initialization="init.mp4">
+        <SegmentTimeline><S t="0" d="96000" r="1"/><S d="48000"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="a1" bandwidth="128000" codecs="mp4a.40.2"/>
+    </AdaptationSet>
+    <EventStream schemeIdUri="urn:example" timescale="1000">
+      <Event presentationTime="1000" duration="500" id="1"/>
+    </EventStream>
+  </Period>
+</MPD>"#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(mpd.mpd_type.as_deref(), Some("dynamic"));
+        assert_eq!(mpd.min_buffer_time, Some(2.));
+        assert_eq!(mpd.availability_start_time, Some(1704067200000.));
+        assert_eq!(mpd.locations, vec!["https://example.com/live.mpd"]);
+        assert_eq!(
+            mpd.base_urls,
+            vec![BaseUrl {
+                value: "https://cdn1.example.com/".to_owned(),
+                service_location: Some("cdn1".to_owned()),
+            }]
+        );
+        assert_eq!(mpd.namespaces[0].key, "scte35");
+
+        let period = &mpd.periods[0];
+        assert_eq!(period.id.as_deref(), Some("p0"));
+        assert_eq!(period.start, Some(0.));
+
+        let adaptation_set = &period.adaptation_sets[0];
+        assert_eq!(adaptation_set.language.as_deref(), Some("fr"));
+        assert_eq!(
+            adaptation_set.segment_alignment,
+            Some(U64OrBool::Bool(true))
+        );
+        assert_eq!(adaptation_set.roles[0].value.as_deref(), Some("main"));
+        assert_eq!(adaptation_set.labels, vec!["Français"]);
+
+        let segment_template = adaptation_set.segment_template.as_ref().unwrap();
+        assert_eq!(segment_template.base.timescale, Some(48000));
+        assert_eq!(segment_template.media.as_deref(), Some("$Time$.mp4"));
+        assert_eq!(
+            segment_template
+                .base
+                .initialization
+                .as_ref()
+                .unwrap()
+                .media
+                .as_deref(),
+            Some("init.mp4")
+        );
+        let timeline = &segment_template.timeline.as_ref().unwrap().0;
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[1].start, 192000);
+
+        let representation = &adaptation_set.representations[0];
+        assert_eq!(representation.id.as_deref(), Some("a1"));
+        assert_eq!(representation.bandwidth, Some(128000));
+
+        let event = &period.event_streams[0].events[0];
+        assert_eq!(event.presentation_time, Some(1000));
+        assert_eq!(event.duration, Some(500));
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let (mpd, _) = Mpd::parse(
+            r#"<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" xmlns:cenc="urn:mpeg:cenc:2013"
+              type="static" mediaPresentationDuration="PT1H2M3.5S"
+              publishTime="2024-01-01T00:00:00.125Z">
+  <BaseURL>https://cdn.example.com/a&amp;b/</BaseURL>
+  <ContentSteering queryBeforeStart="true">https://steering.example.com/</ContentSteering>
+  <Period id="1" duration="PT1H2M3.5S">
+    <EventStream schemeIdUri="urn:scte:scte35:2013:xml" timescale="90000">
+      <Event presentationTime="90000" id="1"><Signal xmlns="urn:example"><Binary>/DAAAA==</Binary></Signal></Event>
+      <Event id="2" messageData="start"/>
+    </EventStream>
+    <AdaptationSet id="v" segmentAlignment="1" maxWidth="1920">
+      <ContentProtection schemeIdUri="urn:mpeg:dash:mp4protection:2011" value="cenc"
+        cenc:default_KID="9eb4050d-e44b-4802-932e-27d75083e266"/>
+      <ContentProtection schemeIdUri="urn:uuid:edef8ba9-79d6-4ace-a3c8-27dcd51d21ed">
+        <cenc:pssh>AAAAIHBzc2gAAAAA7e+LqXnWSs6jyCfc1R0h7QAAAAA=</cenc:pssh>
+      </ContentProtection>
+      <SegmentTemplate timescale="90000" media="$Time$.m4s" initialization="init.mp4">
+        <SegmentTimeline><S t="0" d="180000" r="2"/><S t="540000" d="180000"/><S d="90000" n="10" k="4"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="v1" bandwidth="5000000" width="1920" height="1080">
+        <SegmentBase indexRange="0-100"><Initialization range="101-200"/></SegmentBase>
+      </Representation>
+      <Representation id="v2" bandwidth="2000000">
+        <SegmentList timescale="1000" duration="2000">
+          <SegmentTimeline><S t="0" d="2000" r="1"/></SegmentTimeline>
+          <SegmentURL media="s1.m4s"/><SegmentURL media="s2.m4s"/>
+        </SegmentList>
+      </Representation>
+    </AdaptationSet>
+  </Period>
+  <UTCTiming schemeIdUri="urn:mpeg:dash:utc:http-iso:2014" value="https://time.example.com"/>
+</MPD>"#
+                .as_bytes(),
+        )
+        .unwrap();
+        let xml = mpd.to_xml().unwrap();
+        assert!(xml.contains(r#"mediaPresentationDuration="PT1H2M3.5S""#));
+        assert!(xml.contains(r#"<S t="0" d="180000" r="3"/>"#));
+        assert!(xml.contains(r#"<S n="10" d="90000" k="4"/>"#));
+        assert!(xml.contains("a&amp;b"));
+        assert!(xml.contains(
+            r#"<SegmentTimeline>
+            <S t="0" d="2000" r="1"/>
+          </SegmentTimeline>
+          <SegmentURL media="s1.m4s"/>"#
+        ));
+        assert!(xml.contains(r#"publishTime="2024-01-01T00:00:00.125Z""#));
+        assert!(xml.contains(
+            r#"<ContentSteering queryBeforeStart="true">https://steering.example.com/</ContentSteering>"#
+        ));
+        assert!(xml.contains(
+            r#"<Event presentationTime="90000" id="1"><Signal xmlns="urn:example"><Binary>/DAAAA==</Binary></Signal></Event>"#
+        ));
+        assert!(xml.contains("<cenc:pssh>AAAAIHBzc2gAAAAA7e+LqXnWSs6jyCfc1R0h7QAAAAA=</cenc:pssh>"));
+
+        let (mut reparsed, errors) = Mpd::parse(xml.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        let timeline = &reparsed.periods[0].adaptation_sets[0]
+            .segment_template
+            .as_ref()
+            .unwrap()
+            .timeline
+            .as_ref()
+            .unwrap()
+            .0;
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].repeat_count, 3);
+
+        // Apart from the compacted SegmentTimeline and where the `<Event>`
+        // elements are in the document, the model stays the same
+        let mut expected = mpd;
+        expected.namespaces = reparsed.namespaces.clone();
+        expected.periods[0].adaptation_sets[0]
+            .segment_template
+            .as_mut()
+            .unwrap()
+            .timeline = Some(SegmentTimeline(timeline.clone()));
+        for mpd in [&mut expected, &mut reparsed] {
+            for event in &mut mpd.periods[0].event_streams[0].events {
+                event.range = None;
+            }
+        }
+        assert_eq!(reparsed, expected);
+    }
+
+    #[test]
+    fn test_serialize_content_protection_children() {
+        let mpd = r#"<MPD xmlns:mspr="urn:microsoft:playready"><Period><AdaptationSet>
+  <ContentProtection schemeIdUri="urn:uuid:9a04f079-9840-4286-ab92-e65be0885f95">
+    <mspr:pro>RgAAAAEAAQA8ADwAVwBSAE0ASABFAEEARABFAFIAIAB2AGUAcgBzAGkAbwBuAD0AIgA0AC4AMAAuADAALgAwACIALwA+AA==</mspr:pro>
+    <mspr:IsEncrypted>1</mspr:IsEncrypted>
+    <dashif:Laurl licenseType="EME-1.0">https://drm.example.com/license</dashif:Laurl>
+    <dashif:Authzurl>https://drm.example.com/auth</dashif:Authzurl>
+  </ContentProtection>
+  <ContentProtection schemeIdUri="urn:uuid:e2719d58-a985-b3c9-781a-b030af78d30e">
+    <clearkey:Laurl Lic_type="EME-1.0">https://ck.example.com/</clearkey:Laurl>
+  </ContentProtection>
+</AdaptationSet></Period></MPD>"#;
+        let (mpd, errors) = Mpd::parse(mpd.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        let content_protection = &mpd.periods[0].adaptation_sets[0].content_protections[0];
+        assert_eq!(
+            content_protection.mspr_pro.as_deref(),
+            Some("RgAAAAEAAQA8ADwAVwBSAE0ASABFAEEARABFAFIAIAB2AGUAcgBzAGkAbwBuAD0AIgA0AC4AMAAuADAALgAwACIALwA+AA==")
+        );
+        assert_eq!(content_protection.mspr_is_encrypted, Some(true));
+        assert_eq!(
+            content_protection.dashif_la_urls,
+            vec![LicenseUrl {
+                url: "https://drm.example.com/license".to_owned(),
+                license_type: Some("EME-1.0".to_owned()),
+            }]
+        );
+        assert_eq!(content_protection.dashif_authz_urls[0].license_type, None);
+
+        let xml = mpd.to_xml().unwrap();
+        assert!(
+            xml.contains("<mspr:pro>RgAAAAEAAQA8ADwAVwBSAE0ASABFAEEARABFAFIAIAB2AGUAcgBzAGkAbwBuAD0AIgA0AC4AMAAuADAALgAwACIALwA+AA==</mspr:pro>")
+        );
+        assert!(xml.contains("<mspr:IsEncrypted>1</mspr:IsEncrypted>"));
+        assert!(xml.contains(
+            r#"<clearkey:Laurl Lic_type="EME-1.0">https://ck.example.com/</clearkey:Laurl>"#
+        ));
+        assert!(xml.contains(r#"xmlns:dashif="https://dashif.org/CPS""#));
+        let (reparsed, _) = Mpd::parse(xml.as_bytes()).unwrap();
+        // Namespaces used but not declared are declared on the `<MPD>`
+        let mut expected = mpd;
+        expected.namespaces = reparsed.namespaces.clone();
+        assert_eq!(reparsed, expected);
+    }
+
+    #[test]
+    fn test_serialize_event_content() {
+        let mpd = r#"<MPD xmlns:scte35="http://www.scte.org/schemas/35/2016"><Period>
+  <EventStream schemeIdUri="urn:scte:scte35:2013:xml" timescale="90000">
+    <Event presentationTime="900000" duration="2700000" id="1"><scte35:SpliceInfoSection><scte35:SpliceInsert spliceEventId="1" outOfNetworkIndicator="true"/></scte35:SpliceInfoSection></Event>
+    <Event id="2" contentEncoding="base64">AAEC/w==</Event>
+    <Event id="3" messageData="a &amp; b"/>
+  </EventStream>
+</Period></MPD>"#;
+        let (mpd, errors) = Mpd::parse(mpd.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        let events = &mpd.periods[0].event_streams[0].events;
+        assert_eq!(
+            events[0].content.as_deref(),
+            Some(
+                r#"<scte35:SpliceInfoSection><scte35:SpliceInsert spliceEventId="1" outOfNetworkIndicator="true"/></scte35:SpliceInfoSection>"#
+            )
+        );
+        assert_eq!(events[1].message_data, Some(vec![0, 1, 2, 255]));
+        assert_eq!(events[2].content, None);
+
+        let xml = mpd.to_xml().unwrap();
+        assert!(xml.contains(
+            r#"<Event presentationTime="900000" duration="2700000" id="1"><scte35:SpliceInfoSection><scte35:SpliceInsert spliceEventId="1" outOfNetworkIndicator="true"/></scte35:SpliceInfoSection></Event>"#
+        ));
+        assert!(xml.contains(r#"<Event id="2" contentEncoding="base64">AAEC/w==</Event>"#));
+        assert!(xml.contains(r#"<Event id="3" messageData="a &amp; b"/>"#));
+
+        // Only where the `<Event>` elements are in the document changes
+        let (mut reparsed, errors) = Mpd::parse(xml.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        let mut expected = mpd;
+        for mpd in [&mut expected, &mut reparsed] {
+            for event in &mut mpd.periods[0].event_streams[0].events {
+                event.range = None;
+            }
+        }
+        assert_eq!(reparsed, expected);
+    }
+
+    #[test]
+    fn test_serialize_low_latency_elements() {
+        let mpd = r#"<MPD type="dynamic">
+  <ServiceDescription id="0">
+    <Scope schemeIdUri="urn:dvb:dash:lowlatency:scope:2019"/>
+    <Latency referenceId="0" target="3000" min="2000" max="6000"/>
+    <PlaybackRate min="0.96" max="1.04"/>
+    <OperatingBandwidth mediaType="video" min="500000" max="9000000" target="4000000"/>
+  </ServiceDescription>
+  <Period id="1"><AdaptationSet>
+    <ProducerReferenceTime id="0" type="encoder" presentationTime="0" wallClockTime="2024-01-01T00:00:00Z">
+      <UTCTiming schemeIdUri="urn:mpeg:dash:utc:http-iso:2014" value="https://time.example.com/"/>
+    </ProducerReferenceTime>
+    <Resync type="2" dT="1000000" dImax="0.3" dImin="0.1" marker="true"/>
+  </AdaptationSet></Period>
+</MPD>"#;
+        let (mpd, errors) = Mpd::parse(mpd.as_bytes()).unwrap();
+        assert!(errors.is_empty());
+        let service_description = &mpd.service_descriptions[0];
+        assert_eq!(service_description.latencies[0].target, Some(3000));
+        assert_eq!(service_description.playback_rates[0].max, Some(1.04));
+        assert_eq!(service_description.operating_bandwidths.len(), 1);
+        assert!(service_description.operating_qualities.is_empty());
+        let adaptation_set = &mpd.periods[0].adaptation_sets[0];
+        assert_eq!(
+            adaptation_set.producer_reference_times[0].utc_timings.len(),
+            1
+        );
+        assert_eq!(adaptation_set.resyncs[0].marker, Some(true));
+
+        let xml = mpd.to_xml().unwrap();
+        assert!(xml.contains("<OperatingBandwidth "));
+        let (reparsed, _) = Mpd::parse(xml.as_bytes()).unwrap();
+        assert_eq!(reparsed, mpd);
+    }
+
+    #[test]
+    fn test_serialize_declares_used_namespaces() {
+        let mut mpd = Mpd::default();
+        mpd.periods.push(Period {
+            xlink_href: Some("https://example.com/period.xml".to_owned()),
+            ..Default::default()
+        });
+        let xml = mpd.to_xml().unwrap();
+        assert!(xml.contains(r#"xmlns:xlink="http://www.w3.org/1999/xlink""#));
+        assert!(!xml.contains("xmlns:cenc"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let (mpd, errors) =
+            Mpd::parse(r#"<MPD><Period start="invalid"/></MPD>"#.as_bytes()).unwrap();
+        assert_eq!(mpd.periods.len(), 1);
+        assert_eq!(mpd.periods[0].start, None);
+        assert_eq!(errors.len(), 1);
+
+        assert!(Mpd::parse(r#"<Period/>"#.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,2204 @@
+use std::io::BufRead;
+use std::mem;
+
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::{Reader, Writer};
+
+use super::builder::{self, Node};
+use super::serializer::XmlElement;
+use super::*;
+use crate::events::{AttributeName, TagName};
+use crate::sink::{AttrValue, MpdEventSink};
+use crate::utils;
+
+/// A parsed MPD Patch document, describing modifications to apply to a
+/// previously parsed MPD (@see `Mpd::apply_patch`).
+///
+/// MPD Patches are defined by the DASH specification (ISO/IEC 23009-1) on top
+/// of the XML Patch operations defined in RFC 5261.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    /// `mpdId` attribute: `id` of the MPD this Patch applies to.
+    pub mpd_id: Option<String>,
+    /// `originalPublishTime` attribute: `publishTime` of the MPD this Patch
+    /// applies to, in milliseconds since the Unix epoch.
+    pub original_publish_time: Option<f64>,
+    /// `publishTime` attribute: `publishTime` of the MPD once patched, in
+    /// milliseconds since the Unix epoch.
+    pub publish_time: Option<f64>,
+    pub operations: Vec<PatchOperation>,
+}
+
+/// An `<add>`, `<replace>` or `<remove>` element of an MPD Patch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchOperation {
+    pub kind: PatchOperationKind,
+    /// `sel` attribute: XPath-like selector of the targeted element or
+    /// attribute, e.g. `/MPD/Period[@id='1']/@duration`.
+    pub selector: String,
+    /// Content of the operation's element, as raw XML.
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOperationKind {
+    Add {
+        /// `pos` attribute: where elements are added relative to the selected
+        /// one.
+        position: AddPosition,
+        /// `type` attribute, e.g. `@duration` to add an attribute instead of
+        /// elements.
+        node_type: Option<String>,
+    },
+    Replace,
+    Remove,
+}
+
+/// Value of the `pos` attribute of an `<add>` operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddPosition {
+    /// Add as the last children of the selected element (the default).
+    Append,
+    /// Add as the first children of the selected element.
+    Prepend,
+    /// Add as siblings, just before the selected element.
+    Before,
+    /// Add as siblings, just after the selected element.
+    After,
+}
+
+impl Patch {
+    /// Parse the whole MPD Patch document read from `reader`.
+    ///
+    /// Unlike when parsing an MPD, any error makes the parsing fail: applying
+    /// a partially-understood Patch would lead to an invalid MPD.
+    pub fn parse<R: BufRead>(mut reader: R) -> Result<Patch> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut reader = Reader::from_reader(data.as_slice());
+        reader.trim_text(true);
+
+        let mut patch: Option<Patch> = None;
+
+        // Operation being currently parsed, with the position of the start of
+        // its content in `data`.
+        let mut current: Option<(PatchOperationKind, String, usize)> = None;
+
+        // Depth of the element currently parsed in the current operation.
+        let mut depth = 0u32;
+
+        loop {
+            let initial_pos = reader.buffer_position();
+            match reader.read_event()? {
+                XmlEvent::Start(_) if current.is_some() => depth += 1,
+                XmlEvent::End(_) if depth > 0 => depth -= 1,
+                XmlEvent::End(_) => {
+                    if let (Some(patch), Some((kind, selector, content_start))) =
+                        (&mut patch, current.take())
+                    {
+                        let content = std::str::from_utf8(&data[content_start..initial_pos])?;
+                        patch.operations.push(PatchOperation {
+                            kind,
+                            selector,
+                            content: content.to_owned(),
+                        });
+                    }
+                }
+                XmlEvent::Start(tag) => match &patch {
+                    None => patch = Some(Patch::from_patch_element(&tag)?),
+                    Some(_) => {
+                        let (kind, selector) = parse_operation_element(&tag)?;
+                        current = Some((kind, selector, reader.buffer_position()));
+                    }
+                },
+                XmlEvent::Empty(tag) if current.is_none() => match &mut patch {
+                    None => patch = Some(Patch::from_patch_element(&tag)?),
+                    Some(patch) => {
+                        let (kind, selector) = parse_operation_element(&tag)?;
+                        patch.operations.push(PatchOperation {
+                            kind,
+                            selector,
+                            content: String::new(),
+                        });
+                    }
+                },
+                XmlEvent::Eof => break,
+                _ => {}
+            }
+        }
+        patch.ok_or_else(|| patch_error("No Patch element found.".to_owned()))
+    }
+
+    fn from_patch_element(tag: &BytesStart) -> Result<Patch> {
+        if tag.local_name().as_ref() != b"Patch" {
+            return Err(patch_error("No Patch element found.".to_owned()));
+        }
+        let mut patch = Patch::default();
+        for attr in tag.attributes() {
+            let attr = attr?;
+            match attr.key.as_ref() {
+                b"mpdId" => patch.mpd_id = Some(attr.unescape_value()?.into_owned()),
+                b"originalPublishTime" => {
+                    patch.original_publish_time =
+                        Some(utils::parse_iso_8601_date_time(&attr.value)?)
+                }
+                b"publishTime" => {
+                    patch.publish_time = Some(utils::parse_iso_8601_date_time(&attr.value)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(patch)
+    }
+}
+
+/// Parse the start of an operation's element, returning its kind and its
+/// selector.
+fn parse_operation_element(tag: &BytesStart) -> Result<(PatchOperationKind, String)> {
+    let mut selector = None;
+    let mut position = AddPosition::Append;
+    let mut node_type = None;
+    for attr in tag.attributes() {
+        let attr = attr?;
+        match attr.key.as_ref() {
+            b"sel" => selector = Some(attr.unescape_value()?.into_owned()),
+            b"pos" => {
+                position = match attr.value.as_ref() {
+                    b"prepend" => AddPosition::Prepend,
+                    b"before" => AddPosition::Before,
+                    b"after" => AddPosition::After,
+                    _ => {
+                        return Err(patch_error(format!(
+                            "Invalid Patch `pos` attribute: {}",
+                            String::from_utf8_lossy(&attr.value)
+                        )))
+                    }
+                }
+            }
+            b"type" => node_type = Some(attr.unescape_value()?.into_owned()),
+            _ => {}
+        }
+    }
+    let kind = match tag.local_name().as_ref() {
+        b"add" => PatchOperationKind::Add {
+            position,
+            node_type,
+        },
+        b"replace" => PatchOperationKind::Replace,
+        b"remove" => PatchOperationKind::Remove,
+        name => {
+            return Err(patch_error(format!(
+                "Unknown Patch operation: {}",
+                String::from_utf8_lossy(name)
+            )))
+        }
+    };
+    let selector = selector
+        .ok_or_else(|| patch_error("Patch operation without a `sel` attribute.".to_owned()))?;
+    Ok((kind, selector))
+}
+
+impl Mpd {
+    /// Apply an MPD Patch to this `Mpd`.
+    ///
+    /// The Patch is only applied if it targets this MPD (same `id` and
+    /// `publishTime`) and if all of its operations succeed. This `Mpd` is left
+    /// untouched otherwise, in which case the whole MPD should be loaded
+    /// again.
+    ///
+    /// Operations on elements and attributes which are not represented in the
+    /// `Mpd` are ignored.
+    ///
+    /// As when parsing an MPD, minor errors encountered when parsing the
+    /// elements added by the Patch do not stop its application. They are
+    /// returned on success, with a path and offset relative to the content
+    /// of the operation concerned.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<Vec<ParsingError>> {
+        if patch.mpd_id != self.id || patch.original_publish_time != self.publish_time {
+            return Err(patch_error(
+                "The Patch does not apply to this MPD.".to_owned(),
+            ));
+        }
+        let mut patched = self.clone();
+        let mut errors = Vec::new();
+        for operation in &patch.operations {
+            patched.apply_patch_operation(operation, &mut errors)?;
+        }
+        if patch.publish_time.is_some() {
+            patched.publish_time = patch.publish_time;
+        }
+        *self = patched;
+        Ok(errors)
+    }
+
+    fn apply_patch_operation(
+        &mut self,
+        operation: &PatchOperation,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        let selector = Selector::parse(&operation.selector)?;
+        let (root_step, steps) = selector.steps.split_first().unwrap();
+        if root_step.name != "MPD" {
+            return Err(no_element_error(root_step));
+        }
+        select_index(1, root_step, |_, name| {
+            attribute_value(self, TagName::MPD, name)
+        })?;
+
+        let text;
+        let action = match (&operation.kind, &selector.attribute) {
+            (PatchOperationKind::Add { node_type, .. }, None) if node_type.is_some() => {
+                match node_type.as_deref().and_then(|t| t.strip_prefix('@')) {
+                    Some(name) => {
+                        text = unescaped_text(&operation.content)?;
+                        Action::SetAttribute(name, &text)
+                    }
+                    None => {
+                        return Err(patch_error(format!(
+                            "Unsupported Patch `type` attribute: {}",
+                            node_type.as_deref().unwrap_or_default()
+                        )))
+                    }
+                }
+            }
+            (PatchOperationKind::Add { position, .. }, None) => {
+                let xml = &operation.content;
+                match position {
+                    AddPosition::Append => Action::AddChildren {
+                        xml,
+                        at_start: false,
+                    },
+                    AddPosition::Prepend => Action::AddChildren {
+                        xml,
+                        at_start: true,
+                    },
+                    AddPosition::Before => Action::AddSiblings { xml, after: false },
+                    AddPosition::After => Action::AddSiblings { xml, after: true },
+                }
+            }
+            (PatchOperationKind::Add { .. }, Some(_)) => {
+                return Err(patch_error(
+                    "Patch `add` operation selecting an attribute.".to_owned(),
+                ))
+            }
+            (PatchOperationKind::Replace, None) => Action::Replace(&operation.content),
+            (PatchOperationKind::Replace, Some(name)) => {
+                text = unescaped_text(&operation.content)?;
+                Action::SetAttribute(name, &text)
+            }
+            (PatchOperationKind::Remove, None) => Action::Remove,
+            (PatchOperationKind::Remove, Some(name)) => Action::RemoveAttribute(name),
+        };
+        apply(self, TagName::MPD, steps, &action, errors)
+    }
+}
+
+fn patch_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidPatch, message)
+}
+
+/// Returns the text content of an operation, for those modifying attributes.
+fn unescaped_text(content: &str) -> Result<String> {
+    Ok(quick_xml::escape::unescape(content.trim())?.into_owned())
+}
+
+/// Selector of an MPD Patch operation.
+///
+/// Only the subset of XPath used by MPD Patches is supported: a list of
+/// element names from the root, each optionally followed by attribute value
+/// (`[@id='1']`) or position (`[2]`) predicates, optionally followed by an
+/// attribute name (`/@duration`).
+#[derive(Debug, PartialEq)]
+struct Selector {
+    steps: Vec<Step>,
+    attribute: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Step {
+    name: String,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Predicate {
+    /// The element's attribute, whose name is the first value, should be
+    /// equal to the second value.
+    Attribute(String, String),
+    /// Position of the element among the ones matching the previous
+    /// predicates, starting at `1`.
+    Position(usize),
+}
+
+impl Selector {
+    fn parse(sel: &str) -> Result<Selector> {
+        let error = || patch_error(format!("Unsupported Patch selector: {}", sel));
+        let mut steps = Vec::new();
+        let mut attribute = None;
+        let mut rest = sel.trim();
+        while !rest.is_empty() {
+            rest = rest.strip_prefix('/').ok_or_else(error)?;
+            if let Some(name) = rest.strip_prefix('@') {
+                if !is_valid_name(name) {
+                    return Err(error());
+                }
+                attribute = Some(name.to_owned());
+                break;
+            }
+            let name_len = rest.find(['/', '[']).unwrap_or(rest.len());
+            let name = &rest[..name_len];
+            if !is_valid_name(name) {
+                return Err(error());
+            }
+            rest = &rest[name_len..];
+
+            let mut predicates = Vec::new();
+            while let Some(predicate_start) = rest.strip_prefix('[') {
+                let predicate_len = predicate_length(predicate_start).ok_or_else(error)?;
+                let predicate = predicate_start[..predicate_len].trim();
+                rest = &predicate_start[predicate_len + 1..];
+                predicates.push(Predicate::parse(predicate).ok_or_else(error)?);
+            }
+            steps.push(Step {
+                name: name.to_owned(),
+                predicates,
+            });
+        }
+        if steps.is_empty() {
+            return Err(error());
+        }
+        Ok(Selector { steps, attribute })
+    }
+}
+
+impl Predicate {
+    fn parse(predicate: &str) -> Option<Predicate> {
+        match predicate.strip_prefix('@') {
+            Some(attr) => {
+                let (name, value) = attr.split_once('=')?;
+                let (name, value) = (name.trim(), value.trim());
+                let quote = value.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+                let value = value.strip_prefix(quote)?.strip_suffix(quote)?;
+                if !is_valid_name(name) {
+                    return None;
+                }
+                Some(Predicate::Attribute(name.to_owned(), value.to_owned()))
+            }
+            None => match predicate.parse::<usize>() {
+                Ok(position) if position > 0 => Some(Predicate::Position(position)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Returns the length of the predicate starting at the beginning of `data`
+/// (just after its `[`), `None` if it is not closed.
+fn predicate_length(data: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in data.char_indices() {
+        match (quote, c) {
+            (None, ']') => return Some(i),
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ':' || c == '_' || c == '-' || c == '.')
+}
+
+/// Modification to apply to the element selected by an operation's selector.
+enum Action<'a> {
+    /// Add the elements described by the XML in the selected element.
+    AddChildren {
+        xml: &'a str,
+        at_start: bool,
+    },
+    /// Add the elements described by the XML next to the selected element.
+    AddSiblings {
+        xml: &'a str,
+        after: bool,
+    },
+    /// Add the elements described by the XML, which all have the name of the
+    /// selector's last step, to the other elements with that name.
+    ///
+    /// Only used internally when applying an `AddChildren` action.
+    Insert {
+        xml: &'a str,
+        at_start: bool,
+    },
+    /// Replace the selected element by the one described by the XML.
+    Replace(&'a str),
+    Remove,
+    /// Set the attribute with the given name to the given value.
+    SetAttribute(&'a str, &'a str),
+    RemoveAttribute(&'a str),
+}
+
+/// An element of the `Mpd` which can be modified by an MPD Patch.
+trait PatchTarget: Default + Sized {
+    /// Convert that element into a `Node`, so it can be modified as when it
+    /// was being parsed.
+    fn into_node(self, tag_name: TagName) -> Node;
+
+    /// Convert back a `Node` into that element, `None` if it is another one.
+    fn from_node(node: Node, tag_name: TagName) -> Option<Self>;
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement);
+
+    /// Apply `action` to the child element selected by `step` or, if `rest`
+    /// is not empty, to one of its descendants.
+    ///
+    /// Children not represented in the `Mpd` cannot be patched, which is an
+    /// error as the patched `Mpd` would not match the one the Patch targets.
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        _rest: &[Step],
+        _action: &Action,
+        _errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        Err(unsupported_element_error(step))
+    }
+}
+
+/// Apply `action` to `elt` or, if `steps` is not empty, to the descendant it
+/// selects.
+fn apply<T: PatchTarget>(
+    elt: &mut T,
+    tag_name: TagName,
+    steps: &[Step],
+    action: &Action,
+    errors: &mut Vec<ParsingError>,
+) -> Result<()> {
+    if let Some((step, rest)) = steps.split_first() {
+        return elt.apply_to_child(step, rest, action, errors);
+    }
+    match *action {
+        Action::AddChildren { xml, at_start } => {
+            let mut groups = split_elements(xml)?;
+            if at_start {
+                // Each group is added before the previous one
+                groups.reverse();
+            }
+            for (name, xml) in groups {
+                let step = Step {
+                    name,
+                    predicates: Vec::new(),
+                };
+                let action = Action::Insert { xml, at_start };
+                elt.apply_to_child(&step, &[], &action, errors)?;
+            }
+            Ok(())
+        }
+        Action::SetAttribute(name, value) => {
+            update_attribute(elt, tag_name, name, value, false, errors)
+        }
+        Action::RemoveAttribute(name) => match attribute_value(elt, tag_name, name) {
+            Some(value) => update_attribute(elt, tag_name, name, &value, true, errors),
+            None => Ok(()),
+        },
+        Action::AddSiblings { .. }
+        | Action::Insert { .. }
+        | Action::Replace(_)
+        | Action::Remove => Err(patch_error(format!(
+            "Patch operation not applicable to the {} element.",
+            xml_name(tag_name)
+        ))),
+    }
+}
+
+/// Apply `action` to the element selected by `step` in `elements` or, if
+/// `rest` is not empty, to one of its descendants.
+fn apply_in_list<T: PatchTarget>(
+    elements: &mut Vec<T>,
+    tag_name: TagName,
+    step: &Step,
+    rest: &[Step],
+    action: &Action,
+    errors: &mut Vec<ParsingError>,
+) -> Result<()> {
+    if let (true, Action::Insert { xml, at_start }) = (rest.is_empty(), action) {
+        let new_elements = parse_elements(xml, tag_name, errors)?;
+        let index = if *at_start { 0 } else { elements.len() };
+        elements.splice(index..index, new_elements);
+        return Ok(());
+    }
+
+    let index = select_index(elements.len(), step, |i, name| {
+        attribute_value(&elements[i], tag_name, name)
+    })?;
+    match (rest.is_empty(), action) {
+        (true, Action::AddSiblings { xml, after }) => {
+            let new_elements = parse_elements(xml, tag_name, errors)?;
+            let index = if *after { index + 1 } else { index };
+            elements.splice(index..index, new_elements);
+            Ok(())
+        }
+        (true, Action::Replace(xml)) => {
+            let new_element = parse_single_element(xml, tag_name, errors)?;
+            elements[index] = new_element;
+            Ok(())
+        }
+        (true, Action::Remove) => {
+            elements.remove(index);
+            Ok(())
+        }
+        _ => apply(&mut elements[index], tag_name, rest, action, errors),
+    }
+}
+
+/// Same as `apply_in_list` for elements which can only be present once.
+fn apply_in_option<T: PatchTarget>(
+    element: &mut Option<T>,
+    tag_name: TagName,
+    step: &Step,
+    rest: &[Step],
+    action: &Action,
+    errors: &mut Vec<ParsingError>,
+) -> Result<()> {
+    let mut elements: Vec<T> = element.take().into_iter().collect();
+    apply_in_list(&mut elements, tag_name, step, rest, action, errors)?;
+    if elements.len() > 1 {
+        return Err(patch_error(format!(
+            "Patch operation leading to multiple {} elements.",
+            xml_name(tag_name)
+        )));
+    }
+    *element = elements.pop();
+    Ok(())
+}
+
+/// Same as `apply_in_list` for elements only represented by their text
+/// content, such as `<Location>` elements.
+fn apply_in_texts(
+    texts: &mut Vec<String>,
+    step: &Step,
+    rest: &[Step],
+    action: &Action,
+) -> Result<()> {
+    if !rest.is_empty() {
+        return Ok(());
+    }
+    if let Action::Insert { xml, at_start } = action {
+        let index = if *at_start { 0 } else { texts.len() };
+        texts.splice(index..index, parse_texts(xml)?);
+        return Ok(());
+    }
+    let index = select_index(texts.len(), step, |_, _| None)?;
+    match action {
+        Action::AddSiblings { xml, after } => {
+            let index = if *after { index + 1 } else { index };
+            texts.splice(index..index, parse_texts(xml)?);
+        }
+        Action::Replace(xml) => {
+            texts.splice(index..=index, parse_texts(xml)?);
+        }
+        Action::Remove => {
+            texts.remove(index);
+        }
+        _ => {
+            return Err(patch_error(format!(
+                "Patch operation not applicable to the {} element.",
+                step.name
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Same as `apply_in_option` for the `<SegmentTimeline>` element, whose `<S>`
+/// children can be selected and modified.
+///
+/// As `<S>` elements are represented with their starting time, modifying one
+/// does not update the starting time of the following ones.
+fn apply_in_timeline(
+    timeline: &mut Option<SegmentTimeline>,
+    step: &Step,
+    rest: &[Step],
+    action: &Action,
+    errors: &mut Vec<ParsingError>,
+) -> Result<()> {
+    if let (true, Action::Insert { xml, .. }) = (rest.is_empty(), action) {
+        if timeline.is_some() {
+            return Err(patch_error(
+                "Patch operation leading to multiple SegmentTimeline elements.".to_owned(),
+            ));
+        }
+        *timeline = Some(SegmentTimeline(parse_segments(xml, 0, errors)?));
+        return Ok(());
+    }
+
+    select_index(timeline.iter().len(), step, |_, _| None)?;
+    let segments = match timeline {
+        Some(SegmentTimeline(segments)) => segments,
+        None => unreachable!(),
+    };
+    match (rest, action) {
+        ([], Action::AddChildren { xml, at_start }) => {
+            let index = if *at_start { 0 } else { segments.len() };
+            let time_base = segments_end(&segments[..index]);
+            let new_segments = parse_segments(xml, time_base, errors)?;
+            segments.splice(index..index, new_segments);
+        }
+        ([], Action::Replace(xml)) => {
+            *timeline = Some(SegmentTimeline(parse_segments(xml, 0, errors)?));
+        }
+        ([], Action::Remove) => *timeline = None,
+        ([s_step], _) if s_step.name == "S" => {
+            let index = select_index(segments.len(), s_step, |i, name| {
+                let segment = &segments[i];
+                match name {
+                    "t" => Some(segment.start.to_string()),
+                    "d" => Some(segment.duration.to_string()),
+                    "r" => Some(segment.repeat_count.to_string()),
+                    "n" => segment.number.map(|n| n.to_string()),
+                    "k" => segment.chunk_count.map(|k| k.to_string()),
+                    _ => None,
+                }
+            })?;
+            let (index, remove_count, xml) = match *action {
+                Action::AddSiblings { xml, after } => (index + after as usize, 0, xml.to_owned()),
+                Action::Replace(xml) => (index, 1, xml.to_owned()),
+                Action::Remove => (index, 1, String::new()),
+                Action::SetAttribute(name, value) => (
+                    index,
+                    1,
+                    s_element_xml(&segments[index], name, Some(value))?,
+                ),
+                Action::RemoveAttribute(name) => {
+                    (index, 1, s_element_xml(&segments[index], name, None)?)
+                }
+                _ => {
+                    return Err(patch_error(
+                        "Patch operation not applicable to the S element.".to_owned(),
+                    ))
+                }
+            };
+            let time_base = segments_end(&segments[..index]);
+            let new_segments = parse_segments(&xml, time_base, errors)?;
+            segments.splice(index..index + remove_count, new_segments);
+        }
+        _ => {
+            return Err(patch_error(
+                "Patch operation not applicable to the SegmentTimeline element.".to_owned(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Returns the XML of an `<S>` element corresponding to `segment`, with its
+/// attribute `name` set to `value` (or removed if `None`).
+fn s_element_xml(segment: &SegmentObject, name: &str, value: Option<&str>) -> Result<String> {
+    let mut start = BytesStart::new("S");
+    let mut attributes = vec![
+        ("t", segment.start.to_string()),
+        ("d", segment.duration.to_string()),
+        ("r", segment.repeat_count.to_string()),
+    ];
+    if let Some(number) = segment.number {
+        attributes.push(("n", number.to_string()));
+    }
+    if let Some(chunk_count) = segment.chunk_count {
+        attributes.push(("k", chunk_count.to_string()));
+    }
+    attributes.retain(|(key, _)| *key != name);
+    if let Some(value) = value {
+        start.push_attribute((name, value));
+    }
+    for (key, value) in &attributes {
+        start.push_attribute((*key, value.as_str()));
+    }
+    write_element(start)
+}
+
+/// Returns the timestamp at which the last of the given segments ends, `0`
+/// if there is none.
+///
+/// This is the `time_base` the `MPDProcessor` would use for the next `<S>`
+/// element.
+fn segments_end(segments: &[SegmentObject]) -> u64 {
+    segments.last().map_or(0, SegmentObject::end)
+}
+
+/// Select the element matching `step` among `len` elements with its name.
+///
+/// `attribute` returns the value of an element's attribute, from its index
+/// and the attribute's name.
+///
+/// Like in RFC 5261, exactly one element has to be matched.
+fn select_index<F: Fn(usize, &str) -> Option<String>>(
+    len: usize,
+    step: &Step,
+    attribute: F,
+) -> Result<usize> {
+    let mut candidates: Vec<usize> = (0..len).collect();
+    for predicate in &step.predicates {
+        match predicate {
+            Predicate::Attribute(name, value) => {
+                candidates.retain(|i| attribute(*i, name).as_ref() == Some(value))
+            }
+            Predicate::Position(position) => {
+                candidates = candidates.get(position - 1).copied().into_iter().collect()
+            }
+        }
+    }
+    match candidates.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(no_element_error(step)),
+        _ => Err(patch_error(format!(
+            "Patch selector matching multiple {} elements.",
+            step.name
+        ))),
+    }
+}
+
+fn no_element_error(step: &Step) -> ParsingError {
+    patch_error(format!("Patch selector matching no {} element.", step.name))
+}
+
+/// Returns the value of the attribute `name` of `elt`, as it would be
+/// written in the MPD.
+fn attribute_value<T: PatchTarget>(elt: &T, tag_name: TagName, name: &str) -> Option<String> {
+    let mut xml_elt = XmlElement::new(xml_name(tag_name));
+    elt.set_xml_attributes(&mut xml_elt);
+    xml_elt.attribute(name).map(str::to_owned)
+}
+
+/// Set the attribute `name` of `elt` by parsing `value` as it would be in an
+/// MPD or, if `unset` is `true`, unset that attribute.
+///
+/// Attributes which are not represented in the `Mpd` are ignored.
+fn update_attribute<T: PatchTarget>(
+    elt: &mut T,
+    tag_name: TagName,
+    name: &str,
+    value: &str,
+    unset: bool,
+    errors: &mut Vec<ParsingError>,
+) -> Result<()> {
+    let mut start = BytesStart::new(xml_name(tag_name));
+    start.push_attribute((name, value));
+    let xml = in_parent_element(tag_name, write_element(start)?);
+
+    let sink = AttributeSink {
+        node: mem::take(elt).into_node(tag_name),
+        in_node: false,
+        unset,
+        errors: Vec::new(),
+    };
+    let mut processor = MPDProcessor::new(xml.as_bytes(), sink);
+    processor.process_tags()?;
+    let sink = processor.into_sink();
+    *elt = T::from_node(sink.node, tag_name).unwrap_or_default();
+    match sink.errors.into_iter().next() {
+        Some(err) if !unset => Err(ParsingError::new(
+            err.kind,
+            format!("Invalid {} attribute in Patch: {}", name, err.message),
+        )),
+        Some(err) => {
+            errors.push(err);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// `MpdEventSink` setting the attributes reported for an element on an
+/// existing `Node`.
+struct AttributeSink {
+    node: Node,
+    /// `true` when attributes reported concern `node`.
+    in_node: bool,
+    /// If `true`, reported attributes are unset instead.
+    unset: bool,
+    errors: Vec<ParsingError>,
+}
+
+impl MpdEventSink for AttributeSink {
+    fn tag_open(&mut self, tag_name: TagName) {
+        self.in_node = tag_name == self.node.tag_name();
+    }
+
+    fn tag_close(&mut self, _tag_name: TagName) {
+        self.in_node = false;
+    }
+
+    fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+        // The range is not an attribute but where the element is in the MPD,
+        // and the XML would be that of the element only holding `attr_name`
+        if self.in_node
+            && attr_name != AttributeName::EventStreamEltRange
+            && attr_name != AttributeName::EventStreamEltXml
+        {
+            let value = if self.unset { None } else { Some(value) };
+            self.node.set_attribute(attr_name, value);
+        }
+    }
+
+    fn error(&mut self, err: &ParsingError) {
+        self.errors.push(err.clone());
+    }
+}
+
+/// `MpdEventSink` only collecting errors.
+struct ErrorSink<'a>(&'a mut Vec<ParsingError>);
+
+impl MpdEventSink for ErrorSink<'_> {
+    fn tag_open(&mut self, _tag_name: TagName) {}
+    fn tag_close(&mut self, _tag_name: TagName) {}
+    fn attribute(&mut self, _attr_name: AttributeName, _value: AttrValue) {}
+    fn error(&mut self, err: &ParsingError) {
+        self.0.push(err.clone());
+    }
+}
+
+fn write_element(start: BytesStart) -> Result<String> {
+    let mut xml = Vec::new();
+    Writer::new(&mut xml).write_event(XmlEvent::Empty(start))?;
+    Ok(String::from_utf8(xml)?)
+}
+
+/// `<Event>` elements are only reported by the parser when in an
+/// `<EventStream>` element, wrap their XML in one in that case.
+fn in_parent_element(tag_name: TagName, xml: String) -> String {
+    match tag_name {
+        TagName::EventStreamElt => format!("<EventStream>{}</EventStream>", xml),
+        _ => xml,
+    }
+}
+
+/// Parse `xml` into elements which should all be `tag_name` elements.
+fn parse_elements<T: PatchTarget>(
+    xml: &str,
+    tag_name: TagName,
+    errors: &mut Vec<ParsingError>,
+) -> Result<Vec<T>> {
+    let (mut nodes, parsing_errors) =
+        builder::parse_fragment(&in_parent_element(tag_name, xml.to_owned()));
+    errors.extend(parsing_errors);
+    if tag_name == TagName::EventStreamElt {
+        nodes = match nodes.pop() {
+            Some(Node::EventStream(event_stream)) => {
+                // Make ranges relative to `xml` rather than to its wrapper
+                let offset = "<EventStream>".len() as f64;
+                event_stream
+                    .events
+                    .into_iter()
+                    .map(|mut event| {
+                        if let Some((start, end)) = event.range {
+                            event.range = Some((start - offset, end - offset));
+                        }
+                        Node::Event(event)
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+    }
+    nodes
+        .into_iter()
+        .map(|node| {
+            T::from_node(node, tag_name).ok_or_else(|| {
+                patch_error(format!(
+                    "Unexpected element in a Patch operation on {} elements.",
+                    xml_name(tag_name)
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Parse `xml` into a single `tag_name` element.
+fn parse_single_element<T: PatchTarget>(
+    xml: &str,
+    tag_name: TagName,
+    errors: &mut Vec<ParsingError>,
+) -> Result<T> {
+    let mut elements = parse_elements(xml, tag_name, errors)?;
+    match (elements.pop(), elements.is_empty()) {
+        (Some(element), true) => Ok(element),
+        _ => Err(patch_error(format!(
+            "A single {} element was expected in a Patch operation.",
+            xml_name(tag_name)
+        ))),
+    }
+}
+
+/// Parse the `<S>` elements found in `xml`, `time_base` being the
+/// timestamp at which the first one starts if it has no `t` attribute.
+fn parse_segments(
+    xml: &str,
+    mut time_base: u64,
+    errors: &mut Vec<ParsingError>,
+) -> Result<Vec<SegmentObject>> {
+    let mut reader = Reader::from_str(xml);
+    let mut segments = Vec::new();
+    loop {
+        match reader.read_event()? {
+            XmlEvent::Start(tag) | XmlEvent::Empty(tag) if tag.name().as_ref() == b"S" => {
+                let segment =
+                    SegmentObject::from_s_element(&tag, time_base, &mut ErrorSink(errors))?;
+                time_base = segment.end();
+                segments.push(segment);
+            }
+            XmlEvent::Eof => return Ok(segments),
+            _ => {}
+        }
+    }
+}
+
+/// Parse the text content of each element at the root of `xml`.
+fn parse_texts(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut texts = Vec::new();
+    let mut depth = 0u32;
+    loop {
+        match reader.read_event()? {
+            XmlEvent::Start(_) => {
+                depth += 1;
+                if depth == 1 {
+                    texts.push(String::new());
+                }
+            }
+            XmlEvent::Empty(_) if depth == 0 => texts.push(String::new()),
+            XmlEvent::End(_) => depth = depth.saturating_sub(1),
+            XmlEvent::Text(text) if depth == 1 => {
+                if let Some(last) = texts.last_mut() {
+                    last.push_str(&text.unescape()?);
+                }
+            }
+            XmlEvent::Eof => return Ok(texts),
+            _ => {}
+        }
+    }
+}
+
+/// Split `xml` into groups of contiguous elements with the same name, at its
+/// root.
+fn split_elements(xml: &str) -> Result<Vec<(String, &str)>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut groups: Vec<(String, &str)> = Vec::new();
+
+    // Name and starting position of the root element currently read
+    let mut current: Option<(String, usize)> = None;
+    let mut depth = 0u32;
+    loop {
+        let initial_pos = reader.buffer_position();
+        let (name, start) = match reader.read_event()? {
+            XmlEvent::Start(_) if depth > 0 => {
+                depth += 1;
+                continue;
+            }
+            XmlEvent::Start(tag) => {
+                depth = 1;
+                let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+                current = Some((name, initial_pos));
+                continue;
+            }
+            XmlEvent::End(_) if depth > 1 => {
+                depth -= 1;
+                continue;
+            }
+            XmlEvent::End(_) => {
+                depth = 0;
+                match current.take() {
+                    Some(current) => current,
+                    None => continue,
+                }
+            }
+            XmlEvent::Empty(tag) if depth == 0 => {
+                let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+                (name, initial_pos)
+            }
+            XmlEvent::Text(_) if depth == 0 => {
+                return Err(patch_error(
+                    "Unexpected text in a Patch operation.".to_owned(),
+                ))
+            }
+            XmlEvent::Eof => return Ok(groups),
+            _ => continue,
+        };
+        let end = reader.buffer_position();
+        match groups.last_mut() {
+            Some((last_name, last_xml)) if *last_name == name => {
+                let last_start = last_xml.as_ptr() as usize - xml.as_ptr() as usize;
+                *last_xml = &xml[last_start..end];
+            }
+            _ => groups.push((name, &xml[start..end])),
+        }
+    }
+}
+
+/// Returns the name of the XML element corresponding to a `TagName`.
+fn xml_name(tag_name: TagName) -> &'static str {
+    match tag_name {
+        TagName::MPD => "MPD",
+        TagName::Period => "Period",
+        TagName::UtcTiming => "UTCTiming",
+        TagName::AdaptationSet => "AdaptationSet",
+        TagName::EventStream => "EventStream",
+        TagName::EventStreamElt => "Event",
+        TagName::Representation => "Representation",
+        TagName::Accessibility => "Accessibility",
+        TagName::AudioChannelConfiguration => "AudioChannelConfiguration",
+        TagName::ContentComponent => "ContentComponent",
+        TagName::ContentProtection => "ContentProtection",
+        TagName::Preselection => "Preselection",
+        TagName::EssentialProperty => "EssentialProperty",
+        TagName::InbandEventStream => "InbandEventStream",
+        TagName::Role => "Role",
+        TagName::SupplementalProperty => "SupplementalProperty",
+        TagName::SegmentBase => "SegmentBase",
+        TagName::SegmentList => "SegmentList",
+        TagName::SegmentTemplate => "SegmentTemplate",
+        TagName::SegmentUrl => "SegmentURL",
+        TagName::BaseURL => "BaseURL",
+        TagName::ContentSteering => "ContentSteering",
+        TagName::ProducerReferenceTime => "ProducerReferenceTime",
+        TagName::Resync => "Resync",
+        TagName::ServiceDescription => "ServiceDescription",
+        TagName::Scope => "Scope",
+        TagName::Latency => "Latency",
+        TagName::PlaybackRate => "PlaybackRate",
+        TagName::OperatingQuality => "OperatingQuality",
+        TagName::OperatingBandwidth => "OperatingBandwidth",
+    }
+}
+
+/// Error for elements which are either not represented in the `Mpd` or
+/// cannot be modified by a Patch.
+fn unsupported_element_error(step: &Step) -> ParsingError {
+    patch_error(format!(
+        "Patch operations on {} elements are not supported.",
+        step.name
+    ))
+}
+
+impl PatchTarget for Mpd {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Mpd(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Mpd(mpd) => Some(mpd),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Mpd::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "BaseURL" => apply_in_list(
+                &mut self.base_urls,
+                TagName::BaseURL,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Location" => apply_in_texts(&mut self.locations, step, rest, action),
+            "ServiceDescription" => apply_in_list(
+                &mut self.service_descriptions,
+                TagName::ServiceDescription,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "ContentSteering" => apply_in_option(
+                &mut self.content_steering,
+                TagName::ContentSteering,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Period" => apply_in_list(
+                &mut self.periods,
+                TagName::Period,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "UTCTiming" => apply_in_list(
+                &mut self.utc_timings,
+                TagName::UtcTiming,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for Period {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Period(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Period(period) => Some(period),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Period::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        use TagName::*;
+        match step.name.as_str() {
+            "BaseURL" => apply_in_list(&mut self.base_urls, BaseURL, step, rest, action, errors),
+            "AdaptationSet" => apply_in_list(
+                &mut self.adaptation_sets,
+                AdaptationSet,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "EventStream" => apply_in_list(
+                &mut self.event_streams,
+                EventStream,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "ServiceDescription" => apply_in_list(
+                &mut self.service_descriptions,
+                ServiceDescription,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Preselection" => apply_in_list(
+                &mut self.preselections,
+                Preselection,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentBase" => apply_in_option(
+                &mut self.segment_base,
+                SegmentBase,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentList" => apply_in_option(
+                &mut self.segment_list,
+                SegmentList,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentTemplate" => apply_in_option(
+                &mut self.segment_template,
+                SegmentTemplate,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for AdaptationSet {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::AdaptationSet(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::AdaptationSet(adaptation_set) => Some(adaptation_set),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        AdaptationSet::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        use TagName::*;
+        match step.name.as_str() {
+            "BaseURL" => apply_in_list(&mut self.base_urls, BaseURL, step, rest, action, errors),
+            "Accessibility" => apply_in_list(
+                &mut self.accessibilities,
+                Accessibility,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "AudioChannelConfiguration" => apply_in_list(
+                &mut self.audio_channel_configurations,
+                AudioChannelConfiguration,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "ContentComponent" => apply_in_list(
+                &mut self.content_components,
+                ContentComponent,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "ContentProtection" => apply_in_list(
+                &mut self.content_protections,
+                ContentProtection,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "EssentialProperty" => apply_in_list(
+                &mut self.essential_properties,
+                EssentialProperty,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "InbandEventStream" => apply_in_list(
+                &mut self.inband_event_streams,
+                InbandEventStream,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Role" => apply_in_list(&mut self.roles, Role, step, rest, action, errors),
+            "SupplementalProperty" => apply_in_list(
+                &mut self.supplemental_properties,
+                SupplementalProperty,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Label" => apply_in_texts(&mut self.labels, step, rest, action),
+            "ProducerReferenceTime" => apply_in_list(
+                &mut self.producer_reference_times,
+                ProducerReferenceTime,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Resync" => apply_in_list(&mut self.resyncs, Resync, step, rest, action, errors),
+            "Preselection" => apply_in_list(
+                &mut self.preselections,
+                Preselection,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Representation" => apply_in_list(
+                &mut self.representations,
+                Representation,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentBase" => apply_in_option(
+                &mut self.segment_base,
+                SegmentBase,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentList" => apply_in_option(
+                &mut self.segment_list,
+                SegmentList,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentTemplate" => apply_in_option(
+                &mut self.segment_template,
+                SegmentTemplate,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for Representation {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Representation(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Representation(representation) => Some(representation),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Representation::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        use TagName::*;
+        match step.name.as_str() {
+            "BaseURL" => apply_in_list(&mut self.base_urls, BaseURL, step, rest, action, errors),
+            "AudioChannelConfiguration" => apply_in_list(
+                &mut self.audio_channel_configurations,
+                AudioChannelConfiguration,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "ContentProtection" => apply_in_list(
+                &mut self.content_protections,
+                ContentProtection,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "EssentialProperty" => apply_in_list(
+                &mut self.essential_properties,
+                EssentialProperty,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "InbandEventStream" => apply_in_list(
+                &mut self.inband_event_streams,
+                InbandEventStream,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SupplementalProperty" => apply_in_list(
+                &mut self.supplemental_properties,
+                SupplementalProperty,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Label" => apply_in_texts(&mut self.labels, step, rest, action),
+            "ProducerReferenceTime" => apply_in_list(
+                &mut self.producer_reference_times,
+                ProducerReferenceTime,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Resync" => apply_in_list(&mut self.resyncs, Resync, step, rest, action, errors),
+            "SegmentBase" => apply_in_option(
+                &mut self.segment_base,
+                SegmentBase,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentList" => apply_in_option(
+                &mut self.segment_list,
+                SegmentList,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentTemplate" => apply_in_option(
+                &mut self.segment_template,
+                SegmentTemplate,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for BaseUrl {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::BaseUrl(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::BaseUrl(base_url) => Some(base_url),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        BaseUrl::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for ContentSteering {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::ContentSteering(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::ContentSteering(content_steering) => Some(content_steering),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        ContentSteering::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for Scheme {
+    fn into_node(self, tag_name: TagName) -> Node {
+        Node::Scheme(tag_name, self)
+    }
+
+    fn from_node(node: Node, tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Scheme(scheme_tag_name, scheme) if scheme_tag_name == tag_name => Some(scheme),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Scheme::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for ContentComponent {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::ContentComponent(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::ContentComponent(content_component) => Some(content_component),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        ContentComponent::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for Preselection {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Preselection(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Preselection(preselection) => Some(preselection),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Preselection::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        use TagName::*;
+        match step.name.as_str() {
+            "Accessibility" => apply_in_list(
+                &mut self.accessibilities,
+                Accessibility,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "AudioChannelConfiguration" => apply_in_list(
+                &mut self.audio_channel_configurations,
+                AudioChannelConfiguration,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "Role" => apply_in_list(&mut self.roles, Role, step, rest, action, errors),
+            "Label" => apply_in_texts(&mut self.labels, step, rest, action),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for ContentProtection {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::ContentProtection(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::ContentProtection(content_protection) => Some(content_protection),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        ContentProtection::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        _errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "cenc:pssh" => apply_in_texts(&mut self.cenc_pssh, step, rest, action),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for SegmentBase {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::SegmentBase(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::SegmentBase(segment_base) => Some(segment_base),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        SegmentBase::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        _rest: &[Step],
+        _action: &Action,
+        _errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "Initialization" => Err(unsupported_element_error(step)),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for SegmentList {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::SegmentList(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::SegmentList(segment_list) => Some(segment_list),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        self.base.set_xml_attributes(elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "SegmentURL" => apply_in_list(
+                &mut self.segment_urls,
+                TagName::SegmentUrl,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "SegmentTimeline" => apply_in_timeline(&mut self.timeline, step, rest, action, errors),
+            "Initialization" => Err(unsupported_element_error(step)),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for SegmentUrl {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::SegmentUrl(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::SegmentUrl(segment_url) => Some(segment_url),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        SegmentUrl::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for SegmentTemplate {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::SegmentTemplate(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::SegmentTemplate(segment_template) => Some(segment_template),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        SegmentTemplate::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "SegmentTimeline" => apply_in_timeline(&mut self.timeline, step, rest, action, errors),
+            "Initialization" => Err(unsupported_element_error(step)),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for EventStream {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::EventStream(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::EventStream(event_stream) => Some(event_stream),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        EventStream::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "Event" => apply_in_list(
+                &mut self.events,
+                TagName::EventStreamElt,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for Event {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Event(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Event(event) => Some(event),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Event::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for ServiceDescription {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::ServiceDescription(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::ServiceDescription(service_description) => Some(service_description),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        ServiceDescription::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        use TagName::*;
+        match step.name.as_str() {
+            "Scope" => apply_in_list(&mut self.scopes, Scope, step, rest, action, errors),
+            "Latency" => apply_in_list(&mut self.latencies, Latency, step, rest, action, errors),
+            "PlaybackRate" => apply_in_list(
+                &mut self.playback_rates,
+                PlaybackRate,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "OperatingQuality" => apply_in_list(
+                &mut self.operating_qualities,
+                OperatingQuality,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            "OperatingBandwidth" => apply_in_list(
+                &mut self.operating_bandwidths,
+                OperatingBandwidth,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for Latency {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Latency(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Latency(latency) => Some(latency),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Latency::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for PlaybackRate {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::PlaybackRate(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::PlaybackRate(playback_rate) => Some(playback_rate),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        PlaybackRate::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for OperatingQuality {
+    fn into_node(self, tag_name: TagName) -> Node {
+        Node::OperatingQuality(tag_name, self)
+    }
+
+    fn from_node(node: Node, tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::OperatingQuality(node_tag_name, operating_quality)
+                if node_tag_name == tag_name =>
+            {
+                Some(operating_quality)
+            }
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        OperatingQuality::set_xml_attributes(self, elt)
+    }
+}
+
+impl PatchTarget for ProducerReferenceTime {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::ProducerReferenceTime(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::ProducerReferenceTime(producer_reference_time) => Some(producer_reference_time),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        ProducerReferenceTime::set_xml_attributes(self, elt)
+    }
+
+    fn apply_to_child(
+        &mut self,
+        step: &Step,
+        rest: &[Step],
+        action: &Action,
+        errors: &mut Vec<ParsingError>,
+    ) -> Result<()> {
+        match step.name.as_str() {
+            "UTCTiming" => apply_in_list(
+                &mut self.utc_timings,
+                TagName::UtcTiming,
+                step,
+                rest,
+                action,
+                errors,
+            ),
+            _ => Err(unsupported_element_error(step)),
+        }
+    }
+}
+
+impl PatchTarget for Resync {
+    fn into_node(self, _tag_name: TagName) -> Node {
+        Node::Resync(self)
+    }
+
+    fn from_node(node: Node, _tag_name: TagName) -> Option<Self> {
+        match node {
+            Node::Resync(resync) => Some(resync),
+            _ => None,
+        }
+    }
+
+    fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        Resync::set_xml_attributes(self, elt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MPD: &str = r#"<MPD id="live" type="dynamic" publishTime="2024-01-01T00:00:00Z">
+  <Location>https://example.com/live.mpd</Location>
+  <Period id="1" start="PT0S">
+    <AdaptationSet id="v" contentType="video">
+      <SegmentTemplate timescale="1000" media="$Time$.m4s">
+        <SegmentTimeline><S t="0" d="2000" r="1"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="v1" bandwidth="1000000"/>
+      <Representation id="v2" bandwidth="2000000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+    fn parse_mpd() -> Mpd {
+        Mpd::parse(MPD.as_bytes()).unwrap().0
+    }
+
+    fn patch(operations: &str) -> Patch {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Patch xmlns="urn:mpeg:dash:schema:mpd-patch:2020" mpdId="live"
+  originalPublishTime="2024-01-01T00:00:00Z" publishTime="2024-01-01T00:00:10Z">
+{}
+</Patch>"#,
+            operations
+        );
+        Patch::parse(xml.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = patch(
+            r#"<replace sel="/MPD/@publishTime">2024-01-01T00:00:10Z</replace>
+<add sel="/MPD/Period[@id='1']" pos="after"><Period id="2"><AdaptationSet/></Period></add>
+<add sel="/MPD/Period[1]" type="@duration">PT10S</add>
+<remove sel="/MPD/Location"/>"#,
+        );
+        assert_eq!(patch.mpd_id.as_deref(), Some("live"));
+        assert_eq!(patch.original_publish_time, Some(1704067200000.));
+        assert_eq!(patch.publish_time, Some(1704067210000.));
+        assert_eq!(
+            patch.operations,
+            vec![
+                PatchOperation {
+                    kind: PatchOperationKind::Replace,
+                    selector: "/MPD/@publishTime".to_owned(),
+                    content: "2024-01-01T00:00:10Z".to_owned(),
+                },
+                PatchOperation {
+                    kind: PatchOperationKind::Add {
+                        position: AddPosition::After,
+                        node_type: None,
+                    },
+                    selector: "/MPD/Period[@id='1']".to_owned(),
+                    content: r#"<Period id="2"><AdaptationSet/></Period>"#.to_owned(),
+                },
+                PatchOperation {
+                    kind: PatchOperationKind::Add {
+                        position: AddPosition::Append,
+                        node_type: Some("@duration".to_owned()),
+                    },
+                    selector: "/MPD/Period[1]".to_owned(),
+                    content: "PT10S".to_owned(),
+                },
+                PatchOperation {
+                    kind: PatchOperationKind::Remove,
+                    selector: "/MPD/Location".to_owned(),
+                    content: String::new(),
+                },
+            ]
+        );
+
+        assert!(Patch::parse("<MPD/>".as_bytes()).is_err());
+        assert!(Patch::parse(r#"<Patch><move sel="/MPD"/></Patch>"#.as_bytes()).is_err());
+        assert!(Patch::parse(r#"<Patch><remove/></Patch>"#.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(
+            Selector::parse(r#"/MPD/Period[@id="1"][2]/@start"#).unwrap(),
+            Selector {
+                steps: vec![
+                    Step {
+                        name: "MPD".to_owned(),
+                        predicates: vec![],
+                    },
+                    Step {
+                        name: "Period".to_owned(),
+                        predicates: vec![
+                            Predicate::Attribute("id".to_owned(), "1".to_owned()),
+                            Predicate::Position(2),
+                        ],
+                    },
+                ],
+                attribute: Some("start".to_owned()),
+            }
+        );
+        assert_eq!(
+            Selector::parse("/MPD/Period[@id='a]b']").unwrap().steps[1].predicates,
+            vec![Predicate::Attribute("id".to_owned(), "a]b".to_owned())]
+        );
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("MPD").is_err());
+        assert!(Selector::parse("//Period").is_err());
+        assert!(Selector::parse("/MPD/Period[0]").is_err());
+        assert!(Selector::parse("/MPD/Period[last()]").is_err());
+        assert!(Selector::parse("/MPD/Period[@id='1'").is_err());
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut mpd = parse_mpd();
+        let errors = mpd
+            .apply_patch(&patch(
+                r#"<replace sel="/MPD/@type">static</replace>
+<add sel="/MPD/Period[@id='1']" pos="after"><Period id="2" start="PT4S"/><Period id="3"/></add>
+<remove sel="/MPD/Period[@id='3']"/>
+<add sel="/MPD/Period[@id='1']" type="@duration">PT4S</add>
+<remove sel="/MPD/Period[@id='1']/@start"/>
+<add sel="/MPD/Period[@id='1']/AdaptationSet/SegmentTemplate/SegmentTimeline"><S d="2000"/><S d="1000"/></add>
+<replace sel="/MPD/Period[@id='1']/AdaptationSet/Representation[@id='v2']"><Representation id="v3"/></replace>
+<add sel="/MPD/Period[@id='1']/AdaptationSet" pos="prepend"><Label>a</Label><Label>b</Label><Role value="main"/></add>
+<replace sel="/MPD/Location[1]"><Location>https://example.com/other.mpd</Location></replace>"#,
+            ))
+            .unwrap();
+        assert!(errors.is_empty());
+
+        assert_eq!(mpd.publish_time, Some(1704067210000.));
+        assert_eq!(mpd.mpd_type.as_deref(), Some("static"));
+        assert_eq!(mpd.locations, vec!["https://example.com/other.mpd"]);
+        assert_eq!(mpd.periods.len(), 2);
+        assert_eq!(mpd.periods[1].id.as_deref(), Some("2"));
+        assert_eq!(mpd.periods[1].start, Some(4.));
+
+        let period = &mpd.periods[0];
+        assert_eq!(period.start, None);
+        assert_eq!(period.duration, Some(4.));
+
+        let adaptation_set = &period.adaptation_sets[0];
+        assert_eq!(adaptation_set.labels, vec!["a", "b"]);
+        assert_eq!(adaptation_set.roles[0].value.as_deref(), Some("main"));
+        let representation_ids: Vec<_> = adaptation_set
+            .representations
+            .iter()
+            .map(|r| r.id.as_deref().unwrap())
+            .collect();
+        assert_eq!(representation_ids, vec!["v1", "v3"]);
+
+        let timeline = &adaptation_set
+            .segment_template
+            .as_ref()
+            .unwrap()
+            .timeline
+            .as_ref()
+            .unwrap()
+            .0;
+        let starts: Vec<_> = timeline.iter().map(|s| s.start).collect();
+        assert_eq!(starts, vec![0, 4000, 6000]);
+    }
+
+    #[test]
+    fn test_apply_patch_on_events() {
+        let mut mpd = parse_mpd();
+        let errors = mpd
+            .apply_patch(&patch(
+                r#"<add sel="/MPD/Period[@id='1']" pos="prepend"><EventStream schemeIdUri="urn:example"><Event id="1"><Splice a="1"/></Event></EventStream></add>
+<add sel="/MPD/Period/EventStream/Event[@id='1']" type="@duration">10</add>"#,
+            ))
+            .unwrap();
+        assert!(errors.is_empty());
+        let event = &mpd.periods[0].event_streams[0].events[0];
+        assert_eq!(event.duration, Some(10));
+        assert_eq!(event.content.as_deref(), Some(r#"<Splice a="1"/>"#));
+    }
+
+    #[test]
+    fn test_apply_patch_on_s_elements() {
+        let mut mpd = parse_mpd();
+        mpd.apply_patch(&patch(
+            r#"<replace sel="/MPD/Period/AdaptationSet/SegmentTemplate/SegmentTimeline/S[@t='0']/@r">2</replace>
+<add sel="/MPD/Period/AdaptationSet/SegmentTemplate/SegmentTimeline/S[1]" pos="after"><S d="500"/></add>"#,
+        ))
+        .unwrap();
+        let timeline = &mpd.periods[0].adaptation_sets[0]
+            .segment_template
+            .as_ref()
+            .unwrap()
+            .timeline
+            .as_ref()
+            .unwrap()
+            .0;
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].repeat_count, 2);
+        assert_eq!(timeline[1].start, 6000);
+        assert_eq!(timeline[1].duration, 500);
+    }
+
+    #[test]
+    fn test_apply_patch_errors() {
+        let original = parse_mpd();
+
+        let mut mpd = original.clone();
+        let mut other_patch = patch("");
+        other_patch.mpd_id = Some("other".to_owned());
+        assert!(mpd.apply_patch(&other_patch).is_err());
+
+        // Patches are applied atomically
+        for operations in [
+            r#"<remove sel="/MPD/Location"/><remove sel="/MPD/Period[@id='2']"/>"#,
+            r#"<remove sel="/MPD/Location"/><remove sel="/MPD/Period/AdaptationSet/Representation"/>"#,
+            r#"<remove sel="/MPD/Location"/><replace sel="/MPD/Period"><AdaptationSet/></replace>"#,
+            r#"<remove sel="/MPD/Location"/><add sel="/MPD/@id">a</add>"#,
+            r#"<remove sel="/MPD/Location"/><remove sel="/Patch/Period"/>"#,
+            r#"<remove sel="/MPD/Location"/><add sel="/MPD/Period"><Unknown/></add>"#,
+            r#"<remove sel="/MPD/Location"/><remove sel="/MPD/Period/AdaptationSet/Unknown"/>"#,
+        ] {
+            assert!(mpd.apply_patch(&patch(operations)).is_err());
+            assert_eq!(mpd, original);
+        }
+
+        // Minor errors in added elements are reported
+        let errors = mpd
+            .apply_patch(&patch(
+                r#"<add sel="/MPD"><Period id="2" start="foo"/></add>"#,
+            ))
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(mpd.periods.len(), 2);
+    }
+}
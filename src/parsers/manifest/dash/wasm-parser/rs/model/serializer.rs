@@ -0,0 +1,914 @@
+use std::fmt::Display;
+use std::io::Write;
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event as XmlEvent};
+use quick_xml::Writer;
+
+use super::*;
+use crate::utils;
+
+/// Namespace of all MPD elements.
+const MPD_NAMESPACE: &str = "urn:mpeg:dash:schema:mpd:2011";
+
+/// Namespaces for prefixes the parser knows about.
+///
+/// Those are declared on the `<MPD>` element if an element or attribute uses
+/// them without them being declared in the serialized `Mpd`.
+const KNOWN_NAMESPACES: [(&str, &str); 6] = [
+    ("cenc", "urn:mpeg:cenc:2013"),
+    ("mspr", "urn:microsoft:playready"),
+    ("dashif", "https://dashif.org/CPS"),
+    ("clearkey", "http://dashif.org/guidelines/clearKey"),
+    ("xlink", "http://www.w3.org/1999/xlink"),
+    ("scte214", "urn:scte:dash:scte214-extensions"),
+];
+
+impl Mpd {
+    /// Serialize this `Mpd` as an MPD document written into `writer`.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<()> {
+        let mut root = self.to_xml_element();
+        declare_missing_namespaces(&mut root);
+
+        let mut writer = Writer::new_with_indent(writer, b' ', 2);
+        writer.write_event(XmlEvent::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        root.write(&mut writer)?;
+        Ok(())
+    }
+
+    /// Serialize this `Mpd` as an MPD document.
+    pub fn to_xml(&self) -> Result<String> {
+        let mut output = Vec::new();
+        self.write_to(&mut output)?;
+        Ok(String::from_utf8(output)?)
+    }
+
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("MPD");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(self.base_urls.iter().map(BaseUrl::to_xml_element));
+        elt.children(
+            self.locations
+                .iter()
+                .map(|l| XmlElement::text("Location", l)),
+        );
+        elt.children(
+            self.service_descriptions
+                .iter()
+                .map(ServiceDescription::to_xml_element),
+        );
+        elt.children(
+            self.content_steering
+                .iter()
+                .map(ContentSteering::to_xml_element),
+        );
+        elt.children(self.periods.iter().map(Period::to_xml_element));
+        elt.children(
+            self.utc_timings
+                .iter()
+                .map(|s| s.to_xml_element("UTCTiming")),
+        );
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.attr("xmlns", MPD_NAMESPACE);
+        elt.namespaces(&self.namespaces);
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("profiles", &self.profiles);
+        elt.opt_attr("type", &self.mpd_type);
+        elt.opt_date_time("availabilityStartTime", self.availability_start_time);
+        elt.opt_date_time("availabilityEndTime", self.availability_end_time);
+        elt.opt_date_time("publishTime", self.publish_time);
+        elt.opt_duration(
+            "mediaPresentationDuration",
+            self.media_presentation_duration,
+        );
+        elt.opt_duration("minimumUpdatePeriod", self.minimum_update_period);
+        elt.opt_duration("minBufferTime", self.min_buffer_time);
+        elt.opt_duration("timeShiftBufferDepth", self.time_shift_buffer_depth);
+        elt.opt_duration(
+            "suggestedPresentationDelay",
+            self.suggested_presentation_delay,
+        );
+        elt.opt_duration("maxSegmentDuration", self.max_segment_duration);
+        elt.opt_duration("maxSubsegmentDuration", self.max_subsegment_duration);
+    }
+}
+
+impl Period {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Period");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(self.base_urls.iter().map(BaseUrl::to_xml_element));
+        elt.segment_children(
+            &self.segment_base,
+            &self.segment_list,
+            &self.segment_template,
+        );
+        elt.children(self.event_streams.iter().map(EventStream::to_xml_element));
+        elt.children(
+            self.service_descriptions
+                .iter()
+                .map(ServiceDescription::to_xml_element),
+        );
+        elt.children(
+            self.adaptation_sets
+                .iter()
+                .map(AdaptationSet::to_xml_element),
+        );
+        elt.children(self.preselections.iter().map(Preselection::to_xml_element));
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.namespaces(&self.namespaces);
+        elt.opt_attr("xlink:href", &self.xlink_href);
+        elt.opt_attr("xlink:actuate", &self.xlink_actuate);
+        elt.opt_attr("id", &self.id);
+        elt.opt_duration("start", self.start);
+        elt.opt_duration("duration", self.duration);
+        elt.opt_attr("bitstreamSwitching", &self.bitstream_switching);
+        elt.opt_number("availabilityTimeOffset", self.availability_time_offset);
+    }
+}
+
+impl AdaptationSet {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("AdaptationSet");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(
+            self.audio_channel_configurations
+                .iter()
+                .map(|s| s.to_xml_element("AudioChannelConfiguration")),
+        );
+        elt.children(
+            self.content_protections
+                .iter()
+                .map(ContentProtection::to_xml_element),
+        );
+        elt.children(
+            self.essential_properties
+                .iter()
+                .map(|s| s.to_xml_element("EssentialProperty")),
+        );
+        elt.children(
+            self.supplemental_properties
+                .iter()
+                .map(|s| s.to_xml_element("SupplementalProperty")),
+        );
+        elt.children(
+            self.inband_event_streams
+                .iter()
+                .map(|s| s.to_xml_element("InbandEventStream")),
+        );
+        elt.children(self.labels.iter().map(|l| XmlElement::text("Label", l)));
+        elt.children(
+            self.producer_reference_times
+                .iter()
+                .map(ProducerReferenceTime::to_xml_element),
+        );
+        elt.children(self.resyncs.iter().map(Resync::to_xml_element));
+        elt.children(
+            self.accessibilities
+                .iter()
+                .map(|s| s.to_xml_element("Accessibility")),
+        );
+        elt.children(self.roles.iter().map(|s| s.to_xml_element("Role")));
+        elt.children(
+            self.content_components
+                .iter()
+                .map(ContentComponent::to_xml_element),
+        );
+        elt.children(self.base_urls.iter().map(BaseUrl::to_xml_element));
+        elt.segment_children(
+            &self.segment_base,
+            &self.segment_list,
+            &self.segment_template,
+        );
+        elt.children(
+            self.representations
+                .iter()
+                .map(Representation::to_xml_element),
+        );
+        elt.children(self.preselections.iter().map(Preselection::to_xml_element));
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("group", &self.group);
+        elt.opt_attr("lang", &self.language);
+        elt.opt_attr("contentType", &self.content_type);
+        elt.opt_attr("par", &self.par);
+        elt.opt_attr("minBandwidth", &self.min_bandwidth);
+        elt.opt_attr("maxBandwidth", &self.max_bandwidth);
+        elt.opt_attr("minWidth", &self.min_width);
+        elt.opt_attr("maxWidth", &self.max_width);
+        elt.opt_attr("minHeight", &self.min_height);
+        elt.opt_attr("maxHeight", &self.max_height);
+        elt.opt_attr("minFrameRate", &self.min_frame_rate);
+        elt.opt_attr("maxFrameRate", &self.max_frame_rate);
+        elt.opt_attr("segmentAlignment", &self.segment_alignment);
+        elt.opt_attr("subsegmentAlignment", &self.subsegment_alignment);
+        elt.opt_attr("bitstreamSwitching", &self.bitstream_switching);
+        elt.opt_attr("profiles", &self.profiles);
+        elt.opt_attr("width", &self.width);
+        elt.opt_attr("height", &self.height);
+        elt.opt_attr("frameRate", &self.frame_rate);
+        elt.opt_attr("audioSamplingRate", &self.audio_sampling_rate);
+        elt.opt_attr("mimeType", &self.mime_type);
+        elt.opt_attr("segmentProfiles", &self.segment_profiles);
+        elt.opt_attr("codecs", &self.codecs);
+        elt.opt_attr("scte214:supplementalCodecs", &self.supplemental_codecs);
+        elt.opt_number("maxSAPPeriod", self.max_sap_period);
+        elt.opt_number("maxPlayoutRate", self.max_playout_rate);
+        elt.opt_attr("codingDependency", &self.coding_dependency);
+        elt.opt_attr("selectionPriority", &self.selection_priority);
+        elt.opt_number("availabilityTimeOffset", self.availability_time_offset);
+        elt.opt_attr("availabilityTimeComplete", &self.availability_time_complete);
+    }
+}
+
+impl Representation {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Representation");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(
+            self.audio_channel_configurations
+                .iter()
+                .map(|s| s.to_xml_element("AudioChannelConfiguration")),
+        );
+        elt.children(
+            self.content_protections
+                .iter()
+                .map(ContentProtection::to_xml_element),
+        );
+        elt.children(
+            self.essential_properties
+                .iter()
+                .map(|s| s.to_xml_element("EssentialProperty")),
+        );
+        elt.children(
+            self.supplemental_properties
+                .iter()
+                .map(|s| s.to_xml_element("SupplementalProperty")),
+        );
+        elt.children(
+            self.inband_event_streams
+                .iter()
+                .map(|s| s.to_xml_element("InbandEventStream")),
+        );
+        elt.children(self.labels.iter().map(|l| XmlElement::text("Label", l)));
+        elt.children(
+            self.producer_reference_times
+                .iter()
+                .map(ProducerReferenceTime::to_xml_element),
+        );
+        elt.children(self.resyncs.iter().map(Resync::to_xml_element));
+        elt.children(self.base_urls.iter().map(BaseUrl::to_xml_element));
+        elt.segment_children(
+            &self.segment_base,
+            &self.segment_list,
+            &self.segment_template,
+        );
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("bandwidth", &self.bandwidth);
+        elt.opt_attr("qualityRanking", &self.quality_ranking);
+        elt.opt_attr("profiles", &self.profiles);
+        elt.opt_attr("width", &self.width);
+        elt.opt_attr("height", &self.height);
+        elt.opt_attr("frameRate", &self.frame_rate);
+        elt.opt_attr("audioSamplingRate", &self.audio_sampling_rate);
+        elt.opt_attr("mimeType", &self.mime_type);
+        elt.opt_attr("segmentProfiles", &self.segment_profiles);
+        elt.opt_attr("codecs", &self.codecs);
+        elt.opt_attr("scte214:supplementalCodecs", &self.supplemental_codecs);
+        elt.opt_number("maxSAPPeriod", self.max_sap_period);
+        elt.opt_number("maxPlayoutRate", self.max_playout_rate);
+        elt.opt_attr("codingDependency", &self.coding_dependency);
+        elt.opt_number("availabilityTimeOffset", self.availability_time_offset);
+        elt.opt_attr("availabilityTimeComplete", &self.availability_time_complete);
+    }
+}
+
+impl BaseUrl {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::text("BaseURL", &self.value);
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("serviceLocation", &self.service_location);
+    }
+}
+
+impl ContentSteering {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::text("ContentSteering", &self.value);
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("defaultServiceLocation", &self.default_service_location);
+        elt.opt_attr("queryBeforeStart", &self.query_before_start);
+        elt.opt_attr("proxyServerURL", &self.proxy_server_url);
+    }
+}
+
+impl ServiceDescription {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("ServiceDescription");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(self.scopes.iter().map(|s| s.to_xml_element("Scope")));
+        elt.children(self.latencies.iter().map(Latency::to_xml_element));
+        elt.children(self.playback_rates.iter().map(PlaybackRate::to_xml_element));
+        elt.children(
+            self.operating_qualities
+                .iter()
+                .map(|o| o.to_xml_element("OperatingQuality")),
+        );
+        elt.children(
+            self.operating_bandwidths
+                .iter()
+                .map(|o| o.to_xml_element("OperatingBandwidth")),
+        );
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+    }
+}
+
+impl Latency {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Latency");
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("referenceId", &self.reference_id);
+        elt.opt_attr("target", &self.target);
+        elt.opt_attr("min", &self.min);
+        elt.opt_attr("max", &self.max);
+    }
+}
+
+impl PlaybackRate {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("PlaybackRate");
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("min", &self.min);
+        elt.opt_attr("max", &self.max);
+    }
+}
+
+impl OperatingQuality {
+    fn to_xml_element(&self, name: &'static str) -> XmlElement {
+        let mut elt = XmlElement::new(name);
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("mediaType", &self.media_type);
+        elt.opt_attr("min", &self.min);
+        elt.opt_attr("max", &self.max);
+        elt.opt_attr("target", &self.target);
+        elt.opt_attr("type", &self.quality_type);
+        elt.opt_attr("maxDifference", &self.max_difference);
+    }
+}
+
+impl ProducerReferenceTime {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("ProducerReferenceTime");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(
+            self.utc_timings
+                .iter()
+                .map(|s| s.to_xml_element("UTCTiming")),
+        );
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("inband", &self.inband);
+        elt.opt_attr("type", &self.reference_type);
+        elt.opt_attr("applicationScheme", &self.application_scheme);
+        elt.opt_date_time("wallClockTime", self.wall_clock_time);
+        elt.opt_attr("presentationTime", &self.presentation_time);
+    }
+}
+
+impl Resync {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Resync");
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("type", &self.resync_type);
+        elt.opt_attr("dT", &self.d_t);
+        elt.opt_number("dImax", self.d_i_max);
+        elt.opt_number("dImin", self.d_i_min);
+        elt.opt_attr("marker", &self.marker);
+    }
+}
+
+impl Scheme {
+    fn to_xml_element(&self, name: &'static str) -> XmlElement {
+        let mut elt = XmlElement::new(name);
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("schemeIdUri", &self.scheme_id_uri);
+        elt.opt_attr("value", &self.value);
+    }
+}
+
+impl ContentComponent {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("ContentComponent");
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("lang", &self.language);
+        elt.opt_attr("contentType", &self.content_type);
+        elt.opt_attr("par", &self.par);
+    }
+}
+
+impl Preselection {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Preselection");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(
+            self.accessibilities
+                .iter()
+                .map(|s| s.to_xml_element("Accessibility")),
+        );
+        elt.children(self.roles.iter().map(|s| s.to_xml_element("Role")));
+        elt.children(
+            self.audio_channel_configurations
+                .iter()
+                .map(|s| s.to_xml_element("AudioChannelConfiguration")),
+        );
+        elt.children(self.labels.iter().map(|l| XmlElement::text("Label", l)));
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("preselectionComponents", &self.preselection_components);
+        elt.opt_attr("lang", &self.language);
+        elt.opt_attr("tag", &self.tag);
+        elt.opt_attr("codecs", &self.codecs);
+    }
+}
+
+impl ContentProtection {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("ContentProtection");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(
+            self.cenc_pssh
+                .iter()
+                .map(|p| XmlElement::text("cenc:pssh", p)),
+        );
+        elt.children(
+            self.mspr_pro
+                .iter()
+                .map(|p| XmlElement::text("mspr:pro", p)),
+        );
+        elt.children(
+            self.mspr_is_encrypted
+                .iter()
+                .map(|e| XmlElement::text("mspr:IsEncrypted", if *e { "1" } else { "0" })),
+        );
+        elt.children(
+            self.dashif_la_urls
+                .iter()
+                .map(|u| u.to_xml_element("dashif:Laurl", "licenseType")),
+        );
+        elt.children(
+            self.dashif_authz_urls
+                .iter()
+                .map(|u| u.to_xml_element("dashif:Authzurl", "licenseType")),
+        );
+        elt.children(
+            self.clearkey_la_urls
+                .iter()
+                .map(|u| u.to_xml_element("clearkey:Laurl", "Lic_type")),
+        );
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("schemeIdUri", &self.scheme_id_uri);
+        elt.opt_attr("value", &self.value);
+        elt.opt_attr("cenc:default_KID", &self.default_kid);
+        elt.opt_attr("robustness", &self.robustness);
+        elt.opt_attr("refId", &self.ref_id);
+    }
+}
+
+impl LicenseUrl {
+    fn to_xml_element(&self, name: &'static str, license_type_attr: &str) -> XmlElement {
+        let mut elt = XmlElement::text(name, &self.url);
+        elt.opt_attr(license_type_attr, &self.license_type);
+        elt
+    }
+}
+
+impl SegmentBase {
+    /// Set the attributes shared by `<SegmentBase>`, `<SegmentList>` and
+    /// `<SegmentTemplate>` elements on `elt`.
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("timescale", &self.timescale);
+        elt.opt_attr("duration", &self.duration);
+        elt.opt_attr("startNumber", &self.start_number);
+        elt.opt_attr("endNumber", &self.end_number);
+        elt.opt_attr("presentationTimeOffset", &self.presentation_time_offset);
+        elt.opt_range("indexRange", self.index_range);
+        elt.opt_attr("indexRangeExact", &self.index_range_exact);
+        elt.opt_number("availabilityTimeOffset", self.availability_time_offset);
+        elt.opt_attr("availabilityTimeComplete", &self.availability_time_complete);
+    }
+
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("SegmentBase");
+        self.set_xml_attributes(&mut elt);
+        elt.children(
+            self.initialization
+                .iter()
+                .map(Initialization::to_xml_element),
+        );
+        elt
+    }
+}
+
+impl Initialization {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Initialization");
+        elt.opt_attr("sourceURL", &self.media);
+        elt.opt_range("range", self.range);
+        elt
+    }
+}
+
+impl SegmentList {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("SegmentList");
+        self.base.set_xml_attributes(&mut elt);
+        elt.children(
+            self.base
+                .initialization
+                .iter()
+                .map(Initialization::to_xml_element),
+        );
+        elt.children(self.timeline.iter().map(SegmentTimeline::to_xml_element));
+        elt.children(self.segment_urls.iter().map(SegmentUrl::to_xml_element));
+        elt
+    }
+}
+
+impl SegmentUrl {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("SegmentURL");
+        self.set_xml_attributes(&mut elt);
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("media", &self.media);
+        elt.opt_range("mediaRange", self.media_range);
+        elt.opt_attr("index", &self.index);
+        elt.opt_range("indexRange", self.index_range);
+    }
+}
+
+impl SegmentTemplate {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("SegmentTemplate");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(self.timeline.iter().map(SegmentTimeline::to_xml_element));
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("media", &self.media);
+        elt.opt_attr("index", &self.index);
+        if let Some(initialization) = &self.base.initialization {
+            elt.opt_attr("initialization", &initialization.media);
+        }
+        elt.opt_attr("bitstreamSwitching", &self.bitstream_switching);
+        self.base.set_xml_attributes(elt);
+    }
+}
+
+impl SegmentTimeline {
+    /// Convert this `SegmentTimeline` into an XML element.
+    ///
+    /// Contiguous `<S>` elements with the same duration are merged through
+    /// their `r` attribute and `t` attributes are only written for the first
+    /// segment and when a segment does not start at the end of the previous
+    /// one.
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("SegmentTimeline");
+
+        // Segments merged together
+        let mut compacted: Vec<SegmentObject> = Vec::with_capacity(self.0.len());
+        for segment in &self.0 {
+            if let Some(last) = compacted.last_mut() {
+                if last.repeat_count >= 0
+                    && segment.repeat_count >= 0
+                    && last.duration == segment.duration
+                    && last.chunk_count == segment.chunk_count
+                    && segment.number.is_none()
+                    && last.end() == segment.start
+                {
+                    last.repeat_count += segment.repeat_count + 1;
+                    continue;
+                }
+            }
+            compacted.push(*segment);
+        }
+
+        let mut previous_end = None;
+        for segment in compacted {
+            let mut s_elt = XmlElement::new("S");
+            if previous_end != Some(segment.start) {
+                s_elt.attr("t", segment.start);
+            }
+            s_elt.opt_attr("n", &segment.number);
+            s_elt.attr("d", segment.duration);
+            s_elt.opt_attr("k", &segment.chunk_count);
+            if segment.repeat_count != 0 {
+                s_elt.attr("r", segment.repeat_count);
+            }
+            previous_end = if segment.repeat_count < 0 {
+                None
+            } else {
+                Some(segment.end())
+            };
+            elt.child(s_elt);
+        }
+        elt
+    }
+}
+
+impl EventStream {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("EventStream");
+        self.set_xml_attributes(&mut elt);
+
+        elt.children(self.events.iter().map(Event::to_xml_element));
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.namespaces(&self.namespaces);
+        elt.opt_attr("schemeIdUri", &self.scheme_id_uri);
+        elt.opt_attr("value", &self.value);
+        elt.opt_attr("timescale", &self.timescale);
+        elt.opt_attr("presentationTimeOffset", &self.presentation_time_offset);
+    }
+}
+
+impl Event {
+    fn to_xml_element(&self) -> XmlElement {
+        let mut elt = XmlElement::new("Event");
+        self.set_xml_attributes(&mut elt);
+        match (&self.content, &self.message_data) {
+            (Some(content), _) => elt.content = Some(content.clone()),
+            (None, Some(data)) if self.is_base64_encoded() => {
+                elt.text = Some(utils::encode_base64(data))
+            }
+            _ => {}
+        }
+        elt
+    }
+
+    pub(super) fn set_xml_attributes(&self, elt: &mut XmlElement) {
+        elt.opt_attr("presentationTime", &self.presentation_time);
+        elt.opt_attr("duration", &self.duration);
+        elt.opt_attr("id", &self.id);
+        elt.opt_attr("contentEncoding", &self.content_encoding);
+        if !self.is_base64_encoded() {
+            if let Some(data) = &self.message_data {
+                elt.attr("messageData", String::from_utf8_lossy(data));
+            }
+        }
+    }
+
+    /// Returns `true` if the `message_data` is the element's content rather
+    /// than its `messageData` attribute.
+    fn is_base64_encoded(&self) -> bool {
+        self.content_encoding.as_deref() == Some("base64")
+    }
+}
+
+impl Display for U64OrBool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            U64OrBool::U64(val) => val.fmt(f),
+            U64OrBool::Bool(val) => val.fmt(f),
+        }
+    }
+}
+
+/// Simple XML element tree, to which the `Mpd` is first converted before
+/// being written.
+///
+/// Going through it allows to know if an element has children before writing
+/// it, to write childless elements as empty elements.
+pub(super) struct XmlElement {
+    name: &'static str,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    /// XML content, written as is after the text.
+    content: Option<String>,
+    children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    pub(super) fn new(name: &'static str) -> Self {
+        XmlElement {
+            name,
+            attributes: Vec::new(),
+            text: None,
+            content: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Create an element with only a text content, such as a `<BaseURL>`.
+    fn text(name: &'static str, text: &str) -> Self {
+        let mut elt = XmlElement::new(name);
+        elt.text = Some(text.to_owned());
+        elt
+    }
+
+    /// Returns the value of the attribute `name` set on this element, if any.
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn attr<T: Display>(&mut self, name: &str, value: T) {
+        self.attributes.push((name.to_owned(), value.to_string()));
+    }
+
+    fn opt_attr<T: Display>(&mut self, name: &str, value: &Option<T>) {
+        if let Some(value) = value {
+            self.attr(name, value);
+        }
+    }
+
+    /// Numbers which may be infinite, like `availabilityTimeOffset`, which
+    /// uses the "INF" special value in that case.
+    fn opt_number(&mut self, name: &str, value: Option<f64>) {
+        match value {
+            Some(val) if val == f64::INFINITY => self.attr(name, "INF"),
+            Some(val) => self.attr(name, val),
+            None => {}
+        }
+    }
+
+    /// Durations in seconds, written in the ISO 8601 format.
+    fn opt_duration(&mut self, name: &str, value: Option<f64>) {
+        if let Some(val) = value {
+            self.attr(name, utils::format_iso_8601_duration(val));
+        }
+    }
+
+    fn opt_date_time(&mut self, name: &str, value: Option<f64>) {
+        if let Some(val) = value {
+            self.attr(name, utils::format_iso_8601_date_time(val));
+        }
+    }
+
+    fn opt_range(&mut self, name: &str, value: Option<(f64, f64)>) {
+        if let Some((start, end)) = value {
+            self.attr(name, format!("{}-{}", start, end));
+        }
+    }
+
+    fn namespaces(&mut self, namespaces: &[Namespace]) {
+        for ns in namespaces {
+            self.attr(&format!("xmlns:{}", ns.key), &ns.value);
+        }
+    }
+
+    fn child(&mut self, child: XmlElement) {
+        self.children.push(child);
+    }
+
+    fn children<I: Iterator<Item = XmlElement>>(&mut self, children: I) {
+        self.children.extend(children);
+    }
+
+    fn segment_children(
+        &mut self,
+        segment_base: &Option<SegmentBase>,
+        segment_list: &Option<SegmentList>,
+        segment_template: &Option<SegmentTemplate>,
+    ) {
+        self.children(segment_base.iter().map(SegmentBase::to_xml_element));
+        self.children(segment_list.iter().map(SegmentList::to_xml_element));
+        self.children(segment_template.iter().map(SegmentTemplate::to_xml_element));
+    }
+
+    fn write<W: Write>(&self, writer: &mut Writer<W>) -> quick_xml::Result<()> {
+        let mut start = BytesStart::new(self.name);
+        for (key, value) in &self.attributes {
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+        if self.text.is_none() && self.content.is_none() && self.children.is_empty() {
+            return writer.write_event(XmlEvent::Empty(start));
+        }
+        writer.write_event(XmlEvent::Start(start))?;
+        if let Some(text) = &self.text {
+            writer.write_event(XmlEvent::Text(BytesText::new(text)))?;
+        }
+        if let Some(content) = &self.content {
+            writer.write_event(XmlEvent::Text(BytesText::from_escaped(content)))?;
+        }
+        for child in &self.children {
+            child.write(writer)?;
+        }
+        writer.write_event(XmlEvent::End(BytesEnd::new(self.name)))
+    }
+}
+
+/// Declare on `root` the known namespaces which are used in its tree but not
+/// declared in it.
+fn declare_missing_namespaces(root: &mut XmlElement) {
+    fn collect(elt: &XmlElement, declared: &mut Vec<String>, missing: &mut Vec<&'static str>) {
+        let initial_len = declared.len();
+        for (key, _) in &elt.attributes {
+            if let Some(prefix) = key.strip_prefix("xmlns:") {
+                declared.push(prefix.to_owned());
+            }
+        }
+        let used = elt
+            .attributes
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .chain(std::iter::once(elt.name));
+        for name in used {
+            if let Some((prefix, _)) = name.split_once(':') {
+                if prefix == "xmlns" || declared.iter().any(|d| d == prefix) {
+                    continue;
+                }
+                if let Some((known, _)) = KNOWN_NAMESPACES.iter().find(|(p, _)| *p == prefix) {
+                    if !missing.contains(known) {
+                        missing.push(known);
+                    }
+                }
+            }
+        }
+        for child in &elt.children {
+            collect(child, declared, missing);
+        }
+        declared.truncate(initial_len);
+    }
+
+    let mut missing = Vec::new();
+    collect(root, &mut Vec::new(), &mut missing);
+    for (prefix, uri) in KNOWN_NAMESPACES {
+        if missing.contains(&prefix) {
+            root.attr(&format!("xmlns:{}", prefix), uri);
+        }
+    }
+}
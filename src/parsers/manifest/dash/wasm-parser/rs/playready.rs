@@ -0,0 +1,319 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::utils::decode_base64;
+
+/// Type of the PlayReady Object records containing a WRM header.
+const WRM_HEADER_RECORD_TYPE: u16 = 1;
+
+/// A PlayReady Object, as found base64-encoded in `<mspr:pro>` elements.
+///
+/// It is a list of records, each being either a WRM header (an XML document
+/// describing the content's keys) or an embedded license store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayReadyObject {
+    pub records: Vec<PlayReadyRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayReadyRecord {
+    /// `1` for a WRM header, `3` for an embedded license store.
+    pub record_type: u16,
+    pub value: Vec<u8>,
+}
+
+/// Information parsed from the WRM header of a PlayReady Object, versions
+/// `4.0.0.0` to `4.3.0.0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WrmHeader {
+    pub version: String,
+
+    /// Key ids, converted from the GUID byte order used by PlayReady to the
+    /// UUID one (e.g. the one of `cenc:default_KID` attributes).
+    pub key_ids: Vec<[u8; 16]>,
+
+    /// URL of the license server.
+    pub la_url: Option<String>,
+
+    /// URL of a web page allowing to obtain a license.
+    pub lui_url: Option<String>,
+
+    /// Inner XML of the `<CUSTOMATTRIBUTES>` element, as is.
+    pub custom_attributes: Option<String>,
+}
+
+impl PlayReadyObject {
+    /// Parse the given PlayReady Object, once decoded from base64.
+    pub fn parse(data: &[u8]) -> Result<PlayReadyObject> {
+        let read_u16 = |pos: usize, field: &str| -> Result<u16> {
+            match data.get(pos..pos + 2) {
+                Some(bytes) => Ok(u16::from_le_bytes([bytes[0], bytes[1]])),
+                None => Err(pro_error(format!(
+                    "Truncated PlayReady Object: no {} at offset {}.",
+                    field, pos
+                ))),
+            }
+        };
+        let length = match data.get(..4) {
+            Some(bytes) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            None => return Err(pro_error("Truncated PlayReady Object: no length.")),
+        };
+        if length as usize != data.len() {
+            return Err(pro_error(format!(
+                "The PlayReady Object length ({}) does not match its data length ({}).",
+                length,
+                data.len()
+            )));
+        }
+        let record_count = read_u16(4, "record count")?;
+        let mut records = Vec::with_capacity(record_count as usize);
+        let mut pos = 6;
+        for i in 0..record_count {
+            let record_type = read_u16(pos, "record type")?;
+            let record_length = read_u16(pos + 2, "record length")? as usize;
+            pos += 4;
+            let value = data.get(pos..pos + record_length).ok_or_else(|| {
+                pro_error(format!(
+                    "Truncated PlayReady Object: record {} needs {} bytes at offset {} but only {} \
+                     remain.",
+                    i,
+                    record_length,
+                    pos,
+                    data.len() - pos
+                ))
+            })?;
+            records.push(PlayReadyRecord {
+                record_type,
+                value: value.to_vec(),
+            });
+            pos += record_length;
+        }
+        if pos != data.len() {
+            return Err(pro_error(format!(
+                "{} unexpected bytes after the last record.",
+                data.len() - pos
+            )));
+        }
+        Ok(PlayReadyObject { records })
+    }
+
+    /// Parse the first WRM header record of this PlayReady Object, if one.
+    pub fn wrm_header(&self) -> Result<Option<WrmHeader>> {
+        match self
+            .records
+            .iter()
+            .find(|record| record.record_type == WRM_HEADER_RECORD_TYPE)
+        {
+            Some(record) => WrmHeader::parse(&decode_utf16_le(&record.value)?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Text elements of the WRM header whose content is retrieved.
+#[derive(Clone, Copy)]
+enum WrmField {
+    Kid,
+    LaUrl,
+    LuiUrl,
+}
+
+impl WrmHeader {
+    /// Parse the given WRM header XML document.
+    pub fn parse(xml: &str) -> Result<WrmHeader> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut header = WrmHeader::default();
+        let mut field = None;
+        loop {
+            match reader.read_event()? {
+                Event::Start(tag) if tag.name().as_ref() == b"CUSTOMATTRIBUTES" => {
+                    field = None;
+                    let end = tag.to_end().into_owned();
+                    let span = reader.read_to_end(end.name())?;
+                    header.custom_attributes = Some(xml[span.start..span.end].to_owned());
+                }
+                Event::Start(tag) => field = header.read_tag(&tag)?,
+                Event::Empty(tag) => {
+                    field = None;
+                    header.read_tag(&tag)?;
+                }
+                Event::Text(text) => {
+                    let text = text.unescape()?;
+                    match field {
+                        Some(WrmField::Kid) => header.key_ids.push(parse_guid(text.as_bytes())?),
+                        Some(WrmField::LaUrl) => header.la_url = Some(text.into_owned()),
+                        Some(WrmField::LuiUrl) => header.lui_url = Some(text.into_owned()),
+                        None => {}
+                    }
+                }
+                Event::End(_) => field = None,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        match header.version.as_str() {
+            "4.0.0.0" | "4.1.0.0" | "4.2.0.0" | "4.3.0.0" => Ok(header),
+            "" => Err(pro_error("No WRMHEADER version.")),
+            version => Err(pro_error(format!(
+                "Unsupported WRMHEADER version: {}.",
+                version
+            ))),
+        }
+    }
+
+    /// Read the attributes of the given WRM header element, returning the
+    /// field its text content corresponds to, if one.
+    fn read_tag(&mut self, tag: &BytesStart) -> Result<Option<WrmField>> {
+        let value = |name: &str| -> Result<Option<String>> {
+            match tag.try_get_attribute(name)? {
+                Some(attr) => Ok(Some(attr.unescape_value()?.into_owned())),
+                None => Ok(None),
+            }
+        };
+        match tag.name().as_ref() {
+            b"WRMHEADER" => self.version = value("version")?.unwrap_or_default(),
+            // Starting from version 4.1, the key id is an attribute
+            b"KID" => match value("VALUE")? {
+                Some(kid) => self.key_ids.push(parse_guid(kid.as_bytes())?),
+                None => return Ok(Some(WrmField::Kid)),
+            },
+            b"LA_URL" => return Ok(Some(WrmField::LaUrl)),
+            b"LUI_URL" => return Ok(Some(WrmField::LuiUrl)),
+            _ => {}
+        }
+        Ok(None)
+    }
+}
+
+/// Parse a base64-encoded PlayReady key id, converting it from the GUID
+/// byte order (whose first three fields are little-endian) to the UUID one.
+fn parse_guid(value: &[u8]) -> Result<[u8; 16]> {
+    let bytes = decode_base64(value)?;
+    if bytes.len() != 16 {
+        return Err(ParsingError::new(
+            ParsingErrorKind::InvalidKeyId,
+            format!("Invalid PlayReady key id length: {} bytes.", bytes.len()),
+        ));
+    }
+    let mut key_id = [0u8; 16];
+    key_id.copy_from_slice(&bytes);
+    key_id[0..4].reverse();
+    key_id[4..6].reverse();
+    key_id[6..8].reverse();
+    Ok(key_id)
+}
+
+fn decode_utf16_le(data: &[u8]) -> Result<String> {
+    if !data.len().is_multiple_of(2) {
+        return Err(pro_error("Invalid UTF-16 WRM header: odd length."));
+    }
+    let units: Vec<u16> = data
+        .chunks(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+    let text = String::from_utf16(&units).map_err(|_| pro_error("Invalid UTF-16 WRM header."))?;
+    Ok(text.trim_start_matches('\u{feff}').to_owned())
+}
+
+fn pro_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidPlayReadyObject, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UUID of the key whose GUID is `DQW0nkvkAkiTLifXUIPiZg==` in base64.
+    const KEY_ID: [u8; 16] = [
+        0x9e, 0xb4, 0x05, 0x0d, 0xe4, 0x4b, 0x48, 0x02, 0x93, 0x2e, 0x27, 0xd7, 0x50, 0x83, 0xe2,
+        0x66,
+    ];
+
+    fn build_pro(wrm_header: &str) -> Vec<u8> {
+        let header: Vec<u8> = wrm_header
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let mut pro = ((header.len() + 10) as u32).to_le_bytes().to_vec();
+        pro.extend(1u16.to_le_bytes());
+        pro.extend(1u16.to_le_bytes());
+        pro.extend((header.len() as u16).to_le_bytes());
+        pro.extend(header);
+        pro
+    }
+
+    #[test]
+    fn test_parse_wrm_header_v4_0() {
+        let pro = build_pro(
+            r#"<WRMHEADER xmlns="http://schemas.microsoft.com/DRM/2007/03/PlayReadyHeader" version="4.0.0.0"><DATA><PROTECTINFO><KEYLEN>16</KEYLEN><ALGID>AESCTR</ALGID></PROTECTINFO><KID>DQW0nkvkAkiTLifXUIPiZg==</KID><LA_URL>https://license.example.com/?a=1&amp;b=2</LA_URL><LUI_URL>https://example.com/</LUI_URL><CUSTOMATTRIBUTES><IIS_DRM_VERSION>8.1</IIS_DRM_VERSION></CUSTOMATTRIBUTES></DATA></WRMHEADER>"#,
+        );
+        let pro = PlayReadyObject::parse(&pro).unwrap();
+        assert_eq!(pro.records.len(), 1);
+        assert_eq!(
+            pro.wrm_header().unwrap().unwrap(),
+            WrmHeader {
+                version: "4.0.0.0".to_owned(),
+                key_ids: vec![KEY_ID],
+                la_url: Some("https://license.example.com/?a=1&b=2".to_owned()),
+                lui_url: Some("https://example.com/".to_owned()),
+                custom_attributes: Some("<IIS_DRM_VERSION>8.1</IIS_DRM_VERSION>".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_wrm_header_v4_3() {
+        let header = WrmHeader::parse(
+            r#"<WRMHEADER version="4.3.0.0"><DATA><PROTECTINFO><KIDS>
+                <KID ALGID="AESCBC" VALUE="DQW0nkvkAkiTLifXUIPiZg=="/>
+                <KID ALGID="AESCBC" VALUE="AAAAAAAAAAAAAAAAAAAAAQ=="></KID>
+            </KIDS></PROTECTINFO><LA_URL>https://license.example.com/</LA_URL></DATA></WRMHEADER>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            header.key_ids,
+            vec![KEY_ID, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]]
+        );
+        assert_eq!(
+            header.la_url.as_deref(),
+            Some("https://license.example.com/")
+        );
+        assert_eq!(header.lui_url, None);
+        assert_eq!(header.custom_attributes, None);
+    }
+
+    #[test]
+    fn test_reject_malformed_playready_objects() {
+        let error = |data: &[u8]| {
+            PlayReadyObject::parse(data)
+                .and_then(|pro| pro.wrm_header())
+                .unwrap_err()
+        };
+        let pro = build_pro(r#"<WRMHEADER version="4.0.0.0"/>"#);
+        assert_eq!(
+            error(&pro[..pro.len() - 1]).message,
+            "The PlayReady Object length (70) does not match its data length (69)."
+        );
+        let mut truncated = pro[..pro.len() - 2].to_vec();
+        truncated[0] = 68;
+        assert_eq!(
+            error(&truncated).message,
+            "Truncated PlayReady Object: record 0 needs 60 bytes at offset 10 but only 58 \
+             remain."
+        );
+        let unsupported = build_pro(r#"<WRMHEADER version="5.0.0.0"/>"#);
+        assert_eq!(
+            error(&unsupported).message,
+            "Unsupported WRMHEADER version: 5.0.0.0."
+        );
+        let invalid_kid =
+            build_pro(r#"<WRMHEADER version="4.1.0.0"><KID VALUE="AAAA"/></WRMHEADER>"#);
+        assert_eq!(error(&invalid_kid).kind, ParsingErrorKind::InvalidKeyId);
+        assert_eq!(
+            error(&[4, 0, 0]).kind,
+            ParsingErrorKind::InvalidPlayReadyObject
+        );
+    }
+}
@@ -1,249 +1,383 @@
-use crate::errors::ParsingError;
+use crate::audio_channels::AudioChannelConfiguration;
+use crate::errors::{ParsingError, ParsingErrorKind};
 use crate::events::AttributeName::*;
+use crate::playready::PlayReadyObject;
+use crate::pssh::PsshBox;
+use crate::sink::{AttrValue, MpdEventSink};
+use crate::utils::{decode_base64, parse_bool};
+use quick_xml::events::attributes::Attribute;
 
-pub fn report_mpd_attrs(e: &quick_xml::events::BytesStart) {
+pub fn report_mpd_attrs<S: MpdEventSink>(e: &quick_xml::events::BytesStart, sink: &mut S) {
     for res_attr in e.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"id" => Id.try_report_as_string(&attr),
-                b"profiles" => Profiles.try_report_as_string(&attr),
-                b"type" => Type.try_report_as_string(&attr),
-                b"availabilityStartTime" => AvailabilityStartTime.try_report_as_string(&attr),
-                b"availabilityEndTime" => AvailabilityEndTime.try_report_as_string(&attr),
-                b"publishTime" => PublishTime.try_report_as_string(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"profiles" => Profiles.try_report_as_string(&attr, sink),
+                b"type" => Type.try_report_as_string(&attr, sink),
+                b"availabilityStartTime" => {
+                    AvailabilityStartTime.try_report_as_iso_8601_date_time(&attr, sink)
+                }
+                b"availabilityEndTime" => {
+                    AvailabilityEndTime.try_report_as_iso_8601_date_time(&attr, sink)
+                }
+                b"publishTime" => PublishTime.try_report_as_iso_8601_date_time(&attr, sink),
                 b"mediaPresentationDuration" => {
-                    MediaPresentationDuration.try_report_as_iso_8601_duration(&attr)
+                    MediaPresentationDuration.try_report_as_iso_8601_duration(&attr, sink)
                 }
                 b"minimumUpdatePeriod" => {
-                    MinimumUpdatePeriod.try_report_as_iso_8601_duration(&attr)
+                    MinimumUpdatePeriod.try_report_as_iso_8601_duration(&attr, sink)
                 }
-                b"minBufferTime" => MinBufferTime.try_report_as_iso_8601_duration(&attr),
+                b"minBufferTime" => MinBufferTime.try_report_as_iso_8601_duration(&attr, sink),
                 b"timeShiftBufferDepth" => {
-                    TimeShiftBufferDepth.try_report_as_iso_8601_duration(&attr)
+                    TimeShiftBufferDepth.try_report_as_iso_8601_duration(&attr, sink)
                 }
                 b"suggestedPresentationDelay" => {
-                    SuggestedPresentationDelay.try_report_as_iso_8601_duration(&attr)
+                    SuggestedPresentationDelay.try_report_as_iso_8601_duration(&attr, sink)
+                }
+                b"maxSegmentDuration" => {
+                    MaxSegmentDuration.try_report_as_iso_8601_duration(&attr, sink)
                 }
-                b"maxSegmentDuration" => MaxSegmentDuration.try_report_as_iso_8601_duration(&attr),
                 b"maxSubsegmentDuration" => {
-                    MaxSubsegmentDuration.try_report_as_iso_8601_duration(&attr)
+                    MaxSubsegmentDuration.try_report_as_iso_8601_duration(&attr, sink)
                 }
                 x => {
                     if x.len() > 6 && &x[..6] == b"xmlns:" {
-                        Namespace.try_report_as_key_value(&x[6..], &attr);
+                        Namespace.try_report_as_key_value(&x[6..], &attr, sink);
                     }
                 }
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_period_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_period_attrs<S: MpdEventSink>(tag_bs: &quick_xml::events::BytesStart, sink: &mut S) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"id" => Id.try_report_as_string(&attr),
-                b"start" => Start.try_report_as_iso_8601_duration(&attr),
-                b"duration" => Duration.try_report_as_iso_8601_duration(&attr),
-                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"start" => Start.try_report_as_iso_8601_duration(&attr, sink),
+                b"duration" => Duration.try_report_as_iso_8601_duration(&attr, sink),
+                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr, sink),
                 b"availabilityTimeOffset" => match attr.value.as_ref() {
-                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY),
-                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr),
+                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY, sink),
+                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr, sink),
                 },
-                b"xlink:href" => XLinkHref.try_report_as_string(&attr),
-                b"xlink:actuate" => XLinkActuate.try_report_as_string(&attr),
+                b"xlink:href" => XLinkHref.try_report_as_string(&attr, sink),
+                b"xlink:actuate" => XLinkActuate.try_report_as_string(&attr, sink),
                 x => {
                     if x.len() > 6 && &x[..6] == b"xmlns:" {
-                        Namespace.try_report_as_key_value(&x[6..], &attr);
+                        Namespace.try_report_as_key_value(&x[6..], &attr, sink);
                     }
                 }
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_adaptation_set_attrs(e: &quick_xml::events::BytesStart) {
+pub fn report_adaptation_set_attrs<S: MpdEventSink>(
+    e: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in e.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"id" => Id.try_report_as_string(&attr),
-                b"group" => Group.try_report_as_u64(&attr),
-                b"lang" => Language.try_report_as_string(&attr),
-                b"contentType" => ContentType.try_report_as_string(&attr),
-                b"par" => Par.try_report_as_string(&attr),
-                b"minBandwidth" => MinBandwidth.try_report_as_u64(&attr),
-                b"maxBandwidth" => MaxBandwidth.try_report_as_u64(&attr),
-                b"minWidth" => MinWidth.try_report_as_u64(&attr),
-                b"maxWidth" => MaxWidth.try_report_as_u64(&attr),
-                b"minHeight" => MinHeight.try_report_as_u64(&attr),
-                b"maxHeight" => MaxHeight.try_report_as_u64(&attr),
-                b"minFrameRate" => MinFrameRate.try_report_as_string(&attr),
-                b"maxFrameRate" => MaxFrameRate.try_report_as_string(&attr),
-                b"selectionPriority" => SelectionPriority.try_report_as_u64(&attr),
-                b"segmentAlignment" => SegmentAlignment.try_report_as_u64_or_bool(&attr),
-                b"subsegmentAlignment" => SubsegmentAlignment.try_report_as_u64_or_bool(&attr),
-                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr),
-                b"audioSamplingRate" => AudioSamplingRate.try_report_as_string(&attr),
-                b"codecs" => Codecs.try_report_as_string(&attr),
-                b"scte214:supplementalCodecs" => SupplementalCodecs.try_report_as_string(&attr),
-                b"profiles" => Profiles.try_report_as_string(&attr),
-                b"segmentProfiles" => SegmentProfiles.try_report_as_string(&attr),
-                b"mimeType" => MimeType.try_report_as_string(&attr),
-                b"codingDependency" => CodingDependency.try_report_as_bool(&attr),
-                b"frameRate" => FrameRate.try_report_as_string(&attr),
-                b"height" => Height.try_report_as_u64(&attr),
-                b"width" => Width.try_report_as_u64(&attr),
-                b"maxPlayoutRate" => MaxPlayoutRate.try_report_as_f64(&attr),
-                b"maxSAPPeriod" => MaxSAPPeriod.try_report_as_f64(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"xlink:href" => XLinkHref.try_report_as_string(&attr, sink),
+                b"xlink:actuate" => XLinkActuate.try_report_as_string(&attr, sink),
+                b"group" => Group.try_report_as_u64(&attr, sink),
+                b"lang" => Language.try_report_as_string(&attr, sink),
+                b"contentType" => ContentType.try_report_as_string(&attr, sink),
+                b"par" => Par.try_report_as_string(&attr, sink),
+                b"minBandwidth" => MinBandwidth.try_report_as_u64(&attr, sink),
+                b"maxBandwidth" => MaxBandwidth.try_report_as_u64(&attr, sink),
+                b"minWidth" => MinWidth.try_report_as_u64(&attr, sink),
+                b"maxWidth" => MaxWidth.try_report_as_u64(&attr, sink),
+                b"minHeight" => MinHeight.try_report_as_u64(&attr, sink),
+                b"maxHeight" => MaxHeight.try_report_as_u64(&attr, sink),
+                b"minFrameRate" => MinFrameRate.try_report_as_string(&attr, sink),
+                b"maxFrameRate" => MaxFrameRate.try_report_as_string(&attr, sink),
+                b"selectionPriority" => SelectionPriority.try_report_as_u64(&attr, sink),
+                b"segmentAlignment" => SegmentAlignment.try_report_as_u64_or_bool(&attr, sink),
+                b"subsegmentAlignment" => {
+                    SubsegmentAlignment.try_report_as_u64_or_bool(&attr, sink)
+                }
+                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr, sink),
+                b"audioSamplingRate" => AudioSamplingRate.try_report_as_string(&attr, sink),
+                b"codecs" => Codecs.try_report_as_string(&attr, sink),
+                b"scte214:supplementalCodecs" => {
+                    SupplementalCodecs.try_report_as_string(&attr, sink)
+                }
+                b"profiles" => Profiles.try_report_as_string(&attr, sink),
+                b"segmentProfiles" => SegmentProfiles.try_report_as_string(&attr, sink),
+                b"mimeType" => MimeType.try_report_as_string(&attr, sink),
+                b"codingDependency" => CodingDependency.try_report_as_bool(&attr, sink),
+                b"frameRate" => FrameRate.try_report_as_string(&attr, sink),
+                b"height" => Height.try_report_as_u64(&attr, sink),
+                b"width" => Width.try_report_as_u64(&attr, sink),
+                b"maxPlayoutRate" => MaxPlayoutRate.try_report_as_f64(&attr, sink),
+                b"maxSAPPeriod" => MaxSAPPeriod.try_report_as_f64(&attr, sink),
                 b"availabilityTimeOffset" => match attr.value.as_ref() {
-                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY),
-                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr),
+                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY, sink),
+                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr, sink),
                 },
-                b"availabilityTimeComplete" => AvailabilityTimeComplete.try_report_as_bool(&attr),
+                b"availabilityTimeComplete" => {
+                    AvailabilityTimeComplete.try_report_as_bool(&attr, sink)
+                }
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_representation_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_representation_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"id" => Id.try_report_as_string(&attr),
-                b"audioSamplingRate" => AudioSamplingRate.try_report_as_string(&attr),
-                b"bandwidth" => Bitrate.try_report_as_u64(&attr),
-                b"codecs" => Codecs.try_report_as_string(&attr),
-                b"scte214:supplementalCodecs" => SupplementalCodecs.try_report_as_string(&attr),
-                b"codingDependency" => CodingDependency.try_report_as_bool(&attr),
-                b"frameRate" => FrameRate.try_report_as_string(&attr),
-                b"height" => Height.try_report_as_u64(&attr),
-                b"width" => Width.try_report_as_u64(&attr),
-                b"maxPlayoutRate" => MaxPlayoutRate.try_report_as_f64(&attr),
-                b"maxSAPPeriod" => MaxSAPPeriod.try_report_as_f64(&attr),
-                b"mimeType" => MimeType.try_report_as_string(&attr),
-                b"profiles" => Profiles.try_report_as_string(&attr),
-                b"qualityRanking" => QualityRanking.try_report_as_u64(&attr),
-                b"segmentProfiles" => SegmentProfiles.try_report_as_string(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"audioSamplingRate" => AudioSamplingRate.try_report_as_string(&attr, sink),
+                b"bandwidth" => Bitrate.try_report_as_u64(&attr, sink),
+                b"codecs" => Codecs.try_report_as_string(&attr, sink),
+                b"scte214:supplementalCodecs" => {
+                    SupplementalCodecs.try_report_as_string(&attr, sink)
+                }
+                b"codingDependency" => CodingDependency.try_report_as_bool(&attr, sink),
+                b"frameRate" => FrameRate.try_report_as_string(&attr, sink),
+                b"height" => Height.try_report_as_u64(&attr, sink),
+                b"width" => Width.try_report_as_u64(&attr, sink),
+                b"maxPlayoutRate" => MaxPlayoutRate.try_report_as_f64(&attr, sink),
+                b"maxSAPPeriod" => MaxSAPPeriod.try_report_as_f64(&attr, sink),
+                b"mimeType" => MimeType.try_report_as_string(&attr, sink),
+                b"profiles" => Profiles.try_report_as_string(&attr, sink),
+                b"qualityRanking" => QualityRanking.try_report_as_u64(&attr, sink),
+                b"segmentProfiles" => SegmentProfiles.try_report_as_string(&attr, sink),
                 b"availabilityTimeOffset" => match attr.value.as_ref() {
-                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY),
-                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr),
+                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY, sink),
+                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr, sink),
                 },
-                b"availabilityTimeComplete" => AvailabilityTimeComplete.try_report_as_bool(&attr),
+                b"availabilityTimeComplete" => {
+                    AvailabilityTimeComplete.try_report_as_bool(&attr, sink)
+                }
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_base_url_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_base_url_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => {
                 if let b"serviceLocation" = attr.key.as_ref() {
-                    ServiceLocation.try_report_as_string(&attr)
+                    ServiceLocation.try_report_as_string(&attr, sink)
                 }
             }
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_segment_template_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_segment_template_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"initialization" => InitializationMedia.try_report_as_string(&attr),
-                b"index" => Index.try_report_as_string(&attr),
-                b"timescale" => TimeScale.try_report_as_u64(&attr),
-                b"presentationTimeOffset" => PresentationTimeOffset.try_report_as_f64(&attr),
-                b"indexRange" => IndexRange.try_report_as_range(&attr),
-                b"IndexRangeExact" => IndexRangeExact.try_report_as_bool(&attr),
+                b"initialization" => InitializationMedia.try_report_as_template(&attr, sink),
+                b"index" => Index.try_report_as_template(&attr, sink),
+                b"timescale" => TimeScale.try_report_as_u64(&attr, sink),
+                b"presentationTimeOffset" => {
+                    PresentationTimeOffset.try_report_as_timestamp(&attr, sink)
+                }
+                b"indexRange" => IndexRange.try_report_as_range(&attr, sink),
+                b"IndexRangeExact" => IndexRangeExact.try_report_as_bool(&attr, sink),
                 b"availabilityTimeOffset" => match attr.value.as_ref() {
-                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY),
-                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr),
+                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY, sink),
+                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr, sink),
                 },
-                b"availabilityTimeComplete" => AvailabilityTimeComplete.try_report_as_bool(&attr),
-                b"duration" => Duration.try_report_as_u64(&attr),
-                b"startNumber" => StartNumber.try_report_as_u64(&attr),
-                b"endNumber" => EndNumber.try_report_as_u64(&attr),
-                b"media" => Media.try_report_as_string(&attr),
-                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr),
+                b"availabilityTimeComplete" => {
+                    AvailabilityTimeComplete.try_report_as_bool(&attr, sink)
+                }
+                b"duration" => Duration.try_report_as_u64(&attr, sink),
+                b"startNumber" => StartNumber.try_report_as_u64(&attr, sink),
+                b"endNumber" => EndNumber.try_report_as_u64(&attr, sink),
+                b"media" => Media.try_report_as_template(&attr, sink),
+                b"bitstreamSwitching" => BitstreamSwitching.try_report_as_bool(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_segment_base_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_segment_base_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => report_segment_base_attr(&attr, sink),
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+/// Same as `report_segment_base_attrs`, with the XLink attributes a
+/// `<SegmentList>` may also have.
+pub fn report_segment_list_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"timescale" => TimeScale.try_report_as_u64(&attr),
-                b"presentationTimeOffset" => PresentationTimeOffset.try_report_as_f64(&attr),
-                b"indexRange" => IndexRange.try_report_as_range(&attr),
-                b"indexRangeExact" => IndexRangeExact.try_report_as_bool(&attr),
-                b"availabilityTimeOffset" => match attr.value.as_ref() {
-                    b"INF" => AvailabilityTimeOffset.report(f64::INFINITY),
-                    _ => AvailabilityTimeOffset.try_report_as_f64(&attr),
-                },
-                b"availabilityTimeComplete" => AvailabilityTimeComplete.try_report_as_bool(&attr),
-                b"duration" => Duration.try_report_as_u64(&attr),
-                b"startNumber" => StartNumber.try_report_as_u64(&attr),
-                b"endNumber" => EndNumber.try_report_as_u64(&attr),
-                _ => {}
+                b"xlink:href" => XLinkHref.try_report_as_string(&attr, sink),
+                b"xlink:actuate" => XLinkActuate.try_report_as_string(&attr, sink),
+                _ => report_segment_base_attr(&attr, sink),
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_content_component_attrs(tag_bs: &quick_xml::events::BytesStart) {
+fn report_segment_base_attr<S: MpdEventSink>(attr: &Attribute, sink: &mut S) {
+    match attr.key.as_ref() {
+        b"timescale" => TimeScale.try_report_as_u64(attr, sink),
+        b"presentationTimeOffset" => PresentationTimeOffset.try_report_as_timestamp(attr, sink),
+        b"indexRange" => IndexRange.try_report_as_range(attr, sink),
+        b"indexRangeExact" => IndexRangeExact.try_report_as_bool(attr, sink),
+        b"availabilityTimeOffset" => match attr.value.as_ref() {
+            b"INF" => AvailabilityTimeOffset.report(f64::INFINITY, sink),
+            _ => AvailabilityTimeOffset.try_report_as_f64(attr, sink),
+        },
+        b"availabilityTimeComplete" => AvailabilityTimeComplete.try_report_as_bool(attr, sink),
+        b"duration" => Duration.try_report_as_u64(attr, sink),
+        b"startNumber" => StartNumber.try_report_as_u64(attr, sink),
+        b"endNumber" => EndNumber.try_report_as_u64(attr, sink),
+        _ => {}
+    }
+}
+
+pub fn report_content_component_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"id" => Id.try_report_as_string(&attr),
-                b"lang" => Language.try_report_as_string(&attr),
-                b"contentType" => ContentType.try_report_as_string(&attr),
-                b"par" => Par.try_report_as_string(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"lang" => Language.try_report_as_string(&attr, sink),
+                b"contentType" => ContentType.try_report_as_string(&attr, sink),
+                b"par" => Par.try_report_as_string(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_content_protection_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_content_protection_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr),
-                b"value" => ContentProtectionValue.try_report_as_string(&attr),
-
-                // TODO convert hex to bytes here?
-                b"cenc:default_KID" => ContentProtectionKeyId.try_report_as_string(&attr),
+                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr, sink),
+                b"value" => ContentProtectionValue.try_report_as_string(&attr, sink),
+                b"cenc:default_KID" => ContentProtectionKeyId.try_report_as_key_ids(&attr, sink),
+                b"robustness" => ContentProtectionRobustness.try_report_as_string(&attr, sink),
+                b"refId" => ContentProtectionRefId.try_report_as_string(&attr, sink),
+                b"ref" => ContentProtectionRef.try_report_as_string(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
+/// Report the content of a `<cenc:pssh>` element: the decoded `pssh` box,
+/// followed by its SystemID and key ids if it could be parsed.
+pub fn report_cenc_pssh<S: MpdEventSink>(text: &[u8], sink: &mut S) {
+    let data = match decode_base64(text) {
+        Ok(data) => data,
+        Err(err) => return err.report_err(sink),
+    };
+    ContentProtectionCencPSSH.report(AttrValue::Bytes(&data), sink);
+    match PsshBox::parse(&data) {
+        Ok(pssh) => {
+            ContentProtectionPsshSystemId.report(AttrValue::Bytes(&pssh.system_id), sink);
+            for key_id in &pssh.key_ids {
+                ContentProtectionPsshKeyId.report(AttrValue::Bytes(key_id), sink);
+            }
+        }
+        Err(err) => err.report_err(sink),
+    }
+}
+
+/// Report the content of a `<mspr:pro>` element: the decoded PlayReady
+/// Object, followed by the information of its WRM header if it could be
+/// parsed.
+pub fn report_playready_object<S: MpdEventSink>(text: &[u8], sink: &mut S) {
+    let data = match decode_base64(text) {
+        Ok(data) => data,
+        Err(err) => return err.report_err(sink),
+    };
+    ContentProtectionPlayReadyObject.report(AttrValue::Bytes(&data), sink);
+    let header = match PlayReadyObject::parse(&data).and_then(|pro| pro.wrm_header()) {
+        Ok(Some(header)) => header,
+        Ok(None) => return,
+        Err(err) => return err.report_err(sink),
+    };
+    for key_id in &header.key_ids {
+        ContentProtectionPlayReadyKeyId.report(AttrValue::Bytes(key_id), sink);
+    }
+    if let Some(la_url) = &header.la_url {
+        ContentProtectionPlayReadyLaUrl.report(la_url.as_str(), sink);
+    }
+    if let Some(lui_url) = &header.lui_url {
+        ContentProtectionPlayReadyLuiUrl.report(lui_url.as_str(), sink);
+    }
+    if let Some(custom_attributes) = &header.custom_attributes {
+        ContentProtectionPlayReadyCustomAttributes.report(custom_attributes.as_str(), sink);
+    }
+}
+
+/// Report the content of a `<mspr:IsEncrypted>` element, either `1` or `0`.
+pub fn report_playready_is_encrypted<S: MpdEventSink>(text: &[u8], sink: &mut S) {
+    match text {
+        b"1" => ContentProtectionPlayReadyIsEncrypted.report(true, sink),
+        b"0" => ContentProtectionPlayReadyIsEncrypted.report(false, sink),
+        _ => match parse_bool(text) {
+            Ok(val) => ContentProtectionPlayReadyIsEncrypted.report(val, sink),
+            Err(err) => err.report_err(sink),
+        },
+    }
+}
+
 /// Report attributes encountered in an `<Initialization>` element.
-pub fn report_initialization_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_initialization_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"range" => InitializationRange.try_report_as_range(&attr),
-                b"sourceURL" => InitializationMedia.try_report_as_string(&attr),
+                b"range" => InitializationRange.try_report_as_range(&attr, sink),
+                b"sourceURL" => InitializationMedia.try_report_as_string(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
@@ -254,62 +388,254 @@ pub fn report_initialization_attrs(tag_bs: &quick_xml::events::BytesStart) {
 /// a string form:
 ///   - "schemeIdUri"
 ///   - "value"
-pub fn report_scheme_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_scheme_attrs<S: MpdEventSink>(tag_bs: &quick_xml::events::BytesStart, sink: &mut S) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr, sink),
+                b"value" => SchemeValue.try_report_as_string(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+/// Report the attributes of an `<AudioChannelConfiguration>` element,
+/// followed by the channel count and layout decoded from them if its scheme
+/// is known.
+pub fn report_audio_channel_configuration_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    report_scheme_attrs(tag_bs, sink);
+    let scheme_id_uri = tag_bs.try_get_attribute("schemeIdUri").ok().flatten();
+    let value = tag_bs.try_get_attribute("value").ok().flatten();
+    let (scheme_id_uri, value) = match (scheme_id_uri, value) {
+        (Some(scheme_id_uri), Some(value)) => (scheme_id_uri, value),
+        _ => return,
+    };
+    match AudioChannelConfiguration::from_scheme(&scheme_id_uri.value, &value.value) {
+        Ok(Some(config)) => {
+            AudioChannelCount.report(f64::from(config.channel_count), sink);
+            if let Some(speakers) = config.speakers {
+                AudioChannelLayout.report(&*speakers.join(" "), sink);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => err.in_attribute(b"value").report_err(sink),
+    }
+}
+
+pub fn report_content_steering_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr),
-                b"value" => SchemeValue.try_report_as_string(&attr),
+                b"defaultServiceLocation" => {
+                    DefaultServiceLocation.try_report_as_string(&attr, sink)
+                }
+                b"queryBeforeStart" => QueryBeforeStart.try_report_as_bool(&attr, sink),
+                b"proxyServerURL" => ProxyServerUrl.try_report_as_string(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_segment_url_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_preselection_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"index" => Index.try_report_as_string(&attr),
-                b"indexRange" => IndexRange.try_report_as_range(&attr),
-                b"media" => Media.try_report_as_string(&attr),
-                b"mediaRange" => MediaRange.try_report_as_range(&attr),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"preselectionComponents" => {
+                    PreselectionComponents.try_report_as_string(&attr, sink)
+                }
+                b"lang" => Language.try_report_as_string(&attr, sink),
+                b"tag" => PreselectionTag.try_report_as_string(&attr, sink),
+                b"codecs" => Codecs.try_report_as_string(&attr, sink),
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_service_description_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) if attr.key.as_ref() == b"id" => Id.try_report_as_string(&attr, sink),
+            Ok(_) => {}
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_event_stream_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_latency_attrs<S: MpdEventSink>(tag_bs: &quick_xml::events::BytesStart, sink: &mut S) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr),
-                b"value" => SchemeValue.try_report_as_string(&attr),
-                b"timescale" => TimeScale.try_report_as_u64(&attr),
+                b"referenceId" => ReferenceId.try_report_as_u64(&attr, sink),
+                b"target" => Target.try_report_as_u64(&attr, sink),
+                b"min" => Min.try_report_as_u64(&attr, sink),
+                b"max" => Max.try_report_as_u64(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_playback_rate_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"min" => Min.try_report_as_f64(&attr, sink),
+                b"max" => Max.try_report_as_f64(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+/// Report the attributes of either an `<OperatingQuality>` or an
+/// `<OperatingBandwidth>` element, the latter having a subset of the former's
+/// ones.
+pub fn report_operating_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"mediaType" => MediaType.try_report_as_string(&attr, sink),
+                b"min" => Min.try_report_as_u64(&attr, sink),
+                b"max" => Max.try_report_as_u64(&attr, sink),
+                b"target" => Target.try_report_as_u64(&attr, sink),
+                b"type" => Type.try_report_as_string(&attr, sink),
+                b"maxDifference" => MaxDifference.try_report_as_u64(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_producer_reference_time_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"inband" => Inband.try_report_as_bool(&attr, sink),
+                b"type" => Type.try_report_as_string(&attr, sink),
+                b"applicationScheme" => ApplicationScheme.try_report_as_string(&attr, sink),
+                b"wallClockTime" => WallClockTime.try_report_as_wall_clock_time(&attr, sink),
+                b"presentationTime" => PresentationTime.try_report_as_timestamp(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_resync_attrs<S: MpdEventSink>(tag_bs: &quick_xml::events::BytesStart, sink: &mut S) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"type" => ResyncType.try_report_as_u64(&attr, sink),
+                b"dT" => ResyncDT.try_report_as_u64(&attr, sink),
+                b"dImax" => ResyncDIMax.try_report_as_f64(&attr, sink),
+                b"dImin" => ResyncDIMin.try_report_as_f64(&attr, sink),
+                b"marker" => ResyncMarker.try_report_as_bool(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_segment_url_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"index" => Index.try_report_as_string(&attr, sink),
+                b"indexRange" => IndexRange.try_report_as_range(&attr, sink),
+                b"media" => Media.try_report_as_string(&attr, sink),
+                b"mediaRange" => MediaRange.try_report_as_range(&attr, sink),
+                _ => {}
+            },
+            Err(err) => ParsingError::from(err).report_err(sink),
+        };
+    }
+}
+
+pub fn report_event_stream_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
+    for res_attr in tag_bs.attributes() {
+        match res_attr {
+            Ok(attr) => match attr.key.as_ref() {
+                b"schemeIdUri" => SchemeIdUri.try_report_as_string(&attr, sink),
+                b"value" => SchemeValue.try_report_as_string(&attr, sink),
+                b"timescale" => TimeScale.try_report_as_u64(&attr, sink),
+                b"presentationTimeOffset" => {
+                    PresentationTimeOffset.try_report_as_timestamp(&attr, sink)
+                }
+                b"xlink:href" => XLinkHref.try_report_as_string(&attr, sink),
+                b"xlink:actuate" => XLinkActuate.try_report_as_string(&attr, sink),
                 x => {
                     if x.len() > 6 && &x[..6] == b"xmlns:" {
-                        Namespace.try_report_as_key_value(&x[6..], &attr);
+                        Namespace.try_report_as_key_value(&x[6..], &attr, sink);
                     }
                 }
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
 
-pub fn report_event_stream_event_attrs(tag_bs: &quick_xml::events::BytesStart) {
+pub fn report_event_stream_event_attrs<S: MpdEventSink>(
+    tag_bs: &quick_xml::events::BytesStart,
+    sink: &mut S,
+) {
     for res_attr in tag_bs.attributes() {
         match res_attr {
             Ok(attr) => match attr.key.as_ref() {
-                b"presentationTime" => EventPresentationTime.try_report_as_u64(&attr),
-                b"duration" => Duration.try_report_as_u64(&attr),
-                b"id" => Id.try_report_as_string(&attr),
+                b"presentationTime" => EventPresentationTime.try_report_as_timestamp(&attr, sink),
+                b"duration" => Duration.try_report_as_u64(&attr, sink),
+                b"id" => Id.try_report_as_string(&attr, sink),
+                b"messageData" => match attr.unescape_value() {
+                    Ok(val) => MessageData.report(AttrValue::Bytes(val.as_bytes()), sink),
+                    Err(_) => ParsingError::new(
+                        ParsingErrorKind::InvalidEncoding,
+                        "Could not escape original value",
+                    )
+                    .in_attribute(attr.key.as_ref())
+                    .report_err(sink),
+                },
                 _ => {}
             },
-            Err(err) => ParsingError::from(err).report_err(),
+            Err(err) => ParsingError::from(err).report_err(sink),
         };
     }
 }
@@ -369,17 +695,17 @@ pub fn report_event_stream_event_attrs(tag_bs: &quick_xml::events::BytesStart) {
 //    for res_attr in e.attributes() {
 //        match res_attr {
 //            Ok(attr) => match attr.key.as_ref() {
-//                b"id" => { mpd_attrs.id = extract_string_attr(&attr); },
-//                b"profiles" => { mpd_attrs.profiles = extract_string_attr(&attr); }
-//                b"type" => { mpd_attrs.mpd_type = extract_string_attr(&attr); }
+//                b"id" => { mpd_attrs.id = extract_string_attr(&attr, sink); },
+//                b"profiles" => { mpd_attrs.profiles = extract_string_attr(&attr, sink); }
+//                b"type" => { mpd_attrs.mpd_type = extract_string_attr(&attr, sink); }
 //                b"availabilityStartTime" => {
-//                    mpd_attrs.availability_start_time = extract_string_attr(&attr);
+//                    mpd_attrs.availability_start_time = extract_string_attr(&attr, sink);
 //                }
 //                b"availabilityEndTime" => {
-//                    mpd_attrs.availability_end_time = extract_string_attr(&attr);
+//                    mpd_attrs.availability_end_time = extract_string_attr(&attr, sink);
 //                }
 //                b"publish_time" => {
-//                    mpd_attrs.publish_time = extract_string_attr(&attr);
+//                    mpd_attrs.publish_time = extract_string_attr(&attr, sink);
 //                }
 //                b"mediaPresentationDuration" =>
 //                    mpd_attrs.media_presentation_duration =
@@ -407,7 +733,7 @@ pub fn report_event_stream_event_attrs(tag_bs: &quick_xml::events::BytesStart) {
 //                        extract_iso_8601_duration_attr(&attr.value),
 //                _ => {},
 //            },
-//            Err(err) => ParsingError::from(err).report_err(),
+//            Err(err) => ParsingError::from(err).report_err(sink),
 //        };
 //    }
 //    mpd_attrs
@@ -0,0 +1,153 @@
+use quick_xml::events::BytesStart;
+
+use crate::url::{is_absolute_url, resolve_url};
+
+/// A `<BaseURL>` resolved against those of its parent elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ResolvedBaseUrl {
+    pub url: String,
+
+    /// `serviceLocation` of the `<BaseURL>`, or else of the parent one it
+    /// was resolved against.
+    pub service_location: Option<String>,
+}
+
+/// Resolves the `<BaseURL>` elements of the MPD, Period, AdaptationSet and
+/// Representation levels against those of their parent level, as the MPD is
+/// browsed (@see `ParsingOptions::resolve_base_urls`).
+///
+/// An element without `<BaseURL>` inherits those of its parent. When a level
+/// has several `<BaseURL>` elements (e.g. one per CDN), each relative one is
+/// resolved against every BaseURL of the parent level.
+pub(super) struct BaseUrlResolver {
+    /// URL of the MPD document itself, against which the `<MPD>`'s BaseURLs
+    /// are resolved.
+    document_url: Option<String>,
+
+    /// One entry per currently open level, from the `<MPD>` to the innermost.
+    levels: Vec<Level>,
+
+    /// `serviceLocation` and text of the `<BaseURL>` currently being parsed.
+    current: Option<(Option<String>, String)>,
+}
+
+struct Level {
+    /// BaseURLs applying to the parent level.
+    inherited: Vec<ResolvedBaseUrl>,
+
+    /// BaseURLs declared on this level, already resolved.
+    own: Vec<ResolvedBaseUrl>,
+}
+
+impl Level {
+    fn base_urls(&self) -> &[ResolvedBaseUrl] {
+        if self.own.is_empty() {
+            &self.inherited
+        } else {
+            &self.own
+        }
+    }
+}
+
+fn is_level(name: &[u8]) -> bool {
+    matches!(
+        name,
+        b"MPD" | b"Period" | b"AdaptationSet" | b"Representation"
+    )
+}
+
+impl BaseUrlResolver {
+    pub fn new(document_url: Option<String>) -> Self {
+        BaseUrlResolver {
+            document_url,
+            levels: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Signal that an element has been opened.
+    pub fn enter(&mut self, tag: &BytesStart) {
+        let name = tag.name();
+        if is_level(name.as_ref()) {
+            let inherited = match self.levels.last() {
+                Some(parent) => parent.base_urls().to_vec(),
+                None => self
+                    .document_url
+                    .iter()
+                    .map(|url| ResolvedBaseUrl {
+                        url: url.clone(),
+                        service_location: None,
+                    })
+                    .collect(),
+            };
+            self.levels.push(Level {
+                inherited,
+                own: Vec::new(),
+            });
+        } else if name.as_ref() == b"BaseURL" && !self.levels.is_empty() {
+            let service_location = tag
+                .try_get_attribute("serviceLocation")
+                .ok()
+                .flatten()
+                .and_then(|attr| attr.unescape_value().ok().map(|val| val.into_owned()));
+            self.current = Some((service_location, String::new()));
+        }
+    }
+
+    /// Signal text found in the current element.
+    pub fn text(&mut self, text: &str) {
+        if let Some((_, url)) = self.current.as_mut() {
+            url.push_str(text);
+        }
+    }
+
+    /// Signal that an element has been closed.
+    ///
+    /// Returns the BaseURLs applying to it if it is a `<Representation>`.
+    pub fn leave(&mut self, name: &[u8]) -> Option<Vec<ResolvedBaseUrl>> {
+        if name == b"BaseURL" {
+            if let Some((service_location, url)) = self.current.take() {
+                self.add_base_url(url.trim(), service_location);
+            }
+            None
+        } else if is_level(name) {
+            let level = self.levels.pop()?;
+            if name == b"Representation" {
+                Some(level.base_urls().to_vec())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    fn add_base_url(&mut self, url: &str, service_location: Option<String>) {
+        let level = match self.levels.last_mut() {
+            Some(level) => level,
+            None => return,
+        };
+        let resolved: Vec<ResolvedBaseUrl> = if level.inherited.is_empty() || is_absolute_url(url) {
+            vec![ResolvedBaseUrl {
+                url: url.to_owned(),
+                service_location,
+            }]
+        } else {
+            level
+                .inherited
+                .iter()
+                .map(|parent| ResolvedBaseUrl {
+                    url: resolve_url(&parent.url, url),
+                    service_location: service_location
+                        .clone()
+                        .or_else(|| parent.service_location.clone()),
+                })
+                .collect()
+        };
+        for base_url in resolved {
+            if !level.own.contains(&base_url) {
+                level.own.push(base_url);
+            }
+        }
+    }
+}
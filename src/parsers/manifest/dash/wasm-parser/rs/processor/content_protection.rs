@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+
+/// Keeps the `<ContentProtection>` elements with a `refId` attribute as they
+/// are encountered, so that the ones referencing them later through a `ref`
+/// attribute can be reported as if they declared the same attributes and
+/// children.
+#[derive(Default)]
+pub(super) struct ContentProtectionRefs {
+    /// Elements which may be referenced, per `refId`.
+    definitions: HashMap<Vec<u8>, Definition>,
+
+    /// `refId` and content of the element currently being recorded, if any.
+    recording: Option<(Vec<u8>, Definition)>,
+}
+
+struct Definition {
+    tag: BytesStart<'static>,
+
+    /// Events read inside the element, its opening and closing tags
+    /// excluded.
+    children: Vec<Event<'static>>,
+}
+
+impl ContentProtectionRefs {
+    /// Signal that a `<ContentProtection>` element, not referencing another
+    /// one, has been opened.
+    ///
+    /// If it has a `refId` attribute, the events read until it is closed are
+    /// recorded (@see `ContentProtectionRefs::record`).
+    pub fn start_definition(&mut self, tag: &BytesStart) {
+        if self.recording.is_some() {
+            return;
+        }
+        if let Some(ref_id) = attribute_value(tag, b"refId") {
+            let definition = Definition {
+                tag: tag.clone().into_owned(),
+                children: Vec::new(),
+            };
+            self.recording = Some((ref_id, definition));
+        }
+    }
+
+    /// Signal an event read from the MPD, before processing it.
+    pub fn record(&mut self, evt: &Event) {
+        let (_, definition) = match self.recording.as_mut() {
+            Some(recording) => recording,
+            None => return,
+        };
+        // Elements with a `refId` never contain `<ContentProtection>`
+        // elements, its first closing tag is thus its own.
+        match evt {
+            Event::End(tag) if tag.name().as_ref() == b"ContentProtection" => {
+                if let Some((ref_id, definition)) = self.recording.take() {
+                    self.definitions.insert(ref_id, definition);
+                }
+            }
+            _ => definition.children.push(evt.clone().into_owned()),
+        }
+    }
+
+    /// If the given `<ContentProtection>` element has a `ref` attribute,
+    /// returns a tag combining the attributes of the element it references
+    /// (except its `refId`) with its own ones, which take precedence, as well
+    /// as the events to process as its children.
+    ///
+    /// Returns an error if no element with that `refId` has been encountered.
+    pub fn resolve(
+        &self,
+        tag: &BytesStart,
+    ) -> Result<Option<(BytesStart<'static>, Vec<Event<'static>>)>> {
+        let reference = match attribute_value(tag, b"ref") {
+            Some(reference) => reference,
+            None => return Ok(None),
+        };
+        let definition = self.definitions.get(&reference).ok_or_else(|| {
+            ParsingError::new(
+                ParsingErrorKind::InvalidReference,
+                format!(
+                    "No ContentProtection with the refId {:?} has been defined before.",
+                    String::from_utf8_lossy(&reference)
+                ),
+            )
+            .in_attribute(b"ref")
+        })?;
+
+        let own_attrs: Vec<Attribute> = tag.attributes().flatten().collect();
+        let mut merged = BytesStart::new("ContentProtection");
+        for attr in definition.tag.attributes().flatten() {
+            if attr.key.as_ref() != b"refId" && !own_attrs.iter().any(|own| own.key == attr.key) {
+                merged.push_attribute(attr);
+            }
+        }
+        merged.extend_attributes(own_attrs);
+        Ok(Some((merged, definition.children.clone())))
+    }
+}
+
+fn attribute_value(tag: &BytesStart, name: &[u8]) -> Option<Vec<u8>> {
+    tag.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .map(|attr| attr.value.into_owned())
+}
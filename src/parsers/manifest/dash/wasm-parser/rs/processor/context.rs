@@ -0,0 +1,129 @@
+use quick_xml::events::BytesStart;
+
+use crate::errors::ParsingError;
+use crate::events::{AttributeName, TagName};
+use crate::sink::{AttrValue, MpdEventSink};
+
+/// `MpdEventSink` wrapper keeping track of where the `MPDProcessor` is in the
+/// MPD, to indicate it in the errors it reports.
+pub(super) struct ContextSink<S: MpdEventSink> {
+    pub(super) inner: S,
+
+    /// Identifiers of the elements currently open, from the root (e.g.
+    /// `Period[2]` or `AdaptationSet[id=3]`).
+    path: Vec<String>,
+
+    /// For the root and for each currently open element, the names of the
+    /// child elements encountered so far with their number.
+    children: Vec<Vec<(Vec<u8>, usize)>>,
+
+    /// Position in bytes in the whole MPD of the event being processed.
+    offset: usize,
+
+    /// If `true`, the first error (other than warnings, @see
+    /// `ParsingErrorKind::is_warning`) stops the parsing.
+    strict: bool,
+
+    /// In strict mode, the error which stopped the parsing. Events are not
+    /// forwarded anymore once set.
+    failure: Option<ParsingError>,
+}
+
+impl<S: MpdEventSink> ContextSink<S> {
+    pub(super) fn new(inner: S, strict: bool) -> Self {
+        ContextSink {
+            inner,
+            path: Vec::new(),
+            children: vec![Vec::new()],
+            offset: 0,
+            strict,
+            failure: None,
+        }
+    }
+
+    pub(super) fn failure(&self) -> Option<&ParsingError> {
+        self.failure.as_ref()
+    }
+
+    pub(super) fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    /// Signal that a new element has been opened.
+    pub(super) fn enter(&mut self, tag: &BytesStart) {
+        let name = tag.name();
+        let name = name.as_ref();
+        let siblings = self.children.last_mut().unwrap();
+        let position = match siblings.iter_mut().find(|(n, _)| n == name) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                siblings.push((name.to_vec(), 1));
+                1
+            }
+        };
+
+        let id = tag
+            .attributes()
+            .flatten()
+            .find(|attr| attr.key.as_ref() == b"id");
+        let name = String::from_utf8_lossy(name);
+        let segment = match id {
+            Some(id) => format!("{}[id={}]", name, String::from_utf8_lossy(&id.value)),
+            None if position > 1 => format!("{}[{}]", name, position),
+            None => name.into_owned(),
+        };
+        self.path.push(segment);
+        self.children.push(Vec::new());
+    }
+
+    /// Signal that the last opened element has been closed.
+    pub(super) fn leave(&mut self) {
+        if self.path.pop().is_some() {
+            self.children.pop();
+        }
+    }
+}
+
+impl<S: MpdEventSink> MpdEventSink for ContextSink<S> {
+    fn tag_open(&mut self, tag_name: TagName) {
+        if self.failure.is_none() {
+            self.inner.tag_open(tag_name);
+        }
+    }
+
+    fn tag_close(&mut self, tag_name: TagName) {
+        if self.failure.is_none() {
+            self.inner.tag_close(tag_name);
+        }
+    }
+
+    fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+        if self.failure.is_none() {
+            self.inner.attribute(attr_name, value);
+        }
+    }
+
+    fn error(&mut self, err: &ParsingError) {
+        if self.failure.is_some() {
+            return;
+        }
+        let mut err = err.clone();
+        err.path = format!("{}{}", self.path.join("/"), err.path);
+        if err.offset.is_none() {
+            err.offset = Some(self.offset);
+        }
+        self.inner.error(&err);
+        if self.strict && !err.kind.is_warning() {
+            self.failure = Some(err);
+        }
+    }
+
+    fn log(&mut self, message: &str) {
+        if self.failure.is_none() {
+            self.inner.log(message);
+        }
+    }
+}
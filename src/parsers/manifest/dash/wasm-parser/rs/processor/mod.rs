@@ -1,472 +1,2334 @@
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
-use std::io::BufReader;
+use std::io::BufRead;
 
 mod attributes;
+mod base_url;
+mod content_protection;
+mod context;
+mod preselection;
+mod push;
+mod raw_xml;
 mod s_element;
+mod validation;
 
-use crate::errors::ParsingError;
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
 use crate::events::*;
-use crate::reader::MPDReader;
+use crate::sink::{AttrValue, MpdEventSink};
+use crate::url::resolve_url;
+use crate::utils::decode_base64;
+use crate::xlink::{
+    RemoteElementLoader, XLinkActuate, MAX_XLINK_DEPTH, RESOLVE_TO_ZERO, XLINK_ELEMENTS,
+};
+use base_url::BaseUrlResolver;
+use content_protection::ContentProtectionRefs;
+use context::ContextSink;
+use preselection::PreselectionComponents;
+use raw_xml::RawXmlRecorder;
+use validation::Validator;
 
+pub use push::MPDPushProcessor;
 pub use s_element::SegmentObject;
+use s_element::TimelineBounds;
 
-pub struct MPDProcessor {
-    reader: quick_xml::Reader<BufReader<MPDReader>>,
+/// Options changing how an MPD is parsed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsingOptions {
+    /// If `true`, the MPD is checked against the DASH specification and the
+    /// parsing stops at the first error or non-conformance encountered (e.g.
+    /// a `<Representation>` without a `bandwidth` attribute).
+    ///
+    /// If `false` (the default), the parser is lenient: errors are reported
+    /// and the parsing goes on with what could be understood.
+    pub strict: bool,
+
+    /// If `true`, negative `repeat_count`s (`r="-1"`) of `<S>` elements are
+    /// replaced by the number of times their segment is actually repeated,
+    /// until the next `<S>` element or the end of the Period (@see
+    /// `SegmentObject::resolve_repeat_counts`).
+    ///
+    /// The end of the Period is taken from `period_durations` or, if not
+    /// indicated there, from the `duration` attribute of the `<Period>`. It
+    /// is converted to the timescale of the timeline through the
+    /// `timescale` and `presentationTimeOffset` of the `<SegmentTemplate>`
+    /// containing it, which may be inherited from those of its parents.
+    ///
+    /// The `start` of the following `<Period>`, which is not read yet when a
+    /// timeline is reported, is not taken into account: when it is what
+    /// ends a Period, its duration has to be given through
+    /// `period_durations`. Otherwise, the last `<S>` element of the
+    /// timelines of that Period keeps its negative `repeat_count`.
+    /// Timelines of `<SegmentList>` elements are never resolved.
+    pub resolve_repeat_counts: bool,
+
+    /// Durations, in seconds, of the `<Period>` elements of the MPD (those
+    /// loaded through XLinks included), in the order they are encountered,
+    /// `None` for those which are unknown.
+    ///
+    /// This allows the caller to indicate where Periods without a `duration`
+    /// attribute end, e.g. from the `start` of the following one, and takes
+    /// precedence over that attribute. Only used when
+    /// `resolve_repeat_counts` is set.
+    pub period_durations: Vec<Option<f64>>,
+
+    /// If `true`, the `<BaseURL>` elements are resolved against those of
+    /// their parents (from the `<MPD>` to the `<Representation>`) and against
+    /// `document_url`, as defined by RFC 3986.
+    ///
+    /// The resulting URLs are then reported at the end of each
+    /// `<Representation>`, as `AttributeName::ResolvedBaseUrl` attributes.
+    pub resolve_base_urls: bool,
+
+    /// URL of the MPD document itself, against which its relative
+    /// `<BaseURL>` elements are resolved when `resolve_base_urls` is set, as
+    /// well as its relative XLinks (@see `RemoteElementLoader`).
+    ///
+    /// If `None`, relative `<BaseURL>` without a parent BaseURL are reported
+    /// as is.
+    pub document_url: Option<String>,
+}
+
+/// Browses an MPD document and reports the elements and attributes it
+/// encounters to an `MpdEventSink`.
+pub struct MPDProcessor<R: BufRead, S: MpdEventSink> {
+    reader: quick_xml::Reader<RawXmlRecorder<R>>,
     reader_buf: Vec<u8>,
-    segment_objs_buf: Vec<SegmentObject>,
+    state: ProcessorState<S>,
 }
 
-impl MPDProcessor {
+impl<R: BufRead, S: MpdEventSink> MPDProcessor<R, S> {
     /// Creates a new MPDProcessor.
     ///
     /// # Arguments
     ///
-    /// * `reader` - A BufReader allowing to read the MPD document
-    pub fn new(reader: BufReader<MPDReader>) -> Self {
-        let mut reader = Reader::from_reader(reader);
-        reader.expand_empty_elements(true);
-        reader.trim_text(true);
-        reader.check_end_names(false);
+    /// * `reader` - A BufRead implementation allowing to read the MPD document
+    ///
+    /// * `sink` - The `MpdEventSink` to which parsed elements and attributes
+    ///   will be reported.
+    pub fn new(reader: R, sink: S) -> Self {
+        MPDProcessor::with_options(reader, sink, ParsingOptions::default())
+    }
+
+    /// Creates a new MPDProcessor parsing the MPD according to the given
+    /// `ParsingOptions`.
+    pub fn with_options(reader: R, sink: S, options: ParsingOptions) -> Self {
         MPDProcessor {
-            reader,
+            reader: create_xml_reader(reader),
             reader_buf: Vec::new(),
-            segment_objs_buf: Vec::new(),
+            state: ProcessorState::new(sink, options),
         }
     }
 
-    pub fn process_tags(&mut self) {
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Start(tag)) => match tag.name().as_ref() {
-                    b"MPD" => {
-                        TagName::MPD.report_tag_open();
-                        attributes::report_mpd_attrs(&tag);
-                    }
-                    b"Period" => {
-                        TagName::Period.report_tag_open();
-                        attributes::report_period_attrs(&tag);
-                    }
-                    b"AdaptationSet" => {
-                        TagName::AdaptationSet.report_tag_open();
-                        attributes::report_adaptation_set_attrs(&tag);
-                    }
-                    b"Representation" => {
-                        TagName::Representation.report_tag_open();
-                        attributes::report_representation_attrs(&tag);
-                    }
-                    b"Accessibility" => {
-                        TagName::Accessibility.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
-                    b"ContentComponent" => {
-                        TagName::ContentComponent.report_tag_open();
-                        attributes::report_content_component_attrs(&tag);
-                    }
-                    b"ContentProtection" => {
-                        TagName::ContentProtection.report_tag_open();
-                        attributes::report_content_protection_attrs(&tag);
-                    }
-                    b"EssentialProperty" => {
-                        TagName::EssentialProperty.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
-                    b"InbandEventStream" => {
-                        TagName::InbandEventStream.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
-                    b"Role" => {
-                        TagName::Role.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
-                    b"SupplementalProperty" => {
-                        TagName::SupplementalProperty.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
-                    b"SegmentBase" => {
-                        TagName::SegmentBase.report_tag_open();
-                        attributes::report_segment_base_attrs(&tag);
-                    }
-                    b"Initialization" => attributes::report_initialization_attrs(&tag),
-                    b"SegmentTemplate" => {
-                        TagName::SegmentTemplate.report_tag_open();
-                        attributes::report_segment_template_attrs(&tag);
-                    }
-                    b"SegmentList" => {
-                        TagName::SegmentList.report_tag_open();
+    /// Resolve the `onLoad` XLinks encountered through the given
+    /// `RemoteElementLoader`, replacing the elements referencing them by the
+    /// elements it loads.
+    ///
+    /// Without a `RemoteElementLoader`, XLinks are only reported as
+    /// attributes.
+    pub fn set_remote_element_loader<L: RemoteElementLoader + 'static>(&mut self, loader: L) {
+        self.state.loader = Some(Box::new(loader));
+    }
 
-                        // Re-use SegmentBase-one as it should not be different
-                        attributes::report_segment_base_attrs(&tag);
-                    }
-                    b"SegmentURL" => {
-                        TagName::SegmentUrl.report_tag_open();
-                        attributes::report_segment_url_attrs(&tag);
-                    }
-                    b"UTCTiming" => {
-                        TagName::UtcTiming.report_tag_open();
-                        attributes::report_scheme_attrs(&tag);
-                    }
+    /// Consumes this MPDProcessor, returning the `MpdEventSink` it was
+    /// reporting to.
+    pub fn into_sink(self) -> S {
+        self.state.sink.inner
+    }
 
-                    b"BaseURL" => {
-                        TagName::BaseURL.report_tag_open();
-                        attributes::report_base_url_attrs(&tag);
-                        self.process_base_url_element();
-                    }
-                    b"cenc:pssh" => self.process_cenc_element(),
-                    b"Location" => self.process_location_element(),
-                    b"Label" => self.process_label_element(),
-                    b"SegmentTimeline" => self.process_segment_timeline_element(),
-
-                    b"EventStream" => {
-                        TagName::EventStream.report_tag_open();
-                        attributes::report_event_stream_attrs(&tag);
-                        self.process_event_stream_element();
-                    }
+    /// Read and process the whole MPD document, until its end.
+    ///
+    /// In strict mode, the parsing stops at the first error, which is then
+    /// returned (after having been reported to the `MpdEventSink`).
+    /// Otherwise, this always returns `Ok`.
+    pub fn process_tags(&mut self) -> Result<()> {
+        process_events(&mut self.reader, &mut self.reader_buf, &mut self.state, 0);
+        self.state.process_eof(self.reader.buffer_position());
+        self.state.status()
+    }
+}
 
-                    _ => {}
-                },
-                Ok(Event::End(tag)) => match tag.name().as_ref() {
-                    b"MPD" => TagName::MPD.report_tag_close(),
-                    b"Period" => TagName::Period.report_tag_close(),
-                    b"AdaptationSet" => TagName::AdaptationSet.report_tag_close(),
-                    b"Representation" => TagName::Representation.report_tag_close(),
-                    b"Accessibility" => TagName::Accessibility.report_tag_close(),
-                    b"ContentComponent" => TagName::ContentComponent.report_tag_close(),
-                    b"ContentProtection" => TagName::ContentProtection.report_tag_close(),
-                    b"EssentialProperty" => TagName::EssentialProperty.report_tag_close(),
-                    b"InbandEventStream" => TagName::InbandEventStream.report_tag_close(),
-                    b"Role" => TagName::Role.report_tag_close(),
-                    b"SupplementalProperty" => TagName::SupplementalProperty.report_tag_close(),
-                    b"SegmentBase" => TagName::SegmentBase.report_tag_close(),
-                    b"SegmentList" => TagName::SegmentList.report_tag_close(),
-                    b"SegmentURL" => TagName::SegmentUrl.report_tag_close(),
-                    b"SegmentTemplate" => TagName::SegmentTemplate.report_tag_close(),
-                    b"UTCTiming" => TagName::UtcTiming.report_tag_close(),
-                    _ => {}
-                },
-                Ok(Event::Eof) => {
-                    break;
-                }
-                Err(e) => ParsingError::from(e).report_err(),
-                _ => (),
+/// Creates the quick-xml `Reader` used to browse the MPD, configured as the
+/// `ProcessorState` expects.
+fn create_xml_reader<R: BufRead>(reader: R) -> Reader<RawXmlRecorder<R>> {
+    let mut reader = Reader::from_reader(RawXmlRecorder::new(reader));
+    reader.expand_empty_elements(true);
+    reader.trim_text(true);
+    reader.check_end_names(false);
+    reader
+}
+
+/// Read all events from `reader` and process them with `state` until the
+/// reader's end is reached or the parsing is stopped.
+///
+/// `base_offset` is the position, in bytes in the whole MPD, of the first
+/// byte read by `reader`.
+fn process_events<R: BufRead, S: MpdEventSink>(
+    reader: &mut Reader<RawXmlRecorder<R>>,
+    reader_buf: &mut Vec<u8>,
+    state: &mut ProcessorState<S>,
+    base_offset: usize,
+) {
+    while !state.is_stopped() {
+        if !reader_buf.is_empty() {
+            reader_buf.clear();
+        }
+        let start_pos = base_offset + reader.buffer_position();
+        reader.get_mut().restart(state.needs_raw_xml());
+        match reader.read_event_into(reader_buf) {
+            Ok(Event::Eof) => break,
+            evt => {
+                let end_pos = base_offset + reader.buffer_position();
+                state.process_event(evt, start_pos, end_pos, reader.get_ref().recorded());
             }
         }
     }
+}
+
+/// Elements whose text content is reported as an attribute of their parent
+/// (or of themselves for the `<BaseURL>` and `<ContentSteering>` elements).
+#[derive(Clone, Copy)]
+enum TextElement {
+    Location,
+    Label,
+    BaseUrl,
+    CencPssh,
+    MsprPro,
+    MsprIsEncrypted,
+    DashIfLaUrl,
+    DashIfAuthzUrl,
+    ClearKeyLaUrl,
+    ContentSteering,
+}
+
+impl TextElement {
+    fn tag_name(self) -> &'static [u8] {
+        match self {
+            TextElement::Location => b"Location",
+            TextElement::Label => b"Label",
+            TextElement::BaseUrl => b"BaseURL",
+            TextElement::CencPssh => b"cenc:pssh",
+            TextElement::MsprPro => b"mspr:pro",
+            TextElement::MsprIsEncrypted => b"mspr:IsEncrypted",
+            TextElement::DashIfLaUrl => b"dashif:Laurl",
+            TextElement::DashIfAuthzUrl => b"dashif:Authzurl",
+            TextElement::ClearKeyLaUrl => b"clearkey:Laurl",
+            TextElement::ContentSteering => b"ContentSteering",
+        }
+    }
+
+    fn attribute_name(self) -> AttributeName {
+        match self {
+            TextElement::Location => AttributeName::Location,
+            TextElement::Label => AttributeName::Label,
+            TextElement::BaseUrl => AttributeName::Text,
+            TextElement::CencPssh => AttributeName::ContentProtectionCencPSSH,
+            TextElement::MsprPro => AttributeName::ContentProtectionPlayReadyObject,
+            TextElement::MsprIsEncrypted => AttributeName::ContentProtectionPlayReadyIsEncrypted,
+            TextElement::DashIfLaUrl => AttributeName::ContentProtectionDashIfLaUrl,
+            TextElement::DashIfAuthzUrl => AttributeName::ContentProtectionDashIfAuthzUrl,
+            TextElement::ClearKeyLaUrl => AttributeName::ContentProtectionClearKeyLaUrl,
+            TextElement::ContentSteering => AttributeName::Text,
+        }
+    }
+
+    fn eof_error(self) -> ParsingError {
+        let msg = match self {
+            TextElement::Location => "Unexpected end of file in a Location tag.",
+            TextElement::Label => "Unexpected end of file in a Label tag.",
+            TextElement::BaseUrl => "Unexpected end of file in a BaseURL.",
+            TextElement::CencPssh => "Unexpected end of file in a cenc:pssh tag.",
+            TextElement::MsprPro => "Unexpected end of file in a mspr:pro tag.",
+            TextElement::MsprIsEncrypted => "Unexpected end of file in a mspr:IsEncrypted tag.",
+            TextElement::DashIfLaUrl => "Unexpected end of file in a dashif:Laurl tag.",
+            TextElement::DashIfAuthzUrl => "Unexpected end of file in a dashif:Authzurl tag.",
+            TextElement::ClearKeyLaUrl => "Unexpected end of file in a clearkey:Laurl tag.",
+            TextElement::ContentSteering => "Unexpected end of file in a ContentSteering.",
+        };
+        ParsingError::new(ParsingErrorKind::UnexpectedEof, msg)
+    }
+}
+
+/// Element whose content is currently being specifically parsed.
+///
+/// The `nested` counters count inner elements with the same name if they
+/// exist, allowing to not close the current element when it is an inner one
+/// that is closed.
+enum InnerElement {
+    /// Elements are reported as they are encountered.
+    None,
+
+    /// Inside a `<SegmentTimeline>` element.
+    SegmentTimeline {
+        nested: u32,
 
-    /// Read the MPD document until an "Event" (@see quick-xml documentation)
-    /// is encountered.
+        /// Ending timestamp of the previous <S> element, starting at `0`.
+        /// Most subsequent <S> elements won't explicitly indicate a starting
+        /// timestamp which indicates that they start at the end of the previous
+        /// <S> element (its starting timestamp + its duration).
+        time_base: u64,
+    },
+
+    /// Inside an element whose text is reported as an attribute.
+    Text {
+        element: TextElement,
+        nested: u32,
+
+        /// For license server URL elements, their license type attribute,
+        /// reported with their text.
+        license_type: Option<String>,
+    },
+
+    /// Inside an `<EventStream>` element.
+    EventStream { nested: u32 },
+
+    /// Inside an element replaced through XLink, whose content is ignored.
+    RemoteElement { nested: u32 },
+
+    /// Inside an `<Event>` element from an `<EventStream>` element.
+    Event {
+        nested: u32,
+        /// `nested` counter of the parent `<EventStream>`.
+        event_stream_nested: u32,
+        /// Position in bytes, in the whole MPD, of the element's start.
+        start_pos: usize,
+        /// `true` if its `contentEncoding` is `base64`.
+        base64: bool,
+    },
+}
+
+/// State of the MPD parsing, persisting from one event to the next.
+///
+/// Events are processed one by one (instead of looping on the reader in each
+/// element-specific method) so the parsing can be interrupted and resumed at
+/// any point, for example when the MPD is parsed chunk by chunk.
+struct ProcessorState<S: MpdEventSink> {
+    sink: ContextSink<S>,
+    inner: InnerElement,
+    segment_objs_buf: Vec<SegmentObject>,
+
+    /// Only set in strict mode.
+    validator: Option<Validator>,
+
+    /// Only set when negative repeat counts are resolved.
+    timeline_bounds: Option<TimelineBounds>,
+
+    /// Only set when BaseURLs are resolved.
+    base_urls: Option<BaseUrlResolver>,
+
+    /// Only set when XLinks are resolved.
+    loader: Option<Box<dyn RemoteElementLoader>>,
+
+    /// Number of remote elements currently being parsed into each other.
+    xlink_depth: u32,
+
+    /// @see `ParsingOptions::document_url`
     ///
-    /// This method is always inlined for optimization reasons as it is both
-    /// short and generally used in loops.
-    #[inline(always)]
-    fn read_next_event(&mut self) -> quick_xml::Result<quick_xml::events::Event> {
-        if !self.reader_buf.is_empty() {
-            self.reader_buf.clear();
+    /// Replaced by the URL of the remote element being parsed while
+    /// resolving an XLink, as its own relative XLinks are relative to it.
+    document_url: Option<String>,
+
+    /// `<ContentProtection>` elements which may be referenced by others.
+    content_protection_refs: ContentProtectionRefs,
+
+    /// Elements which may be combined by `<Preselection>` elements.
+    preselection_components: PreselectionComponents,
+
+    /// Raw XML of the `<Event>` element being read, as read until now (@see
+    /// `AttributeName::EventStreamEltXml`).
+    event_xml: Vec<u8>,
+
+    /// Text of the `<Event>` element being read, only when its content is
+    /// base64-encoded.
+    event_text: Vec<u8>,
+}
+
+impl<S: MpdEventSink> ProcessorState<S> {
+    fn new(sink: S, options: ParsingOptions) -> Self {
+        ProcessorState {
+            sink: ContextSink::new(sink, options.strict),
+            inner: InnerElement::None,
+            segment_objs_buf: Vec::new(),
+            validator: if options.strict {
+                Some(Validator::default())
+            } else {
+                None
+            },
+            timeline_bounds: if options.resolve_repeat_counts {
+                Some(TimelineBounds::new(options.period_durations.clone()))
+            } else {
+                None
+            },
+            base_urls: if options.resolve_base_urls {
+                Some(BaseUrlResolver::new(options.document_url.clone()))
+            } else {
+                None
+            },
+            loader: None,
+            xlink_depth: 0,
+            document_url: options.document_url,
+            content_protection_refs: ContentProtectionRefs::default(),
+            preselection_components: PreselectionComponents::default(),
+            event_xml: Vec::new(),
+            event_text: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the parsing has been stopped because of an error in
+    /// strict mode, in which case no more event should be processed.
+    fn is_stopped(&self) -> bool {
+        self.sink.failure().is_some()
+    }
+
+    /// Returns the error which stopped the parsing, if any.
+    fn status(&self) -> Result<()> {
+        match self.sink.failure() {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
         }
-        self.reader.read_event_into(&mut self.reader_buf)
     }
 
-    /// Loop over a SegmentTimeline's children (to call when a <SegmentTimeline>
-    /// node just has been found).
+    /// Returns `true` if the raw XML of the next event should be given to
+    /// `process_event`, which is only the case inside an `<EventStream>`.
+    fn needs_raw_xml(&self) -> bool {
+        matches!(
+            self.inner,
+            InnerElement::EventStream { .. } | InnerElement::Event { .. }
+        )
+    }
+
+    /// Process a single event read from the MPD.
     ///
-    /// Report its children tag and attributes until either its corresponding
-    /// closing SegmentTemplate tag has been found or until EOF is encountered.
-    fn process_segment_timeline_element(&mut self) {
-        // Count inner SegmentTimeline tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag: u32 = 0;
-
-        // Will store the ending timestamp of the previous <S> element, starting
-        // at `0`.
-        // Most subsequent <S> elements won't explicitly indicate a starting
-        // timestamp which indicates that they start at the end of the previous
-        // <S> element (its starting timestamp + its duration).
-        let mut curr_time_base: f64 = 0.;
-
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) if tag.name().as_ref() == b"S" => {
-                    match SegmentObject::from_s_element(&tag, curr_time_base) {
-                        Ok(segment_obj) => {
-                            if segment_obj.repeat_count == 0. {
-                                curr_time_base = segment_obj.start + segment_obj.duration;
-                            } else {
-                                let duration =
-                                    segment_obj.duration * (segment_obj.repeat_count + 1.);
-                                curr_time_base = segment_obj.start + duration;
-                            }
-                            self.segment_objs_buf.push(segment_obj);
-                        }
-                        Err(err) => err.report_err(),
+    /// `start_pos` and `end_pos` are the positions in bytes in the whole MPD
+    /// respectively before and after that event was read, and `raw` its raw
+    /// XML if `needs_raw_xml` returned `true` before it was read.
+    fn process_event(
+        &mut self,
+        evt: quick_xml::Result<Event>,
+        start_pos: usize,
+        end_pos: usize,
+        raw: &[u8],
+    ) {
+        self.sink.set_offset(start_pos);
+        if let InnerElement::RemoteElement { .. } = self.inner {
+            self.process_remote_element_event(evt);
+            return;
+        }
+        if let (InnerElement::None, Ok(Event::Start(tag))) = (&self.inner, &evt) {
+            if self.try_resolve_xlink(tag, start_pos) {
+                return;
+            }
+        }
+        if let Ok(evt) = &evt {
+            self.content_protection_refs.record(evt);
+        }
+        let closes_element = match &evt {
+            Ok(Event::Start(tag)) => {
+                self.sink.enter(tag);
+                false
+            }
+            Ok(Event::Empty(tag)) => {
+                self.sink.enter(tag);
+                true
+            }
+            Ok(Event::End(_)) => true,
+            _ => false,
+        };
+        if let Some(validator) = self.validator.as_mut() {
+            let validation = match &evt {
+                Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => validator.validate_start(tag),
+                _ => Ok(()),
+            };
+            if let Err(err) = validation {
+                err.report_err(&mut self.sink);
+                return;
+            }
+            match &evt {
+                Ok(Event::Empty(tag)) => validator.validate_end(tag.name().as_ref()),
+                Ok(Event::End(tag)) => validator.validate_end(tag.name().as_ref()),
+                _ => {}
+            }
+        }
+        if let Some(bounds) = self.timeline_bounds.as_mut() {
+            match &evt {
+                Ok(Event::Start(tag)) => bounds.enter(tag),
+                Ok(Event::End(tag)) => bounds.leave(tag.name().as_ref()),
+                _ => {}
+            }
+        }
+        if let Some(resolver) = self.base_urls.as_mut() {
+            match (&self.inner, &evt) {
+                (InnerElement::None, Ok(Event::Start(tag))) => resolver.enter(tag),
+                (InnerElement::Text { .. }, Ok(Event::Text(text))) => {
+                    if let Ok(text) = text.unescape() {
+                        resolver.text(&text);
                     }
                 }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"SegmentTimeline" => {
-                    inner_tag += 1
+                (InnerElement::Text { .. }, Ok(Event::CData(text))) => {
+                    resolver.text(&String::from_utf8_lossy(text));
                 }
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"SegmentTimeline" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        AttributeName::SegmentTimeline.report(self.segment_objs_buf.as_slice());
-                        break;
+                (InnerElement::None, Ok(Event::End(tag)))
+                | (InnerElement::Text { .. }, Ok(Event::End(tag))) => {
+                    for base_url in resolver.leave(tag.name().as_ref()).unwrap_or_default() {
+                        let service_location = base_url.service_location.as_deref().unwrap_or("");
+                        AttributeName::ResolvedBaseUrl.report(
+                            AttrValue::KeyValue(&base_url.url, service_location),
+                            &mut self.sink,
+                        );
                     }
                 }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a SegmentTimeline.".to_owned())
-                        .report_err();
-                    break;
-                }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
+                _ => {}
+            }
+        }
+        match self.inner {
+            InnerElement::None => self.process_top_level_event(evt, start_pos),
+            InnerElement::SegmentTimeline { .. } => self.process_segment_timeline_event(evt),
+            InnerElement::Text { element, .. } => self.process_text_element_event(evt, element),
+            InnerElement::EventStream { .. } => {
+                self.process_event_stream_event(evt, start_pos, raw)
+            }
+            InnerElement::Event { .. } => self.process_event_stream_elt_event(evt, end_pos, raw),
+            InnerElement::RemoteElement { .. } => {}
+        }
+        if closes_element {
+            self.sink.leave();
+        }
+    }
+
+    /// Signal that the end of the MPD, whose size in bytes is `end_pos`, has
+    /// been reached.
+    fn process_eof(&mut self, end_pos: usize) {
+        if self.is_stopped() {
+            return;
+        }
+        self.sink.set_offset(end_pos);
+        let err = match self.inner {
+            InnerElement::None => return,
+            InnerElement::SegmentTimeline { .. } => {
+                self.segment_objs_buf.clear();
+                ParsingError::new(
+                    ParsingErrorKind::UnexpectedEof,
+                    "Unexpected end of file in a SegmentTimeline.",
+                )
+            }
+            InnerElement::Text { element, .. } => element.eof_error(),
+            InnerElement::RemoteElement { .. } => ParsingError::new(
+                ParsingErrorKind::UnexpectedEof,
+                "Unexpected end of file in an element replaced through XLink.",
+            ),
+            InnerElement::EventStream { .. } => ParsingError::new(
+                ParsingErrorKind::UnexpectedEof,
+                "Unexpected end of file in a EventStream.",
+            ),
+            InnerElement::Event { .. } => {
+                ParsingError::new(
+                    ParsingErrorKind::UnexpectedEof,
+                    "Unexpected end of file in an Event element.",
+                )
+                .report_err(&mut self.sink);
+                TagName::EventStreamElt.report_tag_close(&mut self.sink);
+                ParsingError::new(
+                    ParsingErrorKind::UnexpectedEof,
+                    "Unexpected end of file in a EventStream.",
+                )
+            }
+        };
+        self.inner = InnerElement::None;
+        err.report_err(&mut self.sink);
+    }
+
+    /// Check if the element just opened has to be replaced through XLink
+    /// and, if so, report the elements of the remote element in its place
+    /// (or nothing if it resolves to zero).
+    ///
+    /// Returns `true` if the element has been replaced, in which case its
+    /// content has to be ignored.
+    fn try_resolve_xlink(&mut self, tag: &BytesStart, start_pos: usize) -> bool {
+        if self.loader.is_none() || !XLINK_ELEMENTS.contains(&tag.name().as_ref()) {
+            return false;
+        }
+        let mut href = None;
+        let mut actuate = XLinkActuate::OnRequest;
+        for attr in tag.attributes().flatten() {
+            match attr.key.as_ref() {
+                b"xlink:href" => href = attr.unescape_value().ok(),
+                b"xlink:actuate" => actuate = XLinkActuate::from_attr_value(&attr.value),
+                _ => {}
+            }
+        }
+        let href = match href {
+            Some(href) if actuate == XLinkActuate::OnLoad => href,
+            _ => return false,
+        };
+        if href == RESOLVE_TO_ZERO {
+            self.inner = InnerElement::RemoteElement { nested: 0 };
+            return true;
+        }
+
+        let url = match &self.document_url {
+            Some(document_url) => resolve_url(document_url, &href),
+            None => href.into_owned(),
+        };
+        let data = if self.xlink_depth >= MAX_XLINK_DEPTH {
+            Err(ParsingError::new(
+                ParsingErrorKind::InvalidXLink,
+                format!("Too many nested XLinks, not resolving {}.", url),
+            ))
+        } else {
+            // `loader` has been checked to be set above
+            self.loader.as_mut().unwrap().load(&url)
+        };
+        let data = match data {
+            Ok(data) => data,
+            Err(err) => {
+                self.sink.enter(tag);
+                err.in_attribute(b"xlink:href").report_err(&mut self.sink);
+                self.sink.leave();
+                return false;
+            }
+        };
+
+        // Offsets of the remote element's events are those of the element
+        // it replaces, as they are not part of the MPD.
+        let mut reader = create_xml_reader(&data[..]);
+        let mut reader_buf = Vec::new();
+        let parent_url = self.document_url.replace(url);
+        self.xlink_depth += 1;
+        process_events(&mut reader, &mut reader_buf, self, start_pos);
+        self.xlink_depth -= 1;
+        self.document_url = parent_url;
+        if !self.is_stopped() {
+            self.process_eof(start_pos);
+        }
+        self.inner = InnerElement::RemoteElement { nested: 0 };
+        true
+    }
+
+    /// Process an event encountered in an element replaced through XLink,
+    /// ignoring it until the element's end.
+    fn process_remote_element_event(&mut self, evt: quick_xml::Result<Event>) {
+        let nested = match &mut self.inner {
+            InnerElement::RemoteElement { nested } => nested,
+            _ => return,
+        };
+        match evt {
+            Ok(Event::Start(_)) => *nested += 1,
+            Ok(Event::End(_)) => {
+                if *nested > 0 {
+                    *nested -= 1;
+                } else {
+                    self.inner = InnerElement::None;
                 }
-                _ => (),
             }
+            Err(e) => {
+                ParsingError::from(e).report_err(&mut self.sink);
+                self.inner = InnerElement::None;
+            }
+            _ => (),
         }
-        self.segment_objs_buf.clear();
     }
 
-    fn process_location_element(&mut self) {
-        // Count inner Location tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag: u32 = 0;
+    /// Process an event encountered outside of any specifically parsed
+    /// element.
+    ///
+    /// `start_pos` is the position in bytes in the whole MPD just before that
+    /// event.
+    fn process_top_level_event(&mut self, evt: quick_xml::Result<Event>, start_pos: usize) {
+        match evt {
+            Ok(Event::Start(tag)) => match tag.name().as_ref() {
+                b"MPD" => {
+                    TagName::MPD.report_tag_open(&mut self.sink);
+                    attributes::report_mpd_attrs(&tag, &mut self.sink);
+                }
+                b"Period" => {
+                    TagName::Period.report_tag_open(&mut self.sink);
+                    attributes::report_period_attrs(&tag, &mut self.sink);
+                    self.preselection_components.enter_period();
+                }
+                b"AdaptationSet" => {
+                    TagName::AdaptationSet.report_tag_open(&mut self.sink);
+                    attributes::report_adaptation_set_attrs(&tag, &mut self.sink);
+                    self.preselection_components.enter_adaptation_set(&tag);
+                }
+                b"Representation" => {
+                    TagName::Representation.report_tag_open(&mut self.sink);
+                    attributes::report_representation_attrs(&tag, &mut self.sink);
+                }
+                b"Accessibility" => {
+                    TagName::Accessibility.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"ContentComponent" => {
+                    TagName::ContentComponent.report_tag_open(&mut self.sink);
+                    attributes::report_content_component_attrs(&tag, &mut self.sink);
+                    self.preselection_components.enter_content_component(&tag);
+                }
+                b"ContentProtection" => {
+                    TagName::ContentProtection.report_tag_open(&mut self.sink);
+                    self.process_content_protection(&tag, start_pos);
+                }
+                b"EssentialProperty" => {
+                    TagName::EssentialProperty.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"InbandEventStream" => {
+                    TagName::InbandEventStream.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"Role" => {
+                    TagName::Role.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"SupplementalProperty" => {
+                    TagName::SupplementalProperty.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"AudioChannelConfiguration" => {
+                    TagName::AudioChannelConfiguration.report_tag_open(&mut self.sink);
+                    attributes::report_audio_channel_configuration_attrs(&tag, &mut self.sink);
+                }
+                b"ProducerReferenceTime" => {
+                    TagName::ProducerReferenceTime.report_tag_open(&mut self.sink);
+                    attributes::report_producer_reference_time_attrs(&tag, &mut self.sink);
+                }
+                b"Resync" => {
+                    TagName::Resync.report_tag_open(&mut self.sink);
+                    attributes::report_resync_attrs(&tag, &mut self.sink);
+                }
+                b"Preselection" => {
+                    TagName::Preselection.report_tag_open(&mut self.sink);
+                    attributes::report_preselection_attrs(&tag, &mut self.sink);
+                    self.report_preselection_components(&tag);
+                }
+                b"ServiceDescription" => {
+                    TagName::ServiceDescription.report_tag_open(&mut self.sink);
+                    attributes::report_service_description_attrs(&tag, &mut self.sink);
+                }
+                b"Scope" => {
+                    TagName::Scope.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
+                b"Latency" => {
+                    TagName::Latency.report_tag_open(&mut self.sink);
+                    attributes::report_latency_attrs(&tag, &mut self.sink);
+                }
+                b"PlaybackRate" => {
+                    TagName::PlaybackRate.report_tag_open(&mut self.sink);
+                    attributes::report_playback_rate_attrs(&tag, &mut self.sink);
+                }
+                b"OperatingQuality" => {
+                    TagName::OperatingQuality.report_tag_open(&mut self.sink);
+                    attributes::report_operating_attrs(&tag, &mut self.sink);
+                }
+                b"OperatingBandwidth" => {
+                    TagName::OperatingBandwidth.report_tag_open(&mut self.sink);
+                    attributes::report_operating_attrs(&tag, &mut self.sink);
+                }
+                b"SegmentBase" => {
+                    TagName::SegmentBase.report_tag_open(&mut self.sink);
+                    attributes::report_segment_base_attrs(&tag, &mut self.sink);
+                }
+                b"Initialization" => attributes::report_initialization_attrs(&tag, &mut self.sink),
+                b"SegmentTemplate" => {
+                    TagName::SegmentTemplate.report_tag_open(&mut self.sink);
+                    attributes::report_segment_template_attrs(&tag, &mut self.sink);
+                }
+                b"SegmentList" => {
+                    TagName::SegmentList.report_tag_open(&mut self.sink);
+                    attributes::report_segment_list_attrs(&tag, &mut self.sink);
+                }
+                b"SegmentURL" => {
+                    TagName::SegmentUrl.report_tag_open(&mut self.sink);
+                    attributes::report_segment_url_attrs(&tag, &mut self.sink);
+                }
+                b"UTCTiming" => {
+                    TagName::UtcTiming.report_tag_open(&mut self.sink);
+                    attributes::report_scheme_attrs(&tag, &mut self.sink);
+                }
 
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Text(t)) => {
-                    if t.len() > 0 {
-                        match t.unescape() {
-                            Ok(unescaped) => AttributeName::Location.report(unescaped),
-                            Err(err) => ParsingError::from(err).report_err(),
-                        }
-                    }
+                b"BaseURL" => {
+                    TagName::BaseURL.report_tag_open(&mut self.sink);
+                    attributes::report_base_url_attrs(&tag, &mut self.sink);
+                    self.start_text_element(TextElement::BaseUrl);
                 }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"Location" => inner_tag += 1,
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"Location" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        break;
-                    }
+                b"ContentSteering" => {
+                    TagName::ContentSteering.report_tag_open(&mut self.sink);
+                    attributes::report_content_steering_attrs(&tag, &mut self.sink);
+                    self.start_text_element(TextElement::ContentSteering);
                 }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a Location tag.".to_owned())
-                        .report_err();
-                    break;
+                b"cenc:pssh" => self.start_text_element(TextElement::CencPssh),
+                b"mspr:pro" => self.start_text_element(TextElement::MsprPro),
+                b"mspr:IsEncrypted" => self.start_text_element(TextElement::MsprIsEncrypted),
+                b"dashif:Laurl" => {
+                    self.start_license_url_element(TextElement::DashIfLaUrl, &tag, b"licenseType")
                 }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
+                b"dashif:Authzurl" => self.start_license_url_element(
+                    TextElement::DashIfAuthzUrl,
+                    &tag,
+                    b"licenseType",
+                ),
+                b"clearkey:Laurl" => {
+                    self.start_license_url_element(TextElement::ClearKeyLaUrl, &tag, b"Lic_type")
+                }
+                b"Location" => self.start_text_element(TextElement::Location),
+                b"Label" => self.start_text_element(TextElement::Label),
+                b"SegmentTimeline" => {
+                    self.inner = InnerElement::SegmentTimeline {
+                        nested: 0,
+                        time_base: 0,
+                    };
                 }
-                _ => (),
-            }
-            self.reader_buf.clear();
-        }
-    }
 
-    fn process_label_element(&mut self) {
-        // Count inner Label tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag: u32 = 0;
+                b"EventStream" => {
+                    TagName::EventStream.report_tag_open(&mut self.sink);
+                    attributes::report_event_stream_attrs(&tag, &mut self.sink);
+                    self.inner = InnerElement::EventStream { nested: 0 };
+                }
 
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Text(t)) => {
-                    if t.len() > 0 {
-                        match t.unescape() {
-                            Ok(unescaped) => AttributeName::Label.report(unescaped),
-                            Err(err) => ParsingError::from(err).report_err(),
-                        }
-                    }
+                _ => {}
+            },
+            Ok(Event::End(tag)) => match tag.name().as_ref() {
+                b"MPD" => TagName::MPD.report_tag_close(&mut self.sink),
+                b"Period" => TagName::Period.report_tag_close(&mut self.sink),
+                b"AdaptationSet" => TagName::AdaptationSet.report_tag_close(&mut self.sink),
+                b"Representation" => TagName::Representation.report_tag_close(&mut self.sink),
+                b"Accessibility" => TagName::Accessibility.report_tag_close(&mut self.sink),
+                b"ContentComponent" => TagName::ContentComponent.report_tag_close(&mut self.sink),
+                b"ContentProtection" => TagName::ContentProtection.report_tag_close(&mut self.sink),
+                b"EssentialProperty" => TagName::EssentialProperty.report_tag_close(&mut self.sink),
+                b"InbandEventStream" => TagName::InbandEventStream.report_tag_close(&mut self.sink),
+                b"Role" => TagName::Role.report_tag_close(&mut self.sink),
+                b"SupplementalProperty" => {
+                    TagName::SupplementalProperty.report_tag_close(&mut self.sink)
                 }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"Label" => inner_tag += 1,
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"Label" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        break;
-                    }
+                b"AudioChannelConfiguration" => {
+                    TagName::AudioChannelConfiguration.report_tag_close(&mut self.sink)
                 }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a Label tag.".to_owned()).report_err();
-                    break;
+                b"ProducerReferenceTime" => {
+                    TagName::ProducerReferenceTime.report_tag_close(&mut self.sink)
                 }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
+                b"Resync" => TagName::Resync.report_tag_close(&mut self.sink),
+                b"Preselection" => TagName::Preselection.report_tag_close(&mut self.sink),
+                b"ServiceDescription" => {
+                    TagName::ServiceDescription.report_tag_close(&mut self.sink)
                 }
-                _ => (),
-            }
-            self.reader_buf.clear();
+                b"Scope" => TagName::Scope.report_tag_close(&mut self.sink),
+                b"Latency" => TagName::Latency.report_tag_close(&mut self.sink),
+                b"PlaybackRate" => TagName::PlaybackRate.report_tag_close(&mut self.sink),
+                b"OperatingQuality" => TagName::OperatingQuality.report_tag_close(&mut self.sink),
+                b"OperatingBandwidth" => {
+                    TagName::OperatingBandwidth.report_tag_close(&mut self.sink)
+                }
+                b"SegmentBase" => TagName::SegmentBase.report_tag_close(&mut self.sink),
+                b"SegmentList" => TagName::SegmentList.report_tag_close(&mut self.sink),
+                b"SegmentURL" => TagName::SegmentUrl.report_tag_close(&mut self.sink),
+                b"SegmentTemplate" => TagName::SegmentTemplate.report_tag_close(&mut self.sink),
+                b"UTCTiming" => TagName::UtcTiming.report_tag_close(&mut self.sink),
+                _ => {}
+            },
+            Err(e) => ParsingError::from(e).report_err(&mut self.sink),
+            _ => (),
         }
     }
 
-    fn process_base_url_element(&mut self) {
-        // Count inner BaseURL tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag: u32 = 0;
-
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Text(t)) => {
-                    if t.len() > 0 {
-                        match t.unescape() {
-                            Ok(unescaped) => AttributeName::Text.report(unescaped),
-                            Err(err) => ParsingError::from(err).report_err(),
+    /// Process an event encountered in a SegmentTimeline (to call when a
+    /// <SegmentTimeline> node has been found and not closed yet).
+    ///
+    /// Its <S> children are accumulated until its corresponding closing
+    /// SegmentTimeline tag has been found, at which point they are all
+    /// reported at once.
+    fn process_segment_timeline_event(&mut self, evt: quick_xml::Result<Event>) {
+        let (nested, time_base) = match &mut self.inner {
+            InnerElement::SegmentTimeline { nested, time_base } => (nested, time_base),
+            _ => return,
+        };
+        match evt {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) if tag.name().as_ref() == b"S" => {
+                match SegmentObject::from_s_element(&tag, *time_base, &mut self.sink) {
+                    Ok(segment_obj) => {
+                        if let Some(previous) = self.segment_objs_buf.last() {
+                            if let Err(err) = previous.check_continuity(&segment_obj) {
+                                err.in_attribute(b"t").report_err(&mut self.sink);
+                            }
                         }
+                        *time_base = segment_obj.end();
+                        self.segment_objs_buf.push(segment_obj);
                     }
+                    Err(err) => err.report_err(&mut self.sink),
                 }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"BaseURL" => inner_tag += 1,
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"BaseURL" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        TagName::BaseURL.report_tag_close();
-                        break;
+            }
+            Ok(Event::Start(tag)) if tag.name().as_ref() == b"SegmentTimeline" => *nested += 1,
+            Ok(Event::End(tag)) if tag.name().as_ref() == b"SegmentTimeline" => {
+                if *nested > 0 {
+                    *nested -= 1;
+                } else {
+                    if let Some(bounds) = &self.timeline_bounds {
+                        SegmentObject::resolve_repeat_counts(
+                            &mut self.segment_objs_buf,
+                            bounds.timeline_end(),
+                        );
                     }
+                    AttributeName::SegmentTimeline
+                        .report(self.segment_objs_buf.as_slice(), &mut self.sink);
+                    self.segment_objs_buf.clear();
+                    self.inner = InnerElement::None;
                 }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a BaseURL.".to_owned()).report_err();
-                    break;
-                }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
+            }
+            Err(e) => {
+                ParsingError::from(e).report_err(&mut self.sink);
+                self.segment_objs_buf.clear();
+                self.inner = InnerElement::None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Report the attributes of a `<ContentProtection>` element which has
+    /// just been opened at the position `start_pos`.
+    ///
+    /// If it references another one through its `ref` attribute, the
+    /// attributes and children of that other one are reported as well, as if
+    /// they were part of it.
+    fn process_content_protection(&mut self, tag: &BytesStart, start_pos: usize) {
+        match self.content_protection_refs.resolve(tag) {
+            Ok(Some((merged, children))) => {
+                attributes::report_content_protection_attrs(&merged, &mut self.sink);
+                // As for XLinks, offsets of the copied events are those of
+                // the referencing element.
+                for child in children {
+                    self.process_event(Ok(child), start_pos, start_pos, &[]);
                 }
-                _ => (),
             }
-            self.reader_buf.clear();
+            Ok(None) => {
+                attributes::report_content_protection_attrs(tag, &mut self.sink);
+                self.content_protection_refs.start_definition(tag);
+            }
+            Err(err) => {
+                err.report_err(&mut self.sink);
+                attributes::report_content_protection_attrs(tag, &mut self.sink);
+            }
         }
     }
 
-    fn process_cenc_element(&mut self) {
-        // Count inner cenc:pssh tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag: u32 = 0;
+    /// Report the `<AdaptationSet>` and `<ContentComponent>` elements the
+    /// given `<Preselection>` element combines, as listed in its
+    /// `preselectionComponents` attribute.
+    fn report_preselection_components(&mut self, tag: &BytesStart) {
+        let value = match tag.try_get_attribute(b"preselectionComponents") {
+            Ok(Some(attr)) => attr.value,
+            _ => return,
+        };
+        let (components, res) = self.preselection_components.resolve(&value);
+        for component in components {
+            let adaptation_set_id = String::from_utf8_lossy(component.adaptation_set_id);
+            let content_component_id =
+                String::from_utf8_lossy(component.content_component_id.unwrap_or_default());
+            AttributeName::PreselectionComponent.report(
+                AttrValue::KeyValue(&adaptation_set_id, &content_component_id),
+                &mut self.sink,
+            );
+        }
+        if let Err(err) = res {
+            err.report_err(&mut self.sink);
+        }
+    }
 
-        loop {
-            match self.read_next_event() {
-                Ok(Event::Text(t)) => {
-                    if t.len() > 0 {
-                        match t.unescape() {
-                            Ok(unescaped) =>
-                            // TODO parse from base64 here?
-                            {
-                                AttributeName::ContentProtectionCencPSSH.report(unescaped)
-                            }
-                            Err(err) => ParsingError::from(err).report_err(),
-                        }
-                    }
+    fn start_text_element(&mut self, element: TextElement) {
+        self.inner = InnerElement::Text {
+            element,
+            nested: 0,
+            license_type: None,
+        };
+    }
+
+    /// Start an element whose text is a license server URL, reported with
+    /// its `license_type_attr` attribute.
+    fn start_license_url_element(
+        &mut self,
+        element: TextElement,
+        tag: &BytesStart,
+        license_type_attr: &[u8],
+    ) {
+        let license_type = match tag.try_get_attribute(license_type_attr) {
+            Ok(Some(attr)) => match attr.unescape_value() {
+                Ok(val) => Some(val.into_owned()),
+                Err(err) => {
+                    ParsingError::from(err)
+                        .in_attribute(license_type_attr)
+                        .report_err(&mut self.sink);
+                    None
                 }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"cenc:pssh" => inner_tag += 1,
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"cenc:pssh" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        break;
+            },
+            Ok(None) => None,
+            Err(err) => {
+                ParsingError::from(err).report_err(&mut self.sink);
+                None
+            }
+        };
+        self.inner = InnerElement::Text {
+            element,
+            nested: 0,
+            license_type,
+        };
+    }
+
+    /// Process an event encountered in an element whose text content is
+    /// reported as an attribute, until its corresponding closing tag is found.
+    fn process_text_element_event(&mut self, evt: quick_xml::Result<Event>, element: TextElement) {
+        let (nested, license_type) = match &mut self.inner {
+            InnerElement::Text {
+                nested,
+                license_type,
+                ..
+            } => (nested, license_type),
+            _ => return,
+        };
+        match evt {
+            Ok(Event::Text(t)) if !t.is_empty() => match t.unescape() {
+                Ok(unescaped) => match element {
+                    TextElement::CencPssh => {
+                        attributes::report_cenc_pssh(unescaped.as_bytes(), &mut self.sink)
                     }
-                }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a cenc:pssh tag.".to_owned())
-                        .report_err();
-                    break;
-                }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
-                }
-                _ => (),
-            }
-            self.reader_buf.clear();
-        }
-    }
-
-    fn process_event_stream_element(&mut self) {
-        // Count inner EventStream tags if it exists.
-        // Allowing to not close the current node when it is an inner that is closed
-        let mut inner_tag = 0u32;
-
-        loop {
-            // We need to keep the XML as-is in the JS-side when it comes to
-            // EventStream's `<Event> elements, as this is part of its public API.
-            //
-            // That means that we have to communicate in some way this exact data.
-            // Sadly, quick_xml doesn't seem to have corresponding APIs that would
-            // make this easy.
-            // In the meantime, we will just return the first and last position
-            // in bytes of `<Event>` elements (by recording the position just before
-            // it's opening tag is encountered and just after the closing one is).
-            // It will then be up to the JS-side to slice and decode the
-            // corresponding XML.
-            let initial_buffer_pos = self.reader.buffer_position();
-
-            let evt = self.read_next_event();
-            match evt {
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"Event" => {
-                    TagName::EventStreamElt.report_tag_open();
-                    attributes::report_event_stream_event_attrs(&tag);
-                    match self.get_event_stream_event_ending_position() {
-                        Ok(ending_pos) => {
-                            AttributeName::EventStreamEltRange
-                                .report((initial_buffer_pos as f64, ending_pos as f64));
-                        }
-                        Err(e) => e.report_err(),
+                    TextElement::MsprPro => {
+                        attributes::report_playready_object(unescaped.as_bytes(), &mut self.sink)
                     }
-                    TagName::EventStreamElt.report_tag_close();
-                }
-                Ok(Event::Empty(tag)) if tag.name().as_ref() == b"Event" => {
-                    TagName::EventStreamElt.report_tag_open();
-                    attributes::report_event_stream_event_attrs(&tag);
-                    let curr_pos = self.reader.buffer_position();
-                    AttributeName::EventStreamEltRange
-                        .report((initial_buffer_pos as f64, curr_pos as f64));
-                    TagName::EventStreamElt.report_tag_close();
-                }
-                Ok(Event::Start(tag)) if tag.name().as_ref() == b"EventStream" => inner_tag += 1,
-                Ok(Event::End(tag)) if tag.name().as_ref() == b"EventStream" => {
-                    if inner_tag > 0 {
-                        inner_tag -= 1;
-                    } else {
-                        TagName::EventStream.report_tag_close();
-                        break;
+                    TextElement::MsprIsEncrypted => attributes::report_playready_is_encrypted(
+                        unescaped.as_bytes(),
+                        &mut self.sink,
+                    ),
+                    TextElement::DashIfLaUrl
+                    | TextElement::DashIfAuthzUrl
+                    | TextElement::ClearKeyLaUrl => element.attribute_name().report(
+                        AttrValue::KeyValue(&unescaped, license_type.as_deref().unwrap_or("")),
+                        &mut self.sink,
+                    ),
+                    _ => element.attribute_name().report(&*unescaped, &mut self.sink),
+                },
+                Err(err) => ParsingError::from(err).report_err(&mut self.sink),
+            },
+            Ok(Event::Start(tag)) if tag.name().as_ref() == element.tag_name() => *nested += 1,
+            Ok(Event::End(tag)) if tag.name().as_ref() == element.tag_name() => {
+                if *nested > 0 {
+                    *nested -= 1;
+                } else {
+                    match element {
+                        TextElement::BaseUrl => TagName::BaseURL.report_tag_close(&mut self.sink),
+                        TextElement::ContentSteering => {
+                            TagName::ContentSteering.report_tag_close(&mut self.sink)
+                        }
+                        _ => {}
                     }
+                    self.inner = InnerElement::None;
                 }
-                Ok(Event::Eof) => {
-                    ParsingError("Unexpected end of file in a EventStream.".to_owned())
-                        .report_err();
-                    break;
+            }
+            Err(e) => {
+                ParsingError::from(e).report_err(&mut self.sink);
+                self.inner = InnerElement::None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Process an event encountered in an EventStream, until its corresponding
+    /// closing tag is found.
+    ///
+    /// `start_pos` is the position in bytes in the whole MPD before that event
+    /// was read, and `raw` its raw XML.
+    fn process_event_stream_event(
+        &mut self,
+        evt: quick_xml::Result<Event>,
+        start_pos: usize,
+        raw: &[u8],
+    ) {
+        let nested = match &mut self.inner {
+            InnerElement::EventStream { nested } => nested,
+            _ => return,
+        };
+
+        // We need to keep the XML as-is in the JS-side when it comes to
+        // EventStream's `<Event> elements, as this is part of its public API.
+        //
+        // The raw XML of the events read in it is thus kept, and reported
+        // once the element is closed. The position in bytes of `<Event>`
+        // elements in the MPD is also reported, for Rust consumers.
+        match evt {
+            Ok(Event::Start(tag)) if tag.name().as_ref() == b"Event" => {
+                TagName::EventStreamElt.report_tag_open(&mut self.sink);
+                attributes::report_event_stream_event_attrs(&tag, &mut self.sink);
+                let base64 = is_base64_encoded(&tag);
+                self.event_xml.clear();
+                self.event_text.clear();
+                // Skip the whitespace read before the tag
+                let tag_start = raw.iter().position(|c| !c.is_ascii_whitespace());
+                self.event_xml
+                    .extend_from_slice(&raw[tag_start.unwrap_or(raw.len())..]);
+                self.inner = InnerElement::Event {
+                    nested: 0,
+                    event_stream_nested: *nested,
+                    start_pos,
+                    base64,
+                };
+            }
+            Ok(Event::Start(tag)) if tag.name().as_ref() == b"EventStream" => *nested += 1,
+            Ok(Event::End(tag)) if tag.name().as_ref() == b"EventStream" => {
+                if *nested > 0 {
+                    *nested -= 1;
+                } else {
+                    TagName::EventStream.report_tag_close(&mut self.sink);
+                    self.inner = InnerElement::None;
                 }
-                Err(e) => {
-                    ParsingError::from(e).report_err();
-                    break;
+            }
+            Err(e) => {
+                ParsingError::from(e).report_err(&mut self.sink);
+                self.inner = InnerElement::None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Process an event encountered in an `<Event>` element, until its
+    /// corresponding closing tag is found.
+    ///
+    /// `end_pos` is the position in bytes in the whole MPD just after that
+    /// event, and `raw` its raw XML.
+    fn process_event_stream_elt_event(
+        &mut self,
+        evt: quick_xml::Result<Event>,
+        end_pos: usize,
+        raw: &[u8],
+    ) {
+        let (nested, event_stream_nested, start_pos, base64) = match &mut self.inner {
+            InnerElement::Event {
+                nested,
+                event_stream_nested,
+                start_pos,
+                base64,
+            } => (nested, *event_stream_nested, *start_pos, *base64),
+            _ => return,
+        };
+        let evt = match evt {
+            Ok(evt) => evt,
+            Err(e) => {
+                ParsingError::from(e).report_err(&mut self.sink);
+                TagName::EventStreamElt.report_tag_close(&mut self.sink);
+                self.inner = InnerElement::EventStream {
+                    nested: event_stream_nested,
+                };
+                return;
+            }
+        };
+        let is_closed = match &evt {
+            Event::Start(tag) if tag.name().as_ref() == b"Event" => {
+                *nested += 1;
+                false
+            }
+            Event::End(tag) if tag.name().as_ref() == b"Event" => {
+                if *nested > 0 {
+                    *nested -= 1;
+                    false
+                } else {
+                    true
+                }
+            }
+            Event::Text(text) if base64 => {
+                if let Ok(text) = text.unescape() {
+                    self.event_text.extend(text.as_bytes());
                 }
-                _ => (),
+                false
             }
-            self.reader_buf.clear();
+            Event::CData(text) if base64 => {
+                self.event_text.extend(text.as_ref());
+                false
+            }
+            _ => false,
+        };
+        self.event_xml.extend_from_slice(raw);
+        if is_closed {
+            self.report_event_content(base64);
+            AttributeName::EventStreamEltRange
+                .report((start_pos as f64, end_pos as f64), &mut self.sink);
+            TagName::EventStreamElt.report_tag_close(&mut self.sink);
+            self.inner = InnerElement::EventStream {
+                nested: event_stream_nested,
+            };
         }
     }
 
-    /// Returns the ending position (not included), in bytes in the whole parsed MPD, where the
-    /// current `<Event>` element ends.
-    fn get_event_stream_event_ending_position(&mut self) -> Result<usize, ParsingError> {
-        let mut inner_event_tag = 0u32;
-        loop {
-            match self.read_next_event()? {
-                Event::Start(tag) if tag.name().as_ref() == b"Event" => inner_event_tag += 1,
-                Event::End(tag) if tag.name().as_ref() == b"Event" => {
-                    if inner_event_tag > 0 {
-                        inner_event_tag -= 1;
-                    } else {
-                        return Ok(self.reader.buffer_position());
-                    }
+    /// Report the XML of the `<Event>` element which has just been read and,
+    /// if `base64` is set, its content decoded.
+    fn report_event_content(&mut self, base64: bool) {
+        if base64 {
+            match decode_base64(&self.event_text) {
+                Ok(data) => {
+                    AttributeName::MessageData.report(AttrValue::Bytes(&data), &mut self.sink)
                 }
-                Event::Eof => {
-                    return Err(ParsingError(
-                        "Unexpected end of file in an Event element.".to_owned(),
-                    ));
+                Err(err) => err.report_err(&mut self.sink),
+            }
+        }
+        match std::str::from_utf8(&self.event_xml) {
+            Ok(xml) => AttributeName::EventStreamEltXml.report(xml, &mut self.sink),
+            Err(err) => ParsingError::from(err).report_err(&mut self.sink),
+        }
+    }
+}
+
+/// Returns `true` if the given `<Event>` element's content is base64-encoded.
+fn is_base64_encoded(tag: &BytesStart) -> bool {
+    matches!(
+        tag.try_get_attribute(b"contentEncoding"),
+        Ok(Some(attr)) if attr.value.as_ref() == b"base64"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::AttrValue;
+
+    /// Owned version of an `AttrValue`, so it can be recorded.
+    #[derive(Debug, PartialEq)]
+    enum Attr {
+        String(String),
+        F64(f64),
+        U64(u64),
+        Bool(bool),
+        Range(f64, f64),
+        Timeline(Vec<SegmentObject>),
+        KeyValue(String, String),
+        Bytes(Vec<u8>),
+    }
+
+    impl From<AttrValue<'_>> for Attr {
+        fn from(val: AttrValue) -> Self {
+            match val {
+                AttrValue::String(s) => Attr::String(s.to_owned()),
+                AttrValue::F64(v) => Attr::F64(v),
+                AttrValue::U64(v) => Attr::U64(v),
+                AttrValue::Bool(b) => Attr::Bool(b),
+                AttrValue::Range(start, end) => Attr::Range(start, end),
+                AttrValue::Timeline(t) => Attr::Timeline(t.to_vec()),
+                AttrValue::KeyValue(k, v) => Attr::KeyValue(k.to_owned(), v.to_owned()),
+                AttrValue::Bytes(b) => Attr::Bytes(b.to_vec()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum RecordedEvent {
+        TagOpen(TagName),
+        TagClose(TagName),
+        Attribute(AttributeName, Attr),
+        Error(ParsingError),
+        Log(String),
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(Vec<RecordedEvent>);
+
+    impl MpdEventSink for RecordingSink {
+        fn tag_open(&mut self, tag_name: TagName) {
+            self.0.push(RecordedEvent::TagOpen(tag_name));
+        }
+        fn tag_close(&mut self, tag_name: TagName) {
+            self.0.push(RecordedEvent::TagClose(tag_name));
+        }
+        fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+            self.0
+                .push(RecordedEvent::Attribute(attr_name, value.into()));
+        }
+        fn error(&mut self, err: &ParsingError) {
+            self.0.push(RecordedEvent::Error(err.clone()));
+        }
+        fn log(&mut self, message: &str) {
+            self.0.push(RecordedEvent::Log(message.to_owned()));
+        }
+    }
+
+    fn parse(mpd: &str) -> Vec<RecordedEvent> {
+        let mut processor = MPDProcessor::new(mpd.as_bytes(), RecordingSink::default());
+        processor.process_tags().unwrap();
+        processor.into_sink().0
+    }
+
+    #[test]
+    fn test_process_tags() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<MPD type="static" xmlns:scte35="urn:scte:scte35:2013:xml">
+              <Period id="p1"><AdaptationSet>
+                <Representation bandwidth="1000" codingDependency="true"/>
+              </AdaptationSet></Period>
+            </MPD>"#,
+        );
+        assert_eq!(
+            events,
+            vec![
+                TagOpen(TagName::MPD),
+                Attribute(AttributeName::Type, Attr::String("static".to_owned())),
+                Attribute(
+                    AttributeName::Namespace,
+                    Attr::KeyValue("scte35".to_owned(), "urn:scte:scte35:2013:xml".to_owned())
+                ),
+                TagOpen(TagName::Period),
+                Attribute(AttributeName::Id, Attr::String("p1".to_owned())),
+                TagOpen(TagName::AdaptationSet),
+                TagOpen(TagName::Representation),
+                Attribute(AttributeName::Bitrate, Attr::F64(1000.)),
+                Attribute(AttributeName::CodingDependency, Attr::Bool(true)),
+                TagClose(TagName::Representation),
+                TagClose(TagName::AdaptationSet),
+                TagClose(TagName::Period),
+                TagClose(TagName::MPD),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_segment_timeline() {
+        let events = parse(
+            r#"<SegmentTemplate><SegmentTimeline>
+                <S t="10" d="5" r="2"/><S d="3"/>
+            </SegmentTimeline></SegmentTemplate>"#,
+        );
+        let expected = vec![
+            SegmentObject {
+                start: 10,
+                duration: 5,
+                repeat_count: 2,
+                ..Default::default()
+            },
+            SegmentObject {
+                start: 25,
+                duration: 3,
+                repeat_count: 0,
+                ..Default::default()
+            },
+        ];
+        assert!(events.contains(&RecordedEvent::Attribute(
+            AttributeName::SegmentTimeline,
+            Attr::Timeline(expected)
+        )));
+    }
+
+    #[test]
+    fn test_exact_timestamps() {
+        // Past 2^53, consecutive integers cannot all be represented as f64
+        let events = parse(
+            r#"<SegmentTemplate presentationTimeOffset="9007199254740993"><SegmentTimeline>
+                <S t="9007199254740993" d="3" r="1"/><S d="1"/>
+            </SegmentTimeline></SegmentTemplate>"#,
+        );
+        assert!(events.contains(&RecordedEvent::Attribute(
+            AttributeName::PresentationTimeOffset,
+            Attr::U64(9007199254740993)
+        )));
+        let expected = vec![
+            SegmentObject {
+                start: 9007199254740993,
+                duration: 3,
+                repeat_count: 1,
+                ..Default::default()
+            },
+            SegmentObject {
+                start: 9007199254740999,
+                duration: 1,
+                repeat_count: 0,
+                ..Default::default()
+            },
+        ];
+        assert!(events.contains(&RecordedEvent::Attribute(
+            AttributeName::SegmentTimeline,
+            Attr::Timeline(expected)
+        )));
+    }
+
+    fn parse_with_options(mpd: &str, options: ParsingOptions) -> Vec<RecordedEvent> {
+        let mut processor =
+            MPDProcessor::with_options(mpd.as_bytes(), RecordingSink::default(), options);
+        let _ = processor.process_tags();
+        processor.into_sink().0
+    }
+
+    fn timeline(events: &[RecordedEvent]) -> &[SegmentObject] {
+        events
+            .iter()
+            .find_map(|e| match e {
+                RecordedEvent::Attribute(AttributeName::SegmentTimeline, Attr::Timeline(t)) => {
+                    Some(t.as_slice())
                 }
-                _ => {}
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_segment_number_and_chunk_count() {
+        let events = parse(
+            r#"<SegmentTemplate><SegmentTimeline>
+                <S t="0" d="4" n="7" k="8"/><S d="4"/>
+            </SegmentTimeline></SegmentTemplate>"#,
+        );
+        let timeline = timeline(&events);
+        assert_eq!(timeline[0].number, Some(7));
+        assert_eq!(timeline[0].chunk_count, Some(8));
+        assert_eq!(timeline[1].number, None);
+        assert_eq!(timeline[1].chunk_count, None);
+    }
+
+    #[test]
+    fn test_resolve_repeat_counts() {
+        let mpd = r#"<MPD><Period duration="PT10S"><AdaptationSet>
+            <SegmentTemplate timescale="1000" presentationTimeOffset="500"><SegmentTimeline>
+                <S t="500" d="1000" r="-1"/><S t="3500" d="2000" r="-1"/>
+            </SegmentTimeline></SegmentTemplate>
+        </AdaptationSet></Period></MPD>"#;
+        let events = parse(mpd);
+        let repeat_counts: Vec<_> = timeline(&events).iter().map(|s| s.repeat_count).collect();
+        assert_eq!(repeat_counts, vec![-1, -1]);
+
+        let options = ParsingOptions {
+            resolve_repeat_counts: true,
+            ..Default::default()
+        };
+        let events = parse_with_options(mpd, options.clone());
+        let repeat_counts: Vec<_> = timeline(&events).iter().map(|s| s.repeat_count).collect();
+        // Until 3500, then until 10500 (the last segment overflowing it)
+        assert_eq!(repeat_counts, vec![2, 3]);
+
+        // The end of the Period is not known without a duration, unless the
+        // caller indicates it
+        let without_duration = mpd.replace(r#" duration="PT10S""#, "");
+        let events = parse_with_options(&without_duration, options.clone());
+        let repeat_counts: Vec<_> = timeline(&events).iter().map(|s| s.repeat_count).collect();
+        assert_eq!(repeat_counts, vec![2, -1]);
+        let with_durations = ParsingOptions {
+            period_durations: vec![Some(6.)],
+            ..options.clone()
+        };
+        let events = parse_with_options(&without_duration, with_durations);
+        let repeat_counts: Vec<_> = timeline(&events).iter().map(|s| s.repeat_count).collect();
+        assert_eq!(repeat_counts, vec![2, 1]);
+
+        // Timing attributes are inherited from parent SegmentTemplates
+        let inherited = r#"<MPD><Period duration="PT10S">
+            <SegmentTemplate timescale="1000"/>
+            <AdaptationSet><SegmentTemplate presentationTimeOffset="500"/>
+                <Representation><SegmentTemplate><SegmentTimeline>
+                    <S t="500" d="2000" r="-1"/>
+                </SegmentTimeline></SegmentTemplate></Representation>
+            </AdaptationSet>
+        </Period></MPD>"#;
+        let events = parse_with_options(inherited, options.clone());
+        assert_eq!(timeline(&events)[0].repeat_count, 4);
+
+        // And the timescale defaults to 1
+        let events = parse_with_options(&inherited.replace(r#" timescale="1000""#, ""), options);
+        assert_eq!(timeline(&events)[0].repeat_count, 0);
+    }
+
+    #[test]
+    fn test_timeline_discontinuities() {
+        let mpd = r#"<MPD profiles="p" minBufferTime="PT2S"><Period><SegmentTemplate><SegmentTimeline>
+            <S t="0" d="10" r="1"/><S t="25" d="10"/><S t="30" d="10"/><S d="10"/>
+        </SegmentTimeline></SegmentTemplate></Period></MPD>"#;
+        let errors: Vec<_> = parse(mpd)
+            .into_iter()
+            .filter_map(|e| match e {
+                RecordedEvent::Error(err) => Some(err),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, ParsingErrorKind::TimelineGap);
+        assert_eq!(
+            errors[0].path,
+            "MPD/Period/SegmentTemplate/SegmentTimeline/S[2]@t"
+        );
+        assert_eq!(errors[1].kind, ParsingErrorKind::TimelineOverlap);
+        assert_eq!(
+            errors[1].path,
+            "MPD/Period/SegmentTemplate/SegmentTimeline/S[3]@t"
+        );
+
+        // Gaps and overlaps are only warnings, even in strict mode
+        let options = ParsingOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let mut processor =
+            MPDProcessor::with_options(mpd.as_bytes(), RecordingSink::default(), options);
+        assert_eq!(processor.process_tags(), Ok(()));
+        let events = processor.into_sink().0;
+        assert!(events.iter().any(|e| matches!(
+            e,
+            RecordedEvent::Error(err) if err.kind == ParsingErrorKind::TimelineOverlap
+        )));
+        assert!(events.contains(&RecordedEvent::TagClose(TagName::MPD)));
+    }
+
+    #[test]
+    fn test_resolve_base_urls() {
+        let mpd = r#"<MPD>
+            <BaseURL serviceLocation="a">https://cdn-a.com/content/</BaseURL>
+            <BaseURL serviceLocation="b">https://cdn-b.com/content/</BaseURL>
+            <Period><BaseURL>../period/</BaseURL><AdaptationSet>
+                <BaseURL>video/?token=1</BaseURL>
+                <Representation id="1"><BaseURL>./1/../1/</BaseURL></Representation>
+                <Representation id="2"/>
+                <Representation id="3"><BaseURL>https://other.com/3/</BaseURL></Representation>
+            </AdaptationSet></Period>
+            <Period><AdaptationSet><Representation id="4"/></AdaptationSet></Period>
+        </MPD>"#;
+        let resolved_base_urls = |events: Vec<RecordedEvent>| -> Vec<Vec<(String, String)>> {
+            let mut representations = Vec::new();
+            for evt in events {
+                match evt {
+                    RecordedEvent::TagOpen(TagName::Representation) => {
+                        representations.push(Vec::new())
+                    }
+                    RecordedEvent::Attribute(
+                        AttributeName::ResolvedBaseUrl,
+                        Attr::KeyValue(url, service_location),
+                    ) => representations
+                        .last_mut()
+                        .unwrap()
+                        .push((url, service_location)),
+                    _ => {}
+                }
+            }
+            representations
+        };
+        let url = |url: &str, service_location: &str| (url.to_owned(), service_location.to_owned());
+
+        assert!(resolved_base_urls(parse(mpd)).iter().all(Vec::is_empty));
+
+        let options = ParsingOptions {
+            resolve_base_urls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            resolved_base_urls(parse_with_options(mpd, options)),
+            vec![
+                vec![
+                    url("https://cdn-a.com/period/video/1/", "a"),
+                    url("https://cdn-b.com/period/video/1/", "b"),
+                ],
+                vec![
+                    url("https://cdn-a.com/period/video/?token=1", "a"),
+                    url("https://cdn-b.com/period/video/?token=1", "b"),
+                ],
+                vec![url("https://other.com/3/", "")],
+                vec![
+                    url("https://cdn-a.com/content/", "a"),
+                    url("https://cdn-b.com/content/", "b"),
+                ],
+            ]
+        );
+
+        let mpd = r#"<MPD><Period><AdaptationSet>
+            <Representation><BaseURL>seg/</BaseURL></Representation>
+            <Representation/>
+        </AdaptationSet></Period></MPD>"#;
+        let options = ParsingOptions {
+            resolve_base_urls: true,
+            document_url: Some("https://example.com/live/manifest.mpd?v=2".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolved_base_urls(parse_with_options(mpd, options)),
+            vec![
+                vec![url("https://example.com/live/seg/", "")],
+                vec![url("https://example.com/live/manifest.mpd?v=2", "")],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_errors() {
+        let events = parse(r#"<MPD mediaPresentationDuration="12"></MPD>"#);
+        assert!(events.iter().any(|e| matches!(e, RecordedEvent::Error(_))));
+
+        let events = parse(r#"<MPD publishTime="2024-01-01"></MPD>"#);
+        assert!(events.iter().any(|e| matches!(e, RecordedEvent::Error(_))));
+
+        let events = parse(r#"<SegmentTemplate media="$Number%5d$.mp4"/>"#);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            RecordedEvent::Error(err) if err.kind == ParsingErrorKind::InvalidTemplate
+                && err.path == "SegmentTemplate@media"
+        )));
+
+        let events = parse(
+            r#"<AdaptationSet><ContentProtection cenc:default_KID="9eb4050d-e44b"/></AdaptationSet>"#,
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            RecordedEvent::Error(err) if err.kind == ParsingErrorKind::InvalidKeyId
+                && err.path == "AdaptationSet/ContentProtection@cenc:default_KID"
+        )));
+        assert!(!events.iter().any(|e| matches!(
+            e,
+            RecordedEvent::Attribute(AttributeName::ContentProtectionKeyId, _)
+        )));
+    }
+
+    #[test]
+    fn test_log_approximated_durations() {
+        let events = parse(r#"<MPD mediaPresentationDuration="P1M" minBufferTime="PT2S"></MPD>"#);
+        let logs: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, RecordedEvent::Log(_)))
+            .collect();
+        assert_eq!(logs.len(), 1);
+        assert!(events.contains(&RecordedEvent::Attribute(
+            AttributeName::MediaPresentationDuration,
+            Attr::F64(2592000.)
+        )));
+    }
+
+    #[test]
+    fn test_error_context() {
+        let mpd = r#"<MPD><Period id="1"/><Period>
+  <AdaptationSet id="3"><SegmentTemplate duration="a"/></AdaptationSet>
+</Period><Period><AdaptationSet>"#;
+        let errors: Vec<_> = parse(mpd)
+            .into_iter()
+            .filter_map(|e| match e {
+                RecordedEvent::Error(err) => Some(err),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParsingErrorKind::InvalidNumber);
+        assert_eq!(
+            errors[0].path,
+            "MPD/Period[2]/AdaptationSet[id=3]/SegmentTemplate@duration"
+        );
+        assert_eq!(errors[0].offset, mpd.find("<SegmentTemplate"));
+
+        let mpd = r#"<MPD><Period><SegmentTemplate><SegmentTimeline><S d="1"/><S t="-1"/>"#;
+        let errors: Vec<_> = parse(mpd)
+            .into_iter()
+            .filter_map(|e| match e {
+                RecordedEvent::Error(err) => Some(err),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].path,
+            "MPD/Period/SegmentTemplate/SegmentTimeline/S[2]@t"
+        );
+        assert_eq!(errors[1].kind, ParsingErrorKind::UnexpectedEof);
+        assert_eq!(errors[1].path, "MPD/Period/SegmentTemplate/SegmentTimeline");
+        assert_eq!(errors[1].offset, Some(mpd.len()));
+    }
+
+    fn parse_strict(mpd: &str) -> (Vec<RecordedEvent>, Result<()>) {
+        let options = ParsingOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let mut processor =
+            MPDProcessor::with_options(mpd.as_bytes(), RecordingSink::default(), options);
+        let res = processor.process_tags();
+        (processor.into_sink().0, res)
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let valid = r#"<MPD type="static" profiles="urn:mpeg:dash:profile:isoff-live:2011" minBufferTime="PT2S">
+          <Period duration="PT30S"><AdaptationSet>
+            <SegmentTemplate timescale="1000"><SegmentTimeline><S d="1000"/></SegmentTimeline></SegmentTemplate>
+            <Representation id="1" bandwidth="1000"/>
+          </AdaptationSet></Period>
+        </MPD>"#;
+        let (events, res) = parse_strict(valid);
+        assert_eq!(res, Ok(()));
+        assert_eq!(events, parse(valid));
+
+        let (events, res) = parse_strict(&valid.replace(r#" bandwidth="1000""#, ""));
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::MissingAttribute);
+        assert_eq!(
+            err.path,
+            "MPD/Period/AdaptationSet/Representation[id=1]@bandwidth"
+        );
+        assert_eq!(events.last(), Some(&RecordedEvent::Error(err)));
+        assert!(!events.contains(&RecordedEvent::TagOpen(TagName::Representation)));
+
+        let (_, res) = parse_strict(&valid.replace("static", "live"));
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::UnknownValue);
+        assert_eq!(err.path, "MPD@type");
+
+        let (_, res) = parse_strict(&valid.replace(
+            r#"timescale="1000">"#,
+            r#"timescale="1000" duration="2000">"#,
+        ));
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidStructure);
+        assert_eq!(
+            err.path,
+            "MPD/Period/AdaptationSet/SegmentTemplate/SegmentTimeline"
+        );
+
+        let (_, res) = parse_strict(&valid.replace("PT30S", "P4W"));
+        assert_eq!(res.unwrap_err().kind, ParsingErrorKind::InvalidDuration);
+    }
+
+    #[test]
+    fn test_strict_mode_stops_at_first_error() {
+        let mpd = r#"<MPD type="static" profiles="p" minBufferTime="PT2S" publishTime="a">
+          <Period><AdaptationSet><Representation id="1"/></AdaptationSet></Period>
+        </MPD>"#;
+        let (events, res) = parse_strict(mpd);
+        let err = res.unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidDateTime);
+        let errors: Vec<_> = events
+            .iter()
+            .filter(|e| matches!(e, RecordedEvent::Error(_)))
+            .collect();
+        assert_eq!(errors, vec![&RecordedEvent::Error(err)]);
+        assert!(!events.contains(&RecordedEvent::TagOpen(TagName::Period)));
+        assert!(!events.contains(&RecordedEvent::TagClose(TagName::MPD)));
+
+        // The lenient mode goes on and ignores non-conformances
+        let events = parse(mpd);
+        assert!(events.contains(&RecordedEvent::TagOpen(TagName::Representation)));
+    }
+
+    /// Stand-in `RemoteElementLoader` serving remote elements from memory.
+    struct LocalLoader(Vec<(&'static str, &'static str)>);
+
+    impl RemoteElementLoader for LocalLoader {
+        fn load(&mut self, url: &str) -> Result<Vec<u8>> {
+            match self.0.iter().find(|(u, _)| *u == url) {
+                Some((_, data)) => Ok(data.as_bytes().to_vec()),
+                None => Err(ParsingError::new(
+                    ParsingErrorKind::Io,
+                    format!("Not found: {}", url),
+                )),
             }
         }
     }
+
+    #[test]
+    fn test_resolve_xlinks() {
+        use RecordedEvent::*;
+        let mpd = r#"<MPD xmlns:xlink="http://www.w3.org/1999/xlink">
+            <Period id="1" xlink:href="remote/periods.xml" xlink:actuate="onLoad">
+                <AdaptationSet id="local"/>
+            </Period>
+            <Period id="2" xlink:href="urn:mpeg:dash:resolve-to-zero:2013" xlink:actuate="onLoad"/>
+            <Period id="3" xlink:href="https://example.com/p3.xml"/>
+            <Period id="4" xlink:href="missing.xml" xlink:actuate="onLoad"/>
+        </MPD>"#;
+        let loader = LocalLoader(vec![
+            (
+                "https://example.com/live/remote/periods.xml",
+                r#"<?xml version="1.0"?><Period id="remote-1">
+                    <AdaptationSet id="a" xlink:href="as.xml" xlink:actuate="onLoad"/>
+                </Period><Period id="remote-2"/>"#,
+            ),
+            (
+                // Relative to the remote element referencing it
+                "https://example.com/live/remote/as.xml",
+                r#"<AdaptationSet id="remote-a"/>"#,
+            ),
+        ]);
+        let options = ParsingOptions {
+            document_url: Some("https://example.com/live/manifest.mpd".to_owned()),
+            ..Default::default()
+        };
+        let mut processor =
+            MPDProcessor::with_options(mpd.as_bytes(), RecordingSink::default(), options);
+        processor.set_remote_element_loader(loader);
+        processor.process_tags().unwrap();
+        let events: Vec<_> = processor
+            .into_sink()
+            .0
+            .into_iter()
+            .filter(|evt| {
+                matches!(
+                    evt,
+                    TagOpen(TagName::Period)
+                        | TagOpen(TagName::AdaptationSet)
+                        | Attribute(AttributeName::Id, _)
+                        | Attribute(AttributeName::XLinkHref, _)
+                        | Error(_)
+                )
+            })
+            .collect();
+        let id = |id: &str| Attribute(AttributeName::Id, Attr::String(id.to_owned()));
+        // Elements start right after the previous markup, whitespaces included
+        let period_4_offset = mpd[..mpd.find(r#"<Period id="4""#).unwrap()]
+            .trim_end()
+            .len();
+        assert_eq!(
+            events,
+            vec![
+                TagOpen(TagName::Period),
+                id("remote-1"),
+                TagOpen(TagName::AdaptationSet),
+                id("remote-a"),
+                TagOpen(TagName::Period),
+                id("remote-2"),
+                // `onRequest` XLinks are not resolved
+                TagOpen(TagName::Period),
+                id("3"),
+                Attribute(
+                    AttributeName::XLinkHref,
+                    Attr::String("https://example.com/p3.xml".to_owned())
+                ),
+                Error(ParsingError {
+                    kind: ParsingErrorKind::Io,
+                    message: "Not found: https://example.com/live/missing.xml".to_owned(),
+                    path: "MPD/Period[id=4]@xlink:href".to_owned(),
+                    offset: Some(period_4_offset),
+                }),
+                TagOpen(TagName::Period),
+                id("4"),
+                Attribute(
+                    AttributeName::XLinkHref,
+                    Attr::String("missing.xml".to_owned())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xlink_attributes_only_on_xlink_elements() {
+        let events = parse(
+            r#"<Representation xmlns:xlink="http://www.w3.org/1999/xlink">
+                <SegmentBase xlink:href="base.xml" timescale="10"/>
+                <SegmentList xlink:href="list.xml" timescale="10"/>
+            </Representation>"#,
+        );
+        let hrefs: Vec<_> = events
+            .iter()
+            .filter_map(|evt| match evt {
+                RecordedEvent::Attribute(AttributeName::XLinkHref, Attr::String(href)) => {
+                    Some(href.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hrefs, vec!["list.xml"]);
+    }
+
+    #[test]
+    fn test_xlink_depth_limit() {
+        let period = r#"<Period xlink:href="https://example.com/p.xml" xlink:actuate="onLoad"/>"#;
+        let mpd = format!("<MPD>{}</MPD>", period);
+        let mut processor = MPDProcessor::new(mpd.as_bytes(), RecordingSink::default());
+        processor
+            .set_remote_element_loader(LocalLoader(vec![("https://example.com/p.xml", period)]));
+        processor.process_tags().unwrap();
+        let events = processor.into_sink().0;
+        let errors: Vec<_> = events
+            .iter()
+            .filter_map(|evt| match evt {
+                RecordedEvent::Error(err) => Some(err.kind),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, vec![ParsingErrorKind::InvalidXLink]);
+        let periods = events
+            .iter()
+            .filter(|evt| **evt == RecordedEvent::TagOpen(TagName::Period))
+            .count();
+        assert_eq!(periods, 1);
+    }
+
+    #[test]
+    fn test_process_cenc_pssh() {
+        use RecordedEvent::*;
+        let mut pssh = vec![0, 0, 0, 52];
+        pssh.extend(b"pssh");
+        pssh.extend([1, 0, 0, 0]);
+        pssh.extend([0xaa; 16]);
+        pssh.extend([0, 0, 0, 1]);
+        pssh.extend([0xbb; 16]);
+        pssh.extend([0, 0, 0, 0]);
+        let mpd = format!(
+            r#"<MPD><Period><AdaptationSet><ContentProtection>
+                <cenc:pssh>{}</cenc:pssh>
+                <cenc:pssh>AAAA</cenc:pssh>
+                <cenc:pssh>AA*A</cenc:pssh>
+            </ContentProtection></AdaptationSet></Period></MPD>"#,
+            crate::utils::encode_base64(&pssh)
+        );
+        let events: Vec<_> = parse(&mpd)
+            .into_iter()
+            .filter_map(|evt| match evt {
+                Attribute(name, value) => Some(Ok((name, value))),
+                Error(err) => Some(Err((err.kind, err.path))),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                Ok((AttributeName::ContentProtectionCencPSSH, Attr::Bytes(pssh))),
+                Ok((
+                    AttributeName::ContentProtectionPsshSystemId,
+                    Attr::Bytes(vec![0xaa; 16])
+                )),
+                Ok((
+                    AttributeName::ContentProtectionPsshKeyId,
+                    Attr::Bytes(vec![0xbb; 16])
+                )),
+                Ok((
+                    AttributeName::ContentProtectionCencPSSH,
+                    Attr::Bytes(vec![0, 0, 0])
+                )),
+                Err((
+                    ParsingErrorKind::InvalidPssh,
+                    "MPD/Period/AdaptationSet/ContentProtection/cenc:pssh[2]".to_owned()
+                )),
+                Err((
+                    ParsingErrorKind::InvalidBase64,
+                    "MPD/Period/AdaptationSet/ContentProtection/cenc:pssh[3]".to_owned()
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_playready_object() {
+        use RecordedEvent::*;
+        let header: Vec<u8> = r#"<WRMHEADER version="4.2.0.0"><DATA><PROTECTINFO><KIDS>
+                <KID VALUE="DQW0nkvkAkiTLifXUIPiZg=="/>
+            </KIDS></PROTECTINFO><LA_URL>https://pr.example.com/</LA_URL></DATA></WRMHEADER>"#
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let mut pro = ((header.len() + 10) as u32).to_le_bytes().to_vec();
+        pro.extend([1, 0, 1, 0]);
+        pro.extend((header.len() as u16).to_le_bytes());
+        pro.extend(header);
+        let mpd = format!(
+            r#"<ContentProtection schemeIdUri="urn:uuid:9a04f079-9840-4286-ab92-e65be0885f95">
+                <mspr:pro>{}</mspr:pro>
+                <mspr:IsEncrypted>1</mspr:IsEncrypted>
+            </ContentProtection>"#,
+            crate::utils::encode_base64(&pro)
+        );
+        let events: Vec<_> = parse(&mpd)
+            .into_iter()
+            .filter(|evt| !matches!(evt, Attribute(AttributeName::SchemeIdUri, _)))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                TagOpen(TagName::ContentProtection),
+                Attribute(
+                    AttributeName::ContentProtectionPlayReadyObject,
+                    Attr::Bytes(pro)
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionPlayReadyKeyId,
+                    Attr::Bytes(vec![
+                        0x9e, 0xb4, 0x05, 0x0d, 0xe4, 0x4b, 0x48, 0x02, 0x93, 0x2e, 0x27, 0xd7,
+                        0x50, 0x83, 0xe2, 0x66
+                    ])
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionPlayReadyLaUrl,
+                    Attr::String("https://pr.example.com/".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionPlayReadyIsEncrypted,
+                    Attr::Bool(true)
+                ),
+                TagClose(TagName::ContentProtection),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_license_urls() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<ContentProtection schemeIdUri="urn:uuid:e2719d58-a985-b3c9-781a-b030af78d30e"
+                robustness="SW_SECURE_CRYPTO">
+                <dashif:Laurl licenseType="EME-1.0">https://drm.example.com/license</dashif:Laurl>
+                <dashif:Authzurl>https://drm.example.com/auth</dashif:Authzurl>
+                <clearkey:Laurl Lic_type="EME-1.0">https://ck.example.com/</clearkey:Laurl>
+            </ContentProtection>"#,
+        );
+        assert_eq!(
+            events[2..],
+            [
+                Attribute(
+                    AttributeName::ContentProtectionRobustness,
+                    Attr::String("SW_SECURE_CRYPTO".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionDashIfLaUrl,
+                    Attr::KeyValue(
+                        "https://drm.example.com/license".to_owned(),
+                        "EME-1.0".to_owned()
+                    )
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionDashIfAuthzUrl,
+                    Attr::KeyValue("https://drm.example.com/auth".to_owned(), "".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionClearKeyLaUrl,
+                    Attr::KeyValue("https://ck.example.com/".to_owned(), "EME-1.0".to_owned())
+                ),
+                TagClose(TagName::ContentProtection),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_content_protection_refs() {
+        use RecordedEvent::*;
+        let mpd = r#"<MPD><Period>
+            <ContentProtection schemeIdUri="urn:mpeg:dash:mp4protection:2011" value="cenc"
+                refId="cp1" cenc:default_KID="9eb4050d-e44b-4802-932e-27d75083e266">
+                <dashif:Laurl>https://drm.example.com/license</dashif:Laurl>
+            </ContentProtection>
+            <AdaptationSet>
+                <ContentProtection ref="cp1" robustness="HW_SECURE_ALL"/>
+                <ContentProtection ref="cp2"/>
+            </AdaptationSet>
+        </Period></MPD>"#;
+        let events = parse(mpd);
+        let adaptation_set_start = events
+            .iter()
+            .position(|evt| *evt == TagOpen(TagName::AdaptationSet))
+            .unwrap();
+        let ref_pos = mpd.find(r#"<ContentProtection ref="cp2""#).unwrap();
+        assert_eq!(
+            events[adaptation_set_start + 1..],
+            [
+                TagOpen(TagName::ContentProtection),
+                Attribute(
+                    AttributeName::SchemeIdUri,
+                    Attr::String("urn:mpeg:dash:mp4protection:2011".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionValue,
+                    Attr::String("cenc".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionKeyId,
+                    Attr::Bytes(vec![
+                        0x9e, 0xb4, 0x05, 0x0d, 0xe4, 0x4b, 0x48, 0x02, 0x93, 0x2e, 0x27, 0xd7,
+                        0x50, 0x83, 0xe2, 0x66
+                    ])
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionRef,
+                    Attr::String("cp1".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionRobustness,
+                    Attr::String("HW_SECURE_ALL".to_owned())
+                ),
+                Attribute(
+                    AttributeName::ContentProtectionDashIfLaUrl,
+                    Attr::KeyValue("https://drm.example.com/license".to_owned(), "".to_owned())
+                ),
+                TagClose(TagName::ContentProtection),
+                TagOpen(TagName::ContentProtection),
+                Error(ParsingError {
+                    kind: ParsingErrorKind::InvalidReference,
+                    message: "No ContentProtection with the refId \"cp2\" has been defined \
+                        before."
+                        .to_owned(),
+                    path: "MPD/Period/AdaptationSet/ContentProtection[2]@ref".to_owned(),
+                    offset: Some(mpd[..ref_pos].trim_end().len()),
+                }),
+                Attribute(
+                    AttributeName::ContentProtectionRef,
+                    Attr::String("cp2".to_owned())
+                ),
+                TagClose(TagName::ContentProtection),
+                TagClose(TagName::AdaptationSet),
+                TagClose(TagName::Period),
+                TagClose(TagName::MPD),
+            ]
+        );
+        assert!(events.contains(&Attribute(
+            AttributeName::ContentProtectionRefId,
+            Attr::String("cp1".to_owned())
+        )));
+
+        // Referencing elements do not need a `schemeIdUri` in strict mode
+        let (_, res) = parse_strict(
+            r#"<Period>
+                <ContentProtection schemeIdUri="urn:mpeg:dash:mp4protection:2011" refId="1"/>
+                <AdaptationSet><ContentProtection ref="1"/></AdaptationSet>
+            </Period>"#,
+        );
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn test_process_audio_channel_configuration() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<AdaptationSet>
+                <AudioChannelConfiguration
+                    schemeIdUri="urn:mpeg:mpegB:cicp:ChannelConfiguration" value="6"/>
+                <Representation>
+                    <AudioChannelConfiguration
+                        schemeIdUri="tag:dolby.com,2014:dash:audio_channel_configuration:2011"
+                        value="0000XY"/>
+                </Representation>
+            </AdaptationSet>"#,
+        );
+        assert_eq!(
+            events[1..6],
+            [
+                TagOpen(TagName::AudioChannelConfiguration),
+                Attribute(
+                    AttributeName::SchemeIdUri,
+                    Attr::String("urn:mpeg:mpegB:cicp:ChannelConfiguration".to_owned())
+                ),
+                Attribute(AttributeName::SchemeValue, Attr::String("6".to_owned())),
+                Attribute(AttributeName::AudioChannelCount, Attr::F64(6.)),
+                Attribute(
+                    AttributeName::AudioChannelLayout,
+                    Attr::String("C L R Ls Rs LFE".to_owned())
+                ),
+            ]
+        );
+        assert!(events.iter().any(|evt| matches!(
+            evt,
+            Error(err) if err.kind == ParsingErrorKind::InvalidNumber
+                && err.path == "AdaptationSet/Representation/AudioChannelConfiguration@value"
+        )));
+        assert!(!events[7..]
+            .iter()
+            .any(|evt| matches!(evt, Attribute(AttributeName::AudioChannelCount, _))));
+    }
+
+    #[test]
+    fn test_process_low_latency_elements() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<MPD><ServiceDescription id="0">
+                <Scope schemeIdUri="urn:dvb:dash:lowlatency:scope:2019"/>
+                <Latency referenceId="7" target="3500" min="2000" max="6000"/>
+                <PlaybackRate min="0.96" max="1.04"/>
+                <OperatingBandwidth mediaType="video" min="500000"/>
+            </ServiceDescription>
+            <Period><AdaptationSet>
+                <ProducerReferenceTime id="7" type="encoder" wallClockTime="2024-01-01T00:00:00Z"
+                    presentationTime="90000">
+                    <UTCTiming schemeIdUri="urn:mpeg:dash:utc:http-iso:2014"/>
+                </ProducerReferenceTime>
+                <Resync type="2" dT="1000" marker="true"/>
+            </AdaptationSet></Period></MPD>"#,
+        );
+        let string = |val: &str| Attr::String(val.to_owned());
+        assert_eq!(
+            events[1..],
+            [
+                TagOpen(TagName::ServiceDescription),
+                Attribute(AttributeName::Id, string("0")),
+                TagOpen(TagName::Scope),
+                Attribute(
+                    AttributeName::SchemeIdUri,
+                    string("urn:dvb:dash:lowlatency:scope:2019")
+                ),
+                TagClose(TagName::Scope),
+                TagOpen(TagName::Latency),
+                Attribute(AttributeName::ReferenceId, Attr::F64(7.)),
+                Attribute(AttributeName::Target, Attr::F64(3500.)),
+                Attribute(AttributeName::Min, Attr::F64(2000.)),
+                Attribute(AttributeName::Max, Attr::F64(6000.)),
+                TagClose(TagName::Latency),
+                TagOpen(TagName::PlaybackRate),
+                Attribute(AttributeName::Min, Attr::F64(0.96)),
+                Attribute(AttributeName::Max, Attr::F64(1.04)),
+                TagClose(TagName::PlaybackRate),
+                TagOpen(TagName::OperatingBandwidth),
+                Attribute(AttributeName::MediaType, string("video")),
+                Attribute(AttributeName::Min, Attr::F64(500000.)),
+                TagClose(TagName::OperatingBandwidth),
+                TagClose(TagName::ServiceDescription),
+                TagOpen(TagName::Period),
+                TagOpen(TagName::AdaptationSet),
+                TagOpen(TagName::ProducerReferenceTime),
+                Attribute(AttributeName::Id, string("7")),
+                Attribute(AttributeName::Type, string("encoder")),
+                Attribute(AttributeName::WallClockTime, Attr::F64(1704067200000.)),
+                Attribute(AttributeName::PresentationTime, Attr::U64(90000)),
+                TagOpen(TagName::UtcTiming),
+                Attribute(
+                    AttributeName::SchemeIdUri,
+                    string("urn:mpeg:dash:utc:http-iso:2014")
+                ),
+                TagClose(TagName::UtcTiming),
+                TagClose(TagName::ProducerReferenceTime),
+                TagOpen(TagName::Resync),
+                Attribute(AttributeName::ResyncType, Attr::F64(2.)),
+                Attribute(AttributeName::ResyncDT, Attr::F64(1000.)),
+                Attribute(AttributeName::ResyncMarker, Attr::Bool(true)),
+                TagClose(TagName::Resync),
+                TagClose(TagName::AdaptationSet),
+                TagClose(TagName::Period),
+                TagClose(TagName::MPD),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_preselection() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<Period>
+                <AdaptationSet id="1" codecs="ac-4.02.01.01">
+                    <ContentComponent id="10"/>
+                    <ContentComponent id="11"/>
+                </AdaptationSet>
+                <AdaptationSet id="2"/>
+                <Preselection id="100" tag="1" preselectionComponents="1 11 2" lang="en"
+                    codecs="ac-4.02.01.01">
+                    <Role schemeIdUri="urn:mpeg:dash:role:2011" value="commentary"/>
+                    <Label>Commentary</Label>
+                </Preselection>
+                <Preselection id="101" preselectionComponents="1 12"/>
+            </Period>"#,
+        );
+        let string = |val: &str| Attr::String(val.to_owned());
+        let component = |adaptation_set_id: &str, content_component_id: &str| {
+            Attribute(
+                AttributeName::PreselectionComponent,
+                Attr::KeyValue(
+                    adaptation_set_id.to_owned(),
+                    content_component_id.to_owned(),
+                ),
+            )
+        };
+        let start = events
+            .iter()
+            .position(|evt| *evt == TagOpen(TagName::Preselection))
+            .unwrap();
+        assert_eq!(
+            events[start..start + 19],
+            [
+                TagOpen(TagName::Preselection),
+                Attribute(AttributeName::Id, string("100")),
+                Attribute(AttributeName::PreselectionTag, string("1")),
+                Attribute(AttributeName::PreselectionComponents, string("1 11 2")),
+                Attribute(AttributeName::Language, string("en")),
+                Attribute(AttributeName::Codecs, string("ac-4.02.01.01")),
+                component("1", ""),
+                component("1", "11"),
+                component("2", ""),
+                TagOpen(TagName::Role),
+                Attribute(
+                    AttributeName::SchemeIdUri,
+                    string("urn:mpeg:dash:role:2011")
+                ),
+                Attribute(AttributeName::SchemeValue, string("commentary")),
+                TagClose(TagName::Role),
+                Attribute(AttributeName::Label, string("Commentary")),
+                TagClose(TagName::Preselection),
+                TagOpen(TagName::Preselection),
+                Attribute(AttributeName::Id, string("101")),
+                Attribute(AttributeName::PreselectionComponents, string("1 12")),
+                component("1", ""),
+            ]
+        );
+        assert!(matches!(
+            &events[start + 19],
+            Error(err) if err.kind == ParsingErrorKind::InvalidReference
+                && err.path == "Period/Preselection[id=101]@preselectionComponents"
+        ));
+        assert_eq!(
+            events[start + 20..],
+            [TagClose(TagName::Preselection), TagClose(TagName::Period)]
+        );
+    }
+
+    #[test]
+    fn test_process_content_steering() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<MPD>
+                <BaseURL serviceLocation="cdn-a">https://cdn-a.example.com/</BaseURL>
+                <ContentSteering defaultServiceLocation="cdn-a" queryBeforeStart="true"
+                    proxyServerURL="https://proxy.example.com/steering">
+                    https://steering.example.com/manifest.json
+                </ContentSteering>
+            </MPD>"#,
+        );
+        let string = |val: &str| Attr::String(val.to_owned());
+        assert_eq!(
+            events[5..],
+            [
+                TagOpen(TagName::ContentSteering),
+                Attribute(AttributeName::DefaultServiceLocation, string("cdn-a")),
+                Attribute(AttributeName::QueryBeforeStart, Attr::Bool(true)),
+                Attribute(
+                    AttributeName::ProxyServerUrl,
+                    string("https://proxy.example.com/steering")
+                ),
+                Attribute(
+                    AttributeName::Text,
+                    string("https://steering.example.com/manifest.json")
+                ),
+                TagClose(TagName::ContentSteering),
+                TagClose(TagName::MPD),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_event_payloads() {
+        use RecordedEvent::*;
+        let events = parse(
+            r#"<EventStream schemeIdUri="urn:example" presentationTimeOffset="9007199254740993">
+                <Event presentationTime="9007199254740995" id="1" messageData="hé"/>
+                <Event id="2" contentEncoding="base64">AAEC<!-- x -->/w==</Event>
+                <Event id="3"><Splice xmlns="urn:splice" a = '1'>
+                  <B> t </B><C/></Splice></Event>
+            </EventStream>"#,
+        );
+        let string = |val: &str| Attr::String(val.to_owned());
+        assert!(events.contains(&Attribute(
+            AttributeName::PresentationTimeOffset,
+            Attr::U64(9007199254740993)
+        )));
+        assert!(events.contains(&Attribute(
+            AttributeName::EventPresentationTime,
+            Attr::U64(9007199254740995)
+        )));
+        assert!(events.contains(&Attribute(
+            AttributeName::MessageData,
+            Attr::Bytes("hé".as_bytes().to_vec())
+        )));
+        assert!(events.contains(&Attribute(
+            AttributeName::MessageData,
+            Attr::Bytes(vec![0, 1, 2, 255])
+        )));
+        assert!(events.contains(&Attribute(
+            AttributeName::EventStreamEltXml,
+            string(r#"<Event presentationTime="9007199254740995" id="1" messageData="hé"/>"#)
+        )));
+        assert!(events.contains(&Attribute(
+            AttributeName::EventStreamEltXml,
+            string(
+                r#"<Event id="3"><Splice xmlns="urn:splice" a = '1'>
+                  <B> t </B><C/></Splice></Event>"#
+            )
+        )));
+        assert!(!events.iter().any(|e| matches!(e, Error(_))));
+
+        let events =
+            parse(r#"<EventStream><Event contentEncoding="base64">!</Event></EventStream>"#);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Error(err) if err.kind == ParsingErrorKind::InvalidBase64
+        )));
+    }
 }
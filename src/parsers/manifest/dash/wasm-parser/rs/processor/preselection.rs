@@ -0,0 +1,119 @@
+use quick_xml::events::BytesStart;
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+
+/// Keeps the ids of the `<AdaptationSet>` and `<ContentComponent>` elements
+/// encountered in the current `<Period>`, so that the `preselectionComponents`
+/// attribute of the `<Preselection>` elements following them can be resolved.
+#[derive(Default)]
+pub(super) struct PreselectionComponents {
+    adaptation_sets: Vec<AdaptationSetIds>,
+}
+
+/// Ids of an `<AdaptationSet>` of the current `<Period>`.
+struct AdaptationSetIds {
+    id: Option<Vec<u8>>,
+
+    /// `id` of its `<ContentComponent>` children.
+    content_components: Vec<Vec<u8>>,
+}
+
+/// A component of a `<Preselection>`, once resolved.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct PreselectionComponent<'a> {
+    /// `id` of the `<AdaptationSet>` referenced, or of the one containing the
+    /// `<ContentComponent>` referenced.
+    pub adaptation_set_id: &'a [u8],
+
+    /// `id` of the `<ContentComponent>` referenced, if any.
+    pub content_component_id: Option<&'a [u8]>,
+}
+
+impl PreselectionComponents {
+    /// Signal that a `<Period>` element has been opened.
+    pub fn enter_period(&mut self) {
+        self.adaptation_sets.clear();
+    }
+
+    /// Signal that an `<AdaptationSet>` element has been opened.
+    pub fn enter_adaptation_set(&mut self, tag: &BytesStart) {
+        self.adaptation_sets.push(AdaptationSetIds {
+            id: id(tag),
+            content_components: Vec::new(),
+        });
+    }
+
+    /// Signal that a `<ContentComponent>` element, which is part of the last
+    /// `<AdaptationSet>` opened, has been opened.
+    pub fn enter_content_component(&mut self, tag: &BytesStart) {
+        if let (Some(adaptation_set), Some(id)) = (self.adaptation_sets.last_mut(), id(tag)) {
+            adaptation_set.content_components.push(id);
+        }
+    }
+
+    /// Resolve the space-separated ids of a `preselectionComponents`
+    /// attribute.
+    ///
+    /// As defined by the DASH specification, the first id is the one of the
+    /// main `<AdaptationSet>`. The following ones are either those of
+    /// `<ContentComponent>` elements of that main `<AdaptationSet>` or those
+    /// of other `<AdaptationSet>` elements.
+    ///
+    /// Returns an error for the first id which matches none of the elements
+    /// encountered before in the `<Period>`, in which case the components
+    /// resolved until then are still returned.
+    pub fn resolve<'a>(&'a self, value: &[u8]) -> (Vec<PreselectionComponent<'a>>, Result<()>) {
+        let mut components = Vec::new();
+        // `id` of the main `<AdaptationSet>`, and its ids
+        let mut main: Option<(&[u8], &AdaptationSetIds)> = None;
+        for component_id in value.split(u8::is_ascii_whitespace) {
+            if component_id.is_empty() {
+                continue;
+            }
+            let content_component = main.and_then(|(adaptation_set_id, ids)| {
+                let id = ids
+                    .content_components
+                    .iter()
+                    .find(|id| id.as_slice() == component_id)?;
+                Some(PreselectionComponent {
+                    adaptation_set_id,
+                    content_component_id: Some(id.as_slice()),
+                })
+            });
+            let component = content_component.or_else(|| {
+                let (id, ids) = self.adaptation_sets.iter().find_map(|ids| {
+                    let id = ids.id.as_deref().filter(|id| *id == component_id)?;
+                    Some((id, ids))
+                })?;
+                main.get_or_insert((id, ids));
+                Some(PreselectionComponent {
+                    adaptation_set_id: id,
+                    content_component_id: None,
+                })
+            });
+            match component {
+                Some(component) => components.push(component),
+                None => {
+                    let err = ParsingError::new(
+                        ParsingErrorKind::InvalidReference,
+                        format!(
+                            "No AdaptationSet or ContentComponent with the id {:?} has been \
+                             defined before.",
+                            String::from_utf8_lossy(component_id)
+                        ),
+                    )
+                    .in_attribute(b"preselectionComponents");
+                    return (components, Err(err));
+                }
+            }
+        }
+        (components, Ok(()))
+    }
+}
+
+fn id(tag: &BytesStart) -> Option<Vec<u8>> {
+    tag.try_get_attribute(b"id")
+        .ok()
+        .flatten()
+        .map(|attr| attr.value.into_owned())
+}
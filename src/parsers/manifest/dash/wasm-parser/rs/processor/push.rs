@@ -0,0 +1,408 @@
+use super::{create_xml_reader, process_events, ParsingOptions, ProcessorState};
+use crate::errors::Result;
+use crate::sink::MpdEventSink;
+use crate::xlink::RemoteElementLoader;
+
+/// Push-based version of the `MPDProcessor`, allowing to parse an MPD chunk by
+/// chunk, as its data arrives (e.g. while the MPD request is still pending).
+///
+/// Chunks are given through the `feed` method and `finish` has to be called
+/// once the whole MPD has been fed.
+/// Chunks can be split anywhere, even in the middle of an element or of a
+/// multi-byte character: the data is only processed once a whole XML markup
+/// (tag, comment, CDATA section...) is available.
+/// The parsing state (e.g. the `SegmentTimeline` being currently parsed) is
+/// kept between chunks, so the reported events are the same than if the whole
+/// MPD was parsed at once.
+pub struct MPDPushProcessor<S: MpdEventSink> {
+    /// MPD data received but not processed yet.
+    pending: Vec<u8>,
+
+    /// Position in bytes in the whole MPD of the first byte of `pending`.
+    offset: usize,
+
+    /// Position in `pending` up to which data has already been scanned.
+    scan_pos: usize,
+
+    /// State of the scanner at `scan_pos`.
+    scan_state: ScanState,
+
+    reader_buf: Vec<u8>,
+    state: ProcessorState<S>,
+}
+
+/// What is being scanned when looking for the end of the last complete markup
+/// in the pending data.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ScanState {
+    /// Outside of any markup.
+    Text,
+
+    /// A `<` has been encountered at the contained position in the pending
+    /// data but not enough data is there yet to know which markup it starts.
+    MarkupStart(usize),
+
+    /// In an element's tag. Contains the quote character if inside an
+    /// attribute's value.
+    Tag(Option<u8>),
+
+    /// In a comment. Contains the number of consecutive `-` just encountered.
+    Comment(u8),
+
+    /// In a CDATA section. Contains the number of consecutive `]` just
+    /// encountered.
+    CData(u8),
+
+    /// In a processing instruction (or XML declaration). Contains `true` if
+    /// the last character encountered was a `?`.
+    ProcessingInstruction(bool),
+
+    /// In a DOCTYPE (or other `<!` markup). Contains the depth of `[` brackets
+    /// and the quote character if inside a quoted string.
+    Doctype(u32, Option<u8>),
+}
+
+impl<S: MpdEventSink> MPDPushProcessor<S> {
+    /// Creates a new MPDPushProcessor.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The `MpdEventSink` to which parsed elements and attributes
+    ///   will be reported.
+    pub fn new(sink: S) -> Self {
+        MPDPushProcessor::with_options(sink, ParsingOptions::default())
+    }
+
+    /// Creates a new MPDPushProcessor parsing the MPD according to the given
+    /// `ParsingOptions`.
+    pub fn with_options(sink: S, options: ParsingOptions) -> Self {
+        MPDPushProcessor {
+            pending: Vec::new(),
+            offset: 0,
+            scan_pos: 0,
+            scan_state: ScanState::Text,
+            reader_buf: Vec::new(),
+            state: ProcessorState::new(sink, options),
+        }
+    }
+
+    /// Resolve the `onLoad` XLinks encountered through the given
+    /// `RemoteElementLoader` (@see `MPDProcessor::set_remote_element_loader`).
+    pub fn set_remote_element_loader<L: RemoteElementLoader + 'static>(&mut self, loader: L) {
+        self.state.loader = Some(Box::new(loader));
+    }
+
+    /// Give the next chunk of the MPD document to the MPDPushProcessor.
+    ///
+    /// Every complete element it allows to obtain is directly reported to the
+    /// `MpdEventSink`. The rest is kept until the next `feed` or `finish`
+    /// call.
+    ///
+    /// In strict mode, returns the error which stopped the parsing if one was
+    /// encountered, in which case following chunks are ignored.
+    pub fn feed(&mut self, data: &[u8]) -> Result<()> {
+        if self.state.is_stopped() {
+            return self.state.status();
+        }
+        self.pending.extend_from_slice(data);
+        let processable_len = self.scan();
+        if processable_len > 0 {
+            self.process_pending(processable_len);
+        }
+        self.state.status()
+    }
+
+    /// Signal that the whole MPD has been fed, process all remaining data and
+    /// return the `MpdEventSink` it was reporting to, alongside the error
+    /// which stopped the parsing in strict mode (@see `feed`).
+    pub fn finish(mut self) -> (S, Result<()>) {
+        if !self.pending.is_empty() && !self.state.is_stopped() {
+            self.process_pending(self.pending.len());
+        }
+        self.state.process_eof(self.offset);
+        let status = self.state.status();
+        (self.state.sink.inner, status)
+    }
+
+    /// Process the first `len` bytes of pending data, which should end on an
+    /// XML markup boundary, and remove them from it.
+    fn process_pending(&mut self, len: usize) {
+        let mut reader = create_xml_reader(&self.pending[..len]);
+        process_events(
+            &mut reader,
+            &mut self.reader_buf,
+            &mut self.state,
+            self.offset,
+        );
+        self.pending.drain(..len);
+        self.offset += len;
+        self.scan_pos -= len;
+        if let ScanState::MarkupStart(ref mut start) = self.scan_state {
+            *start -= len;
+        }
+    }
+
+    /// Scan pending data not already scanned and return the position just
+    /// after the last complete XML markup found in it, `0` if none was found.
+    fn scan(&mut self) -> usize {
+        let mut last_boundary = 0;
+        let mut i = self.scan_pos;
+        while i < self.pending.len() {
+            let c = self.pending[i];
+            self.scan_state = match self.scan_state {
+                ScanState::Text => {
+                    if c == b'<' {
+                        ScanState::MarkupStart(i)
+                    } else {
+                        ScanState::Text
+                    }
+                }
+                ScanState::MarkupStart(start) => {
+                    match identify_markup(&self.pending[start..]) {
+                        None => {
+                            // Wait for more data
+                            break;
+                        }
+                        Some((state, markup_start_len)) => {
+                            i = start + markup_start_len;
+                            self.scan_state = state;
+                            continue;
+                        }
+                    }
+                }
+                ScanState::Tag(None) => match c {
+                    b'>' => {
+                        last_boundary = i + 1;
+                        ScanState::Text
+                    }
+                    b'"' | b'\'' => ScanState::Tag(Some(c)),
+                    _ => ScanState::Tag(None),
+                },
+                ScanState::Tag(Some(quote)) => {
+                    if c == quote {
+                        ScanState::Tag(None)
+                    } else {
+                        ScanState::Tag(Some(quote))
+                    }
+                }
+                ScanState::Comment(dashes) => match c {
+                    b'>' if dashes >= 2 => {
+                        last_boundary = i + 1;
+                        ScanState::Text
+                    }
+                    b'-' => ScanState::Comment(dashes.saturating_add(1)),
+                    _ => ScanState::Comment(0),
+                },
+                ScanState::CData(brackets) => match c {
+                    b'>' if brackets >= 2 => {
+                        last_boundary = i + 1;
+                        ScanState::Text
+                    }
+                    b']' => ScanState::CData(brackets.saturating_add(1)),
+                    _ => ScanState::CData(0),
+                },
+                ScanState::ProcessingInstruction(after_question_mark) => match c {
+                    b'>' if after_question_mark => {
+                        last_boundary = i + 1;
+                        ScanState::Text
+                    }
+                    _ => ScanState::ProcessingInstruction(c == b'?'),
+                },
+                ScanState::Doctype(depth, Some(quote)) => {
+                    if c == quote {
+                        ScanState::Doctype(depth, None)
+                    } else {
+                        ScanState::Doctype(depth, Some(quote))
+                    }
+                }
+                ScanState::Doctype(depth, None) => match c {
+                    b'>' if depth == 0 => {
+                        last_boundary = i + 1;
+                        ScanState::Text
+                    }
+                    b'[' => ScanState::Doctype(depth + 1, None),
+                    b']' => ScanState::Doctype(depth.saturating_sub(1), None),
+                    b'"' | b'\'' => ScanState::Doctype(depth, Some(c)),
+                    _ => ScanState::Doctype(depth, None),
+                },
+            };
+            i += 1;
+        }
+        self.scan_pos = i;
+        last_boundary
+    }
+}
+
+/// Identify which markup is started by `data`, which begins with a `<`.
+///
+/// Returns the corresponding scanning state as well as the length of the
+/// markup's opening sequence, or `None` if more data is needed to know.
+fn identify_markup(data: &[u8]) -> Option<(ScanState, usize)> {
+    const COMMENT_START: &[u8] = b"<!--";
+    const CDATA_START: &[u8] = b"<![CDATA[";
+    if data.len() < 2 {
+        return None;
+    }
+    match data[1] {
+        b'?' => Some((ScanState::ProcessingInstruction(false), 2)),
+        b'!' => {
+            if data.starts_with(COMMENT_START) {
+                Some((ScanState::Comment(0), COMMENT_START.len()))
+            } else if data.starts_with(CDATA_START) {
+                Some((ScanState::CData(0), CDATA_START.len()))
+            } else if COMMENT_START.starts_with(data) || CDATA_START.starts_with(data) {
+                None
+            } else {
+                Some((ScanState::Doctype(0, None), 2))
+            }
+        }
+        _ => Some((ScanState::Tag(None), 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ParsingError;
+    use crate::events::{AttributeName, TagName};
+    use crate::processor::MPDProcessor;
+    use crate::sink::AttrValue;
+
+    /// Records every event as a String, so they can easily be compared.
+    #[derive(Default)]
+    struct RecordingSink(Vec<String>);
+
+    impl MpdEventSink for RecordingSink {
+        fn tag_open(&mut self, tag_name: TagName) {
+            self.0.push(format!("open {:?}", tag_name));
+        }
+        fn tag_close(&mut self, tag_name: TagName) {
+            self.0.push(format!("close {:?}", tag_name));
+        }
+        fn attribute(&mut self, attr_name: AttributeName, value: AttrValue) {
+            self.0.push(format!("attr {:?} {:?}", attr_name, value));
+        }
+        fn error(&mut self, err: &ParsingError) {
+            self.0.push(format!("error {}", err));
+        }
+    }
+
+    const MPD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- A comment with <tags> and -- dashes -->
+<!DOCTYPE MPD [ <!ENTITY e "a > b"> ]>
+<MPD type="static" mediaPresentationDuration="PT10S" xmlns:cenc="urn:mpeg:cenc:2013">
+  <Location>https://example.com/é/manifest.mpd</Location>
+  <Period id="p>1" start="PT0S">
+    <BaseURL serviceLocation='a"b'>https://cdn.example.com/</BaseURL>
+    <EventStream schemeIdUri="urn:test" timescale="1000">
+      <Event presentationTime="0" duration="1000" id="1"><![CDATA[some ]] > data]]></Event>
+      <Event presentationTime="1000" duration="1000" id="2"/>
+    </EventStream>
+    <AdaptationSet mimeType="video/mp4">
+      <Label>Vidéo</Label>
+      <ContentProtection schemeIdUri="urn:uuid:1234">
+        <cenc:pssh>AAAAIHBzc2gAAAAA7e+LqXnWSs6jyCfc1R0h7QAAAAA=</cenc:pssh>
+      </ContentProtection>
+      <SegmentTemplate timescale="1000" media="$Number$.mp4">
+        <SegmentTimeline>
+          <S t="0" d="2000" r="2"/>
+          <S d="4000"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="r1" bandwidth="100000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>
+"#;
+
+    fn parse_at_once(mpd: &[u8]) -> Vec<String> {
+        let mut processor = MPDProcessor::new(mpd, RecordingSink::default());
+        processor.process_tags().unwrap();
+        processor.into_sink().0
+    }
+
+    #[test]
+    fn test_push_same_events_for_any_split() {
+        let expected = parse_at_once(MPD.as_bytes());
+        assert!(expected.len() > 30);
+        assert!(!expected.iter().any(|e| e.starts_with("error")));
+        let mpd = MPD.as_bytes();
+        for split in 0..=mpd.len() {
+            let mut processor = MPDPushProcessor::new(RecordingSink::default());
+            processor.feed(&mpd[..split]).unwrap();
+            processor.feed(&mpd[split..]).unwrap();
+            let (sink, _) = processor.finish();
+            assert_eq!(sink.0, expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_push_byte_by_byte() {
+        let expected = parse_at_once(MPD.as_bytes());
+        let mut processor = MPDPushProcessor::new(RecordingSink::default());
+        for byte in MPD.as_bytes() {
+            processor.feed(std::slice::from_ref(byte)).unwrap();
+        }
+        let (sink, _) = processor.finish();
+        assert_eq!(sink.0, expected);
+    }
+
+    #[test]
+    fn test_push_processes_complete_elements_early() {
+        let mut processor = MPDPushProcessor::new(RecordingSink::default());
+        processor
+            .feed(b"<MPD type=\"dynamic\"><Period id=\"a\"></Per")
+            .unwrap();
+        assert_eq!(
+            processor.state.sink.inner.0,
+            vec![
+                "open MPD".to_owned(),
+                "attr Type String(\"dynamic\")".to_owned(),
+                "open Period".to_owned(),
+                "attr Id String(\"a\")".to_owned(),
+            ]
+        );
+        processor
+            .feed(b"iod><SegmentTimeline><S t=\"0\" d=\"1\"/>")
+            .unwrap();
+        assert_eq!(processor.state.sink.inner.0.len(), 5);
+        processor.feed(b"</SegmentTimeline></MPD>").unwrap();
+        let (RecordingSink(events), _) = processor.finish();
+        assert_eq!(events.len(), 7);
+        assert_eq!(events[6], "close MPD");
+    }
+
+    #[test]
+    fn test_push_unexpected_eof() {
+        let mut processor = MPDPushProcessor::new(RecordingSink::default());
+        processor
+            .feed(b"<MPD><SegmentTimeline><S t=\"0\" d=\"1\"/>")
+            .unwrap();
+        let (RecordingSink(events), _) = processor.finish();
+        assert_eq!(
+            events.last().unwrap(),
+            "error MPD/SegmentTimeline: Unexpected end of file in a SegmentTimeline. (at byte 38)"
+        );
+    }
+
+    #[test]
+    fn test_push_strict_mode() {
+        let options = ParsingOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let mut processor = MPDPushProcessor::with_options(RecordingSink::default(), options);
+        processor
+            .feed(b"<MPD profiles=\"p\" minBufferTime=\"PT2S\"><Period><Representation")
+            .unwrap();
+        let err = processor.feed(b" id=\"1\"/>").unwrap_err();
+        assert_eq!(err.path, "MPD/Period/Representation[id=1]@bandwidth");
+        assert_eq!(processor.feed(b"</Period>"), Err(err.clone()));
+        let (RecordingSink(events), res) = processor.finish();
+        assert_eq!(res, Err(err));
+        assert_eq!(
+            events.last().unwrap(),
+            "error MPD/Period/Representation[id=1]@bandwidth: Missing required attribute. (at byte 47)"
+        );
+    }
+}
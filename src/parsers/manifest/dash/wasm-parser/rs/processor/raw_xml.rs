@@ -0,0 +1,65 @@
+use std::io::{BufRead, Read, Result};
+
+/// `BufRead` wrapper which may keep a copy of the bytes read through it, so
+/// the raw XML of some elements can be reported as is.
+///
+/// quick-xml only reads through `fill_buf` and `consume`, the bytes consumed
+/// while reading an event being that event's raw XML (preceded by the
+/// whitespace it skipped, as text is trimmed).
+pub(super) struct RawXmlRecorder<R: BufRead> {
+    inner: R,
+    recording: bool,
+    recorded: Vec<u8>,
+}
+
+impl<R: BufRead> RawXmlRecorder<R> {
+    pub fn new(inner: R) -> Self {
+        RawXmlRecorder {
+            inner,
+            recording: false,
+            recorded: Vec::new(),
+        }
+    }
+
+    /// Forget the bytes recorded until now, then record the bytes read from
+    /// now on only if `recording` is set.
+    pub fn restart(&mut self, recording: bool) {
+        self.recording = recording;
+        self.recorded.clear();
+    }
+
+    /// Bytes read since the last call to `restart`, if recording.
+    pub fn recorded(&self) -> &[u8] {
+        &self.recorded
+    }
+}
+
+impl<R: BufRead> Read for RawXmlRecorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for RawXmlRecorder<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.recording && amt > 0 {
+            // The data consumed was just returned by `fill_buf`, which thus
+            // returns it again without reading.
+            if let Ok(available) = self.inner.fill_buf() {
+                self.recorded.extend_from_slice(&available[..amt]);
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
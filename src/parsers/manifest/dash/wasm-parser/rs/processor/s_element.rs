@@ -1,31 +1,50 @@
-use crate::errors::{ParsingError, Result};
-use crate::utils;
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use quick_xml::events::BytesStart;
+
+use crate::sink::MpdEventSink;
+use crate::utils::{self, DurationParsingMode};
 
 /// Represents a parsed <S> node, itself in a <SegmentTimeline> node from an
 /// MPD.
 ///
-/// Attributes are defined as f64 despite being u64 to simplify Rust-to-JS
-/// communication.
-#[repr(C)] // Used in FFI
-#[derive(Debug, Clone, Copy, Default)]
+/// Attributes are kept as integers, as timestamps of live contents (e.g. with
+/// a 90kHz or 10MHz timescale) often exceed the precision of an f64.
+/// It is up to the FFI to convert them if wanted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct SegmentObject {
     /// Starting timestamp for the segment, in the corresponding Timescale
     ///
     /// This is either equivalent to the `t` attribute of an `<S>` element, or to
     /// the end of the previous one if no `t` attribute is found.
-    pub start: f64,
+    pub start: u64,
 
     /// Duration of the segment, in the corresponding Timescale.
     ///
     /// This is the data contained in the `d` attribute of an `<S>` element.
     /// If not found, it is set to `0`.
-    pub duration: f64,
+    pub duration: u64,
 
     /// Amount of time contiguous segments of the duration are encountered.
     ///
     /// This is the data contained in the `r` attribute of an `<S>` element.
     /// If not found, it is set to `0`.
-    pub repeat_count: f64,
+    ///
+    /// A negative value means that segments are repeated until the start of
+    /// the next `<S>` element or until the end of the Period (@see
+    /// `SegmentObject::resolve_repeat_counts`).
+    pub repeat_count: i64,
+
+    /// Number of the first segment described by this object.
+    ///
+    /// This is the data contained in the `n` attribute of an `<S>` element,
+    /// if found.
+    pub number: Option<u64>,
+
+    /// Number of chunks each segment is made of, for low-latency contents.
+    ///
+    /// This is the data contained in the `k` attribute of an `<S>` element,
+    /// if found.
+    pub chunk_count: Option<u64>,
 }
 
 impl SegmentObject {
@@ -35,9 +54,10 @@ impl SegmentObject {
     /// This function is called very very often on the more large MPDs based
     /// on a SegmentTimeline segment indexing scheme.
     #[inline(always)]
-    pub fn from_s_element(
-        e: &quick_xml::events::BytesStart,
-        time_base: f64,
+    pub fn from_s_element<S: MpdEventSink>(
+        e: &BytesStart,
+        time_base: u64,
+        sink: &mut S,
     ) -> Result<SegmentObject> {
         let mut segment_obj = SegmentObject::default();
         let mut has_t = false;
@@ -48,20 +68,35 @@ impl SegmentObject {
                     let key = attr.key;
                     match key.as_ref() {
                         b"t" => {
-                            segment_obj.start = utils::parse_u64(&attr.value)? as f64;
+                            segment_obj.start = utils::parse_u64(&attr.value)
+                                .map_err(|e| e.in_attribute(key.as_ref()))?;
                             has_t = true;
                         }
                         b"d" => {
-                            segment_obj.duration = utils::parse_u64(&attr.value)? as f64;
+                            segment_obj.duration = utils::parse_u64(&attr.value)
+                                .map_err(|e| e.in_attribute(key.as_ref()))?;
                         }
                         b"r" => {
                             // Note i64 instead of u64 as r can be equal to "-1"
-                            segment_obj.repeat_count = utils::parse_i64(&attr.value)? as f64;
+                            segment_obj.repeat_count = utils::parse_i64(&attr.value)
+                                .map_err(|e| e.in_attribute(key.as_ref()))?;
+                        }
+                        b"n" => {
+                            segment_obj.number = Some(
+                                utils::parse_u64(&attr.value)
+                                    .map_err(|e| e.in_attribute(key.as_ref()))?,
+                            );
+                        }
+                        b"k" => {
+                            segment_obj.chunk_count = Some(
+                                utils::parse_u64(&attr.value)
+                                    .map_err(|e| e.in_attribute(key.as_ref()))?,
+                            );
                         }
                         _ => {}
                     }
                 }
-                Err(err) => ParsingError::from(err).report_err(),
+                Err(err) => ParsingError::from(err).report_err(sink),
             };
         }
         if !has_t {
@@ -69,4 +104,210 @@ impl SegmentObject {
         }
         Ok(segment_obj)
     }
+
+    /// Returns the timestamp at which the last segment described by this
+    /// object ends, which is where the next `<S>` element starts if it has no
+    /// `t` attribute.
+    ///
+    /// A negative `repeat_count` is treated as `0`.
+    pub fn end(&self) -> u64 {
+        let count = self.repeat_count.saturating_add(1).max(1) as u64;
+        self.start
+            .saturating_add(self.duration.saturating_mul(count))
+    }
+
+    /// Returns an error if `next`, the object of the following `<S>`
+    /// element, does not start exactly where this one ends.
+    ///
+    /// Nothing is checked if this object has a negative `repeat_count`.
+    pub fn check_continuity(&self, next: &SegmentObject) -> Result<()> {
+        if self.repeat_count < 0 {
+            return Ok(());
+        }
+        let end = self.end();
+        if next.start < end {
+            Err(ParsingError::new(
+                ParsingErrorKind::TimelineOverlap,
+                format!(
+                    "Segment overlapping the previous one by {}.",
+                    end - next.start
+                ),
+            ))
+        } else if next.start > end {
+            Err(ParsingError::new(
+                ParsingErrorKind::TimelineGap,
+                format!("Gap of {} after the previous segment.", next.start - end),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Replace the negative `repeat_count` of the given objects, which
+    /// follow each other in a `<SegmentTimeline>`, by the number of times
+    /// their segment is actually repeated.
+    ///
+    /// Segments are repeated until the start of the next object or, for the
+    /// last one, until `timeline_end`: the end of the Period, expressed in
+    /// the timescale of the timeline (and thus including the
+    /// `presentationTimeOffset`).
+    /// A negative `repeat_count` which cannot be resolved, because the last
+    /// object has one and `timeline_end` is `None`, is left as is.
+    pub fn resolve_repeat_counts(segments: &mut [SegmentObject], timeline_end: Option<u64>) {
+        for i in 0..segments.len() {
+            if segments[i].repeat_count >= 0 {
+                continue;
+            }
+            let end = match segments.get(i + 1) {
+                Some(next) => Some(next.start),
+                None => timeline_end,
+            };
+            let segment = &mut segments[i];
+            match end {
+                Some(end) if segment.duration > 0 && end > segment.start => {
+                    let span = end - segment.start;
+                    let mut count = span / segment.duration;
+                    if !span.is_multiple_of(segment.duration) {
+                        count += 1;
+                    }
+                    segment.repeat_count = (count - 1).min(i64::MAX as u64) as i64;
+                }
+                Some(_) => segment.repeat_count = 0,
+                None => {}
+            }
+        }
+    }
+}
+
+/// Keeps track of the attributes needed to know where the segments of a
+/// `<SegmentTimeline>` end (@see `ParsingOptions::resolve_repeat_counts`).
+#[derive(Default)]
+pub(super) struct TimelineBounds {
+    /// @see `ParsingOptions::period_durations`
+    period_durations: Vec<Option<f64>>,
+
+    /// Number of `<Period>` elements opened until now.
+    period_count: usize,
+
+    /// Duration of the current `<Period>`, in seconds.
+    period_duration: Option<f64>,
+
+    /// Timing attributes of the `<SegmentTemplate>` elements which apply to
+    /// the `<Period>`, `<AdaptationSet>` and `<Representation>` currently
+    /// opened, from the outer one, those of a parent being inherited.
+    template_timings: Vec<TemplateTiming>,
+
+    /// `true` while in a `<SegmentList>`, whose timing attributes are not
+    /// tracked.
+    in_segment_list: bool,
+}
+
+/// `timescale` and `presentationTimeOffset` of a `<SegmentTemplate>`,
+/// possibly inherited from the one of a parent element.
+#[derive(Debug, Clone, Copy, Default)]
+struct TemplateTiming {
+    timescale: Option<u64>,
+    presentation_time_offset: Option<u64>,
+}
+
+impl TimelineBounds {
+    /// Creates a new `TimelineBounds`, with the durations of the Periods as
+    /// indicated by the caller (@see `ParsingOptions::period_durations`).
+    pub(super) fn new(period_durations: Vec<Option<f64>>) -> Self {
+        TimelineBounds {
+            period_durations,
+            ..Default::default()
+        }
+    }
+
+    /// Signal that an element has just been opened.
+    pub(super) fn enter(&mut self, tag: &BytesStart) {
+        match tag.name().as_ref() {
+            b"Period" => {
+                let supplied = self
+                    .period_durations
+                    .get(self.period_count)
+                    .copied()
+                    .flatten();
+                self.period_count += 1;
+                self.period_duration = supplied.or_else(|| {
+                    let val = find_attribute(tag, b"duration")?;
+                    utils::parse_iso_8601_duration(&val, DurationParsingMode::Lenient)
+                        .ok()
+                        .map(|duration| duration.seconds)
+                });
+                self.template_timings.clear();
+                self.template_timings.push(TemplateTiming::default());
+            }
+            b"AdaptationSet" | b"Representation" => {
+                let inherited = self.template_timings.last().copied().unwrap_or_default();
+                self.template_timings.push(inherited);
+            }
+            b"SegmentTemplate" => {
+                if self.template_timings.is_empty() {
+                    self.template_timings.push(TemplateTiming::default());
+                }
+                // Just pushed if empty
+                let timing = self.template_timings.last_mut().unwrap();
+                if let Some(timescale) =
+                    find_attribute(tag, b"timescale").and_then(|val| utils::parse_u64(&val).ok())
+                {
+                    timing.timescale = Some(timescale);
+                }
+                if let Some(pto) = find_attribute(tag, b"presentationTimeOffset")
+                    .and_then(|val| utils::parse_u64(&val).ok())
+                {
+                    timing.presentation_time_offset = Some(pto);
+                }
+            }
+            b"SegmentList" => self.in_segment_list = true,
+            _ => {}
+        }
+    }
+
+    /// Signal that an element, whose name is given, has just been closed.
+    pub(super) fn leave(&mut self, name: &[u8]) {
+        match name {
+            b"Period" => {
+                self.period_duration = None;
+                self.template_timings.clear();
+            }
+            b"AdaptationSet" | b"Representation" => {
+                self.template_timings.pop();
+            }
+            b"SegmentList" => self.in_segment_list = false,
+            _ => {}
+        }
+    }
+
+    /// Returns the end of the current Period, in the timescale of the
+    /// `<SegmentTimeline>` of the current `<SegmentTemplate>`, if known.
+    ///
+    /// Always `None` for the timeline of a `<SegmentList>`.
+    ///
+    /// Its `timescale` and `presentationTimeOffset` can be inherited from
+    /// the `<SegmentTemplate>` of a parent element, and default to `1` and
+    /// `0` as defined by the DASH specification.
+    pub(super) fn timeline_end(&self) -> Option<u64> {
+        if self.in_segment_list {
+            return None;
+        }
+        let duration = self.period_duration?;
+        let timing = self.template_timings.last().copied().unwrap_or_default();
+        let timescale = timing.timescale.unwrap_or(1);
+        let pto = timing.presentation_time_offset.unwrap_or(0);
+        let scaled = (duration * timescale as f64).round();
+        if scaled.is_finite() && scaled >= 0. {
+            Some(pto.saturating_add(scaled as u64))
+        } else {
+            None
+        }
+    }
+}
+
+fn find_attribute<'a>(tag: &'a BytesStart, name: &[u8]) -> Option<std::borrow::Cow<'a, [u8]>> {
+    tag.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name)
+        .map(|attr| attr.value)
 }
@@ -0,0 +1,123 @@
+use quick_xml::events::BytesStart;
+
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::utils::{self, DurationParsingMode};
+
+/// Checks performed in strict mode on top of the parsing itself, to reject
+/// MPDs not conforming to the DASH specification.
+///
+/// Elements are checked before being reported, so that the first violation
+/// encountered stops the parsing.
+#[derive(Default)]
+pub(super) struct Validator {
+    /// For each `<SegmentTemplate>` element currently open, `true` if it has
+    /// a `duration` attribute.
+    segment_templates: Vec<bool>,
+}
+
+impl Validator {
+    /// Check an element which has just been opened.
+    pub(super) fn validate_start(&mut self, tag: &BytesStart) -> Result<()> {
+        let name = tag.name();
+        let name = name.as_ref();
+        // A `<ContentProtection>` referencing another one gets its
+        // attributes from it.
+        let is_reference = name == b"ContentProtection" && has_attribute(tag, b"ref");
+        for attr_name in required_attributes(name) {
+            if !is_reference && !has_attribute(tag, attr_name) {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::MissingAttribute,
+                    "Missing required attribute.",
+                )
+                .in_attribute(attr_name));
+            }
+        }
+        for attr in tag.attributes() {
+            let attr = attr?;
+            let key = attr.key.as_ref();
+            if duration_attributes(name).contains(&key) {
+                utils::parse_iso_8601_duration(&attr.value, DurationParsingMode::Strict)
+                    .map_err(|e| e.in_attribute(key))?;
+            }
+        }
+
+        match name {
+            b"MPD" => {
+                let mpd_type = tag
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.as_ref() == b"type");
+                if let Some(mpd_type) = mpd_type {
+                    if !matches!(mpd_type.value.as_ref(), b"static" | b"dynamic") {
+                        return Err(ParsingError::new(
+                            ParsingErrorKind::UnknownValue,
+                            format!(
+                                "Unknown MPD type: {}",
+                                String::from_utf8_lossy(&mpd_type.value)
+                            ),
+                        )
+                        .in_attribute(b"type"));
+                    }
+                }
+            }
+            b"SegmentTemplate" => self.segment_templates.push(has_attribute(tag, b"duration")),
+            b"SegmentTimeline" if self.segment_templates.last() == Some(&true) => {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::InvalidStructure,
+                    "A SegmentTemplate cannot have both a duration and a SegmentTimeline.",
+                ));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Signal that an element, whose name is given, has just been closed.
+    pub(super) fn validate_end(&mut self, name: &[u8]) {
+        if name == b"SegmentTemplate" {
+            self.segment_templates.pop();
+        }
+    }
+}
+
+fn has_attribute(tag: &BytesStart, name: &[u8]) -> bool {
+    tag.attributes()
+        .flatten()
+        .any(|attr| attr.key.as_ref() == name)
+}
+
+/// Returns the names of the attributes an element should always have.
+fn required_attributes(name: &[u8]) -> &'static [&'static [u8]] {
+    match name {
+        b"MPD" => &[b"profiles", b"minBufferTime"],
+        b"Representation" => &[b"id", b"bandwidth"],
+        b"Preselection" => &[b"preselectionComponents"],
+        b"Accessibility"
+        | b"ContentProtection"
+        | b"EssentialProperty"
+        | b"EventStream"
+        | b"InbandEventStream"
+        | b"Role"
+        | b"SupplementalProperty"
+        | b"UTCTiming" => &[b"schemeIdUri"],
+        b"S" => &[b"d"],
+        _ => &[],
+    }
+}
+
+/// Returns the names of an element's attributes which are durations.
+fn duration_attributes(name: &[u8]) -> &'static [&'static [u8]] {
+    match name {
+        b"MPD" => &[
+            b"mediaPresentationDuration",
+            b"minimumUpdatePeriod",
+            b"minBufferTime",
+            b"timeShiftBufferDepth",
+            b"suggestedPresentationDelay",
+            b"maxSegmentDuration",
+            b"maxSubsegmentDuration",
+        ],
+        b"Period" => &[b"start", b"duration"],
+        _ => &[],
+    }
+}
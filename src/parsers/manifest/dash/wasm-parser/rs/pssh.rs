@@ -0,0 +1,158 @@
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::isobmff::{BoxHeader, BoxReader};
+
+/// A Protection System Specific Header (`pssh`) ISOBMFF box, as defined by
+/// ISO/IEC 23001-7 and found, base64-encoded, in `<cenc:pssh>` elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsshBox {
+    /// Version of the box: `0`, or `1` if it lists key ids.
+    pub version: u8,
+
+    /// Identifies the DRM system the box is for.
+    pub system_id: [u8; 16],
+
+    /// Key ids the box concerns. Always empty for version `0`.
+    pub key_ids: Vec<[u8; 16]>,
+
+    /// DRM system-specific data.
+    pub data: Vec<u8>,
+}
+
+impl PsshBox {
+    /// Parse the given data, which should contain a single `pssh` box.
+    pub fn parse(data: &[u8]) -> Result<PsshBox> {
+        let header = BoxHeader::read(data, 0, ParsingErrorKind::InvalidPssh)?;
+        if &header.box_type != b"pssh" {
+            return Err(pssh_error(format!(
+                "Not a pssh box: type is {:?}.",
+                String::from_utf8_lossy(&header.box_type)
+            )));
+        }
+        if header.size != data.len() {
+            return Err(pssh_error(format!(
+                "The box size ({}) does not match its data length ({}).",
+                header.size,
+                data.len()
+            )));
+        }
+        let mut cursor = BoxReader::new(data, header.header_size, ParsingErrorKind::InvalidPssh);
+        let version = cursor.read_bytes(4, "version and flags")?[0];
+        if version > 1 {
+            return Err(pssh_error(format!("Unknown version: {}.", version)));
+        }
+        let system_id = cursor.read_id("SystemID")?;
+        let mut key_ids = Vec::new();
+        if version == 1 {
+            let kid_count = cursor.read_u32("KID_count")?;
+            for _ in 0..kid_count {
+                key_ids.push(cursor.read_id("KID")?);
+            }
+        }
+        let data_size = cursor.read_u32("DataSize")?;
+        let box_data = cursor.read_bytes(data_size as usize, "Data")?.to_vec();
+        if cursor.pos != data.len() {
+            return Err(pssh_error(format!(
+                "{} unexpected bytes after Data.",
+                data.len() - cursor.pos
+            )));
+        }
+        Ok(PsshBox {
+            version,
+            system_id,
+            key_ids,
+            data: box_data,
+        })
+    }
+}
+
+fn pssh_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidPssh, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Widevine's SystemID.
+    const SYSTEM_ID: [u8; 16] = [
+        0xed, 0xef, 0x8b, 0xa9, 0x79, 0xd6, 0x4a, 0xce, 0xa3, 0xc8, 0x27, 0xdc, 0xd5, 0x1d, 0x21,
+        0xed,
+    ];
+
+    fn build_box(version: u8, key_ids: &[[u8; 16]], data: &[u8]) -> Vec<u8> {
+        let mut pssh = Vec::new();
+        pssh.extend(b"pssh");
+        pssh.extend([version, 0, 0, 0]);
+        pssh.extend(SYSTEM_ID);
+        if version == 1 {
+            pssh.extend((key_ids.len() as u32).to_be_bytes());
+            key_ids.iter().for_each(|kid| pssh.extend(kid));
+        }
+        pssh.extend((data.len() as u32).to_be_bytes());
+        pssh.extend(data);
+        let mut with_size = ((pssh.len() + 4) as u32).to_be_bytes().to_vec();
+        with_size.extend(pssh);
+        with_size
+    }
+
+    #[test]
+    fn test_parse_pssh() {
+        let pssh = PsshBox::parse(&build_box(0, &[], b"abc")).unwrap();
+        assert_eq!(
+            pssh,
+            PsshBox {
+                version: 0,
+                system_id: SYSTEM_ID,
+                key_ids: vec![],
+                data: b"abc".to_vec(),
+            }
+        );
+
+        let key_ids = [[1; 16], [2; 16]];
+        let pssh = PsshBox::parse(&build_box(1, &key_ids, b"")).unwrap();
+        assert_eq!(pssh.version, 1);
+        assert_eq!(pssh.key_ids, key_ids.to_vec());
+        assert!(pssh.data.is_empty());
+    }
+
+    #[test]
+    fn test_reject_malformed_pssh() {
+        let valid = build_box(1, &[[1; 16]], b"abc");
+        let error = |data: &[u8]| {
+            let err = PsshBox::parse(data).unwrap_err();
+            assert_eq!(err.kind, ParsingErrorKind::InvalidPssh);
+            err.message
+        };
+
+        assert_eq!(
+            error(&valid[..6]),
+            "Truncated box: type needs 4 bytes at offset 4 but only 2 remain."
+        );
+        let mut wrong_type = valid.clone();
+        wrong_type[4..8].copy_from_slice(b"moov");
+        assert_eq!(error(&wrong_type), "Not a pssh box: type is \"moov\".");
+        assert_eq!(
+            error(&valid[..valid.len() - 1]),
+            "Invalid size for the \"pssh\" box at offset 0: 55 bytes, while 54 remain."
+        );
+        let mut shorter = valid.clone();
+        shorter[0..4].copy_from_slice(&54u32.to_be_bytes());
+        assert_eq!(
+            error(&shorter),
+            "The box size (54) does not match its data length (55)."
+        );
+        let mut wrong_version = valid.clone();
+        wrong_version[8] = 2;
+        assert_eq!(error(&wrong_version), "Unknown version: 2.");
+        let mut too_many_kids = valid.clone();
+        too_many_kids[31] = 2;
+        assert_eq!(
+            error(&too_many_kids),
+            "Truncated box: KID needs 16 bytes at offset 48 but only 7 remain."
+        );
+        let mut trailing = valid;
+        trailing[0..4].copy_from_slice(&56u32.to_be_bytes());
+        trailing.push(0);
+        assert_eq!(error(&trailing), "1 unexpected bytes after Data.");
+    }
+}
@@ -10,7 +10,7 @@ impl Read for MPDReader {
         // Hopefully, the JavaScript-side should just put data maximum until
         // `buf.len()`.
         unsafe {
-            actual_size = super::readNext((*buf).as_ptr(), buf.len());
+            actual_size = crate::ffi::readNext((*buf).as_ptr(), buf.len());
         }
         Ok(actual_size)
     }
@@ -0,0 +1,98 @@
+use crate::errors::ParsingError;
+use crate::events::{AttributeName, TagName};
+use crate::processor::SegmentObject;
+
+/// Value of a parsed attribute, as reported to an `MpdEventSink`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrValue<'a> {
+    /// An UTF-8 string, e.g. an `id` attribute or the text of a `<BaseURL>`
+    /// element.
+    String(&'a str),
+
+    /// A number, for both floating point and integer values.
+    F64(f64),
+
+    /// An integer which has to stay exact even when exceeding the precision
+    /// of an f64, such as a timestamp.
+    U64(u64),
+
+    /// A boolean value.
+    Bool(bool),
+
+    /// A byte range, with first the starting byte and then the ending byte
+    /// (both included).
+    Range(f64, f64),
+
+    /// All `<S>` elements parsed in a `<SegmentTimeline>` element.
+    Timeline(&'a [SegmentObject]),
+
+    /// A key-value couple, such as an XML namespace's name and its URI.
+    KeyValue(&'a str, &'a str),
+
+    /// Binary data, such as a decoded base64 value.
+    Bytes(&'a [u8]),
+}
+
+impl<'a> From<&'a str> for AttrValue<'a> {
+    fn from(val: &'a str) -> Self {
+        AttrValue::String(val)
+    }
+}
+
+impl From<f64> for AttrValue<'_> {
+    fn from(val: f64) -> Self {
+        AttrValue::F64(val)
+    }
+}
+
+impl From<u64> for AttrValue<'_> {
+    fn from(val: u64) -> Self {
+        AttrValue::U64(val)
+    }
+}
+
+impl From<bool> for AttrValue<'_> {
+    fn from(val: bool) -> Self {
+        AttrValue::Bool(val)
+    }
+}
+
+impl From<(f64, f64)> for AttrValue<'_> {
+    fn from(val: (f64, f64)) -> Self {
+        AttrValue::Range(val.0, val.1)
+    }
+}
+
+impl<'a> From<&'a [SegmentObject]> for AttrValue<'a> {
+    fn from(val: &'a [SegmentObject]) -> Self {
+        AttrValue::Timeline(val)
+    }
+}
+
+/// Trait implemented by the consumers of the events produced by the
+/// `MPDProcessor` as it browses an MPD.
+///
+/// When running as WebAssembly, the implementation used forwards each of
+/// those events to the corresponding JS callback. Other implementations can
+/// be used when the parser is used as a native Rust library, for example to
+/// record the reported events in tests.
+pub trait MpdEventSink {
+    /// Called each time a new known tag is encountered in the MPD.
+    fn tag_open(&mut self, tag_name: TagName);
+
+    /// Called each time a previously-opened known tag is now closed.
+    fn tag_close(&mut self, tag_name: TagName);
+
+    /// Called when a new attribute has been parsed in the last encountered
+    /// element.
+    fn attribute(&mut self, attr_name: AttributeName, value: AttrValue);
+
+    /// Called when a minor error has been encountered while parsing the MPD
+    /// (e.g. invalid attributes' data). Parsing continues after that.
+    fn error(&mut self, err: &ParsingError);
+
+    /// Called to log various information, for example for debugging.
+    ///
+    /// Does nothing by default.
+    fn log(&mut self, _message: &str) {}
+}
@@ -0,0 +1,283 @@
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+use crate::json::JsonValue;
+use crate::model::BaseUrl;
+use crate::url::{with_host, with_query_params};
+
+/// Default `TTL` of a steering manifest, in seconds.
+pub const DEFAULT_STEERING_TTL: f64 = 300.;
+
+/// A DASH-IF Content Steering manifest, the JSON document returned by the
+/// steering server indicated by the MPD's `<ContentSteering>` element.
+///
+/// Pathways are identified by the `serviceLocation` attribute of the
+/// `<BaseURL>` elements they correspond to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteeringManifest {
+    /// `VERSION` of the manifest, always `1`.
+    pub version: u64,
+
+    /// `TTL`: number of seconds after which the manifest should be reloaded.
+    /// `DEFAULT_STEERING_TTL` if not indicated.
+    pub ttl: f64,
+
+    /// `RELOAD-URI`: URL, possibly relative to the one the manifest was
+    /// loaded from, at which it should be reloaded.
+    pub reload_uri: Option<String>,
+
+    /// `PATHWAY-PRIORITY`: pathways to use, from the preferred one.
+    pub pathway_priority: Vec<String>,
+
+    /// `PATHWAY-CLONES`: pathways to create by copying existing ones.
+    pub pathway_clones: Vec<PathwayClone>,
+}
+
+/// A pathway created from another one, through a steering manifest's
+/// `PATHWAY-CLONES`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathwayClone {
+    /// `BASE-ID`: pathway whose BaseURLs are copied.
+    pub base_id: String,
+
+    /// `ID`: pathway created.
+    pub id: String,
+
+    /// `URI-REPLACEMENT`: changes made to the copied BaseURLs.
+    pub uri_replacement: UriReplacement,
+}
+
+/// `URI-REPLACEMENT` of a `PathwayClone`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UriReplacement {
+    /// `HOST`: host replacing the one of the URLs.
+    pub host: Option<String>,
+
+    /// `PARAMS`: query parameters set on the URLs, in the order they are
+    /// written.
+    pub params: Vec<(String, String)>,
+}
+
+impl SteeringManifest {
+    /// Parse the given steering manifest.
+    pub fn parse(data: &[u8]) -> Result<SteeringManifest> {
+        let root = JsonValue::parse(data).map_err(steering_error)?;
+        if !matches!(root, JsonValue::Object(_)) {
+            return Err(steering_error("The manifest is not a JSON object."));
+        }
+
+        let version = match root.get("VERSION") {
+            Some(JsonValue::Number(version)) if *version == 1. => 1,
+            Some(JsonValue::Number(version)) => {
+                return Err(steering_error(format!("Unknown VERSION: {}.", version)))
+            }
+            _ => return Err(steering_error("Missing or invalid VERSION.")),
+        };
+        let ttl = match root.get("TTL") {
+            None => DEFAULT_STEERING_TTL,
+            Some(JsonValue::Number(ttl)) if *ttl >= 0. => *ttl,
+            Some(_) => return Err(steering_error("TTL is not a positive number.")),
+        };
+        let reload_uri = match root.get("RELOAD-URI") {
+            None => None,
+            Some(JsonValue::String(uri)) => Some(uri.clone()),
+            Some(_) => return Err(steering_error("RELOAD-URI is not a string.")),
+        };
+        let pathway_priority = match root.get("PATHWAY-PRIORITY") {
+            Some(JsonValue::Array(ids)) => ids
+                .iter()
+                .map(|id| string_member(Some(id), "PATHWAY-PRIORITY element"))
+                .collect::<Result<Vec<String>>>()?,
+            _ => return Err(steering_error("Missing or invalid PATHWAY-PRIORITY.")),
+        };
+        let pathway_clones = match root.get("PATHWAY-CLONES") {
+            None => Vec::new(),
+            Some(JsonValue::Array(clones)) => clones
+                .iter()
+                .map(PathwayClone::from_json)
+                .collect::<Result<Vec<PathwayClone>>>()?,
+            Some(_) => return Err(steering_error("PATHWAY-CLONES is not an array.")),
+        };
+        Ok(SteeringManifest {
+            version,
+            ttl,
+            reload_uri,
+            pathway_priority,
+            pathway_clones,
+        })
+    }
+
+    /// Returns the BaseURLs of the pathways created by the manifest's
+    /// `PATHWAY-CLONES`, from the given ones.
+    ///
+    /// Each clone copies the BaseURLs whose `serviceLocation` is its
+    /// `BASE-ID`, which may be a pathway created by a previous clone, giving
+    /// them its `ID` as `serviceLocation` and applying its `URI-REPLACEMENT`
+    /// to them. Clones whose `ID` is an existing pathway are ignored.
+    pub fn clone_base_urls(&self, base_urls: &[BaseUrl]) -> Vec<BaseUrl> {
+        let mut clones: Vec<BaseUrl> = Vec::new();
+        for clone in &self.pathway_clones {
+            let exists = |base_url: &BaseUrl| {
+                base_url.service_location.as_deref() == Some(clone.id.as_str())
+            };
+            if base_urls.iter().chain(&clones).any(exists) {
+                continue;
+            }
+            let cloned: Vec<BaseUrl> = base_urls
+                .iter()
+                .chain(&clones)
+                .filter(|base_url| {
+                    base_url.service_location.as_deref() == Some(clone.base_id.as_str())
+                })
+                .map(|base_url| BaseUrl {
+                    value: clone.uri_replacement.apply(&base_url.value),
+                    service_location: Some(clone.id.clone()),
+                })
+                .collect();
+            clones.extend(cloned);
+        }
+        clones
+    }
+}
+
+impl PathwayClone {
+    fn from_json(value: &JsonValue) -> Result<PathwayClone> {
+        if !matches!(value, JsonValue::Object(_)) {
+            return Err(steering_error("A PATHWAY-CLONES element is not an object."));
+        }
+        let uri_replacement = match value.get("URI-REPLACEMENT") {
+            Some(replacement @ JsonValue::Object(_)) => UriReplacement::from_json(replacement)?,
+            _ => return Err(steering_error("Missing or invalid URI-REPLACEMENT.")),
+        };
+        Ok(PathwayClone {
+            base_id: string_member(value.get("BASE-ID"), "BASE-ID")?,
+            id: string_member(value.get("ID"), "ID")?,
+            uri_replacement,
+        })
+    }
+}
+
+impl UriReplacement {
+    fn from_json(value: &JsonValue) -> Result<UriReplacement> {
+        let host = match value.get("HOST") {
+            None => None,
+            host => Some(string_member(host, "HOST")?),
+        };
+        let params = match value.get("PARAMS") {
+            None => Vec::new(),
+            Some(JsonValue::Object(params)) => params
+                .iter()
+                .map(|(name, value)| {
+                    Ok((name.clone(), string_member(Some(value), "PARAMS value")?))
+                })
+                .collect::<Result<Vec<(String, String)>>>()?,
+            Some(_) => return Err(steering_error("PARAMS is not an object.")),
+        };
+        Ok(UriReplacement { host, params })
+    }
+
+    /// Apply those replacements to the given URL.
+    pub fn apply(&self, url: &str) -> String {
+        let url = match &self.host {
+            Some(host) => with_host(url, host),
+            None => url.to_owned(),
+        };
+        with_query_params(&url, &self.params)
+    }
+}
+
+fn string_member(value: Option<&JsonValue>, name: &str) -> Result<String> {
+    match value {
+        Some(JsonValue::String(val)) => Ok(val.clone()),
+        _ => Err(steering_error(format!("Missing or invalid {}.", name))),
+    }
+}
+
+fn steering_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidSteeringManifest, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_url(url: &str, service_location: &str) -> BaseUrl {
+        BaseUrl {
+            value: url.to_owned(),
+            service_location: Some(service_location.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_parse_steering_manifest() {
+        let manifest = SteeringManifest::parse(
+            br#"{
+                "VERSION": 1,
+                "TTL": 60,
+                "RELOAD-URI": "steering?session=1",
+                "PATHWAY-PRIORITY": ["cdn-c", "cdn-a", "cdn-b"],
+                "PATHWAY-CLONES": [{
+                    "BASE-ID": "cdn-a",
+                    "ID": "cdn-c",
+                    "URI-REPLACEMENT": {
+                        "HOST": "cdn-c.example.com",
+                        "PARAMS": { "token": "abc" }
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            SteeringManifest {
+                version: 1,
+                ttl: 60.,
+                reload_uri: Some("steering?session=1".to_owned()),
+                pathway_priority: vec!["cdn-c".into(), "cdn-a".into(), "cdn-b".into()],
+                pathway_clones: vec![PathwayClone {
+                    base_id: "cdn-a".to_owned(),
+                    id: "cdn-c".to_owned(),
+                    uri_replacement: UriReplacement {
+                        host: Some("cdn-c.example.com".to_owned()),
+                        params: vec![("token".to_owned(), "abc".to_owned())],
+                    },
+                }],
+            }
+        );
+
+        let base_urls = [
+            base_url("https://cdn-a.example.com/live/", "cdn-a"),
+            base_url("https://cdn-b.example.com/live/", "cdn-b"),
+        ];
+        assert_eq!(
+            manifest.clone_base_urls(&base_urls),
+            [base_url(
+                "https://cdn-c.example.com/live/?token=abc",
+                "cdn-c"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_reject_invalid_steering_manifest() {
+        let error = |data: &str| {
+            let err = SteeringManifest::parse(data.as_bytes()).unwrap_err();
+            assert_eq!(err.kind, ParsingErrorKind::InvalidSteeringManifest);
+            err.message
+        };
+        assert_eq!(
+            error(r#"{"VERSION": 2, "PATHWAY-PRIORITY": []}"#),
+            "Unknown VERSION: 2."
+        );
+        assert_eq!(
+            error(r#"{"VERSION": 1}"#),
+            "Missing or invalid PATHWAY-PRIORITY."
+        );
+        assert_eq!(
+            error(r#"{"VERSION": 1, "PATHWAY-PRIORITY": ["a"], "PATHWAY-CLONES": [{"ID": "b"}]}"#),
+            "Missing or invalid URI-REPLACEMENT."
+        );
+        assert_eq!(
+            error("[1,"),
+            "Invalid JSON at offset 3: unexpected end of data."
+        );
+    }
+}
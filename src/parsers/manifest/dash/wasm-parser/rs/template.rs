@@ -0,0 +1,236 @@
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
+
+/// A compiled URL template, as found in the `media`, `initialization`,
+/// `index` and `bitstreamSwitching` attributes of a `<SegmentTemplate>`
+/// element.
+///
+/// Templates may contain the following identifiers, replaced when expanded:
+///   - `$RepresentationID$`
+///   - `$Number$`, `$Bandwidth$`, `$Time$` and `$SubNumber$`, which may
+///     include a width format tag such as in `$Number%05d$`
+///   - `$$`, which is an escaped `$` character
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+/// An identifier which can be used in a `UrlTemplate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateIdentifier {
+    RepresentationId,
+    Number,
+    Bandwidth,
+    Time,
+    SubNumber,
+}
+
+/// Values substituted to the identifiers of a `UrlTemplate`.
+///
+/// Values left to `None` cannot be used by the expanded template.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TemplateValues<'a> {
+    pub representation_id: Option<&'a str>,
+    pub bandwidth: Option<u64>,
+    pub number: Option<u64>,
+    pub time: Option<u64>,
+    pub sub_number: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Identifier {
+        identifier: TemplateIdentifier,
+        /// Minimum number of digits, from the `%0[width]d` format tag.
+        width: usize,
+    },
+}
+
+impl TemplateIdentifier {
+    fn from_name(name: &str) -> Option<TemplateIdentifier> {
+        match name {
+            "RepresentationID" => Some(TemplateIdentifier::RepresentationId),
+            "Number" => Some(TemplateIdentifier::Number),
+            "Bandwidth" => Some(TemplateIdentifier::Bandwidth),
+            "Time" => Some(TemplateIdentifier::Time),
+            "SubNumber" => Some(TemplateIdentifier::SubNumber),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TemplateIdentifier::RepresentationId => "RepresentationID",
+            TemplateIdentifier::Number => "Number",
+            TemplateIdentifier::Bandwidth => "Bandwidth",
+            TemplateIdentifier::Time => "Time",
+            TemplateIdentifier::SubNumber => "SubNumber",
+        }
+    }
+}
+
+impl UrlTemplate {
+    /// Compile the given template, returning an error if it contains an
+    /// unknown identifier, an invalid format tag or an unterminated one.
+    pub fn compile(template: &str) -> Result<UrlTemplate> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('$') {
+            literal.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let end = after
+                .find('$')
+                .ok_or_else(|| template_error("Unterminated identifier."))?;
+            let content = &after[..end];
+            rest = &after[end + 1..];
+            if content.is_empty() {
+                literal.push('$');
+                continue;
+            }
+
+            let (name, format) = match content.find('%') {
+                Some(pos) => (&content[..pos], Some(&content[pos..])),
+                None => (content, None),
+            };
+            let identifier = TemplateIdentifier::from_name(name)
+                .ok_or_else(|| template_error(format!("Unknown identifier: ${}$.", content)))?;
+            let width = match format {
+                None => 1,
+                Some(_) if identifier == TemplateIdentifier::RepresentationId => {
+                    return Err(template_error(
+                        "The RepresentationID identifier cannot have a format tag.",
+                    ));
+                }
+                Some(format) => parse_width(format)?,
+            };
+            if !literal.is_empty() {
+                parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(TemplatePart::Identifier { identifier, width });
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(UrlTemplate { parts })
+    }
+
+    /// Returns `true` if the given identifier is used by this template.
+    pub fn uses(&self, identifier: TemplateIdentifier) -> bool {
+        self.parts.iter().any(|part| match part {
+            TemplatePart::Identifier { identifier: id, .. } => *id == identifier,
+            TemplatePart::Literal(_) => false,
+        })
+    }
+
+    /// Replace the identifiers of this template by the given values.
+    ///
+    /// Returns an error if an identifier used has no value.
+    pub fn expand(&self, values: &TemplateValues) -> Result<String> {
+        let mut url = String::new();
+        for part in &self.parts {
+            let (identifier, width) = match part {
+                TemplatePart::Literal(literal) => {
+                    url.push_str(literal);
+                    continue;
+                }
+                TemplatePart::Identifier { identifier, width } => (*identifier, *width),
+            };
+            let value = match identifier {
+                TemplateIdentifier::RepresentationId => {
+                    if let Some(id) = values.representation_id {
+                        url.push_str(id);
+                        continue;
+                    }
+                    None
+                }
+                TemplateIdentifier::Number => values.number,
+                TemplateIdentifier::Bandwidth => values.bandwidth,
+                TemplateIdentifier::Time => values.time,
+                TemplateIdentifier::SubNumber => values.sub_number,
+            };
+            match value {
+                Some(value) => url.push_str(&format!("{:0width$}", value, width = width)),
+                None => {
+                    return Err(template_error(format!(
+                        "No value for the {} identifier.",
+                        identifier.name()
+                    )))
+                }
+            }
+        }
+        Ok(url)
+    }
+}
+
+/// Parse a format tag such as `%05d`, returning its width.
+fn parse_width(format: &str) -> Result<usize> {
+    let invalid = || template_error(format!("Invalid format tag: {}.", format));
+    let digits = format
+        .strip_prefix("%0")
+        .and_then(|f| f.strip_suffix('d'))
+        .ok_or_else(invalid)?;
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    match digits.parse::<usize>() {
+        // Limit the width to avoid allocating huge strings
+        Ok(width) if width <= 64 => Ok(width),
+        _ => Err(invalid()),
+    }
+}
+
+fn template_error<T: Into<String>>(message: T) -> ParsingError {
+    ParsingError::new(ParsingErrorKind::InvalidTemplate, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_templates() {
+        let values = TemplateValues {
+            representation_id: Some("video-1"),
+            bandwidth: Some(500000),
+            number: Some(42),
+            time: Some(9007199254740993),
+            sub_number: Some(3),
+        };
+        let expand = |template: &str| UrlTemplate::compile(template).unwrap().expand(&values);
+        assert_eq!(
+            expand("$RepresentationID$/$Bandwidth$/seg-$Number%05d$.m4s").unwrap(),
+            "video-1/500000/seg-00042.m4s"
+        );
+        assert_eq!(
+            expand("$Time$_$SubNumber%03d$.mp4").unwrap(),
+            "9007199254740993_003.mp4"
+        );
+        assert_eq!(expand("a$$b$$$Number%01d$").unwrap(), "a$b$42");
+        assert_eq!(expand("$Number%02d$").unwrap(), "42");
+        assert_eq!(expand("init.mp4").unwrap(), "init.mp4");
+
+        let template = UrlTemplate::compile("$Time$.m4s").unwrap();
+        assert!(template.uses(TemplateIdentifier::Time));
+        assert!(!template.uses(TemplateIdentifier::Number));
+        let err = template.expand(&TemplateValues::default()).unwrap_err();
+        assert_eq!(err.kind, ParsingErrorKind::InvalidTemplate);
+    }
+
+    #[test]
+    fn test_reject_invalid_templates() {
+        for template in &[
+            "$Number",
+            "$Foo$",
+            "$RepresentationID%05d$",
+            "$Number%5d$",
+            "$Number%05x$",
+            "$Number%0d$",
+            "$Number%0999d$",
+        ] {
+            let err = UrlTemplate::compile(template).unwrap_err();
+            assert_eq!(err.kind, ParsingErrorKind::InvalidTemplate, "{}", template);
+        }
+    }
+}
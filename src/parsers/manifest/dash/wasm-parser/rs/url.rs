@@ -0,0 +1,369 @@
+//! Resolution of relative URLs, as defined by RFC 3986.
+
+/// Components of a URL reference (RFC 3986, section 3).
+struct UrlParts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> UrlParts<'a> {
+    fn parse(url: &'a str) -> UrlParts<'a> {
+        let (rest, fragment) = match url.find('#') {
+            Some(pos) => (&url[..pos], Some(&url[pos + 1..])),
+            None => (url, None),
+        };
+        let (rest, query) = match rest.find('?') {
+            Some(pos) => (&rest[..pos], Some(&rest[pos + 1..])),
+            None => (rest, None),
+        };
+        let (scheme, rest) = match rest.find(':') {
+            Some(pos) if is_scheme(&rest[..pos]) => (Some(&rest[..pos]), &rest[pos + 1..]),
+            _ => (None, rest),
+        };
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => match rest.find('/') {
+                Some(pos) => (Some(&rest[..pos]), &rest[pos..]),
+                None => (Some(rest), ""),
+            },
+            None => (None, rest),
+        };
+        UrlParts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+impl<'a> UrlParts<'a> {
+    fn to_url(&self) -> String {
+        let mut url = String::new();
+        if let Some(scheme) = self.scheme {
+            url.push_str(scheme);
+            url.push(':');
+        }
+        if let Some(authority) = self.authority {
+            url.push_str("//");
+            url.push_str(authority);
+        }
+        url.push_str(self.path);
+        if let Some(query) = self.query {
+            url.push('?');
+            url.push_str(query);
+        }
+        if let Some(fragment) = self.fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        url
+    }
+}
+
+fn is_scheme(val: &str) -> bool {
+    let mut chars = val.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+}
+
+/// Returns `true` if the given URL reference has a scheme, and thus does not
+/// depend on a base URL.
+pub fn is_absolute_url(url: &str) -> bool {
+    UrlParts::parse(url).scheme.is_some()
+}
+
+/// Resolve the URL reference `reference` against the base URL `base`
+/// (RFC 3986, section 5.2).
+pub fn resolve_url(base: &str, reference: &str) -> String {
+    let base = UrlParts::parse(base);
+    let reference = UrlParts::parse(reference);
+    let (scheme, authority, path, query) = if reference.scheme.is_some() {
+        (
+            reference.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.authority.is_some() {
+        (
+            base.scheme,
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.scheme,
+            base.authority,
+            base.path.to_owned(),
+            reference.query.or(base.query),
+        )
+    } else if reference.path.starts_with('/') {
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else {
+        let merged = if base.authority.is_some() && base.path.is_empty() {
+            format!("/{}", reference.path)
+        } else {
+            let dir_end = base.path.rfind('/').map_or(0, |pos| pos + 1);
+            format!("{}{}", &base.path[..dir_end], reference.path)
+        };
+        (
+            base.scheme,
+            base.authority,
+            remove_dot_segments(&merged),
+            reference.query,
+        )
+    };
+
+    let mut url = String::new();
+    if let Some(scheme) = scheme {
+        url.push_str(scheme);
+        url.push(':');
+    }
+    if let Some(authority) = authority {
+        url.push_str("//");
+        url.push_str(authority);
+    }
+    url.push_str(&path);
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    if let Some(fragment) = reference.fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+/// Returns `url` with its host replaced by `host`.
+///
+/// Its user information and port are kept, unless `host` indicates a port.
+/// URLs without authority are returned as is.
+pub fn with_host(url: &str, host: &str) -> String {
+    let parts = UrlParts::parse(url);
+    let authority = match parts.authority {
+        Some(authority) => authority,
+        None => return url.to_owned(),
+    };
+    let (user_info, host_port) = match authority.rfind('@') {
+        Some(pos) => (&authority[..=pos], &authority[pos + 1..]),
+        None => ("", authority),
+    };
+    let port = if port_position(host).is_some() {
+        ""
+    } else {
+        port_position(host_port).map_or("", |pos| &host_port[pos..])
+    };
+    let authority = format!("{}{}{}", user_info, host, port);
+    UrlParts {
+        authority: Some(&authority),
+        ..parts
+    }
+    .to_url()
+}
+
+/// Returns the position of the `:` preceding the port of a `host[:port]`
+/// string, if any.
+fn port_position(host_port: &str) -> Option<usize> {
+    // The host may be an IPv6 address between brackets, containing `:`
+    let host_end = host_port.rfind(']').unwrap_or(0);
+    host_port[host_end..].find(':').map(|pos| pos + host_end)
+}
+
+/// Returns `url` with each of the given query parameters set: the first
+/// existing parameter with the same name gets the new value (the other ones
+/// being removed), or else the parameter is added at the end of the query.
+///
+/// Names and values are percent-encoded.
+pub fn with_query_params(url: &str, params: &[(String, String)]) -> String {
+    if params.is_empty() {
+        return url.to_owned();
+    }
+    let parts = UrlParts::parse(url);
+    let mut query: Vec<String> = parts
+        .query
+        .map(|query| {
+            query
+                .split('&')
+                .filter(|param| !param.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+    for (name, value) in params {
+        let name = percent_encode(name);
+        let param = format!("{}={}", name, percent_encode(value));
+        let has_name = |existing: &String| existing.split('=').next() == Some(name.as_str());
+        match query.iter().position(has_name) {
+            Some(pos) => {
+                query[pos] = param;
+                let mut index = 0;
+                query.retain(|existing| {
+                    index += 1;
+                    index <= pos + 1 || !has_name(existing)
+                });
+            }
+            None => query.push(param),
+        }
+    }
+    let query = query.join("&");
+    UrlParts {
+        query: Some(&query),
+        ..parts
+    }
+    .to_url()
+}
+
+/// Percent-encode all characters of `val` but the unreserved ones
+/// (RFC 3986, section 2.3).
+fn percent_encode(val: &str) -> String {
+    let mut encoded = String::with_capacity(val.len());
+    for byte in val.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Remove the `.` and `..` segments of a path (RFC 3986, section 5.2.4).
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../") {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("./") {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            let last_segment = output.rfind('/').unwrap_or(0);
+            output.truncate(last_segment);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = usize::from(input.starts_with('/'));
+            let end = input[start..]
+                .find('/')
+                .map_or(input.len(), |pos| pos + start);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_url() {
+        // Examples of RFC 3986, section 5.4
+        let base = "http://a/b/c/d;p?q";
+        let examples = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("g;x", "http://a/b/c/g;x"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("g?y/./x", "http://a/b/c/g?y/./x"),
+            ("g#s/../x", "http://a/b/c/g#s/../x"),
+        ];
+        for (reference, expected) in &examples {
+            assert_eq!(resolve_url(base, reference), *expected, "{}", reference);
+        }
+        assert_eq!(
+            resolve_url("https://cdn.com", "a.mp4"),
+            "https://cdn.com/a.mp4"
+        );
+    }
+
+    #[test]
+    fn test_with_host() {
+        assert_eq!(
+            with_host("https://cdn-a.com/v/1.mp4?t=1", "cdn-b.com"),
+            "https://cdn-b.com/v/1.mp4?t=1"
+        );
+        assert_eq!(
+            with_host("http://user@cdn-a.com:8080/", "cdn-b.com"),
+            "http://user@cdn-b.com:8080/"
+        );
+        assert_eq!(
+            with_host("http://[::1]:8080/", "cdn-b.com:81"),
+            "http://cdn-b.com:81/"
+        );
+        assert_eq!(with_host("video/1/", "cdn-b.com"), "video/1/");
+    }
+
+    #[test]
+    fn test_with_query_params() {
+        let params = [
+            ("token".to_owned(), "a b".to_owned()),
+            ("cdn".to_owned(), "b".to_owned()),
+        ];
+        assert_eq!(
+            with_query_params("https://cdn.com/v/", &params),
+            "https://cdn.com/v/?token=a%20b&cdn=b"
+        );
+        assert_eq!(
+            with_query_params("https://cdn.com/v/?cdn=a&x&cdn=c#f", &params),
+            "https://cdn.com/v/?cdn=b&x&token=a%20b#f"
+        );
+    }
+
+    #[test]
+    fn test_is_absolute_url() {
+        assert!(is_absolute_url("https://cdn.com/"));
+        assert!(!is_absolute_url("//cdn.com/"));
+        assert!(!is_absolute_url("video/1/"));
+        assert!(!is_absolute_url("a/b:c"));
+    }
+}
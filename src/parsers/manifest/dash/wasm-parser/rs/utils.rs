@@ -1,4 +1,4 @@
-use crate::errors::{ParsingError, Result};
+use crate::errors::{ParsingError, ParsingErrorKind, Result};
 
 /// Try to parse the given array of bytes into an f64, by first converting
 /// it to the corresponding ASCII (or even here, UTF-8) values.
@@ -55,7 +55,10 @@ pub fn parse_bool(value: &[u8]) -> Result<bool> {
             let mut base_str = "Invalid boolean: ".to_owned();
             let val = std::str::from_utf8(val)?;
             base_str.push_str(val);
-            Err(ParsingError(base_str))
+            Err(ParsingError::new(
+                ParsingErrorKind::InvalidBoolean,
+                base_str,
+            ))
         }
     }
 }
@@ -64,145 +67,820 @@ pub fn parse_bool(value: &[u8]) -> Result<bool> {
 // It seems to be but it's not yet handled here
 // (We could use e.g. the `INFINITY` float value)
 pub fn parse_byte_range(value: &[u8]) -> Result<(f64, f64)> {
+    let invalid = |reason: &str| {
+        ParsingError::new(
+            ParsingErrorKind::InvalidRange,
+            format!("Invalid byte-range: {}", reason),
+        )
+    };
     let mut cursor = 0usize;
     let start;
     loop {
         if cursor >= value.len() {
-            let e = ParsingError("Invalid byte-range: end encountered too soon".to_owned());
-            return Err(e);
+            return Err(invalid("end encountered too soon"));
         }
         if value[cursor] == b'-' {
-            start = parse_u64(&value[0..cursor])?;
+            start = parse_u64(&value[0..cursor]).map_err(|e| invalid(&e.message))?;
             break;
         }
         cursor += 1;
     }
 
-    let end = parse_u64(&value[cursor + 1..value.len()])?;
+    let end = parse_u64(&value[cursor + 1..value.len()]).map_err(|e| invalid(&e.message))?;
     Ok((start as f64, end as f64))
 }
 
-/// Parse ISO 8601 duration format (e.g. P5Y10M43HT22H8M3S) into the
-/// corresponding seconds in a float format.
-/// This code could be much simpler if it was RegExp-based but I preferred not
-/// to, mainly because I didn't want to incur the size cost of importing regex
-/// code in here
-pub fn parse_iso_8601_duration(value: &[u8]) -> Result<f64> {
-    if value.is_empty() || value[0] != b'P' {
-        let err = ParsingError("Unexpected duration. Should start with \"P\"".to_owned());
-        return Err(err);
-    }
-    let mut base = 1;
-    let mut result = 0.;
-    if value[1] != b'T' {
-        loop {
-            let (number, i) = read_next_float(value, base)?;
-            if i == value.len() {
-                let e =
-                    ParsingError("Invalid ISO 8601 duration: end encountered too soon".to_owned());
-                return Err(e);
+/// How strictly ISO 8601 durations should be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationParsingMode {
+    /// Only accept the xs:duration format, which is the one MPDs have to
+    /// follow.
+    Strict,
+    /// Also accept the other forms allowed by ISO 8601 and commonly found in
+    /// the wild: weeks (`W` unit), fractional values for any unit and commas
+    /// as decimal separators.
+    Lenient,
+}
+
+/// A parsed ISO 8601 duration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedDuration {
+    /// The duration in seconds. Negative for negative durations.
+    pub seconds: f64,
+    /// `true` if the duration had years or months, which don't have a fixed
+    /// length. Years are then considered to last 365 days and months 30 days.
+    pub is_approximate: bool,
+}
+
+/// Parse an ISO 8601 duration (e.g. -P5Y10M43DT22H8M3.5S) into the
+/// corresponding number of seconds.
+///
+/// The value is read without RegExp, mainly because we don't want to incur
+/// the size cost of importing regex code in here.
+pub fn parse_iso_8601_duration(value: &[u8], mode: DurationParsingMode) -> Result<ParsedDuration> {
+    let invalid = |reason: &str| {
+        ParsingError::new(
+            ParsingErrorKind::InvalidDuration,
+            format!(
+                "Invalid ISO 8601 duration \"{}\": {}",
+                String::from_utf8_lossy(value),
+                reason
+            ),
+        )
+    };
+    let lenient = mode == DurationParsingMode::Lenient;
+
+    let (is_negative, value_start) = match value {
+        [b'-', b'P', ..] => (true, 2),
+        [b'P', ..] => (false, 1),
+        _ => return Err(invalid("should start with \"P\"")),
+    };
+
+    let mut seconds = 0.;
+    let mut is_approximate = false;
+    let mut in_time_part = false;
+
+    // Index in `DURATION_UNITS` of the last unit read, units having to be
+    // in that order.
+    let mut last_unit: Option<usize> = None;
+
+    // `true` if a component has been read since the start or since `T`.
+    let mut has_component = false;
+
+    let mut i = value_start;
+    while i < value.len() {
+        if value[i] == b'T' {
+            if in_time_part {
+                return Err(invalid("multiple \"T\" separators"));
             }
-            let factor = match value[i] {
-                b'Y' => 365 * 24 * 60 * 60,
-                b'M' => 30 * 24 * 60 * 60,
-                b'D' => 24 * 60 * 60,
-                _ => {
-                    let e = ParsingError("Invalid duration: unexpected unit.".to_owned());
-                    return Err(e);
-                }
-            };
-            result += number * (factor as f64);
-            base = i + 1;
-            if base == value.len() {
-                return Ok(result);
+            if value_start != i && !has_component {
+                return Err(invalid("no value before \"T\""));
             }
-            if value[base] == b'T' {
-                break;
+            in_time_part = true;
+            has_component = false;
+            i += 1;
+            continue;
+        }
+
+        let number_start = i;
+        while i < value.len() && value[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == number_start {
+            return Err(invalid("expected a number"));
+        }
+        let mut has_fraction = false;
+        if i < value.len() && (value[i] == b'.' || (lenient && value[i] == b',')) {
+            i += 1;
+            let fraction_start = i;
+            while i < value.len() && value[i].is_ascii_digit() {
+                i += 1;
             }
+            if i == fraction_start {
+                return Err(invalid("expected digits after the decimal separator"));
+            }
+            has_fraction = true;
         }
-    }
-    base += 1;
-    loop {
-        let (number, i) = read_next_float(value, base)?;
-        if i == value.len() {
-            let e = ParsingError("Invalid ISO 8601 duration: end encountered too soon".to_owned());
-            return Err(e);
-        }
-        let factor = match value[i] {
-            b'H' => 60 * 60,
-            b'M' => 60,
-            b'S' => 1,
-            _ => {
-                let e = ParsingError("Invalid duration: unexpected unit.".to_owned());
-                return Err(e);
+        // Rust only accepts `.` as a decimal separator
+        let number_str: String = value[number_start..i]
+            .iter()
+            .map(|&c| if c == b',' { '.' } else { c as char })
+            .collect();
+        let number = number_str
+            .parse::<f64>()
+            .map_err(|e| invalid(&e.to_string()))?;
+
+        let designator = match value.get(i) {
+            Some(designator) => *designator,
+            None => return Err(invalid("end encountered too soon")),
+        };
+        i += 1;
+        let unit = DURATION_UNITS
+            .iter()
+            .position(|unit| unit.designator == designator && unit.is_time == in_time_part)
+            .ok_or_else(|| invalid("unexpected unit"))?;
+        if last_unit.is_some_and(|last_unit| last_unit >= unit) {
+            return Err(invalid("units in the wrong order"));
+        }
+        let unit_info = &DURATION_UNITS[unit];
+        if !lenient {
+            if designator == b'W' {
+                return Err(invalid("weeks are not allowed"));
+            }
+            if has_fraction && !(in_time_part && designator == b'S') {
+                return Err(invalid("only seconds can have a fractional part"));
             }
+        }
+        last_unit = Some(unit);
+        has_component = true;
+        is_approximate |= unit_info.is_approximate && number != 0.;
+        seconds += number * unit_info.seconds;
+    }
+    if !has_component {
+        let reason = if in_time_part {
+            "no value after \"T\""
+        } else {
+            "no value"
         };
-        result += number * (factor as f64);
-        base = i + 1;
-        if base == value.len() {
-            return Ok(result);
+        return Err(invalid(reason));
+    }
+    if !seconds.is_finite() {
+        return Err(invalid("value too large"));
+    }
+    Ok(ParsedDuration {
+        seconds: if is_negative { -seconds } else { seconds },
+        is_approximate,
+    })
+}
+
+struct DurationUnit {
+    designator: u8,
+    /// `true` for units found after the `T` separator.
+    is_time: bool,
+    seconds: f64,
+    is_approximate: bool,
+}
+
+/// Units of ISO 8601 durations, in the order in which they have to appear.
+const DURATION_UNITS: [DurationUnit; 7] = [
+    DurationUnit {
+        designator: b'Y',
+        is_time: false,
+        seconds: 365. * 24. * 60. * 60.,
+        is_approximate: true,
+    },
+    DurationUnit {
+        designator: b'M',
+        is_time: false,
+        seconds: 30. * 24. * 60. * 60.,
+        is_approximate: true,
+    },
+    DurationUnit {
+        designator: b'W',
+        is_time: false,
+        seconds: 7. * 24. * 60. * 60.,
+        is_approximate: false,
+    },
+    DurationUnit {
+        designator: b'D',
+        is_time: false,
+        seconds: 24. * 60. * 60.,
+        is_approximate: false,
+    },
+    DurationUnit {
+        designator: b'H',
+        is_time: true,
+        seconds: 60. * 60.,
+        is_approximate: false,
+    },
+    DurationUnit {
+        designator: b'M',
+        is_time: true,
+        seconds: 60.,
+        is_approximate: false,
+    },
+    DurationUnit {
+        designator: b'S',
+        is_time: true,
+        seconds: 1.,
+        is_approximate: false,
+    },
+];
+
+/// Parse an xs:dateTime value (an ISO 8601 date and time of day, e.g.
+/// 2024-01-31T12:30:00.5+01:00) into the corresponding number of
+/// milliseconds since the Unix epoch.
+///
+/// Seconds, their fractional part and the time zone offset are optional.
+/// Values without a time zone offset are considered to be in UTC, as
+/// recommended by the DASH-IF, instead of in local time.
+pub fn parse_iso_8601_date_time(value: &[u8]) -> Result<f64> {
+    let invalid = || {
+        ParsingError::new(
+            ParsingErrorKind::InvalidDateTime,
+            format!("Invalid dateTime: {}", String::from_utf8_lossy(value)),
+        )
+    };
+    let mut cursor = DateTimeCursor { value, pos: 0 };
+
+    let negative_year = cursor.eat(b'-');
+    let year_len = cursor.count_digits();
+    if year_len < 4 {
+        return Err(invalid());
+    }
+    let year = cursor.read_number(year_len).ok_or_else(invalid)? as i64;
+    let year = if negative_year { -year } else { year };
+    if !cursor.eat(b'-') {
+        return Err(invalid());
+    }
+    let month = cursor.read_number(2).ok_or_else(invalid)?;
+    if !cursor.eat(b'-') {
+        return Err(invalid());
+    }
+    let day = cursor.read_number(2).ok_or_else(invalid)?;
+    if !cursor.eat(b'T') {
+        return Err(invalid());
+    }
+    let hours = cursor.read_number(2).ok_or_else(invalid)?;
+    if !cursor.eat(b':') {
+        return Err(invalid());
+    }
+    let minutes = cursor.read_number(2).ok_or_else(invalid)?;
+    let mut seconds = 0.;
+    if cursor.eat(b':') {
+        seconds = cursor.read_number(2).ok_or_else(invalid)? as f64;
+        if cursor.eat(b'.') || cursor.eat(b',') {
+            let frac_len = cursor.count_digits();
+            if frac_len == 0 {
+                return Err(invalid());
+            }
+            // The separator may be a `,`, which Rust cannot parse
+            let mut frac = b"0.".to_vec();
+            frac.extend_from_slice(&value[cursor.pos..cursor.pos + frac_len]);
+            cursor.pos += frac_len;
+            seconds += parse_f64(&frac).map_err(|_| invalid())?;
+        }
+    }
+
+    let offset_minutes = match cursor.next() {
+        None => 0,
+        Some(b'Z') => 0,
+        Some(sign @ b'+') | Some(sign @ b'-') => {
+            let offset_hours = cursor.read_number(2).ok_or_else(invalid)?;
+            cursor.eat(b':');
+            let offset_minutes = match cursor.count_digits() {
+                0 => 0,
+                _ => cursor.read_number(2).ok_or_else(invalid)?,
+            };
+            if offset_hours > 14 || offset_minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = (offset_hours * 60 + offset_minutes) as i64;
+            if sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
         }
+        Some(_) => return Err(invalid()),
+    };
+    if cursor.pos != value.len() {
+        return Err(invalid());
+    }
+
+    let is_end_of_day = hours == 24 && minutes == 0 && seconds == 0.;
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || (hours > 23 && !is_end_of_day)
+        || minutes > 59
+        || seconds >= 60.
+    {
+        return Err(invalid());
     }
+
+    let days = days_from_civil(year, month, day);
+    let minutes = (days * 24 + hours as i64) * 60 + minutes as i64 - offset_minutes;
+    Ok((minutes as f64 * 60. + seconds) * 1000.)
 }
 
-/// Parse a floating point number, represented by `value` in ASCII, starting at
-/// the position `base_offset`.
-/// The decimal separator can either a be a point ('.') or a colon (',').
+/// Number of seconds between the NTP epoch (1900-01-01) and the Unix one.
+const NTP_UNIX_EPOCH_OFFSET: f64 = 2_208_988_800.;
+
+/// Parse the `wallClockTime` attribute of a `<ProducerReferenceTime>` into
+/// the corresponding number of milliseconds since the Unix epoch.
 ///
-/// If it succeeds, returns both the floating point number as an f64 and the
-/// offset coming just after that float in `value` (or the length of `value` if
-/// it ended with a float).
-fn read_next_float(value: &[u8], base_offset: usize) -> Result<(f64, usize)> {
-    let mut i = base_offset;
-    while i < value.len() && value[i] >= b'0' && value[i] <= b'9' {
-        i += 1;
+/// Its format depends on the scheme of the `<UTCTiming>` it refers to: it is
+/// an xs:dateTime for most of them, but a 64-bit NTP timestamp (seconds since
+/// 1900 in its 32 most significant bits, their fractional part in the 32
+/// others) for the NTP one.
+pub fn parse_wall_clock_time(value: &[u8]) -> Result<f64> {
+    if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+        return parse_iso_8601_date_time(value);
     }
-    if i == value.len() || (value[i] != b'.' && value[i] != b',') {
-        // UNSAFE: We already checked that this string represents a valid integer
-        let val_str = unsafe { std::str::from_utf8_unchecked(&value[base_offset..i]) };
-        let val_u64 = val_str.parse::<u64>()?;
-        return Ok((val_u64 as f64, i));
+    let ntp_timestamp = parse_u64(value)?;
+    let seconds = (ntp_timestamp >> 32) as f64 - NTP_UNIX_EPOCH_OFFSET;
+    let fraction = (ntp_timestamp & 0xffff_ffff) as f64 / 4_294_967_296.;
+    Ok((seconds + fraction) * 1000.)
+}
+
+/// Format a number of milliseconds since the Unix epoch into an xs:dateTime
+/// value in UTC (e.g. 2024-01-31T11:30:00.5Z).
+///
+/// This is the inverse of `parse_iso_8601_date_time`. The result is rounded
+/// to the millisecond.
+pub fn format_iso_8601_date_time(millis: f64) -> String {
+    let total_ms = millis.round() as i64;
+    let days = total_ms.div_euclid(24 * 60 * 60 * 1000);
+    let day_ms = total_ms.rem_euclid(24 * 60 * 60 * 1000);
+    let (year, month, day) = civil_from_days(days);
+    let mut result = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        day_ms / (60 * 60 * 1000),
+        day_ms / (60 * 1000) % 60,
+        day_ms / 1000 % 60
+    );
+    let ms = day_ms % 1000;
+    if ms > 0 {
+        let frac = format!("{:03}", ms);
+        result.push('.');
+        result.push_str(frac.trim_end_matches('0'));
     }
+    result.push('Z');
+    result
+}
 
-    i += 1;
-    while i < value.len() && value[i] >= b'0' && value[i] <= b'9' {
-        i += 1;
+/// Simple cursor over the bytes of an xs:dateTime value.
+struct DateTimeCursor<'a> {
+    value: &'a [u8],
+    pos: usize,
+}
+
+impl DateTimeCursor<'_> {
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.value.get(self.pos).copied();
+        if byte.is_some() {
+            self.pos += 1;
+        }
+        byte
+    }
+
+    /// Advance past `expected` if it is the next byte, returning `true` in
+    /// that case.
+    fn eat(&mut self, expected: u8) -> bool {
+        if self.value.get(self.pos) == Some(&expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the number of consecutive ASCII digits from the current
+    /// position.
+    fn count_digits(&self) -> usize {
+        self.value[self.pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    }
+
+    /// Read a number written with exactly `len` digits.
+    fn read_number(&mut self, len: usize) -> Option<u32> {
+        if self.count_digits() < len || len > 9 {
+            return None;
+        }
+        let digits = &self.value[self.pos..self.pos + len];
+        self.pos += len;
+        Some(
+            digits
+                .iter()
+                .fold(0, |acc, digit| acc * 10 + (digit - b'0') as u32),
+        )
     }
-    // UNSAFE: We already checked that this string represents a valid float
-    let val_str = unsafe { std::str::from_utf8_unchecked(&value[base_offset..i]) };
-    let val_f64 = val_str.parse::<f64>()?;
-    Ok((val_f64, i))
 }
 
-pub fn u32_to_u8_slice_be(x: u32) -> [u8; 4] {
-    let b1: u8 = ((x >> 24) & 0xff) as u8;
-    let b2: u8 = ((x >> 16) & 0xff) as u8;
-    let b3: u8 = ((x >> 8) & 0xff) as u8;
-    let b4: u8 = (x & 0xff) as u8;
-    [b1, b2, b3, b4]
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days between the Unix epoch and the given date of
+/// the proleptic Gregorian calendar.
+///
+/// Based on Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`, returning the year, month and day.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Format a number of seconds into an ISO 8601 duration (e.g. P1DT2H3M4.5S).
+///
+/// This is the inverse of `parse_iso_8601_duration`. Only day, hour, minute
+/// and second units are used, as years and months don't have a fixed length.
+/// The result is rounded to the microsecond.
+pub fn format_iso_8601_duration(seconds: f64) -> String {
+    let mut result = if seconds < 0. {
+        "-P".to_owned()
+    } else {
+        "P".to_owned()
+    };
+    let total_us = (seconds.abs() * 1_000_000.).round() as u64;
+    let days = total_us / (24 * 60 * 60 * 1_000_000);
+    let hours = total_us / (60 * 60 * 1_000_000) % 24;
+    let minutes = total_us / (60 * 1_000_000) % 60;
+    let secs = total_us / 1_000_000 % 60;
+    let micros = total_us % 1_000_000;
+
+    if days > 0 {
+        result.push_str(&format!("{}D", days));
+    }
+    if hours == 0 && minutes == 0 && secs == 0 && micros == 0 {
+        if days == 0 {
+            result.push_str("T0S");
+        }
+        return result;
+    }
+    result.push('T');
+    if hours > 0 {
+        result.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        result.push_str(&format!("{}M", minutes));
+    }
+    if secs > 0 || micros > 0 {
+        if micros > 0 {
+            let frac = format!("{:06}", micros);
+            result.push_str(&format!("{}.{}S", secs, frac.trim_end_matches('0')));
+        } else {
+            result.push_str(&format!("{}S", secs));
+        }
+    }
+    result
+}
+
+/// Parse a whitespace-separated list of key ids, such as the value of a
+/// `cenc:default_KID` attribute, into 16 bytes each.
+///
+/// Each key id is either a UUID in its usual dashed form (e.g.
+/// `9eb4050d-e44b-4802-932e-27d75083e266`) or its 32 hexadecimal digits.
+pub fn parse_key_ids(value: &[u8]) -> Result<Vec<[u8; 16]>> {
+    let key_ids = value
+        .split(|c| c.is_ascii_whitespace())
+        .filter(|kid| !kid.is_empty())
+        .map(parse_key_id)
+        .collect::<Result<Vec<_>>>()?;
+    if key_ids.is_empty() {
+        return Err(ParsingError::new(
+            ParsingErrorKind::InvalidKeyId,
+            "Empty key id.",
+        ));
+    }
+    Ok(key_ids)
+}
+
+fn parse_key_id(value: &[u8]) -> Result<[u8; 16]> {
+    let invalid = || {
+        ParsingError::new(
+            ParsingErrorKind::InvalidKeyId,
+            format!("Invalid key id: {}", String::from_utf8_lossy(value)),
+        )
+    };
+    let is_dashed = value.len() == 36 && [8, 13, 18, 23].iter().all(|&i| value[i] == b'-');
+    let digits: Vec<u8> = match value.len() {
+        32 => value.to_vec(),
+        36 if is_dashed => value.iter().copied().filter(|&c| c != b'-').collect(),
+        _ => return Err(invalid()),
+    };
+    let mut key_id = [0u8; 16];
+    for (byte, pair) in key_id.iter_mut().zip(digits.chunks(2)) {
+        let high = (pair[0] as char).to_digit(16).ok_or_else(invalid)?;
+        let low = (pair[1] as char).to_digit(16).ok_or_else(invalid)?;
+        *byte = (high * 16 + low) as u8;
+    }
+    Ok(key_id)
+}
+
+/// Format a key id in the dashed UUID form (@see `parse_key_ids`).
+pub fn format_key_id(key_id: &[u8; 16]) -> String {
+    let mut formatted = String::with_capacity(36);
+    for (i, byte) in key_id.iter().enumerate() {
+        if i == 4 || i == 6 || i == 8 || i == 10 {
+            formatted.push('-');
+        }
+        formatted.push_str(&format!("{:02x}", byte));
+    }
+    formatted
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Decode the given base64 string (RFC 4648, with padding being optional).
+///
+/// Whitespaces are ignored, as the text of an XML element (such as
+/// `<cenc:pssh>`) may be split on several lines.
+pub fn decode_base64(value: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(value.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut acc_len = 0u32;
+    let mut padding = 0;
+    for (i, &c) in value.iter().enumerate() {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => continue,
+            c => {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::InvalidBase64,
+                    format!(
+                        "Invalid base64 character {:?} at position {}.",
+                        c as char, i
+                    ),
+                ))
+            }
+        };
+        if padding > 0 {
+            return Err(ParsingError::new(
+                ParsingErrorKind::InvalidBase64,
+                format!(
+                    "Invalid base64: data found after padding at position {}.",
+                    i
+                ),
+            ));
+        }
+        acc = (acc << 6) | u32::from(sextet);
+        acc_len += 6;
+        if acc_len >= 8 {
+            acc_len -= 8;
+            decoded.push((acc >> acc_len) as u8);
+            acc &= (1 << acc_len) - 1;
+        }
+    }
+    // A lone remaining character cannot encode a byte
+    if acc_len >= 6 || padding > 2 {
+        return Err(ParsingError::new(
+            ParsingErrorKind::InvalidBase64,
+            "Invalid base64: truncated data.",
+        ));
+    }
+    Ok(decoded)
+}
+
+/// Encode the given data in base64 (RFC 4648, with padding).
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triplet =
+            (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let sextet = (triplet >> (18 - 6 * i)) & 0x3F;
+                encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse_duration(value: &[u8]) -> Result<f64> {
+        parse_iso_8601_duration(value, DurationParsingMode::Lenient).map(|d| d.seconds)
+    }
+
+    fn parse_strict_duration(value: &[u8]) -> Result<f64> {
+        parse_iso_8601_duration(value, DurationParsingMode::Strict).map(|d| d.seconds)
+    }
+
     #[test]
     fn test_parse_8601_duration() {
+        assert_eq!(parse_duration(b"P1Y10M43DT22H8M3S").unwrap(), 61250883.);
+        assert_eq!(parse_duration(b"PT3S").unwrap(), 3.);
+        assert_eq!(parse_duration(b"PT1M3.4S").unwrap(), 63.4);
+        assert_eq!(parse_duration(b"P0D").unwrap(), 0.);
+        assert_eq!(parse_duration(b"PT0S").unwrap(), 0.);
+        assert_eq!(parse_duration(b"P1D").unwrap(), 86400.);
+        assert_eq!(parse_duration(b"PT36H").unwrap(), 129600.);
+        assert_eq!(parse_duration(b"-PT1M30S").unwrap(), -90.);
+        assert_eq!(parse_duration(b"P2W").unwrap(), 1209600.);
+        assert_eq!(parse_duration(b"P1W1DT1S").unwrap(), 691201.);
+        assert_eq!(parse_duration(b"PT1,5S").unwrap(), 1.5);
+        assert_eq!(parse_duration(b"PT0.5M").unwrap(), 30.);
+
+        assert!(parse_duration(b"").is_err());
+        assert!(parse_duration(b"P").is_err());
+        assert!(parse_duration(b"PT").is_err());
+        assert!(parse_duration(b"-P").is_err());
+        assert!(parse_duration(b"P1DT").is_err());
+        assert!(parse_duration(b"3S").is_err());
+        assert!(parse_duration(b"T3S").is_err());
+        assert!(parse_duration(b"P3S").is_err());
+        assert!(parse_duration(b"PT3D").is_err());
+        assert!(parse_duration(b"P1D1Y").is_err());
+        assert!(parse_duration(b"PT1S1M").is_err());
+        assert!(parse_duration(b"P1Y1Y").is_err());
+        assert!(parse_duration(b"PT1TS").is_err());
+        assert!(parse_duration(b"PT.5S").is_err());
+        assert!(parse_duration(b"PT1.S").is_err());
+        assert!(parse_duration(b"PT1").is_err());
+        assert!(parse_duration(b"+PT1S").is_err());
+        assert!(parse_duration(b"PT1s").is_err());
+
+        let mut huge = b"P".to_vec();
+        huge.extend([b'9'; 400]);
+        huge.push(b'Y');
+        assert!(parse_duration(&huge).is_err());
+    }
+
+    #[test]
+    fn test_parse_strict_8601_duration() {
+        assert_eq!(parse_strict_duration(b"P1DT2H3M4.5S").unwrap(), 93784.5);
+        assert_eq!(parse_strict_duration(b"-P0D").unwrap(), 0.);
+
+        assert!(parse_strict_duration(b"P2W").is_err());
+        assert!(parse_strict_duration(b"PT1,5S").is_err());
+        assert!(parse_strict_duration(b"PT0.5M").is_err());
+        assert!(parse_strict_duration(b"P1.5D").is_err());
+    }
+
+    #[test]
+    fn test_parse_8601_duration_approximation() {
+        let parse = |value: &[u8]| {
+            parse_iso_8601_duration(value, DurationParsingMode::Strict)
+                .unwrap()
+                .is_approximate
+        };
+        assert!(parse(b"P1Y"));
+        assert!(parse(b"P1MT1S"));
+        assert!(!parse(b"P0Y0M1D"));
+        assert!(!parse(b"PT1M"));
+    }
+
+    #[test]
+    fn test_format_8601_duration() {
+        assert_eq!(format_iso_8601_duration(0.), "PT0S");
+        assert_eq!(format_iso_8601_duration(3.), "PT3S");
+        assert_eq!(format_iso_8601_duration(63.4), "PT1M3.4S");
+        assert_eq!(format_iso_8601_duration(86400.), "P1D");
+        assert_eq!(format_iso_8601_duration(61250883.), "P708DT22H8M3S");
+        assert_eq!(format_iso_8601_duration(0.000_5), "PT0.0005S");
+        for val in [2., 63.4, 3600.25, 61250883.] {
+            let formatted = format_iso_8601_duration(val);
+            assert_eq!(parse_duration(formatted.as_bytes()).unwrap(), val);
+        }
+    }
+
+    #[test]
+    fn test_parse_8601_date_time() {
         assert_eq!(
-            parse_iso_8601_duration(b"P1Y10M43DT22H8M3S").unwrap(),
-            61250883.
+            parse_iso_8601_date_time(b"1970-01-01T00:00:00Z").unwrap(),
+            0.
         );
         assert_eq!(
-            parse_iso_8601_duration(b"P1Y10M43DT22H8M3S").unwrap(),
-            61250883.
+            parse_iso_8601_date_time(b"2024-02-29T12:30:15Z").unwrap(),
+            1709209815000.
         );
-        assert_eq!(parse_iso_8601_duration(b"PT3S").unwrap(), 3.);
-        assert_eq!(parse_iso_8601_duration(b"PT1M3.4S").unwrap(), 63.4);
+        assert_eq!(
+            parse_iso_8601_date_time(b"2024-02-29T12:30:15.25Z").unwrap(),
+            1709209815250.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"2024-02-29T13:30:15,25+01:00").unwrap(),
+            1709209815250.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"2024-02-29T07:00:15-0530").unwrap(),
+            1709209815000.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"2024-02-29T12:30:15").unwrap(),
+            1709209815000.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"2024-02-29T12:30Z").unwrap(),
+            1709209800000.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"1969-12-31T23:59:59Z").unwrap(),
+            -1000.
+        );
+        assert_eq!(
+            parse_iso_8601_date_time(b"2023-12-31T24:00:00Z").unwrap(),
+            parse_iso_8601_date_time(b"2024-01-01T00:00:00Z").unwrap()
+        );
+
+        assert!(parse_iso_8601_date_time(b"").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-02-29").is_err());
+        assert!(parse_iso_8601_date_time(b"2023-02-29T00:00:00Z").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-13-01T00:00:00Z").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-01-01T24:00:01Z").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-01-01T00:60:00Z").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-01-01T00:00:00.Z").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-01-01T00:00:00+1:00").is_err());
+        assert!(parse_iso_8601_date_time(b"2024-01-01T00:00:00ZZ").is_err());
+        assert!(parse_iso_8601_date_time(b"24-01-01T00:00:00Z").is_err());
+    }
 
-        assert!(parse_iso_8601_duration(b"").err().is_some());
-        assert!(parse_iso_8601_duration(b"3S").err().is_some());
-        assert!(parse_iso_8601_duration(b"T3S").err().is_some());
-        assert!(parse_iso_8601_duration(b"P3S").err().is_some());
+    #[test]
+    fn test_format_8601_date_time() {
+        assert_eq!(format_iso_8601_date_time(0.), "1970-01-01T00:00:00Z");
+        assert_eq!(format_iso_8601_date_time(-1000.), "1969-12-31T23:59:59Z");
+        assert_eq!(
+            format_iso_8601_date_time(1709209815250.),
+            "2024-02-29T12:30:15.25Z"
+        );
+        for val in [0., 951782400000., 1709209815250., 4107542400001.] {
+            let formatted = format_iso_8601_date_time(val);
+            assert_eq!(parse_iso_8601_date_time(formatted.as_bytes()).unwrap(), val);
+        }
+    }
+
+    #[test]
+    fn test_parse_wall_clock_time() {
+        assert_eq!(
+            parse_wall_clock_time(b"2024-01-01T00:00:00.5Z").unwrap(),
+            1704067200500.
+        );
+        // 2024-01-01T00:00:00.5Z as an NTP timestamp
+        assert_eq!(
+            parse_wall_clock_time(b"16806447549564059648").unwrap(),
+            1704067200500.
+        );
+        assert!(parse_wall_clock_time(b"").is_err());
+        assert!(parse_wall_clock_time(b"-1").is_err());
     }
 
     #[test]
@@ -214,4 +892,63 @@ mod tests {
         assert!(parse_byte_range(b"A").err().is_some());
         assert!(parse_byte_range(b"15-A").err().is_some());
     }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(decode_base64(b"").unwrap(), b"");
+        assert_eq!(decode_base64(b"Zm9vYmFy").unwrap(), b"foobar");
+        assert_eq!(decode_base64(b"Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(decode_base64(b"Zm9vYmE").unwrap(), b"fooba");
+        assert_eq!(decode_base64(b" Zm9v\n  YmE=\n").unwrap(), b"fooba");
+        assert_eq!(decode_base64(b"AAAA").unwrap(), [0, 0, 0]);
+
+        for invalid in &[&b"Zm9v!"[..], b"Zm9vY", b"Zm=9v", b"Zg==="] {
+            let err = decode_base64(invalid).unwrap_err();
+            assert_eq!(err.kind, ParsingErrorKind::InvalidBase64);
+        }
+
+        for data in &[&b""[..], b"f", b"fo", b"foo", b"foob", b"\x00\xff\x10"] {
+            assert_eq!(
+                decode_base64(encode_base64(data).as_bytes()).unwrap(),
+                *data
+            );
+        }
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+    }
+
+    #[test]
+    fn test_parse_key_ids() {
+        let kid = [
+            0x9e, 0xb4, 0x05, 0x0d, 0xe4, 0x4b, 0x48, 0x02, 0x93, 0x2e, 0x27, 0xd7, 0x50, 0x83,
+            0xe2, 0x66,
+        ];
+        assert_eq!(
+            parse_key_ids(b"9eb4050d-e44b-4802-932e-27d75083e266").unwrap(),
+            vec![kid]
+        );
+        assert_eq!(
+            parse_key_ids(b"9EB4050DE44B4802932E27D75083E266").unwrap(),
+            vec![kid]
+        );
+        assert_eq!(
+            parse_key_ids(
+                b" 9eb4050d-e44b-4802-932e-27d75083e266\n00000000000000000000000000000001 "
+            )
+            .unwrap(),
+            vec![kid, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]]
+        );
+        assert_eq!(format_key_id(&kid), "9eb4050d-e44b-4802-932e-27d75083e266");
+
+        for invalid in &[
+            &b""[..],
+            b"9eb4050d-e44b-4802-932e-27d75083e26",
+            b"9eb4050de44b-4802-932e-27d75083e266-",
+            b"9eb4050d-e44b-4802-932e-27d75083e26g",
+            b"+eb4050de44b4802932e27d75083e266",
+            b"9eb4050d-e44b-4802-932e-27d75083e266 abc",
+        ] {
+            let err = parse_key_ids(invalid).unwrap_err();
+            assert_eq!(err.kind, ParsingErrorKind::InvalidKeyId);
+        }
+    }
 }
@@ -0,0 +1,65 @@
+use crate::errors::Result;
+
+/// Special `xlink:href` value indicating that the element has to be removed
+/// from the MPD.
+pub const RESOLVE_TO_ZERO: &str = "urn:mpeg:dash:resolve-to-zero:2013";
+
+/// Maximum number of nested XLinks resolved, a remote element containing
+/// itself elements with an XLink.
+/// Deeper XLinks are not resolved (an error is reported instead) to avoid
+/// infinite loops between documents referencing each other.
+pub const MAX_XLINK_DEPTH: u32 = 5;
+
+/// Elements which may be replaced by a remote element through XLink.
+pub(crate) const XLINK_ELEMENTS: [&[u8]; 4] =
+    [b"Period", b"AdaptationSet", b"EventStream", b"SegmentList"];
+
+/// Value of an `xlink:actuate` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XLinkActuate {
+    /// The remote element has to be loaded while parsing the MPD.
+    OnLoad,
+
+    /// The remote element is only loaded when needed by the application.
+    /// This is the default.
+    OnRequest,
+}
+
+impl XLinkActuate {
+    pub(crate) fn from_attr_value(val: &[u8]) -> XLinkActuate {
+        match val {
+            b"onLoad" => XLinkActuate::OnLoad,
+            _ => XLinkActuate::OnRequest,
+        }
+    }
+}
+
+/// Loads the remote elements referenced by XLinks, as implemented by the
+/// application parsing the MPD.
+///
+/// When given to an `MPDProcessor` (@see
+/// `MPDProcessor::set_remote_element_loader`), the `Period`, `AdaptationSet`,
+/// `EventStream` and `SegmentList` elements with an `xlink:href` attribute and
+/// an `xlink:actuate="onLoad"` attribute are replaced, while parsing, by the
+/// elements loaded through it, which are reported as if they were part of the
+/// MPD. Those with the `urn:mpeg:dash:resolve-to-zero:2013` `xlink:href` are
+/// just removed.
+///
+/// Elements whose XLink is `onRequest` are reported as is, with their
+/// `xlink:href` and `xlink:actuate` attributes, letting the application load
+/// them when needed.
+///
+/// Loading is synchronous, so no implementation is provided to the
+/// WebAssembly build, whose JavaScript side still loads XLinks itself.
+pub trait RemoteElementLoader {
+    /// Load the remote element at the given URL, which has already been
+    /// resolved against the URL of the document referencing it: the MPD's
+    /// if known (@see `ParsingOptions::document_url`), or the one of the
+    /// remote element containing that XLink.
+    ///
+    /// The returned data should contain zero, one or several elements of the
+    /// same type than the element referencing it.
+    /// If an error is returned, it is reported and the element referencing it
+    /// is parsed as is.
+    fn load(&mut self, url: &str) -> Result<Vec<u8>>;
+}
@@ -37,6 +37,7 @@ import {
   CustomEventType,
   TagName,
 } from "./types";
+import { parseString } from "./utils";
 
 const MAX_READ_SIZE = 15e3;
 
@@ -108,6 +109,15 @@ export default class DashWasmParser {
    */
   private _isParsing : boolean;
 
+  /**
+   * `true` if the WebAssembly communicates timestamps as exact u64 values
+   * instead of f64 ones (bit `2` of the `init` flags).
+   *
+   * Not enabled for now, as only the EventStream generators read timestamps
+   * under that format.
+   */
+  private _exactTimestamps : boolean;
+
   /**
    * Create a new `DashWasmParser`.
    */
@@ -120,6 +130,7 @@ export default class DashWasmParser {
     this._initProm = null;
     this._warnings = [];
     this._isParsing = false;
+    this._exactTimestamps = false;
   }
 
   /**
@@ -193,6 +204,11 @@ export default class DashWasmParser {
         // TODO better types?
         this._linearMemory = this._instance.instance.exports.memory as WebAssembly.Memory;
 
+        // Bit `1` enables the strict mode
+        (this._instance.instance.exports.init as (flags : number) => void)(
+          opts.strict === true ? 1 : 0
+        );
+
         this.status = "initialized";
       }).catch((err : Error) => {
         const message = err instanceof Error ? err.toString() :
@@ -254,9 +270,22 @@ export default class DashWasmParser {
       const linearMemory = self._linearMemory as WebAssembly.Memory;
       const arr = new Uint8Array(linearMemory.buffer, ptr, len);
       if (evt === CustomEventType.Error) {
-        const decoded = textDecoder.decode(arr);
-        log.warn("WASM Error Event:", decoded);
-        self._warnings.push(new Error(decoded));
+        const dataView = new DataView(linearMemory.buffer);
+        const kind = dataView.getUint8(ptr);
+        const offset = dataView.getFloat64(ptr + 1, true);
+        const pathLength = dataView.getUint32(ptr + 9);
+        const path = parseString(textDecoder, linearMemory.buffer, ptr + 13, pathLength);
+        const message = parseString(textDecoder,
+                                    linearMemory.buffer,
+                                    ptr + 13 + pathLength,
+                                    len - 13 - pathLength);
+        let formatted = path.length > 0 ? `${path}: ${message}` :
+                                          message;
+        if (!isNaN(offset)) {
+          formatted += ` (at byte ${offset})`;
+        }
+        log.warn("WASM Error Event:", kind, formatted);
+        self._warnings.push(new Error(formatted));
       } else if (evt === CustomEventType.Log) {
         const decoded = textDecoder.decode(arr);
         log.warn("WASM Log Event:", decoded);
@@ -341,19 +370,29 @@ export default class DashWasmParser {
     const rootChildrenParser = generateRootChildrenParser(rootObj,
                                                           linearMemory,
                                                           this._parsersStack,
-                                                          mpd);
+                                                          this._exactTimestamps);
     this._parsersStack.pushParsers(null, rootChildrenParser, noop);
     this._warnings = [];
 
+    let status : number;
     try {
       // TODO better type this
-      (this._instance.instance.exports.parse as () => void)();
+      status = (this._instance.instance.exports.parse as () => number)();
     } catch (err) {
       this._parsersStack.reset();
       this._warnings = [];
       this._isParsing = false;
       throw err;
     }
+    if (status !== PARSING_STATUS_OK) {
+      // In strict mode, the error which stopped the parsing is the last one
+      const error = this._warnings[this._warnings.length - 1] ??
+                    new Error("DASH Parser: non-conformant MPD");
+      this._parsersStack.reset();
+      this._warnings = [];
+      this._isParsing = false;
+      throw error;
+    }
 
     const parsed = rootObj.mpd ?? null;
     const warnings = this._warnings;
@@ -386,19 +425,29 @@ export default class DashWasmParser {
     const xlinkParser = generateXLinkChildrenParser(rootObj,
                                                     linearMemory,
                                                     this._parsersStack,
-                                                    xlinkData);
+                                                    this._exactTimestamps);
     this._parsersStack.pushParsers(null, xlinkParser, noop);
     this._warnings = [];
 
+    let status : number;
     try {
       // TODO better type this
-      (this._instance.instance.exports.parse as () => void)();
+      status = (this._instance.instance.exports.parse as () => number)();
     } catch (err) {
       this._parsersStack.reset();
       this._warnings = [];
       this._isParsing = false;
       throw err;
     }
+    if (status !== PARSING_STATUS_OK) {
+      // In strict mode, the error which stopped the parsing is the last one
+      const error = this._warnings[this._warnings.length - 1] ??
+                    new Error("DASH Parser: non-conformant MPD");
+      this._parsersStack.reset();
+      this._warnings = [];
+      this._isParsing = false;
+      throw error;
+    }
 
     const { periods } = rootObj;
     const warnings = this._warnings;
@@ -471,7 +520,16 @@ export default class DashWasmParser {
   }
 }
 
+/** Value returned by the WebAssembly's `parse` function on success. */
+const PARSING_STATUS_OK = 0;
+
 /** Options needed when constructing the DASH-WASM parser. */
 export interface IDashWasmParserOptions {
   wasmUrl : string;
+  /**
+   * If `true`, MPDs not conforming to the DASH specification are rejected:
+   * parsing stops at the first error encountered, which is then thrown.
+   * Defaults to `false`, where errors are only returned as warnings.
+   */
+  strict? : boolean;
 }
@@ -32,13 +32,25 @@ import {
   parseFloatOrBool,
   parseString,
 } from "../utils";
+import {
+  generateAudioChannelConfigurationAttrParser,
+} from "./AudioChannelConfiguration";
 import { generateBaseUrlAttrParser } from "./BaseURL";
 import { generateContentComponentAttrParser } from "./ContentComponent";
 import { generateContentProtectionAttrParser } from "./ContentProtection";
+import {
+  generatePreselectionAttrParser,
+  generatePreselectionChildrenParser,
+} from "./Preselection";
+import {
+  generateProducerReferenceTimeAttrParser,
+  generateProducerReferenceTimeChildrenParser,
+} from "./ProducerReferenceTime";
 import {
   generateRepresentationAttrParser,
   generateRepresentationChildrenParser,
 } from "./Representation";
+import { generateResyncAttrParser } from "./Resync";
 import { generateSchemeAttrParser } from "./Scheme";
 import { generateSegmentBaseAttrParser } from "./SegmentBase";
 import { generateSegmentListChildrenParser } from "./SegmentList";
@@ -71,6 +83,19 @@ export function generateAdaptationSetChildrenParser(
         break;
       }
 
+      case TagName.AudioChannelConfiguration: {
+        const audioChannelConfiguration = {};
+        if (adaptationSetChildren.audioChannelConfigurations === undefined) {
+          adaptationSetChildren.audioChannelConfigurations = [];
+        }
+        adaptationSetChildren.audioChannelConfigurations.push(audioChannelConfiguration);
+        const attributeParser =
+          generateAudioChannelConfigurationAttrParser(audioChannelConfiguration,
+                                                      linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
       case TagName.BaseURL: {
         const baseUrl = { value: "", attributes: {} };
         adaptationSetChildren.baseURLs.push(baseUrl);
@@ -130,6 +155,53 @@ export function generateAdaptationSetChildrenParser(
         break;
       }
 
+      case TagName.Preselection: {
+        const preselection = { children: { accessibilities: [],
+                                           audioChannelConfigurations: [],
+                                           roles: [] },
+                               attributes: {} };
+        if (adaptationSetChildren.preselections === undefined) {
+          adaptationSetChildren.preselections = [];
+        }
+        adaptationSetChildren.preselections.push(preselection);
+        const childrenParser = generatePreselectionChildrenParser(preselection.children,
+                                                                  linearMemory,
+                                                                  parsersStack);
+        const attributeParser = generatePreselectionAttrParser(preselection.attributes,
+                                                               linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
+      case TagName.ProducerReferenceTime: {
+        const producerReferenceTime = { children: { utcTimings: [] },
+                                        attributes: {} };
+        if (adaptationSetChildren.producerReferenceTimes === undefined) {
+          adaptationSetChildren.producerReferenceTimes = [];
+        }
+        adaptationSetChildren.producerReferenceTimes.push(producerReferenceTime);
+        const childrenParser =
+          generateProducerReferenceTimeChildrenParser(producerReferenceTime.children,
+                                                      linearMemory,
+                                                      parsersStack);
+        const attributeParser =
+          generateProducerReferenceTimeAttrParser(producerReferenceTime.attributes,
+                                                  linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
+      case TagName.Resync: {
+        const resync = {};
+        if (adaptationSetChildren.resyncs === undefined) {
+          adaptationSetChildren.resyncs = [];
+        }
+        adaptationSetChildren.resyncs.push(resync);
+        parsersStack.pushParsers(nodeId, noop, generateResyncAttrParser(resync,
+                                                                        linearMemory));
+        break;
+      }
+
       case TagName.Representation: {
         const representationObj = { children: { baseURLs: [] },
                                     attributes: {} };
@@ -0,0 +1,61 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import { IAudioChannelConfiguration } from "../../../node_parser_types";
+import { IAttributeParser } from "../parsers_stack";
+import { AttributeName } from "../types";
+import { parseString } from "../utils";
+
+/**
+ * Generate an "attribute parser" once inside an `AudioChannelConfiguration`
+ * node.
+ * @param {Object} audioChannelConfiguration
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+export function generateAudioChannelConfigurationAttrParser(
+  audioChannelConfiguration : IAudioChannelConfiguration,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onAudioChannelConfigurationAttribute(
+    attr : number,
+    ptr : number,
+    len : number
+  ) {
+    switch (attr) {
+      case AttributeName.SchemeIdUri:
+        audioChannelConfiguration.schemeIdUri =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+
+      case AttributeName.SchemeValue:
+        audioChannelConfiguration.value =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+
+      case AttributeName.AudioChannelCount:
+        audioChannelConfiguration.channelCount =
+          new DataView(linearMemory.buffer).getFloat64(ptr, true);
+        break;
+
+      case AttributeName.AudioChannelLayout:
+        audioChannelConfiguration.speakers =
+          parseString(textDecoder, linearMemory.buffer, ptr, len).split(" ");
+        break;
+    }
+  };
+}
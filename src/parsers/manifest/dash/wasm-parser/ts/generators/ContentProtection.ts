@@ -14,14 +14,14 @@
  * limitations under the License.
  */
 
-import { base64ToBytes } from "../../../../../../utils/base64";
-import { hexToBytes } from "../../../../../../utils/string_parsing";
 import {
   IContentProtectionIntermediateRepresentation,
+  ILicenseUrl,
+  IPlayReadyInfo,
 } from "../../../node_parser_types";
 import { IAttributeParser } from "../parsers_stack";
 import { AttributeName } from "../types";
-import { parseString } from "../utils";
+import { parseKeyValue, parseString } from "../utils";
 
 /**
  * @param {Object} cpAttrs
@@ -35,6 +35,17 @@ export function generateContentProtectionAttrParser(
   const cpAttrs = cp.attributes;
   const cpChildren = cp.children;
   const textDecoder = new TextDecoder();
+  const getPlayReadyInfo = () : IPlayReadyInfo => {
+    if (cpChildren.playReady === undefined) {
+      cpChildren.playReady = { keyIds: [] };
+    }
+    return cpChildren.playReady;
+  };
+  const parseLicenseUrl = (ptr : number) : ILicenseUrl => {
+    const { key, value } = parseKeyValue(textDecoder, linearMemory.buffer, ptr);
+    return value === "" ? { url: key } :
+                          { url: key, licenseType: value };
+  };
   return function onContentProtectionAttribute(
     attr : number,
     ptr : number,
@@ -47,15 +58,89 @@ export function generateContentProtectionAttrParser(
       case AttributeName.ContentProtectionValue:
         cpAttrs.value = parseString(textDecoder, linearMemory.buffer, ptr, len);
         break;
-      case AttributeName.ContentProtectionKeyId:
-        const kid = parseString(textDecoder, linearMemory.buffer, ptr, len);
-        cpAttrs.keyId = hexToBytes(kid.replace(/-/g, ""));
+      case AttributeName.ContentProtectionKeyId: {
+        // Already decoded by the parser, reported once per key id
+        const kid = new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len));
+        if (cpAttrs.keyId === undefined) {
+          cpAttrs.keyId = kid;
+        }
+        if (cpAttrs.keyIds === undefined) {
+          cpAttrs.keyIds = [];
+        }
+        cpAttrs.keyIds.push(kid);
         break;
+      }
       case AttributeName.ContentProtectionCencPSSH:
-        try {
-          const b64 = parseString(textDecoder, linearMemory.buffer, ptr, len);
-          cpChildren.cencPssh.push(base64ToBytes(b64));
-        } catch (_) { /* TODO log error? register as warning? */ }
+        // Already decoded from base64 by the parser
+        cpChildren.cencPssh.push(
+          new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len))
+        );
+        break;
+      case AttributeName.ContentProtectionPsshSystemId:
+        if (cpChildren.cencPsshInfo === undefined) {
+          cpChildren.cencPsshInfo = [];
+        }
+        cpChildren.cencPsshInfo.push({
+          systemId: new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len)),
+          keyIds: [],
+        });
+        break;
+      case AttributeName.ContentProtectionPsshKeyId: {
+        const psshInfo = cpChildren.cencPsshInfo?.[cpChildren.cencPsshInfo.length - 1];
+        psshInfo?.keyIds.push(new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len)));
+        break;
+      }
+      case AttributeName.ContentProtectionPlayReadyObject:
+        getPlayReadyInfo().object =
+          new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len));
+        break;
+      case AttributeName.ContentProtectionPlayReadyKeyId:
+        getPlayReadyInfo().keyIds.push(
+          new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len))
+        );
+        break;
+      case AttributeName.ContentProtectionPlayReadyLaUrl:
+        getPlayReadyInfo().laUrl =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionPlayReadyLuiUrl:
+        getPlayReadyInfo().luiUrl =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionPlayReadyCustomAttributes:
+        getPlayReadyInfo().customAttributes =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionPlayReadyIsEncrypted:
+        getPlayReadyInfo().isEncrypted =
+          new DataView(linearMemory.buffer).getUint8(ptr) !== 0;
+        break;
+      case AttributeName.ContentProtectionRobustness:
+        cpAttrs.robustness = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionRefId:
+        cpAttrs.refId = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionRef:
+        cpAttrs.ref = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ContentProtectionDashIfLaUrl:
+        if (cpChildren.laUrls === undefined) {
+          cpChildren.laUrls = [];
+        }
+        cpChildren.laUrls.push(parseLicenseUrl(ptr));
+        break;
+      case AttributeName.ContentProtectionDashIfAuthzUrl:
+        if (cpChildren.authzUrls === undefined) {
+          cpChildren.authzUrls = [];
+        }
+        cpChildren.authzUrls.push(parseLicenseUrl(ptr));
+        break;
+      case AttributeName.ContentProtectionClearKeyLaUrl:
+        if (cpChildren.clearKeyLaUrls === undefined) {
+          cpChildren.clearKeyLaUrls = [];
+        }
+        cpChildren.clearKeyLaUrls.push(parseLicenseUrl(ptr));
         break;
     }
   };
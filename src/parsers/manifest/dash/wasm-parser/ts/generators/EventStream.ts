@@ -28,28 +28,34 @@ import {
   AttributeName,
   TagName,
 } from "../types";
-import { parseString } from "../utils";
+import {
+  parseString,
+  parseTimestamp,
+} from "../utils";
 
 /**
  * Generate a "children parser" once inside a `EventStream` node.
  * @param {Object} childrenObj
  * @param {WebAssembly.Memory} linearMemory
  * @param {ParsersStack} parsersStack
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps - If `true`, timestamps are communicated
+ * as u64 values instead of f64 ones (@see `parseTimestamp`).
  * @returns {Function}
  */
 export function generateEventStreamChildrenParser(
   childrenObj : IEventStreamChildren,
   linearMemory : WebAssembly.Memory,
   parsersStack : ParsersStack,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 )  : IChildrenParser {
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
       case TagName.EventStreamElt: {
         const event = {};
         childrenObj.events.push(event);
-        const attrParser = generateEventAttrParser(event, linearMemory, fullMpd);
+        const attrParser = generateEventAttrParser(event,
+                                                   linearMemory,
+                                                   exactTimestamps);
         parsersStack.pushParsers(nodeId, noop, attrParser);
         break;
       }
@@ -66,11 +72,13 @@ export function generateEventStreamChildrenParser(
 /**
  * @param {Object} esAttrs
  * @param {WebAssembly.Memory} linearMemory
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 export function generateEventStreamAttrParser(
   esAttrs : IEventStreamAttributes,
-  linearMemory : WebAssembly.Memory
+  linearMemory : WebAssembly.Memory,
+  exactTimestamps : boolean
 )  : IAttributeParser {
   const textDecoder = new TextDecoder();
   return function onEventStreamAttribute(attr : number, ptr : number, len : number) {
@@ -87,6 +95,9 @@ export function generateEventStreamAttrParser(
       case AttributeName.TimeScale:
         esAttrs.timescale = dataView.getFloat64(ptr, true);
         break;
+      case AttributeName.PresentationTimeOffset:
+        esAttrs.presentationTimeOffset = parseTimestamp(dataView, ptr, exactTimestamps);
+        break;
       case AttributeName.Namespace:
         const xmlNs = { key: "", value: "" };
         let offset = ptr;
@@ -113,20 +124,20 @@ export function generateEventStreamAttrParser(
 /**
  * @param {Object} eventAttr
  * @param {WebAssembly.Memory} linearMemory
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 function generateEventAttrParser(
   eventAttr : IEventStreamEventIntermediateRepresentation,
   linearMemory : WebAssembly.Memory,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 ) : IAttributeParser {
   const textDecoder = new TextDecoder();
   return function onEventStreamAttribute(attr : number, ptr : number, len : number) {
     const dataView = new DataView(linearMemory.buffer);
     switch (attr) {
       case AttributeName.EventPresentationTime:
-        eventAttr.presentationTime = dataView.getFloat64(ptr, true);
+        eventAttr.presentationTime = parseTimestamp(dataView, ptr, exactTimestamps);
         break;
       case AttributeName.Duration:
         eventAttr.duration = dataView.getFloat64(ptr, true);
@@ -134,10 +145,11 @@ function generateEventAttrParser(
       case AttributeName.Id:
         eventAttr.id = parseString(textDecoder, linearMemory.buffer, ptr, len);
         break;
-      case AttributeName.EventStreamEltRange:
-        const rangeStart = dataView.getFloat64(ptr, true);
-        const rangeEnd = dataView.getFloat64(ptr + 8, true);
-        eventAttr.eventStreamData = fullMpd.slice(rangeStart, rangeEnd);
+      case AttributeName.MessageData:
+        eventAttr.messageData = new Uint8Array(linearMemory.buffer.slice(ptr, ptr + len));
+        break;
+      case AttributeName.EventStreamEltXml:
+        eventAttr.eventStreamData = linearMemory.buffer.slice(ptr, ptr + len);
         break;
     }
   };
@@ -29,25 +29,30 @@ import {
 } from "../types";
 import { parseString } from "../utils";
 import { generateBaseUrlAttrParser } from "./BaseURL";
+import { generateContentSteeringAttrParser } from "./ContentSteering";
 import {
   generatePeriodAttrParser,
   generatePeriodChildrenParser,
 } from "./Period";
 import { generateSchemeAttrParser } from "./Scheme";
+import {
+  generateServiceDescriptionAttrParser,
+  generateServiceDescriptionChildrenParser,
+} from "./ServiceDescription";
 
 /**
  * Generate a "children parser" once inside an `MPD` node.
  * @param {Object} mpdChildren
  * @param {WebAssembly.Memory} linearMemory
  * @param {ParsersStack} parsersStack
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 export function generateMPDChildrenParser(
   mpdChildren : IMPDChildren,
   linearMemory : WebAssembly.Memory,
   parsersStack : ParsersStack,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 )  : IChildrenParser {
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
@@ -62,6 +67,17 @@ export function generateMPDChildrenParser(
         break;
       }
 
+      case TagName.ContentSteering: {
+        const contentSteering = { value: "", attributes: {} };
+        mpdChildren.contentSteering = contentSteering;
+
+        const childrenParser = noop; // ContentSteering have no sub-element
+        const attributeParser = generateContentSteeringAttrParser(contentSteering,
+                                                                  linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
       case TagName.Period: {
         const period = { children: { adaptations: [],
                                      baseURLs: [],
@@ -71,12 +87,34 @@ export function generateMPDChildrenParser(
         const childrenParser = generatePeriodChildrenParser(period.children,
                                                             linearMemory,
                                                             parsersStack,
-                                                            fullMpd);
+                                                            exactTimestamps);
         const attributeParser = generatePeriodAttrParser(period.attributes, linearMemory);
         parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
         break;
       }
 
+      case TagName.ServiceDescription: {
+        const serviceDescription = { children: { scopes: [],
+                                                 latencies: [],
+                                                 playbackRates: [],
+                                                 operatingQualities: [],
+                                                 operatingBandwidths: [] },
+                                     attributes: {} };
+        if (mpdChildren.serviceDescriptions === undefined) {
+          mpdChildren.serviceDescriptions = [];
+        }
+        mpdChildren.serviceDescriptions.push(serviceDescription);
+        const childrenParser =
+          generateServiceDescriptionChildrenParser(serviceDescription.children,
+                                                   linearMemory,
+                                                   parsersStack);
+        const attributeParser =
+          generateServiceDescriptionAttrParser(serviceDescription.attributes,
+                                               linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
       case TagName.UtcTiming: {
         const utcTiming = {};
         mpdChildren.utcTimings.push(utcTiming);
@@ -115,16 +153,16 @@ export function generateMPDAttrParser(
         mpdAttrs.type = parseString(textDecoder, linearMemory.buffer, ptr, len);
         break;
       case AttributeName.AvailabilityStartTime:
-        const startTime = parseString(textDecoder, linearMemory.buffer, ptr, len);
-        mpdAttrs.availabilityStartTime = new Date(startTime).getTime() / 1000;
+        dataView = new DataView(linearMemory.buffer);
+        mpdAttrs.availabilityStartTime = dataView.getFloat64(ptr, true) / 1000;
         break;
       case AttributeName.AvailabilityEndTime:
-        const endTime = parseString(textDecoder, linearMemory.buffer, ptr, len);
-        mpdAttrs.availabilityEndTime = new Date(endTime).getTime() / 1000;
+        dataView = new DataView(linearMemory.buffer);
+        mpdAttrs.availabilityEndTime = dataView.getFloat64(ptr, true) / 1000;
         break;
       case AttributeName.PublishTime:
-        const publishTime = parseString(textDecoder, linearMemory.buffer, ptr, len);
-        mpdAttrs.publishTime = new Date(publishTime).getTime() / 1000;
+        dataView = new DataView(linearMemory.buffer);
+        mpdAttrs.publishTime = dataView.getFloat64(ptr, true) / 1000;
         break;
       case AttributeName.MediaPresentationDuration:
         dataView = new DataView(linearMemory.buffer);
@@ -38,21 +38,29 @@ import {
   generateEventStreamAttrParser,
   generateEventStreamChildrenParser,
 } from "./EventStream";
+import {
+  generatePreselectionAttrParser,
+  generatePreselectionChildrenParser,
+} from "./Preselection";
 import { generateSegmentTemplateAttrParser } from "./SegmentTemplate";
+import {
+  generateServiceDescriptionAttrParser,
+  generateServiceDescriptionChildrenParser,
+} from "./ServiceDescription";
 
 /**
  * Generate a "children parser" once inside a `Perod` node.
  * @param {Object} periodChildren
  * @param {WebAssembly.Memory} linearMemory
  * @param {ParsersStack} parsersStack
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 export function generatePeriodChildrenParser(
   periodChildren : IPeriodChildren,
   linearMemory : WebAssembly.Memory,
   parsersStack : ParsersStack,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 )  : IChildrenParser {
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
@@ -88,13 +96,32 @@ export function generatePeriodChildrenParser(
         const childrenParser = generateEventStreamChildrenParser(eventStream.children,
                                                                  linearMemory,
                                                                  parsersStack,
-                                                                 fullMpd);
+                                                                 exactTimestamps);
         const attrParser = generateEventStreamAttrParser(eventStream.attributes,
-                                                         linearMemory);
+                                                         linearMemory,
+                                                         exactTimestamps);
         parsersStack.pushParsers(nodeId, childrenParser, attrParser);
         break;
       }
 
+      case TagName.Preselection: {
+        const preselection = { children: { accessibilities: [],
+                                           audioChannelConfigurations: [],
+                                           roles: [] },
+                               attributes: {} };
+        if (periodChildren.preselections === undefined) {
+          periodChildren.preselections = [];
+        }
+        periodChildren.preselections.push(preselection);
+        const childrenParser = generatePreselectionChildrenParser(preselection.children,
+                                                                  linearMemory,
+                                                                  parsersStack);
+        const attributeParser = generatePreselectionAttrParser(preselection.attributes,
+                                                               linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
       case TagName.SegmentTemplate: {
         const stObj = {};
         periodChildren.segmentTemplate = stObj;
@@ -104,6 +131,28 @@ export function generatePeriodChildrenParser(
         break;
       }
 
+      case TagName.ServiceDescription: {
+        const serviceDescription = { children: { scopes: [],
+                                                 latencies: [],
+                                                 playbackRates: [],
+                                                 operatingQualities: [],
+                                                 operatingBandwidths: [] },
+                                     attributes: {} };
+        if (periodChildren.serviceDescriptions === undefined) {
+          periodChildren.serviceDescriptions = [];
+        }
+        periodChildren.serviceDescriptions.push(serviceDescription);
+        const childrenParser =
+          generateServiceDescriptionChildrenParser(serviceDescription.children,
+                                                   linearMemory,
+                                                   parsersStack);
+        const attributeParser =
+          generateServiceDescriptionAttrParser(serviceDescription.attributes,
+                                               linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
       default:
         // Allows to make sure we're not mistakenly closing a re-opened
         // tag.
@@ -0,0 +1,135 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import noop from "../../../../../../utils/noop";
+import {
+  IPreselectionAttributes,
+  IPreselectionChildren,
+} from "../../../node_parser_types";
+import ParsersStack, {
+  IAttributeParser,
+  IChildrenParser,
+} from "../parsers_stack";
+import {
+  AttributeName,
+  TagName,
+} from "../types";
+import {
+  parseKeyValue,
+  parseString,
+} from "../utils";
+import {
+  generateAudioChannelConfigurationAttrParser,
+} from "./AudioChannelConfiguration";
+import { generateSchemeAttrParser } from "./Scheme";
+
+/**
+ * Generate a "children parser" once inside a `Preselection` node.
+ * @param {Object} preselectionChildren
+ * @param {WebAssembly.Memory} linearMemory
+ * @param {ParsersStack} parsersStack
+ * @returns {Function}
+ */
+export function generatePreselectionChildrenParser(
+  preselectionChildren : IPreselectionChildren,
+  linearMemory : WebAssembly.Memory,
+  parsersStack : ParsersStack
+)  : IChildrenParser {
+  return function onPreselectionChildren(nodeId : number) {
+    switch (nodeId) {
+
+      case TagName.Accessibility: {
+        const accessibility = {};
+        preselectionChildren.accessibilities.push(accessibility);
+        const attributeParser = generateSchemeAttrParser(accessibility, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.AudioChannelConfiguration: {
+        const audioChannelConfiguration = {};
+        preselectionChildren.audioChannelConfigurations.push(audioChannelConfiguration);
+        const attributeParser =
+          generateAudioChannelConfigurationAttrParser(audioChannelConfiguration,
+                                                      linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.Role: {
+        const role = {};
+        preselectionChildren.roles.push(role);
+        const attributeParser = generateSchemeAttrParser(role, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      default:
+        // Allows to make sure we're not mistakenly closing a re-opened
+        // tag.
+        parsersStack.pushParsers(nodeId, noop, noop);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside a `Preselection` node.
+ * @param {Object} preselectionAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+export function generatePreselectionAttrParser(
+  preselectionAttrs : IPreselectionAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onPreselectionAttribute(attr : number, ptr : number, len : number) {
+    switch (attr) {
+      case AttributeName.Id:
+        preselectionAttrs.id = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.PreselectionComponents:
+        preselectionAttrs.preselectionComponents =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.PreselectionComponent: {
+        const { key, value } = parseKeyValue(textDecoder, linearMemory.buffer, ptr);
+        if (preselectionAttrs.components === undefined) {
+          preselectionAttrs.components = [];
+        }
+        preselectionAttrs.components.push(
+          value === "" ? { adaptationSetId: key } :
+                         { adaptationSetId: key, contentComponentId: value });
+        break;
+      }
+      case AttributeName.Codecs:
+        preselectionAttrs.codecs =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.Label:
+        preselectionAttrs.label = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.Language:
+        preselectionAttrs.language =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.PreselectionTag:
+        preselectionAttrs.tag = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+    }
+  };
+}
@@ -0,0 +1,103 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import noop from "../../../../../../utils/noop";
+import {
+  IProducerReferenceTimeAttributes,
+  IProducerReferenceTimeIntermediateRepresentation,
+} from "../../../node_parser_types";
+import ParsersStack, {
+  IAttributeParser,
+  IChildrenParser,
+} from "../parsers_stack";
+import {
+  AttributeName,
+  TagName,
+} from "../types";
+import { parseString } from "../utils";
+import { generateSchemeAttrParser } from "./Scheme";
+
+/**
+ * Generate a "children parser" once inside a `ProducerReferenceTime` node.
+ * @param {Object} prtChildren
+ * @param {WebAssembly.Memory} linearMemory
+ * @param {ParsersStack} parsersStack
+ * @returns {Function}
+ */
+export function generateProducerReferenceTimeChildrenParser(
+  prtChildren : IProducerReferenceTimeIntermediateRepresentation["children"],
+  linearMemory : WebAssembly.Memory,
+  parsersStack : ParsersStack
+)  : IChildrenParser {
+  return function onProducerReferenceTimeChildren(nodeId : number) {
+    switch (nodeId) {
+      case TagName.UtcTiming: {
+        const utcTiming = {};
+        prtChildren.utcTimings.push(utcTiming);
+        const attributeParser = generateSchemeAttrParser(utcTiming, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      default:
+        // Allows to make sure we're not mistakenly closing a re-opened
+        // tag.
+        parsersStack.pushParsers(nodeId, noop, noop);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside a `ProducerReferenceTime` node.
+ * @param {Object} prtAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+export function generateProducerReferenceTimeAttrParser(
+  prtAttrs : IProducerReferenceTimeAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onProducerReferenceTimeAttribute(
+    attr : number,
+    ptr : number,
+    len : number
+  ) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.Id:
+        prtAttrs.id = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.Inband:
+        prtAttrs.inband = dataView.getUint8(ptr) !== 0;
+        break;
+      case AttributeName.Type:
+        prtAttrs.type = parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.ApplicationScheme:
+        prtAttrs.applicationScheme =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.WallClockTime:
+        prtAttrs.wallClockTime = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.PresentationTime:
+        prtAttrs.presentationTime = dataView.getFloat64(ptr, true);
+        break;
+    }
+  };
+}
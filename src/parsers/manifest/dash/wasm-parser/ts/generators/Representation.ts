@@ -29,8 +29,16 @@ import {
   TagName,
 } from "../types";
 import { parseString } from "../utils";
+import {
+  generateAudioChannelConfigurationAttrParser,
+} from "./AudioChannelConfiguration";
 import { generateBaseUrlAttrParser } from "./BaseURL";
 import { generateContentProtectionAttrParser } from "./ContentProtection";
+import {
+  generateProducerReferenceTimeAttrParser,
+  generateProducerReferenceTimeChildrenParser,
+} from "./ProducerReferenceTime";
+import { generateResyncAttrParser } from "./Resync";
 import { generateSchemeAttrParser } from "./Scheme";
 import { generateSegmentBaseAttrParser } from "./SegmentBase";
 import { generateSegmentListChildrenParser } from "./SegmentList";
@@ -51,6 +59,19 @@ export function generateRepresentationChildrenParser(
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
 
+      case TagName.AudioChannelConfiguration: {
+        const audioChannelConfiguration = {};
+        if (childrenObj.audioChannelConfigurations === undefined) {
+          childrenObj.audioChannelConfigurations = [];
+        }
+        childrenObj.audioChannelConfigurations.push(audioChannelConfiguration);
+        const attributeParser =
+          generateAudioChannelConfigurationAttrParser(audioChannelConfiguration,
+                                                      linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
       case TagName.BaseURL: {
         const baseUrl = { value: "", attributes: {} };
         childrenObj.baseURLs.push(baseUrl);
@@ -84,6 +105,35 @@ export function generateRepresentationChildrenParser(
         break;
       }
 
+      case TagName.ProducerReferenceTime: {
+        const producerReferenceTime = { children: { utcTimings: [] },
+                                        attributes: {} };
+        if (childrenObj.producerReferenceTimes === undefined) {
+          childrenObj.producerReferenceTimes = [];
+        }
+        childrenObj.producerReferenceTimes.push(producerReferenceTime);
+        const childrenParser =
+          generateProducerReferenceTimeChildrenParser(producerReferenceTime.children,
+                                                      linearMemory,
+                                                      parsersStack);
+        const attributeParser =
+          generateProducerReferenceTimeAttrParser(producerReferenceTime.attributes,
+                                                  linearMemory);
+        parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
+        break;
+      }
+
+      case TagName.Resync: {
+        const resync = {};
+        if (childrenObj.resyncs === undefined) {
+          childrenObj.resyncs = [];
+        }
+        childrenObj.resyncs.push(resync);
+        parsersStack.pushParsers(nodeId, noop, generateResyncAttrParser(resync,
+                                                                        linearMemory));
+        break;
+      }
+
       case TagName.SupplementalProperty: {
         const supplementalProperty = {};
         if (childrenObj.supplementalProperties === undefined) {
@@ -0,0 +1,51 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import { IResyncAttributes } from "../../../node_parser_types";
+import { IAttributeParser } from "../parsers_stack";
+import { AttributeName } from "../types";
+
+/**
+ * Generate an "attribute parser" once inside a `Resync` node.
+ * @param {Object} resyncAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+export function generateResyncAttrParser(
+  resyncAttrs : IResyncAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  return function onResyncAttribute(attr : number, ptr : number) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.ResyncType:
+        resyncAttrs.type = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.ResyncDT:
+        resyncAttrs.dT = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.ResyncDIMax:
+        resyncAttrs.dImax = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.ResyncDIMin:
+        resyncAttrs.dImin = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.ResyncMarker:
+        resyncAttrs.marker = dataView.getUint8(ptr) !== 0;
+        break;
+    }
+  };
+}
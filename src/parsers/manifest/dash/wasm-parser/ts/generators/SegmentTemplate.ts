@@ -16,6 +16,7 @@
 
 import {
   ISegmentTemplateIntermediateRepresentation,
+  ISegmentTimelineElement,
 } from "../../../node_parser_types";
 import { IAttributeParser } from "../parsers_stack";
 import { AttributeName } from "../types";
@@ -32,13 +33,23 @@ export function generateSegmentTemplateAttrParser(
         const dataView = new DataView(linearMemory.buffer);
         segmentTemplateAttrs.timeline = [];
         let base = ptr;
-        for (let i = 0; i < len / 24; i++) {
-          segmentTemplateAttrs.timeline.push({
+        for (let i = 0; i < len / 40; i++) {
+          const elt : ISegmentTimelineElement = {
             start: dataView.getFloat64(base, true),
             duration: dataView.getFloat64(base + 8, true),
             repeatCount: dataView.getFloat64(base + 16, true),
-          });
-          base += 24;
+          };
+          // `n` and `k` attributes are set to NaN when not found
+          const number = dataView.getFloat64(base + 24, true);
+          if (!isNaN(number)) {
+            elt.number = number;
+          }
+          const chunkCount = dataView.getFloat64(base + 32, true);
+          if (!isNaN(chunkCount)) {
+            elt.chunkCount = chunkCount;
+          }
+          segmentTemplateAttrs.timeline.push(elt);
+          base += 40;
         }
         break;
       }
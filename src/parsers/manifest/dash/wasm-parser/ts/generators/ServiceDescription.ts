@@ -0,0 +1,250 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import noop from "../../../../../../utils/noop";
+import {
+  ILatencyAttributes,
+  IOperatingBandwidthAttributes,
+  IOperatingQualityAttributes,
+  IPlaybackRateAttributes,
+  IServiceDescriptionChildren,
+  IServiceDescriptionIntermediateRepresentation,
+} from "../../../node_parser_types";
+import ParsersStack, {
+  IAttributeParser,
+  IChildrenParser,
+} from "../parsers_stack";
+import {
+  AttributeName,
+  TagName,
+} from "../types";
+import { parseString } from "../utils";
+import { generateSchemeAttrParser } from "./Scheme";
+
+/**
+ * Generate a "children parser" once inside a `ServiceDescription` node.
+ * @param {Object} serviceDescriptionChildren
+ * @param {WebAssembly.Memory} linearMemory
+ * @param {ParsersStack} parsersStack
+ * @returns {Function}
+ */
+export function generateServiceDescriptionChildrenParser(
+  serviceDescriptionChildren : IServiceDescriptionChildren,
+  linearMemory : WebAssembly.Memory,
+  parsersStack : ParsersStack
+)  : IChildrenParser {
+  return function onServiceDescriptionChildren(nodeId : number) {
+    switch (nodeId) {
+
+      case TagName.Scope: {
+        const scope = {};
+        serviceDescriptionChildren.scopes.push(scope);
+        const attributeParser = generateSchemeAttrParser(scope, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.Latency: {
+        const latency = {};
+        serviceDescriptionChildren.latencies.push(latency);
+        const attributeParser = generateLatencyAttrParser(latency, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.PlaybackRate: {
+        const playbackRate = {};
+        serviceDescriptionChildren.playbackRates.push(playbackRate);
+        const attributeParser = generatePlaybackRateAttrParser(playbackRate,
+                                                               linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.OperatingQuality: {
+        const operatingQuality = {};
+        serviceDescriptionChildren.operatingQualities.push(operatingQuality);
+        const attributeParser = generateOperatingQualityAttrParser(operatingQuality,
+                                                                   linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      case TagName.OperatingBandwidth: {
+        const operatingBandwidth = {};
+        serviceDescriptionChildren.operatingBandwidths.push(operatingBandwidth);
+        const attributeParser =
+          generateOperatingBandwidthAttrParser(operatingBandwidth, linearMemory);
+        parsersStack.pushParsers(nodeId, noop, attributeParser);
+        break;
+      }
+
+      default:
+        // Allows to make sure we're not mistakenly closing a re-opened
+        // tag.
+        parsersStack.pushParsers(nodeId, noop, noop);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside a `ServiceDescription` node.
+ * @param {Object} serviceDescriptionAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+export function generateServiceDescriptionAttrParser(
+  serviceDescriptionAttrs : IServiceDescriptionIntermediateRepresentation["attributes"],
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onServiceDescriptionAttribute(
+    attr : number,
+    ptr : number,
+    len : number
+  ) {
+    if (attr === AttributeName.Id) {
+      serviceDescriptionAttrs.id =
+        parseString(textDecoder, linearMemory.buffer, ptr, len);
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside a `Latency` node.
+ * @param {Object} latencyAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+function generateLatencyAttrParser(
+  latencyAttrs : ILatencyAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  return function onLatencyAttribute(attr : number, ptr : number) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.ReferenceId:
+        latencyAttrs.referenceId = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Target:
+        latencyAttrs.target = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Min:
+        latencyAttrs.min = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Max:
+        latencyAttrs.max = dataView.getFloat64(ptr, true);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside a `PlaybackRate` node.
+ * @param {Object} playbackRateAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+function generatePlaybackRateAttrParser(
+  playbackRateAttrs : IPlaybackRateAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  return function onPlaybackRateAttribute(attr : number, ptr : number) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.Min:
+        playbackRateAttrs.min = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Max:
+        playbackRateAttrs.max = dataView.getFloat64(ptr, true);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside an `OperatingQuality` node.
+ * @param {Object} operatingQualityAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+function generateOperatingQualityAttrParser(
+  operatingQualityAttrs : IOperatingQualityAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onOperatingQualityAttribute(attr : number, ptr : number, len : number) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.MediaType:
+        operatingQualityAttrs.mediaType =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.Min:
+        operatingQualityAttrs.min = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Max:
+        operatingQualityAttrs.max = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Target:
+        operatingQualityAttrs.target = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Type:
+        operatingQualityAttrs.type =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.MaxDifference:
+        operatingQualityAttrs.maxDifference = dataView.getFloat64(ptr, true);
+        break;
+    }
+  };
+}
+
+/**
+ * Generate an "attribute parser" once inside an `OperatingBandwidth` node.
+ * @param {Object} operatingBandwidthAttrs
+ * @param {WebAssembly.Memory} linearMemory
+ * @returns {Function}
+ */
+function generateOperatingBandwidthAttrParser(
+  operatingBandwidthAttrs : IOperatingBandwidthAttributes,
+  linearMemory : WebAssembly.Memory
+)  : IAttributeParser {
+  const textDecoder = new TextDecoder();
+  return function onOperatingBandwidthAttribute(
+    attr : number,
+    ptr : number,
+    len : number
+  ) {
+    const dataView = new DataView(linearMemory.buffer);
+    switch (attr) {
+      case AttributeName.MediaType:
+        operatingBandwidthAttrs.mediaType =
+          parseString(textDecoder, linearMemory.buffer, ptr, len);
+        break;
+      case AttributeName.Min:
+        operatingBandwidthAttrs.min = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Max:
+        operatingBandwidthAttrs.max = dataView.getFloat64(ptr, true);
+        break;
+      case AttributeName.Target:
+        operatingBandwidthAttrs.target = dataView.getFloat64(ptr, true);
+        break;
+    }
+  };
+}
@@ -30,14 +30,14 @@ import {
  * @param {Object} xlinkObj
  * @param {WebAssembly.Memory} linearMemory
  * @param {ParsersStack} parsersStack
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 export function generateXLinkChildrenParser(
   xlinkObj : { periods: IPeriodIntermediateRepresentation[] },
   linearMemory : WebAssembly.Memory,
   parsersStack : ParsersStack,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 )  : IChildrenParser {
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
@@ -50,7 +50,7 @@ export function generateXLinkChildrenParser(
         const childrenParser = generatePeriodChildrenParser(period.children,
                                                             linearMemory,
                                                             parsersStack,
-                                                            fullMpd);
+                                                            exactTimestamps);
         const attributeParser = generatePeriodAttrParser(period.attributes, linearMemory);
         parsersStack.pushParsers(nodeId, childrenParser, attributeParser);
         break;
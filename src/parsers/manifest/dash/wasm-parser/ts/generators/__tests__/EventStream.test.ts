@@ -0,0 +1,83 @@
+/**
+ * Copyright 2015 CANAL+ Group
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+import {
+  IEventStreamAttributes,
+  IEventStreamChildren,
+} from "../../../../node_parser_types";
+import ParsersStack from "../../parsers_stack";
+import {
+  AttributeName,
+  TagName,
+} from "../../types";
+import {
+  generateEventStreamAttrParser,
+  generateEventStreamChildrenParser,
+} from "../EventStream";
+
+/**
+ * Write `val` as a little-endian u64 at `ptr`, as the WebAssembly does when
+ * exact timestamps are enabled.
+ * @param {WebAssembly.Memory} linearMemory
+ * @param {number} ptr
+ * @param {number} val
+ */
+function writeU64(
+  linearMemory : WebAssembly.Memory,
+  ptr : number,
+  val : number
+) : void {
+  const dataView = new DataView(linearMemory.buffer);
+  dataView.setUint32(ptr, val % 0x100000000, true);
+  dataView.setUint32(ptr + 4, Math.floor(val / 0x100000000), true);
+}
+
+describe("DASH WASM parser - EventStream generators", () => {
+  it("should read u64 timestamps when exact timestamps are enabled", () => {
+    const linearMemory = new WebAssembly.Memory({ initial: 1 });
+    const parsersStack = new ParsersStack();
+
+    const esAttrs : IEventStreamAttributes = {};
+    const esAttrParser = generateEventStreamAttrParser(esAttrs, linearMemory, true);
+    writeU64(linearMemory, 0, 10000000000);
+    esAttrParser(AttributeName.PresentationTimeOffset, 0, 8);
+    expect(esAttrs.presentationTimeOffset).toBe(10000000000);
+
+    const children : IEventStreamChildren = { events: [] };
+    const childrenParser = generateEventStreamChildrenParser(children,
+                                                             linearMemory,
+                                                             parsersStack,
+                                                             true);
+    childrenParser(TagName.EventStreamElt);
+    writeU64(linearMemory, 8, 9007199254740991);
+    parsersStack.attributeParser(AttributeName.EventPresentationTime, 8, 8);
+    expect(children.events).toEqual([{ presentationTime: 9007199254740991 }]);
+  });
+
+  it("should read f64 timestamps by default", () => {
+    const linearMemory = new WebAssembly.Memory({ initial: 1 });
+    const parsersStack = new ParsersStack();
+    const children : IEventStreamChildren = { events: [] };
+    const childrenParser = generateEventStreamChildrenParser(children,
+                                                             linearMemory,
+                                                             parsersStack,
+                                                             false);
+    childrenParser(TagName.EventStreamElt);
+    new DataView(linearMemory.buffer).setFloat64(0, 1500, true);
+    parsersStack.attributeParser(AttributeName.EventPresentationTime, 0, 8);
+    expect(children.events).toEqual([{ presentationTime: 1500 }]);
+  });
+});
@@ -29,14 +29,14 @@ import {
  * @param {Object} rootObj
  * @param {WebAssembly.Memory} linearMemory
  * @param {ParsersStack} parsersStack
- * @param {ArrayBuffer} fullMpd
+ * @param {boolean} exactTimestamps
  * @returns {Function}
  */
 export function generateRootChildrenParser(
   rootObj : { mpd? : IMPDIntermediateRepresentation },
   linearMemory : WebAssembly.Memory,
   parsersStack : ParsersStack,
-  fullMpd : ArrayBuffer
+  exactTimestamps : boolean
 )  : (nodeId : number) => void {
   return function onRootChildren(nodeId : number) {
     switch (nodeId) {
@@ -49,7 +49,7 @@ export function generateRootChildrenParser(
         const childrenParser = generateMPDChildrenParser(rootObj.mpd.children,
                                                          linearMemory,
                                                          parsersStack,
-                                                         fullMpd);
+                                                         exactTimestamps);
         const attributeParser = generateMPDAttrParser(rootObj.mpd.children,
                                                       rootObj.mpd.attributes,
                                                       linearMemory);
@@ -29,8 +29,119 @@ export const enum CustomEventType {
    */
   Log = 0,
 
-    /** Variant used to report parsing errors to the RxPlayer. */
+    /**
+     * Variant used to report parsing errors to the RxPlayer.
+     *
+     * Its payload is made of:
+     *   - the kind of error (@see ParsingErrorKind), as an u8
+     *   - the position in bytes in the MPD where it was encountered, as an
+     *     f64 (NaN if unknown)
+     *   - the length in bytes of the path of the concerned element, as a
+     *     big-endian u32
+     *   - that path, in UTF-8
+     *   - the error's message, in UTF-8, until the end of the payload
+     */
   Error = 1,
+
+  /**
+   * Variant used to communicate the URL obtained by expanding a
+   * `<SegmentTemplate>` URL template, in UTF-8.
+   */
+  ExpandedTemplate = 2,
+}
+
+/**
+ * Category of an error reported through the `CustomEventType.Error` event.
+ *
+ * This enum can simply be copy-pasted from the corresponding Rust file as both
+ * the TypeScript syntax and the rust syntax for them are really close.
+ */
+export const enum ParsingErrorKind {
+  /** Any error not part of the other categories. */
+  Other = 0,
+  /** An attribute which should be a number is not one (or too large). */
+  InvalidNumber = 1,
+  /** An attribute which should be a boolean is not one. */
+  InvalidBoolean = 2,
+  /** An attribute which should be an ISO 8601 duration is not one. */
+  InvalidDuration = 3,
+  /** An attribute which should be an xs:dateTime is not one. */
+  InvalidDateTime = 4,
+  /** An attribute which should be a byte range is not one. */
+  InvalidRange = 5,
+  /** Text which is not valid UTF-8 or which contains invalid XML escapes. */
+  InvalidEncoding = 6,
+  /** The document ended in the middle of an element. */
+  UnexpectedEof = 7,
+  /** The document is not well-formed XML. */
+  XmlSyntax = 8,
+  /** An element which was needed was not found. */
+  MissingElement = 9,
+  /**
+   * An MPD Patch document or operation is invalid, or could not be
+   * applied.
+   */
+  InvalidPatch = 10,
+  /** The data could not be read. */
+  Io = 11,
+  /**
+   * An element lacks an attribute it should always have (only reported in
+   * strict mode).
+   */
+  MissingAttribute = 12,
+  /**
+   * An attribute has a value not defined by the specification (only reported
+   * in strict mode).
+   */
+  UnknownValue = 13,
+  /**
+   * Elements or attributes are combined in a way not allowed by the
+   * specification (only reported in strict mode).
+   */
+  InvalidStructure = 14,
+  /**
+   * A segment of a `<SegmentTimeline>` starts before the end of the previous
+   * one.
+   *
+   * Like gaps, this is only a warning, which never stops the parsing.
+   */
+  TimelineOverlap = 15,
+  /**
+   * A segment of a `<SegmentTimeline>` starts after the end of the previous
+   * one.
+   *
+   * This is allowed, errors of this kind thus never stop the parsing.
+   */
+  TimelineGap = 16,
+  /**
+   * A `<SegmentTemplate>` URL template is invalid, or could not be expanded.
+   */
+  InvalidTemplate = 17,
+  /**
+   * An XLink could not be resolved, e.g. because too many XLinks are
+   * nested.
+   */
+  InvalidXLink = 18,
+  /**
+   * A base64-encoded value, such as the text of a `<cenc:pssh>` element, is
+   * invalid.
+   */
+  InvalidBase64 = 19,
+  /** A `pssh` box, from a `<cenc:pssh>` element, is malformed. */
+  InvalidPssh = 20,
+  /** A key id, such as a `cenc:default_KID` attribute, is not a valid UUID. */
+  InvalidKeyId = 21,
+  /** A PlayReady Object, from a `<mspr:pro>` element, is malformed. */
+  InvalidPlayReadyObject = 22,
+  /**
+   * A `ref` attribute, such as the one of a `<ContentProtection>`, references
+   * no element defined before it.
+   */
+  InvalidReference = 23,
+  /** A Content Steering manifest, sent by a steering server, is invalid. */
+  InvalidSteeringManifest = 24,
+  /** An `emsg` box, from a media segment, is malformed. */
+  InvalidEmsg = 25,
 }
 
 /**
@@ -108,6 +219,44 @@ export const enum TagName {
 
   /// Indicate a <SegmentURL> node
   SegmentUrl = 20,
+
+  // -- Inside an <AdaptationSet> or a <Representation> --
+  /// Indicate an <AudioChannelConfiguration> node
+  AudioChannelConfiguration = 21,
+
+  /// Indicate a <ProducerReferenceTime> node
+  ProducerReferenceTime = 22,
+
+  /// Indicate a <Resync> node
+  Resync = 23,
+
+  // -- Inside an <MPD> or a <Period> --
+  /// Indicate a <ServiceDescription> node
+  ServiceDescription = 24,
+
+  // -- Inside a <ServiceDescription> --
+  /// Indicate a <Scope> node
+  Scope = 25,
+
+  /// Indicate a <Latency> node
+  Latency = 26,
+
+  /// Indicate a <PlaybackRate> node
+  PlaybackRate = 27,
+
+  /// Indicate an <OperatingQuality> node
+  OperatingQuality = 28,
+
+  /// Indicate an <OperatingBandwidth> node
+  OperatingBandwidth = 29,
+
+  // -- Inside a <Period> or an <AdaptationSet> --
+  /// Indicate a <Preselection> node
+  Preselection = 30,
+
+  // -- Inside an <MPD> --
+  /// Indicate a <ContentSteering> node
+  ContentSteering = 31,
 }
 
 /**
@@ -135,8 +284,8 @@ export const enum AttributeName {
 
   // ContentProtection
   ContentProtectionValue = 13, // String
-  ContentProtectionKeyId = 14, // ArrayBuffer
-  ContentProtectionCencPSSH = 15, // ArrayBuffer
+  ContentProtectionKeyId = 14, // ArrayBuffer (16 bytes, once per key id)
+  ContentProtectionCencPSSH = 15, // ArrayBuffer (the decoded `pssh` box)
 
   // Various schemes (Accessibility) + EventStream + ContentProtection
   SchemeIdUri = 16, // String
@@ -148,7 +297,7 @@ export const enum AttributeName {
   MediaRange = 18, // [f64, f64]
 
   // SegmentTimeline
-  SegmentTimeline = 19, // Vec<SElement>
+  SegmentTimeline = 19, // Vec<SElement> (t, d, r, n and k, 8 bytes each)
 
   // SegmentTemplate
   StartNumber = 20, // f64
@@ -159,10 +308,10 @@ export const enum AttributeName {
   // SegmentTemplate + SegmentBase
   AvailabilityTimeComplete = 22, // u8 (bool)
   IndexRangeExact = 23, // u8 (bool)
-  PresentationTimeOffset = 24, // f64
+  PresentationTimeOffset = 24, // f64, or u64 with exact timestamps
 
   // EventStream
-  EventPresentationTime = 25, // f64
+  EventPresentationTime = 25, // f64, or u64 with exact timestamps
 
   // EventStreamElt
   Element = 26, // String (XML)
@@ -186,9 +335,9 @@ export const enum AttributeName {
 
   // MPD
   Type = 33, // String
-  AvailabilityStartTime = 34, // f64
-  AvailabilityEndTime = 35, // f64
-  PublishTime = 36, // f64
+  AvailabilityStartTime = 34, // f64 (milliseconds since the Unix epoch)
+  AvailabilityEndTime = 35, // f64 (milliseconds since the Unix epoch)
+  PublishTime = 36, // f64 (milliseconds since the Unix epoch)
   MinimumUpdatePeriod = 37, // f64
   MinBufferTime = 38, // f64
   TimeShiftBufferDepth = 39, // f64
@@ -295,4 +444,172 @@ export const enum AttributeName {
   EndNumber = 76, // f64
 
   SupplementalCodecs = 77, // String
+
+  /// A `<BaseURL>` applying to the current `<Representation>`, resolved
+  /// against those of its parent elements and against the MPD's URL.
+  /// Only reported when BaseURLs are resolved by the parser, right before the
+  /// `<Representation>` closes, once per resolved URL.
+  ///
+  /// Communicated as a key-value pair (@see `AttributeName.Namespace`): the
+  /// resolved URL and its `serviceLocation` (empty if none).
+  ResolvedBaseUrl = 78,
+
+  /// SystemID of the `pssh` box of a `<cenc:pssh>` element, as 16 bytes.
+  /// Reported right after its `ContentProtectionCencPSSH`, if that box could
+  /// be parsed.
+  ContentProtectionPsshSystemId = 79,
+
+  /// A key id listed by that same `pssh` box (only for its version `1`), as
+  /// 16 bytes. Reported once per key id, after the
+  /// `ContentProtectionPsshSystemId`.
+  ContentProtectionPsshKeyId = 80,
+
+  /// A PlayReady Object, decoded from the base64 text of a `<mspr:pro>`
+  /// element.
+  ContentProtectionPlayReadyObject = 81, // ArrayBuffer
+
+  /// A key id listed by the WRM header of that PlayReady Object, as 16
+  /// bytes in the UUID byte order (the GUID one of PlayReady being
+  /// converted). Reported once per key id.
+  ContentProtectionPlayReadyKeyId = 82,
+
+  /// `LA_URL` (license server URL) of that WRM header.
+  ContentProtectionPlayReadyLaUrl = 83, // String
+
+  /// `LUI_URL` (license acquisition web page URL) of that WRM header.
+  ContentProtectionPlayReadyLuiUrl = 84, // String
+
+  /// Inner XML of the `CUSTOMATTRIBUTES` element of that WRM header.
+  ContentProtectionPlayReadyCustomAttributes = 85, // String
+
+  /// Content of a `<mspr:IsEncrypted>` element.
+  ContentProtectionPlayReadyIsEncrypted = 86, // Boolean
+
+  /// `robustness` attribute of a `<ContentProtection>` element.
+  ContentProtectionRobustness = 87, // String
+
+  /// `refId` attribute of a `<ContentProtection>` element, which may then
+  /// be referenced by other `<ContentProtection>` elements.
+  ContentProtectionRefId = 88, // String
+
+  /// `ref` attribute of a `<ContentProtection>` element. When it references
+  /// an element already encountered, the attributes and children of that
+  /// element are reported as if they were part of the referencing one.
+  ContentProtectionRef = 89, // String
+
+  /// Text of a `<dashif:Laurl>` element, the URL of a license server.
+  ///
+  /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+  /// the URL and its `licenseType` (empty if none).
+  ContentProtectionDashIfLaUrl = 90,
+
+  /// Text of a `<dashif:Authzurl>` element, the URL of an authorization
+  /// service, communicated in the same way than
+  /// `ContentProtectionDashIfLaUrl`.
+  ContentProtectionDashIfAuthzUrl = 91,
+
+  /// Text of a `<clearkey:Laurl>` element, the URL of a ClearKey license
+  /// server.
+  ///
+  /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+  /// the URL and its `Lic_type` (empty if none).
+  ContentProtectionClearKeyLaUrl = 92,
+
+  /// Number of audio channels described by an `<AudioChannelConfiguration>`
+  /// element, decoded from its `value` when its `schemeIdUri` is a known
+  /// one.
+  AudioChannelCount = 93, // f64
+
+  /// Space-separated labels of the speakers those audio channels are for
+  /// (e.g. `L R` for stereo or `C L R Ls Rs LFE` for 5.1), when indicated
+  /// by that scheme.
+  AudioChannelLayout = 94, // String
+
+  // ServiceDescription's children
+  /// `min` attribute of `<Latency>` (in milliseconds), `<PlaybackRate>`,
+  /// `<OperatingQuality>` and `<OperatingBandwidth>` (in bits per second)
+  /// elements.
+  Min = 95, // f64
+
+  /// `max` attribute of those same elements.
+  Max = 96, // f64
+
+  /// `target` attribute of `<Latency>`, `<OperatingQuality>` and
+  /// `<OperatingBandwidth>` elements.
+  Target = 97, // f64
+
+  /// `referenceId` attribute of `<Latency>` elements, the `id` of the
+  /// `<ProducerReferenceTime>` the latency is measured against.
+  ReferenceId = 98, // f64
+
+  /// `mediaType` attribute of `<OperatingQuality>` and
+  /// `<OperatingBandwidth>` elements.
+  MediaType = 99, // String
+
+  /// `maxDifference` attribute of `<OperatingQuality>` elements.
+  MaxDifference = 100, // f64
+
+  // ProducerReferenceTime
+  // (its `id` and `type` attributes being reported as `Id` and `Type`, as
+  // is the `type` of `<OperatingQuality>` elements)
+  /// `inband` attribute of `<ProducerReferenceTime>` elements.
+  Inband = 101, // u8 (bool)
+
+  /// `applicationScheme` attribute of `<ProducerReferenceTime>` elements.
+  ApplicationScheme = 102, // String
+
+  /// `wallClockTime` attribute of `<ProducerReferenceTime>` elements,
+  /// whether an xs:dateTime or an NTP timestamp.
+  WallClockTime = 103, // f64 (milliseconds since the Unix epoch)
+
+  /// `presentationTime` attribute of `<ProducerReferenceTime>` elements.
+  PresentationTime = 104, // f64, or u64 with exact timestamps
+
+  // Resync
+  /// `type` attribute of `<Resync>` elements, from `0` to `3`.
+  ResyncType = 105, // f64
+
+  /// `dT` attribute of `<Resync>` elements.
+  ResyncDT = 106, // f64
+
+  /// `dImax` attribute of `<Resync>` elements.
+  ResyncDIMax = 107, // f64
+
+  /// `dImin` attribute of `<Resync>` elements.
+  ResyncDIMin = 108, // f64
+
+  /// `marker` attribute of `<Resync>` elements.
+  ResyncMarker = 109, // u8 (bool)
+
+  // Preselection
+  // (its `id`, `lang` and `codecs` attributes being reported as `Id`,
+  // `Language` and `Codecs`)
+  /// `preselectionComponents` attribute of `<Preselection>` elements, as
+  /// written in the MPD.
+  PreselectionComponents = 110, // String
+
+  /// One of the components of a `<Preselection>`, reported once per id
+  /// listed in its `preselectionComponents` attribute, in the same order.
+  ///
+  /// Communicated as a key-value pair (@see `AttributeName::Namespace`):
+  /// the `id` of the `<AdaptationSet>` referenced (or containing the
+  /// `<ContentComponent>` referenced) and the `id` of the
+  /// `<ContentComponent>` referenced (empty if an `<AdaptationSet>` is).
+  PreselectionComponent = 111,
+
+  /// `tag` attribute of `<Preselection>` elements.
+  PreselectionTag = 112, // String
+
+  // Event
+  /// Binary data of an event: the `message_data` of an `emsg` box or, for
+  /// `<Event>` elements, their `messageData` attribute in UTF-8 or their
+  /// content once decoded when their `contentEncoding` is `base64`.
+  MessageData = 116, // Vec<u8>
+
+  /// The `<Event>` element itself, as the raw XML read in the MPD: its
+  /// opening tag with all its attributes, its content and its closing tag.
+  ///
+  /// This allows the JS-side to construct the corresponding `Element`
+  /// without having to keep the whole MPD.
+  EventStreamEltXml = 117, // String
 }
@@ -31,6 +31,55 @@ function parseString(
   return textDecoder.decode(arr);
 }
 
+/**
+ * Parse a key-value pair, as reported by the parser: the length of the key
+ * as a big-endian u32, the key in UTF-8, then the same for the value.
+ * @param {TextDecoder} textDecoder
+ * @param {ArrayBuffer} buffer
+ * @param {number} ptr
+ * @returns {Object}
+ */
+function parseKeyValue(
+  textDecoder : TextDecoder,
+  buffer : ArrayBuffer,
+  ptr : number
+) : { key : string; value : string } {
+  const dataView = new DataView(buffer);
+  let offset = ptr;
+  const keySize = dataView.getUint32(offset);
+  offset += 4;
+  const key = parseString(textDecoder, buffer, offset, keySize);
+  offset += keySize;
+  const valSize = dataView.getUint32(offset);
+  offset += 4;
+  const value = parseString(textDecoder, buffer, offset, valSize);
+  return { key, value };
+}
+
+/**
+ * Parse an integer which may exceed the precision of an f64, such as a
+ * timestamp, as reported by the parser: an f64 by default or, when exact
+ * timestamps are enabled (bit `2` of the `init` flags), a little-endian u64,
+ * read here as two u32 values (the low one first) as BigInt may not be
+ * available.
+ * @param {DataView} dataView
+ * @param {number} ptr
+ * @param {boolean} exactTimestamps
+ * @returns {number}
+ */
+function parseTimestamp(
+  dataView : DataView,
+  ptr : number,
+  exactTimestamps : boolean
+) : number {
+  if (!exactTimestamps) {
+    return dataView.getFloat64(ptr, true);
+  }
+  const low = dataView.getUint32(ptr, true);
+  const high = dataView.getUint32(ptr + 4, true);
+  return high * 0x100000000 + low;
+}
+
 /**
  * @param {number} val
  * @returns {number|boolean}
@@ -43,5 +92,7 @@ function parseFloatOrBool(val : number) : number | boolean {
 
 export {
   parseString,
+  parseKeyValue,
   parseFloatOrBool,
+  parseTimestamp,
 };